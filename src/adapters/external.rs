@@ -0,0 +1,1066 @@
+//! External adapters: third-party sources running as child processes
+//!
+//! Each entry in `external_adapters.json` is a command the bridge starts and
+//! supervises through `AdapterHandle`, so it gets the same retry/backoff,
+//! lifecycle events and diagnostics as the built-in adapters. The child speaks
+//! JSON-RPC 2.0 over stdin/stdout, one message per line: it publishes zones and
+//! now-playing changes as notifications and answers `command` requests.
+//! The protocol is described in `docs/external-adapters.md`.
+//!
+//! Zones from an external adapter named `jukebox` are `ext-jukebox:<id>`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{mpsc, oneshot, Notify, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::adapters::handle::{AdapterHandle, RetryConfig};
+use crate::adapters::traits::{
+    AdapterCommand, AdapterCommandResponse, AdapterContext, AdapterLogic, Startable,
+};
+use crate::bus::{
+    BusEvent, NowPlaying, PlaybackState, PrefixedZoneId, SharedBus, VolumeControl, VolumeScale,
+    Zone, ZoneCapabilities,
+};
+use crate::config::read_config_file;
+
+/// External adapter definitions (under the config dir)
+pub const EXTERNAL_ADAPTERS_FILE: &str = "external_adapters.json";
+
+/// Prefix shared by every external adapter's zone ids
+pub const EXTERNAL_PREFIX: &str = "ext-";
+
+/// Protocol version sent in `initialize`
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// JSON-RPC error code a child returns for a command it doesn't support
+pub const ERROR_UNSUPPORTED: i64 = -32001;
+
+/// JSON-RPC "method not found"
+const ERROR_METHOD_NOT_FOUND: i64 = -32601;
+
+/// How long a child has to answer a request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a child has to exit after the `shutdown` notification
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
+/// Queued messages to the child before senders wait
+const OUTGOING_CAPACITY: usize = 64;
+
+/// One external adapter definition
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExternalAdapterConfig {
+    /// Short name (lowercase letters, digits and dashes); zones are `ext-<name>:<id>`
+    pub name: String,
+    /// Program to run
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables for the child
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ExternalAdapterConfig {
+    fn validate(&self) -> Result<()> {
+        let valid_name = !self.name.is_empty()
+            && self.name.len() <= 32
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && !self.name.starts_with('-');
+        if !valid_name {
+            bail!(
+                "invalid name '{}' (use lowercase letters, digits and dashes)",
+                self.name
+            );
+        }
+        if self.command.trim().is_empty() {
+            bail!("'{}' has no command", self.name);
+        }
+        Ok(())
+    }
+
+    /// Zone id prefix for this adapter
+    pub fn prefix(&self) -> String {
+        format!("{}{}", EXTERNAL_PREFIX, self.name)
+    }
+}
+
+/// Parse adapter definitions, dropping invalid and duplicate entries
+fn parse_configs(content: &str) -> Vec<ExternalAdapterConfig> {
+    let configs: Vec<ExternalAdapterConfig> = match serde_json::from_str(content) {
+        Ok(configs) => configs,
+        Err(e) => {
+            warn!("Failed to parse {}: {}", EXTERNAL_ADAPTERS_FILE, e);
+            return Vec::new();
+        }
+    };
+
+    let mut seen = std::collections::HashSet::new();
+    configs
+        .into_iter()
+        .filter(|config| match config.validate() {
+            Ok(()) if seen.insert(config.name.clone()) => true,
+            Ok(()) => {
+                warn!(
+                    "External adapter '{}' is defined twice, skipping",
+                    config.name
+                );
+                false
+            }
+            Err(e) => {
+                warn!("Skipping external adapter: {}", e);
+                false
+            }
+        })
+        .collect()
+}
+
+/// Load external adapter definitions from the config dir
+pub fn load_external_configs() -> Vec<ExternalAdapterConfig> {
+    read_config_file(EXTERNAL_ADAPTERS_FILE)
+        .map(|content| parse_configs(&content))
+        .unwrap_or_default()
+}
+
+// =============================================================================
+// Wire format
+// =============================================================================
+
+/// Error member of a JSON-RPC response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Any message from the child: a response (id + result/error) or a notification (method)
+#[derive(Debug, Deserialize)]
+struct RpcMessage {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+fn notification(method: &str, params: Value) -> String {
+    json!({"jsonrpc": "2.0", "method": method, "params": params}).to_string()
+}
+
+/// `command` request parameters for an adapter command
+fn command_params(zone_id: &str, command: &AdapterCommand) -> Value {
+    let value = match command {
+        AdapterCommand::VolumeAbsolute(v)
+        | AdapterCommand::VolumeRelative(v)
+        | AdapterCommand::SeekRelative(v) => json!(v),
+        AdapterCommand::Seek(position) => json!(position),
+        AdapterCommand::Mute(on) | AdapterCommand::Shuffle(on) => json!(on),
+        AdapterCommand::Repeat(mode) => json!(mode),
+        _ => Value::Null,
+    };
+    json!({"zone_id": zone_id, "action": command.name(), "value": value})
+}
+
+/// Volume as reported by the child
+#[derive(Debug, Clone, Deserialize)]
+struct ExternalVolume {
+    value: f32,
+    #[serde(default)]
+    min: f32,
+    #[serde(default = "default_volume_max")]
+    max: f32,
+    #[serde(default = "default_volume_step")]
+    step: f32,
+    #[serde(default)]
+    is_muted: bool,
+    /// Levels are dB rather than 0-100
+    #[serde(default)]
+    db: bool,
+}
+
+fn default_volume_max() -> f32 {
+    100.0
+}
+
+fn default_volume_step() -> f32 {
+    1.0
+}
+
+/// Zone as reported by the child in `zone.discovered`
+#[derive(Debug, Clone, Deserialize)]
+struct ExternalZone {
+    /// Id without the adapter prefix
+    id: String,
+    name: String,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    volume: Option<ExternalVolume>,
+    /// Any of "transport", "skip", "seek", "shuffle", "repeat", "volume", "mute"
+    #[serde(default)]
+    capabilities: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ZoneDiscoveredParams {
+    zone: ExternalZone,
+}
+
+#[derive(Deserialize)]
+struct ZoneUpdatedParams {
+    zone_id: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ZoneRemovedParams {
+    zone_id: String,
+}
+
+#[derive(Deserialize)]
+struct NowPlayingParams {
+    zone_id: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    artist: Option<String>,
+    #[serde(default)]
+    album: Option<String>,
+    #[serde(default)]
+    image_key: Option<String>,
+    #[serde(default)]
+    duration: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct VolumeParams {
+    zone_id: String,
+    value: f32,
+    #[serde(default)]
+    is_muted: bool,
+}
+
+#[derive(Deserialize)]
+struct SeekParams {
+    zone_id: String,
+    position: i64,
+}
+
+#[derive(Deserialize)]
+struct LogParams {
+    #[serde(default)]
+    level: Option<String>,
+    message: String,
+}
+
+/// Refresh the per-state command flags after a state change
+fn apply_state(zone: &mut Zone, state: PlaybackState) {
+    let transport = zone.capabilities.transport;
+    zone.state = state;
+    zone.is_play_allowed = transport && state != PlaybackState::Playing;
+    zone.is_pause_allowed = transport && state == PlaybackState::Playing;
+    zone.last_updated = now_millis();
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn external_zone_to_zone(prefix: &str, external: &ExternalZone) -> Zone {
+    let has = |name: &str| external.capabilities.iter().any(|c| c == name);
+    let zone_id = PrefixedZoneId::external(prefix, &external.id).to_string();
+    let capabilities = ZoneCapabilities {
+        transport: has("transport"),
+        skip: has("skip"),
+        seek: has("seek"),
+        shuffle: has("shuffle"),
+        repeat: has("repeat"),
+        volume: has("volume") || external.volume.is_some(),
+        volume_db: external.volume.as_ref().is_some_and(|v| v.db),
+        mute: has("mute"),
+        ..Default::default()
+    };
+    let volume_control = external.volume.as_ref().map(|v| VolumeControl {
+        value: v.value,
+        min: v.min,
+        max: v.max,
+        step: v.step,
+        is_muted: v.is_muted,
+        scale: if v.db {
+            VolumeScale::Decibel
+        } else {
+            VolumeScale::Percentage
+        },
+        // Volume notifications are matched to the zone by this id
+        output_id: Some(zone_id.clone()),
+    });
+
+    let mut zone = Zone {
+        zone_id,
+        zone_name: external.name.clone(),
+        state: PlaybackState::Unknown,
+        volume_control,
+        now_playing: None,
+        source: prefix.to_string(),
+        is_controllable: capabilities.transport,
+        is_seekable: capabilities.seek,
+        last_updated: 0,
+        is_play_allowed: false,
+        is_pause_allowed: false,
+        is_next_allowed: capabilities.skip,
+        is_previous_allowed: capabilities.skip,
+        capabilities,
+        is_stale: false,
+    };
+    let state = external
+        .state
+        .as_deref()
+        .map(PlaybackState::from)
+        .unwrap_or(PlaybackState::Stopped);
+    apply_state(&mut zone, state);
+    zone
+}
+
+// =============================================================================
+// Adapter
+// =============================================================================
+
+type Reply = std::result::Result<Value, RpcError>;
+
+struct ExternalState {
+    /// Zones by raw id
+    zones: HashMap<String, Zone>,
+    /// Queue of lines for the child's stdin while it runs
+    writer: Option<mpsc::Sender<String>>,
+    pending: HashMap<u64, oneshot::Sender<Reply>>,
+    next_id: u64,
+    running: bool,
+    /// Set by stop(); a child exiting now is not a failure
+    stopping: bool,
+}
+
+/// One supervised external adapter process
+#[derive(Clone)]
+pub struct ExternalAdapter {
+    config: Arc<ExternalAdapterConfig>,
+    /// Leaked once per configured adapter: lifecycle names are `&'static str`
+    prefix: &'static str,
+    state: Arc<RwLock<ExternalState>>,
+    bus: SharedBus,
+    shutdown: Arc<RwLock<CancellationToken>>,
+    /// Signalled whenever the child process exits
+    exited: Arc<Notify>,
+}
+
+impl ExternalAdapter {
+    pub fn new(config: ExternalAdapterConfig, bus: SharedBus) -> Self {
+        let prefix: &'static str = Box::leak(config.prefix().into_boxed_str());
+        Self {
+            config: Arc::new(config),
+            prefix,
+            state: Arc::new(RwLock::new(ExternalState {
+                zones: HashMap::new(),
+                writer: None,
+                pending: HashMap::new(),
+                next_id: 0,
+                running: false,
+                stopping: false,
+            })),
+            bus,
+            shutdown: Arc::new(RwLock::new(CancellationToken::new())),
+            exited: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn config(&self) -> &ExternalAdapterConfig {
+        &self.config
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Start the child under AdapterHandle supervision (internal - use Startable trait)
+    async fn start_internal(&self) -> Result<()> {
+        {
+            let mut state = self.state.write().await;
+            if state.running {
+                return Ok(());
+            }
+            state.running = true;
+            state.stopping = false;
+        }
+
+        let shutdown = {
+            let mut token = self.shutdown.write().await;
+            *token = CancellationToken::new();
+            token.clone()
+        };
+
+        let handle = AdapterHandle::new(self.clone(), self.bus.clone(), shutdown);
+        let state = self.state.clone();
+        tokio::spawn(async move {
+            let _ = handle.run_with_retry(RetryConfig::default()).await;
+            state.write().await.running = false;
+        });
+
+        info!("External adapter {} started", self.prefix);
+        Ok(())
+    }
+
+    /// Ask the child to exit, then cancel the run
+    async fn stop_internal(&self) {
+        let writer = {
+            let mut state = self.state.write().await;
+            state.stopping = true;
+            state.writer.clone()
+        };
+
+        if let Some(writer) = writer {
+            let exited = self.exited.notified();
+            tokio::pin!(exited);
+            exited.as_mut().enable();
+            if writer
+                .send(notification("shutdown", json!({})))
+                .await
+                .is_ok()
+                && tokio::time::timeout(SHUTDOWN_GRACE, exited).await.is_err()
+            {
+                warn!("{}: did not exit after shutdown, killing", self.prefix);
+            }
+        }
+
+        self.shutdown.read().await.cancel();
+        // A killed child never reaches the cleanup at the end of run()
+        self.clear().await;
+        self.state.write().await.running = false;
+        info!("External adapter {} stopped", self.prefix);
+    }
+
+    fn spawn_child(&self) -> Result<Child> {
+        Command::new(&self.config.command)
+            .args(&self.config.args)
+            .envs(&self.config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("{}: failed to run {}", self.prefix, self.config.command))
+    }
+
+    /// Send a request to the child and wait for its answer
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let (tx, rx) = oneshot::channel();
+        let (id, writer) = {
+            let mut state = self.state.write().await;
+            let Some(writer) = state.writer.clone() else {
+                bail!("{} is not running", self.prefix);
+            };
+            state.next_id += 1;
+            let id = state.next_id;
+            state.pending.insert(id, tx);
+            (id, writer)
+        };
+
+        let line = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        let result = async {
+            writer
+                .send(line.to_string())
+                .await
+                .map_err(|_| anyhow!("{} stopped", self.prefix))?;
+            match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+                Ok(Ok(reply)) => reply.map_err(anyhow::Error::new),
+                Ok(Err(_)) => Err(anyhow!(
+                    "{} exited before answering {}",
+                    self.prefix,
+                    method
+                )),
+                Err(_) => Err(anyhow!(
+                    "{} did not answer {} within {:?}",
+                    self.prefix,
+                    method,
+                    REQUEST_TIMEOUT
+                )),
+            }
+        }
+        .await;
+
+        if result.is_err() {
+            self.state.write().await.pending.remove(&id);
+        }
+        result
+    }
+
+    /// Handle one line from the child's stdout
+    async fn handle_line(&self, stdin: &mut ChildStdin, line: &str) {
+        let message: RpcMessage = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("{}: ignoring invalid message: {}", self.prefix, e);
+                return;
+            }
+        };
+
+        match (message.id, message.method) {
+            (Some(id), None) => {
+                let Some(id) = id.as_u64() else {
+                    warn!("{}: response with unknown id {}", self.prefix, id);
+                    return;
+                };
+                let reply = match message.error {
+                    Some(error) => Err(error),
+                    None => Ok(message.result.unwrap_or(Value::Null)),
+                };
+                let pending = self.state.write().await.pending.remove(&id);
+                match pending {
+                    Some(tx) => {
+                        if tx.send(reply).is_err() {
+                            debug!("{}: response {} arrived after timeout", self.prefix, id);
+                        }
+                    }
+                    None => debug!("{}: response to unknown request {}", self.prefix, id),
+                }
+            }
+            (None, Some(method)) => {
+                if let Err(e) = self.handle_notification(&method, message.params).await {
+                    warn!("{}: bad {} notification: {}", self.prefix, method, e);
+                }
+            }
+            // The bridge serves no methods
+            (Some(id), Some(method)) => {
+                let reply = json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {"code": ERROR_METHOD_NOT_FOUND, "message": format!("unknown method {}", method)},
+                });
+                if let Err(e) = write_line(stdin, &reply.to_string()).await {
+                    warn!("{}: failed to answer request: {}", self.prefix, e);
+                }
+            }
+            (None, None) => warn!("{}: ignoring message without id or method", self.prefix),
+        }
+    }
+
+    /// Apply a notification from the child and republish it on the bus
+    async fn handle_notification(&self, method: &str, params: Value) -> Result<()> {
+        let prefix = self.prefix;
+        match method {
+            "zone.discovered" => {
+                let params: ZoneDiscoveredParams = serde_json::from_value(params)?;
+                let zone = external_zone_to_zone(prefix, &params.zone);
+                self.state
+                    .write()
+                    .await
+                    .zones
+                    .insert(params.zone.id, zone.clone());
+                self.bus.publish(BusEvent::ZoneDiscovered { zone });
+            }
+            "zone.updated" => {
+                let params: ZoneUpdatedParams = serde_json::from_value(params)?;
+                let updated = {
+                    let mut state = self.state.write().await;
+                    let zone = state
+                        .zones
+                        .get_mut(&params.zone_id)
+                        .ok_or_else(|| anyhow!("unknown zone {}", params.zone_id))?;
+                    if let Some(name) = params.name {
+                        zone.zone_name = name;
+                    }
+                    if let Some(playback) = params.state.as_deref() {
+                        apply_state(zone, PlaybackState::from(playback));
+                    }
+                    (zone.zone_name.clone(), zone.state)
+                };
+                self.bus.publish(BusEvent::ZoneUpdated {
+                    zone_id: PrefixedZoneId::external(prefix, &params.zone_id),
+                    display_name: updated.0,
+                    state: updated.1.to_string(),
+                });
+            }
+            "zone.removed" => {
+                let params: ZoneRemovedParams = serde_json::from_value(params)?;
+                self.state.write().await.zones.remove(&params.zone_id);
+                self.bus.publish(BusEvent::ZoneRemoved {
+                    zone_id: PrefixedZoneId::external(prefix, &params.zone_id),
+                });
+            }
+            "now_playing" => {
+                let params: NowPlayingParams = serde_json::from_value(params)?;
+                {
+                    let mut state = self.state.write().await;
+                    let zone = state
+                        .zones
+                        .get_mut(&params.zone_id)
+                        .ok_or_else(|| anyhow!("unknown zone {}", params.zone_id))?;
+                    zone.now_playing = Some(NowPlaying {
+                        title: params.title.clone().unwrap_or_default(),
+                        artist: params.artist.clone().unwrap_or_default(),
+                        album: params.album.clone().unwrap_or_default(),
+                        image_key: params.image_key.clone(),
+                        seek_position: None,
+                        duration: params.duration,
+                        metadata: None,
+                    });
+                }
+                self.bus.publish(BusEvent::NowPlayingChanged {
+                    zone_id: PrefixedZoneId::external(prefix, &params.zone_id),
+                    title: params.title,
+                    artist: params.artist,
+                    album: params.album,
+                    image_key: params.image_key,
+                    duration: params.duration,
+                });
+            }
+            "volume" => {
+                let params: VolumeParams = serde_json::from_value(params)?;
+                let output_id = {
+                    let mut state = self.state.write().await;
+                    let zone = state
+                        .zones
+                        .get_mut(&params.zone_id)
+                        .ok_or_else(|| anyhow!("unknown zone {}", params.zone_id))?;
+                    let vc = zone
+                        .volume_control
+                        .as_mut()
+                        .ok_or_else(|| anyhow!("zone {} has no volume", params.zone_id))?;
+                    vc.value = params.value;
+                    vc.is_muted = params.is_muted;
+                    zone.zone_id.clone()
+                };
+                self.bus.publish(BusEvent::VolumeChanged {
+                    output_id,
+                    value: params.value,
+                    is_muted: params.is_muted,
+                });
+            }
+            "seek" => {
+                let params: SeekParams = serde_json::from_value(params)?;
+                if let Some(np) = self
+                    .state
+                    .write()
+                    .await
+                    .zones
+                    .get_mut(&params.zone_id)
+                    .and_then(|z| z.now_playing.as_mut())
+                {
+                    np.seek_position = Some(params.position as f64);
+                }
+                self.bus.publish(BusEvent::SeekPositionChanged {
+                    zone_id: PrefixedZoneId::external(prefix, &params.zone_id),
+                    position: params.position,
+                });
+            }
+            "log" => {
+                let params: LogParams = serde_json::from_value(params)?;
+                match params.level.as_deref() {
+                    Some("error") | Some("warn") => warn!("{}: {}", prefix, params.message),
+                    Some("debug") => debug!("{}: {}", prefix, params.message),
+                    _ => info!("{}: {}", prefix, params.message),
+                }
+            }
+            other => bail!("unknown notification {}", other),
+        }
+        Ok(())
+    }
+
+    /// Forget the child's zones and fail its outstanding requests
+    async fn clear(&self) {
+        let zones: Vec<String> = {
+            let mut state = self.state.write().await;
+            state.writer = None;
+            // Dropping the senders wakes each waiting request with an error
+            state.pending.clear();
+            state.zones.drain().map(|(id, _)| id).collect()
+        };
+        for id in zones {
+            self.bus.publish(BusEvent::ZoneRemoved {
+                zone_id: PrefixedZoneId::external(self.prefix, &id),
+            });
+        }
+        self.exited.notify_waiters();
+    }
+
+    async fn exit_result(&self, status: std::io::Result<std::process::ExitStatus>) -> Result<()> {
+        if self.state.read().await.stopping {
+            return Ok(());
+        }
+        match status {
+            Ok(status) => Err(anyhow!("{} exited unexpectedly ({})", self.prefix, status)),
+            Err(e) => Err(anyhow!("{}: failed to wait for child: {}", self.prefix, e)),
+        }
+    }
+}
+
+async fn write_line(stdin: &mut ChildStdin, line: &str) -> std::io::Result<()> {
+    stdin.write_all(line.as_bytes()).await?;
+    stdin.write_all(b"\n").await?;
+    stdin.flush().await
+}
+
+#[async_trait]
+impl AdapterLogic for ExternalAdapter {
+    fn prefix(&self) -> &'static str {
+        self.prefix
+    }
+
+    async fn run(&self, ctx: AdapterContext) -> Result<()> {
+        let prefix = self.prefix;
+        let mut child = self.spawn_child()?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("{}: child has no stdin", prefix))?;
+        let mut stdout = BufReader::new(
+            child
+                .stdout
+                .take()
+                .ok_or_else(|| anyhow!("{}: child has no stdout", prefix))?,
+        )
+        .lines();
+        let mut stderr = BufReader::new(
+            child
+                .stderr
+                .take()
+                .ok_or_else(|| anyhow!("{}: child has no stderr", prefix))?,
+        )
+        .lines();
+
+        let (writer, mut outgoing) = mpsc::channel::<String>(OUTGOING_CAPACITY);
+        self.state.write().await.writer = Some(writer);
+
+        let init = self.request(
+            "initialize",
+            json!({"protocol": PROTOCOL_VERSION, "prefix": prefix}),
+        );
+        tokio::pin!(init);
+        let mut initialized = false;
+        let mut stderr_open = true;
+
+        let result = loop {
+            tokio::select! {
+                _ = ctx.shutdown.cancelled() => {
+                    if let Err(e) = write_line(&mut stdin, &notification("shutdown", json!({}))).await {
+                        debug!("{}: could not send shutdown: {}", prefix, e);
+                    }
+                    if tokio::time::timeout(SHUTDOWN_GRACE, child.wait()).await.is_err() {
+                        warn!("{}: did not exit after shutdown, killing", prefix);
+                        if let Err(e) = child.kill().await {
+                            warn!("{}: kill failed: {}", prefix, e);
+                        }
+                    }
+                    break Ok(());
+                }
+                reply = &mut init, if !initialized => {
+                    initialized = true;
+                    match reply {
+                        Ok(info) => {
+                            let details = info
+                                .get("name")
+                                .and_then(Value::as_str)
+                                .map(|name| match info.get("version").and_then(Value::as_str) {
+                                    Some(version) => format!("{} {}", name, version),
+                                    None => name.to_string(),
+                                });
+                            info!("{}: initialized ({})", prefix, details.as_deref().unwrap_or("no name"));
+                            self.bus.publish(BusEvent::AdapterConnected {
+                                adapter: prefix.to_string(),
+                                details,
+                            });
+                        }
+                        Err(e) => break Err(e.context(format!("{}: initialize failed", prefix))),
+                    }
+                }
+                Some(line) = outgoing.recv() => {
+                    if let Err(e) = write_line(&mut stdin, &line).await {
+                        break Err(anyhow!("{}: failed to write to child: {}", prefix, e));
+                    }
+                }
+                line = stdout.next_line() => match line {
+                    Ok(Some(line)) => self.handle_line(&mut stdin, &line).await,
+                    // stdout closed: the child is exiting
+                    Ok(None) | Err(_) => break self.exit_result(child.wait().await).await,
+                },
+                line = stderr.next_line(), if stderr_open => match line {
+                    Ok(Some(line)) => info!("{} (stderr): {}", prefix, line),
+                    Ok(None) | Err(_) => stderr_open = false,
+                },
+                status = child.wait() => break self.exit_result(status).await,
+            }
+        };
+
+        self.clear().await;
+        if result.is_err() {
+            self.bus.publish(BusEvent::AdapterDisconnected {
+                adapter: prefix.to_string(),
+                reason: Some("process exited".to_string()),
+            });
+        }
+        result
+    }
+
+    async fn handle_command(
+        &self,
+        zone_id: &str,
+        command: AdapterCommand,
+    ) -> Result<AdapterCommandResponse> {
+        let raw = zone_id
+            .strip_prefix(self.prefix)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .unwrap_or(zone_id);
+
+        match self.request("command", command_params(raw, &command)).await {
+            Ok(_) => Ok(AdapterCommandResponse::from_result(Ok(()))),
+            Err(e)
+                if e.downcast_ref::<RpcError>().is_some_and(|rpc| {
+                    rpc.code == ERROR_UNSUPPORTED || rpc.code == ERROR_METHOD_NOT_FOUND
+                }) =>
+            {
+                Ok(AdapterCommandResponse::unsupported(self.prefix, &command))
+            }
+            Err(e) => Ok(AdapterCommandResponse::from_result(Err(e))),
+        }
+    }
+
+    async fn zone_snapshot(&self) -> Option<Vec<Zone>> {
+        let state = self.state.read().await;
+        Some(state.zones.values().cloned().collect())
+    }
+}
+
+#[async_trait]
+impl Startable for ExternalAdapter {
+    fn name(&self) -> &'static str {
+        self.prefix
+    }
+
+    async fn start(&self) -> Result<()> {
+        self.start_internal().await
+    }
+
+    async fn stop(&self) {
+        self.stop_internal().await
+    }
+}
+
+/// All configured external adapters
+pub struct ExternalAdapters {
+    adapters: Vec<Arc<ExternalAdapter>>,
+}
+
+impl ExternalAdapters {
+    /// Create adapters for every valid entry in `external_adapters.json`
+    pub fn load(bus: SharedBus) -> Self {
+        Self::from_configs(load_external_configs(), bus)
+    }
+
+    pub fn from_configs(configs: Vec<ExternalAdapterConfig>, bus: SharedBus) -> Self {
+        Self {
+            adapters: configs
+                .into_iter()
+                .map(|config| Arc::new(ExternalAdapter::new(config, bus.clone())))
+                .collect(),
+        }
+    }
+
+    /// No external adapters (tests and harnesses)
+    pub fn empty() -> Self {
+        Self {
+            adapters: Vec::new(),
+        }
+    }
+
+    pub fn list(&self) -> &[Arc<ExternalAdapter>] {
+        &self.adapters
+    }
+
+    /// Adapter owning a zone id (`ext-<name>:<id>`)
+    pub fn for_zone(&self, zone_id: &str) -> Option<&Arc<ExternalAdapter>> {
+        let (prefix, _) = zone_id.split_once(':')?;
+        self.adapters.iter().find(|a| a.prefix == prefix)
+    }
+
+    /// Send a command to the external adapter owning the zone
+    pub async fn handle_command(
+        &self,
+        zone_id: &str,
+        command: AdapterCommand,
+    ) -> Result<AdapterCommandResponse> {
+        let adapter = self
+            .for_zone(zone_id)
+            .ok_or_else(|| anyhow!("External adapter not found for zone: {}", zone_id))?;
+        adapter.handle_command(zone_id, command).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::create_bus;
+
+    #[test]
+    fn test_invalid_and_duplicate_configs_are_skipped() {
+        let configs = parse_configs(
+            r#"[
+                {"name": "jukebox", "command": "/usr/bin/jukebox-bridge"},
+                {"name": "Bad Name", "command": "x"},
+                {"name": "nocommand", "command": " "},
+                {"name": "jukebox", "command": "other"},
+                {"name": "radio", "command": "radio", "args": ["--fm"], "enabled": false}
+            ]"#,
+        );
+        let names: Vec<&str> = configs.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["jukebox", "radio"]);
+        assert!(configs[0].enabled);
+        assert_eq!(configs[1].prefix(), "ext-radio");
+    }
+
+    #[test]
+    fn test_command_params_carry_values() {
+        assert_eq!(
+            command_params("a", &AdapterCommand::VolumeRelative(-2)),
+            json!({"zone_id": "a", "action": "volume_relative", "value": -2})
+        );
+        assert_eq!(
+            command_params("a", &AdapterCommand::Repeat(crate::bus::RepeatMode::All)),
+            json!({"zone_id": "a", "action": "repeat", "value": "all"})
+        );
+        assert_eq!(
+            command_params("a", &AdapterCommand::Next),
+            json!({"zone_id": "a", "action": "next", "value": null})
+        );
+    }
+
+    #[test]
+    fn test_external_zone_conversion() {
+        let external: ExternalZone = serde_json::from_value(json!({
+            "id": "den",
+            "name": "Den",
+            "state": "playing",
+            "volume": {"value": 30},
+            "capabilities": ["transport", "skip"]
+        }))
+        .unwrap();
+        let zone = external_zone_to_zone("ext-jukebox", &external);
+        assert_eq!(zone.zone_id, "ext-jukebox:den");
+        assert_eq!(zone.state, PlaybackState::Playing);
+        assert!(zone.is_pause_allowed && !zone.is_play_allowed);
+        assert!(zone.is_next_allowed && !zone.is_seekable);
+        let vc = zone.volume_control.unwrap();
+        assert_eq!((vc.min, vc.max), (0.0, 100.0));
+        assert_eq!(vc.output_id.as_deref(), Some("ext-jukebox:den"));
+        assert!(PrefixedZoneId::parse(&zone.zone_id).is_some());
+    }
+
+    /// Minimal child: answers initialize, announces a zone, acks commands
+    #[cfg(unix)]
+    const TEST_CHILD: &str = r#"
+read -r init
+echo '{"jsonrpc":"2.0","id":1,"result":{"name":"test"}}'
+echo '{"jsonrpc":"2.0","method":"zone.discovered","params":{"zone":{"id":"a","name":"Kitchen","capabilities":["transport"]}}}'
+while read -r line; do
+  case "$line" in
+    *'"shutdown"'*) exit 0 ;;
+    *'"next"'*)
+      id=$(echo "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+      echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"error\":{\"code\":-32001,\"message\":\"no skip\"}}" ;;
+    *'"command"'*)
+      id=$(echo "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+      echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":null}" ;;
+  esac
+done
+"#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_child_process_round_trip() {
+        let bus = create_bus();
+        let mut rx = bus.subscribe();
+        let adapter = ExternalAdapter::new(
+            ExternalAdapterConfig {
+                name: "test".to_string(),
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), TEST_CHILD.to_string()],
+                env: BTreeMap::new(),
+                enabled: true,
+            },
+            bus.clone(),
+        );
+        adapter.start().await.unwrap();
+
+        let zone = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(BusEvent::ZoneDiscovered { zone }) = rx.recv().await {
+                    return zone;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(zone.zone_id, "ext-test:a");
+        assert_eq!(zone.state, PlaybackState::Stopped);
+
+        let resp = adapter
+            .handle_command("ext-test:a", AdapterCommand::Play)
+            .await
+            .unwrap();
+        assert!(resp.success);
+        let resp = adapter
+            .handle_command("ext-test:a", AdapterCommand::Next)
+            .await
+            .unwrap();
+        assert!(resp.is_unsupported());
+        assert_eq!(adapter.zone_snapshot().await.unwrap().len(), 1);
+
+        adapter.stop().await;
+        let stopped = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(BusEvent::AdapterStopped { adapter }) = rx.recv().await {
+                    return adapter;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(stopped, "ext-test");
+        assert!(adapter.zone_snapshot().await.unwrap().is_empty());
+    }
+}
@@ -6,6 +6,7 @@
 use anyhow::Result;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
@@ -55,22 +56,38 @@ pub struct AdapterHandle<T: AdapterLogic> {
     logic: Arc<T>,
     bus: SharedBus,
     shutdown: CancellationToken,
+    /// Name used in lifecycle events (defaults to the prefix)
+    name: &'static str,
 }
 
 impl<T: AdapterLogic> AdapterHandle<T> {
     pub fn new(logic: T, bus: SharedBus, shutdown: CancellationToken) -> Self {
+        let name = logic.prefix();
         Self {
             logic: Arc::new(logic),
             bus,
             shutdown,
+            name,
         }
     }
 
+    /// Report lifecycle events under a different name than the zone prefix
+    /// (companion adapters such as "lms-cli" share their main adapter's prefix)
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
     /// Get the adapter's prefix
     pub fn prefix(&self) -> &'static str {
         self.logic.prefix()
     }
 
+    /// Get the name used in lifecycle events
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
     /// Get access to the underlying logic (for command handling)
     pub fn logic(&self) -> &Arc<T> {
         &self.logic
@@ -84,10 +101,14 @@ impl<T: AdapterLogic> AdapterHandle<T> {
     ///
     /// For automatic retry on error, use `run_with_retry()` instead.
     pub async fn run(self) -> Result<()> {
-        let prefix = self.logic.prefix();
+        let prefix = self.name;
         info!("Starting adapter: {}", prefix);
 
         // Run once without retry
+        self.bus.publish(BusEvent::AdapterStarting {
+            adapter: prefix.to_string(),
+            attempt: 1,
+        });
         let result = self.run_once().await;
 
         // Automatic ACK - publish AdapterStopped
@@ -110,8 +131,10 @@ impl<T: AdapterLogic> AdapterHandle<T> {
     /// This is the preferred method for production use - it handles transient
     /// failures like service restarts automatically.
     pub async fn run_with_retry(self, config: RetryConfig) -> Result<()> {
-        let prefix = self.logic.prefix();
+        let prefix = self.name;
         let mut delay = config.initial_delay;
+        let mut attempt: u32 = 0;
+        let mut consecutive_failures: u32 = 0;
 
         loop {
             // Check for shutdown before attempting
@@ -121,6 +144,11 @@ impl<T: AdapterLogic> AdapterHandle<T> {
             }
 
             info!("{}: starting (retry delay: {:?})", prefix, delay);
+            attempt += 1;
+            self.bus.publish(BusEvent::AdapterStarting {
+                adapter: prefix.to_string(),
+                attempt,
+            });
 
             let start = Instant::now();
             match self.run_once().await {
@@ -138,9 +166,17 @@ impl<T: AdapterLogic> AdapterHandle<T> {
                             prefix, run_duration
                         );
                         delay = config.initial_delay;
+                        consecutive_failures = 0;
                     }
+                    consecutive_failures += 1;
 
                     warn!("{}: error ({}), retrying in {:?}", prefix, e, delay);
+                    self.bus.publish(BusEvent::AdapterRetrying {
+                        adapter: prefix.to_string(),
+                        error: e.to_string(),
+                        consecutive_failures,
+                        retry_in_secs: delay.as_secs(),
+                    });
 
                     // Wait with shutdown check
                     tokio::select! {
@@ -166,13 +202,30 @@ impl<T: AdapterLogic> AdapterHandle<T> {
         Ok(())
     }
 
+    /// Publish every zone the adapter has so lagging consumers can rebuild their state
+    async fn publish_snapshot(&self, reason: Option<&str>) {
+        let Some(zones) = self.logic.zone_snapshot().await else {
+            return;
+        };
+        info!(
+            "{}: resync requested ({}), republishing {} zones",
+            self.name,
+            reason.unwrap_or("no reason given"),
+            zones.len()
+        );
+        self.bus.publish(BusEvent::ZonesSnapshot {
+            adapter: self.logic.prefix().to_string(),
+            zones,
+        });
+    }
+
     /// Run the adapter once (internal helper)
     ///
     /// Returns:
     /// - `Ok(())` on clean shutdown (adapter should not restart)
     /// - `Err(...)` on error (adapter should restart)
     async fn run_once(&self) -> Result<()> {
-        let prefix = self.logic.prefix();
+        let prefix = self.name;
 
         // Initialize
         if let Err(e) = self.logic.init().await {
@@ -200,12 +253,25 @@ impl<T: AdapterLogic> AdapterHandle<T> {
                 result
             }
 
-            // Watch for shutdown signal on bus
+            // Watch the bus for shutdown and resync requests
             _ = async {
-                while let Ok(event) = rx.recv().await {
-                    if matches!(event, BusEvent::ShuttingDown { .. }) {
-                        info!("{}: received ShuttingDown event", prefix);
-                        break;
+                loop {
+                    match rx.recv().await {
+                        Ok(BusEvent::ShuttingDown { .. }) => {
+                            info!("{}: received ShuttingDown event", prefix);
+                            break;
+                        }
+                        Ok(BusEvent::ResyncRequested { adapter, reason }) => {
+                            if adapter.as_deref().is_none_or(|a| a == self.logic.prefix()) {
+                                self.publish_snapshot(reason.as_deref()).await;
+                            }
+                        }
+                        Ok(_) => {}
+                        // Missing events here is harmless; the token still covers shutdown
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("{}: bus watcher lagged, skipped {} events", prefix, skipped);
+                        }
+                        Err(RecvError::Closed) => break,
                     }
                 }
             } => {
@@ -337,6 +403,42 @@ mod tests {
         assert_eq!(attempt_tracker.load(Ordering::SeqCst), 3);
     }
 
+    #[tokio::test]
+    async fn test_run_with_retry_publishes_lifecycle_events() {
+        let bus = test_bus();
+        let mut rx = bus.subscribe();
+        let shutdown = CancellationToken::new();
+        let adapter = MockFailingAdapter::new("mock-failing", 2);
+
+        let handle = AdapterHandle::new(adapter, bus, shutdown).with_name("mock-companion");
+        let config = RetryConfig::new(Duration::from_millis(10), Duration::from_millis(100));
+        handle.run_with_retry(config).await.unwrap();
+
+        let mut starts = Vec::new();
+        let mut failures = Vec::new();
+        let mut stopped = None;
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                BusEvent::AdapterStarting { adapter, attempt } => {
+                    assert_eq!(adapter, "mock-companion");
+                    starts.push(attempt);
+                }
+                BusEvent::AdapterRetrying {
+                    error,
+                    consecutive_failures,
+                    ..
+                } => failures.push((consecutive_failures, error)),
+                BusEvent::AdapterStopped { adapter } => stopped = Some(adapter),
+                _ => {}
+            }
+        }
+        assert_eq!(starts, vec![1, 2, 3]);
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[1].0, 2);
+        assert_eq!(failures[1].1, "Simulated failure 2");
+        assert_eq!(stopped.as_deref(), Some("mock-companion"));
+    }
+
     #[tokio::test]
     async fn test_run_with_retry_shutdown_during_backoff() {
         let bus = test_bus();
@@ -554,4 +656,92 @@ mod tests {
             total
         );
     }
+
+    /// Mock adapter that runs until cancelled and owns one zone
+    struct MockZonesAdapter;
+
+    #[async_trait]
+    impl AdapterLogic for MockZonesAdapter {
+        fn prefix(&self) -> &'static str {
+            "mock-zones"
+        }
+
+        async fn run(&self, ctx: AdapterContext) -> Result<()> {
+            ctx.shutdown.cancelled().await;
+            Ok(())
+        }
+
+        async fn handle_command(
+            &self,
+            _zone_id: &str,
+            command: AdapterCommand,
+        ) -> Result<AdapterCommandResponse> {
+            Ok(AdapterCommandResponse::unsupported("mock-zones", &command))
+        }
+
+        async fn zone_snapshot(&self) -> Option<Vec<crate::bus::Zone>> {
+            Some(vec![crate::bus::Zone {
+                zone_id: "mock-zones:1".to_string(),
+                zone_name: "Mock".to_string(),
+                state: crate::bus::PlaybackState::Stopped,
+                volume_control: None,
+                now_playing: None,
+                source: "mock-zones".to_string(),
+                is_controllable: true,
+                is_seekable: false,
+                last_updated: 0,
+                is_play_allowed: true,
+                is_pause_allowed: false,
+                is_next_allowed: false,
+                is_previous_allowed: false,
+                capabilities: Default::default(),
+                is_stale: false,
+            }])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resync_request_publishes_zone_snapshot() {
+        let bus = test_bus();
+        let mut rx = bus.subscribe();
+        let shutdown = CancellationToken::new();
+
+        let handle = AdapterHandle::new(MockZonesAdapter, bus.clone(), shutdown.clone());
+        let task = tokio::spawn(handle.run());
+        // Let the handle subscribe before asking
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        bus.publish(BusEvent::ResyncRequested {
+            adapter: Some("other".to_string()),
+            reason: None,
+        });
+        bus.publish(BusEvent::ResyncRequested {
+            adapter: None,
+            reason: Some("test".to_string()),
+        });
+
+        let snapshot = tokio::time::timeout(Duration::from_secs(1), async {
+            loop {
+                if let Ok(BusEvent::ZonesSnapshot { adapter, zones }) = rx.recv().await {
+                    return (adapter, zones);
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(snapshot.0, "mock-zones");
+        assert_eq!(snapshot.1.len(), 1);
+
+        shutdown.cancel();
+        task.await.unwrap().unwrap();
+
+        // Only the request addressed to everyone was answered
+        let mut extra = 0;
+        while let Ok(event) = rx.try_recv() {
+            if matches!(event, BusEvent::ZonesSnapshot { .. }) {
+                extra += 1;
+            }
+        }
+        assert_eq!(extra, 0);
+    }
 }
@@ -26,9 +26,11 @@ use tokio::net::{TcpStream, UdpSocket};
 use tokio::sync::{Mutex, RwLock};
 use tokio::time::timeout;
 
+use crate::adapters::traits::{AdapterCommand, AdapterCommandResponse};
 use crate::bus::{
-    BusEvent, NowPlaying as BusNowPlaying, PlaybackState, PrefixedZoneId, SharedBus, TrackMetadata,
-    VolumeControl as BusVolumeControl, VolumeScale, Zone as BusZone,
+    BusEvent, NowPlaying as BusNowPlaying, PlaybackState, PrefixedZoneId, RepeatMode, SharedBus,
+    TrackMetadata, VolumeControl as BusVolumeControl, VolumeScale, Zone as BusZone,
+    ZoneCapabilities,
 };
 use crate::config::{get_config_file_path, read_config_file};
 
@@ -47,7 +49,7 @@ struct SavedHqpConfig {
 }
 
 /// Named instance config (for multi-instance support)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct HqpInstanceConfig {
     pub name: String,
     pub host: String,
@@ -128,8 +130,8 @@ pub fn save_hqp_configs(configs: &[HqpInstanceConfig]) -> bool {
     }
 }
 
-const DEFAULT_PORT: u16 = 4321;
-const DEFAULT_WEB_PORT: u16 = 8088;
+pub const DEFAULT_PORT: u16 = 4321;
+pub const DEFAULT_WEB_PORT: u16 = 8088;
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
 const RESPONSE_TIMEOUT: Duration = Duration::from_secs(3);
 const PROFILE_PATH: &str = "/config/profile/load";
@@ -330,6 +332,8 @@ struct HqpAdapterState {
     shapers: Vec<ListItem>,
     rates: Vec<RateItem>,
     volume_range: Option<VolumeRange>,
+    /// Last pipeline (filter, shaper, rate) published as `HqpPipelineChanged`
+    last_pipeline: Option<(String, String, String)>,
     // Web client state for profiles
     profiles: Vec<HqpProfile>,
     hidden_fields: HashMap<String, String>,
@@ -365,6 +369,7 @@ impl Default for HqpAdapterState {
             shapers: Vec::new(),
             rates: Vec::new(),
             volume_range: None,
+            last_pipeline: None,
             profiles: Vec::new(),
             hidden_fields: HashMap::new(),
             config_title: None,
@@ -1370,6 +1375,21 @@ impl HqpAdapter {
         Ok(())
     }
 
+    /// Set repeat mode (0=off, 1=track, 2=all)
+    pub async fn set_repeat(&self, mode: u8) -> Result<()> {
+        let xml = Self::build_request("SetRepeat", &[("value", &mode.to_string())]);
+        self.send_command(&xml).await?;
+        Ok(())
+    }
+
+    /// Set random (shuffle) playback
+    pub async fn set_random(&self, enabled: bool) -> Result<()> {
+        let value = if enabled { "1" } else { "0" };
+        let xml = Self::build_request("SetRandom", &[("value", value)]);
+        self.send_command(&xml).await?;
+        Ok(())
+    }
+
     /// Control playback
     pub async fn control(&self, action: &str) -> Result<()> {
         match action {
@@ -1382,6 +1402,67 @@ impl HqpAdapter {
         }
     }
 
+    /// Handle a source-agnostic zone command for this instance's zone.
+    ///
+    /// HQPlayer isn't driven through AdapterLogic (instances are managed by
+    /// HqpInstanceManager), but it answers the same command surface.
+    pub async fn handle_command(&self, command: AdapterCommand) -> Result<AdapterCommandResponse> {
+        let result = match command {
+            AdapterCommand::Play => self.play().await,
+            AdapterCommand::Pause => self.pause().await,
+            AdapterCommand::PlayPause => match self.get_state().await {
+                Ok(state) if state.state == 2 => self.pause().await,
+                Ok(_) => self.play().await,
+                Err(e) => Err(e),
+            },
+            AdapterCommand::Stop => self.stop().await,
+            AdapterCommand::Next => self.next().await,
+            AdapterCommand::Previous => self.previous().await,
+            AdapterCommand::VolumeAbsolute(value) => self.set_volume_clamped(value, false).await,
+            AdapterCommand::VolumeRelative(delta) => self.set_volume_clamped(delta, true).await,
+            AdapterCommand::MuteToggle => self.volume_mute().await,
+            // HQPlayer only exposes a mute toggle and doesn't report mute state,
+            // so an explicit mute/unmute can't be honoured reliably
+            AdapterCommand::Mute(_) => {
+                return Ok(AdapterCommandResponse::unsupported("hqplayer", &command));
+            }
+            AdapterCommand::Seek(position) => self.seek(position).await,
+            AdapterCommand::SeekRelative(offset) => match self.get_playback_status().await {
+                Ok(status) => {
+                    let target = (i64::from(status.position) + i64::from(offset)).max(0);
+                    self.seek(target.min(i64::from(u32::MAX)) as u32).await
+                }
+                Err(e) => Err(e),
+            },
+            AdapterCommand::Shuffle(enabled) => self.set_random(enabled).await,
+            AdapterCommand::Repeat(mode) => {
+                let value = match mode {
+                    RepeatMode::Off => 0,
+                    RepeatMode::One => 1,
+                    RepeatMode::All => 2,
+                };
+                self.set_repeat(value).await
+            }
+        };
+
+        Ok(AdapterCommandResponse::from_result(result))
+    }
+
+    /// Set volume, clamped to the instance's reported range.
+    ///
+    /// SAFETY: HQPlayer volume is in dB (e.g. -60..0); never send a value
+    /// outside the range the instance reports.
+    async fn set_volume_clamped(&self, value: i32, relative: bool) -> Result<()> {
+        let range = self.get_volume_range().await?;
+        let target = if relative {
+            let current = self.get_playback_status().await?.volume;
+            current + value
+        } else {
+            value
+        };
+        self.set_volume(target.max(range.min).min(range.max)).await
+    }
+
     /// Get full pipeline status
     pub async fn get_pipeline_status(&self) -> Result<PipelineStatus> {
         // Core data: State + Status (2 TCP commands)
@@ -1453,7 +1534,7 @@ impl HqpAdapter {
             _ => "Unknown",
         };
 
-        Ok(PipelineStatus {
+        let pipeline = PipelineStatus {
             status: PipelineState {
                 state: state_str.to_string(),
                 // State.mode and State.active_mode are INDEX (0,1,2) - look up by ModesItem.index
@@ -1578,7 +1659,44 @@ impl HqpAdapter {
                         .collect(),
                 },
             },
-        })
+        };
+
+        self.publish_pipeline_change(&pipeline.status).await;
+        Ok(pipeline)
+    }
+
+    /// Publish `HqpPipelineChanged` when the active filter, shaper or rate differs
+    /// from the last pipeline seen (rate reads "DSD256" in SDM mode, else Hz)
+    async fn publish_pipeline_change(&self, status: &PipelineState) {
+        let mode = status.active_mode.to_uppercase();
+        let rate = if (mode.contains("SDM") || mode.contains("DSD")) && status.active_rate > 0 {
+            format!("DSD{}", status.active_rate / 44_100)
+        } else {
+            status.active_rate.to_string()
+        };
+        let pipeline = (
+            status.active_filter.clone(),
+            status.active_shaper.clone(),
+            rate,
+        );
+
+        let host = {
+            let mut state = self.state.write().await;
+            if state.last_pipeline.as_ref() == Some(&pipeline) {
+                return;
+            }
+            state.last_pipeline = Some(pipeline.clone());
+            state.host.clone().unwrap_or_default()
+        };
+
+        let (filter, shaper, rate) = pipeline;
+        let non_empty = |s: String| (!s.is_empty()).then_some(s);
+        self.bus.publish(BusEvent::HqpPipelineChanged {
+            host,
+            filter: non_empty(filter),
+            shaper: non_empty(shaper),
+            rate: non_empty(rate),
+        });
     }
 
     // =========================================================================
@@ -2116,6 +2234,21 @@ impl HqpAdapter {
             is_pause_allowed: state == PlaybackState::Playing,
             is_next_allowed: true,
             is_previous_allowed: true,
+            capabilities: ZoneCapabilities {
+                transport: true,
+                skip: true,
+                seek: true,
+                shuffle: true,
+                repeat: true,
+                volume: vol_range.enabled,
+                volume_db: vol_range.enabled,
+                // Only a mute toggle, and explicit mute/unmute is refused (see handle_command)
+                mute: false,
+                grouping: false,
+                source_selection: false,
+                standby: false,
+            },
+            is_stale: false,
         }
     }
 }
@@ -2229,6 +2362,30 @@ impl HqpInstanceManager {
         self.get_or_create("default").await
     }
 
+    /// Find the instance backing an `hqplayer:` zone.
+    /// Zone IDs use the instance name, or the host for unnamed instances.
+    pub async fn get_for_zone(&self, raw_zone_id: &str) -> Option<Arc<HqpAdapter>> {
+        let adapters: Vec<(String, Arc<HqpAdapter>)> = {
+            let instances = self.instances.read().await;
+            instances
+                .iter()
+                .map(|(name, adapter)| (name.clone(), adapter.clone()))
+                .collect()
+        };
+
+        for (name, adapter) in &adapters {
+            if name == raw_zone_id {
+                return Some(adapter.clone());
+            }
+        }
+        for (_, adapter) in adapters {
+            if adapter.get_status().await.host.as_deref() == Some(raw_zone_id) {
+                return Some(adapter);
+            }
+        }
+        None
+    }
+
     /// List all configured instances
     pub async fn list_instances(&self) -> Vec<HqpInstanceInfo> {
         // Clone adapters while holding lock, then release before async operations
@@ -2620,3 +2777,35 @@ fn extract_xml_attr(xml: &str, attr: &str) -> Option<String> {
 
     None
 }
+
+#[cfg(test)]
+pub(crate) fn capability_fixture() -> crate::adapters::traits::CapabilityFixture {
+    // Unconfigured (not loaded from disk), so commands fail without a connection
+    let adapter = Arc::new(HqpAdapter {
+        state: Arc::new(RwLock::new(HqpAdapterState::default())),
+        connection: Arc::new(Mutex::new(None)),
+        http_client: Client::new(),
+        bus: crate::bus::create_bus(),
+    });
+    let range = VolumeRange {
+        min: -60,
+        max: 0,
+        step: 1,
+        enabled: true,
+        adaptive: false,
+    };
+    let zone = HqpAdapter::hqp_status_to_zone(
+        "10.0.0.5",
+        None,
+        &HqpInfo::default(),
+        &HqpStatus::default(),
+        &range,
+    );
+    crate::adapters::traits::CapabilityFixture {
+        zone,
+        send: Box::new(move |command| {
+            let adapter = adapter.clone();
+            Box::pin(async move { adapter.handle_command(command).await })
+        }),
+    }
+}
@@ -34,6 +34,15 @@
 //! Each has its own AdapterHandle with independent retry. Use `create_lms_adapters()`
 //! factory function to create both with shared state.
 //!
+//! ## Multiple Servers
+//!
+//! The server in `lms-config.json` is the primary one and its players keep plain
+//! `lms:<player_id>` zone ids. Additional servers live in `lms-servers.json`; each
+//! gets its own `LmsAdapter`/`LmsCliAdapter` pair (lifecycle names `lms-<id>` and
+//! `lms-<id>-cli`) and namespaced zone ids `lms:<id>/<player_id>`, so a player that
+//! moves between servers never collides with itself. The primary adapter routes
+//! player ids with a namespace to the server that owns them.
+//!
 //! ## Configuration
 //!
 //! - `LMS_POLL_INTERVAL`: Base poll interval in seconds (default: 2)
@@ -44,9 +53,9 @@ use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, Mutex};
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::TcpStream;
@@ -61,35 +70,111 @@ use crate::adapters::traits::{
     AdapterCommand, AdapterCommandResponse, AdapterContext, AdapterLogic,
 };
 use crate::adapters::Startable;
-use crate::bus::{BusEvent, PlaybackState, PrefixedZoneId, SharedBus, VolumeControl, Zone};
+use crate::bus::{
+    BusEvent, PlaybackState, PrefixedZoneId, RepeatMode, SharedBus, TrackMetadata, VolumeControl,
+    Zone, ZoneCapabilities,
+};
 use crate::config::{get_config_file_path, read_config_file};
 
 const LMS_CONFIG_FILE: &str = "lms-config.json";
+/// Additional servers (the primary stays in `lms-config.json`)
+const LMS_SERVERS_FILE: &str = "lms-servers.json";
 /// Request ID for LMS JSON-RPC calls (aids debugging in LMS logs)
 const LMS_REQUEST_ID: i32 = 217;
 
-/// Strip "lms:" prefix from player IDs.
-/// MCP and aggregator use prefixed IDs (e.g., "lms:00:11:22:33:44:55"), but LMS API expects bare IDs.
+/// Strip "lms:" prefix and server namespace from player IDs.
+/// MCP and aggregator use prefixed IDs (e.g., "lms:00:11:22:33:44:55" or
+/// "lms:upstairs/00:11:22:33:44:55"), but LMS API expects bare IDs.
 fn strip_lms_prefix(id: &str) -> &str {
-    id.strip_prefix("lms:").unwrap_or(id)
+    let id = id.strip_prefix("lms:").unwrap_or(id);
+    id.split_once('/').map_or(id, |(_, player_id)| player_id)
 }
 
-/// Saved config for persistence
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SavedLmsConfig {
-    host: String,
-    port: u16,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    username: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    password: Option<String>,
+/// Server namespace of a player or zone ID ("lms:upstairs/00:11:..." -> "upstairs")
+fn lms_server_of(id: &str) -> Option<&str> {
+    let id = id.strip_prefix("lms:").unwrap_or(id);
+    id.split_once('/').map(|(server, _)| server)
+}
+
+/// `&'static` copy of a server id or adapter name. Each distinct string is leaked
+/// once, so removing and re-adding a server doesn't leak again.
+fn intern(s: String) -> &'static str {
+    static INTERNED: LazyLock<Mutex<HashSet<&'static str>>> = LazyLock::new(Default::default);
+    let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+    match interned.get(s.as_str()) {
+        Some(existing) => *existing,
+        None => {
+            let leaked: &'static str = Box::leak(s.into_boxed_str());
+            interned.insert(leaked);
+            leaked
+        }
+    }
+}
+
+/// Zone ID for a player on the given server (None = primary)
+fn lms_zone_id(server: Option<&str>, player_id: &str) -> PrefixedZoneId {
+    match server {
+        Some(server) => PrefixedZoneId::lms(format!("{}/{}", server, player_id)),
+        None => PrefixedZoneId::lms(player_id),
+    }
+}
+
+/// Check an additional server ID (it becomes part of zone ids and adapter names)
+pub(crate) fn validate_server_id(id: &str) -> Result<()> {
+    let valid = !id.is_empty()
+        && id.len() <= 32
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !id.starts_with('-')
+        && !id.ends_with('-');
+    if !valid {
+        return Err(anyhow!(
+            "Invalid server id '{}' (use lowercase letters, digits and dashes)",
+            id
+        ));
+    }
+    // "lms-cli" and "lms-<id>-cli" are taken by the CLI adapters
+    if id == "cli" || id.ends_with("-cli") {
+        return Err(anyhow!("Server id '{}' is reserved", id));
+    }
+    Ok(())
+}
+
+/// Server ID derived from a host name or address ("192.168.1.20" -> "192-168-1-20")
+pub(crate) fn server_id_for_host(host: &str) -> String {
+    let slug: String = host
+        .to_ascii_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.trim_matches('-');
+    let slug = slug.get(..28).unwrap_or(slug).trim_end_matches('-');
+    match slug {
+        "" => "server".to_string(),
+        id if validate_server_id(id).is_ok() => id.to_string(),
+        id => format!("{}-lms", id),
+    }
 }
 
 fn config_path() -> PathBuf {
     get_config_file_path(LMS_CONFIG_FILE)
 }
 
-const DEFAULT_PORT: u16 = 9000;
+/// Saved additional server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct SavedLmsServer {
+    pub(crate) id: String,
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) password: Option<String>,
+}
+
+/// LMS web/JSON-RPC port
+pub const DEFAULT_PORT: u16 = 9000;
 /// CLI telnet port for event subscription
 const CLI_PORT: u16 = 9090;
 /// Default poll interval in seconds (when no subscription active)
@@ -125,6 +210,7 @@ struct NowPlayingUpdate {
     artist: Option<String>,
     album: Option<String>,
     image_key: Option<String>,
+    duration: Option<f64>,
 }
 
 /// Parsed CLI event from LMS
@@ -247,11 +333,22 @@ pub fn parse_cli_event(line: &str) -> CliEvent {
 struct LmsRpc {
     state: Arc<RwLock<LmsState>>,
     client: Client,
+    /// Server namespace for zone ids (None = primary server)
+    server: Option<&'static str>,
 }
 
 impl LmsRpc {
-    fn new(state: Arc<RwLock<LmsState>>, client: Client) -> Self {
-        Self { state, client }
+    fn new(state: Arc<RwLock<LmsState>>, client: Client, server: Option<&'static str>) -> Self {
+        Self {
+            state,
+            client,
+            server,
+        }
+    }
+
+    /// Zone ID for a player on this server
+    fn zone_id(&self, player_id: &str) -> PrefixedZoneId {
+        lms_zone_id(self.server, player_id)
     }
 
     async fn base_url(&self) -> Result<String> {
@@ -321,7 +418,12 @@ impl LmsRpc {
         let result = self
             .execute(
                 Some(player_id),
-                vec![json!("status"), json!("-"), json!(1), json!("tags:aAdltKc")],
+                vec![
+                    json!("status"),
+                    json!("-"),
+                    json!(1),
+                    json!("tags:aAdltKcgoIrT"),
+                ],
             )
             .await?;
 
@@ -405,6 +507,7 @@ impl LmsRpc {
             artwork_track_id: artwork_id.clone(),
             coverid: artwork_id,
             artwork_url,
+            metadata: parse_track_metadata(&playlist_loop),
             ..Default::default()
         })
     }
@@ -470,6 +573,9 @@ pub struct LmsPlayer {
     pub artwork_track_id: Option<String>,
     pub coverid: Option<String>,
     pub artwork_url: Option<String>,
+    /// Stream format details for the current track (codec, sample rate, bit depth)
+    #[serde(default)]
+    pub metadata: Option<TrackMetadata>,
 }
 
 impl Default for LmsPlayer {
@@ -494,6 +600,7 @@ impl Default for LmsPlayer {
             artwork_track_id: None,
             coverid: None,
             artwork_url: None,
+            metadata: None,
         }
     }
 }
@@ -622,6 +729,27 @@ impl Default for LmsState {
     }
 }
 
+/// An additional LMS server: polling and CLI adapters sharing one state
+#[derive(Clone)]
+pub struct LmsServer {
+    pub adapter: Arc<LmsAdapter>,
+    pub cli: Arc<LmsCliAdapter>,
+}
+
+/// Summary of one configured LMS server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LmsServerInfo {
+    /// Server namespace (None for the primary server)
+    pub id: Option<String>,
+    /// Adapter name in lifecycle events and diagnostics
+    pub adapter: String,
+    pub host: Option<String>,
+    pub port: u16,
+    pub connected: bool,
+    pub player_count: usize,
+    pub cli_subscription_active: bool,
+}
+
 /// LMS Adapter
 #[derive(Clone)]
 pub struct LmsAdapter {
@@ -630,26 +758,262 @@ pub struct LmsAdapter {
     bus: SharedBus,
     /// Wrapped in RwLock to allow creating fresh token on restart
     shutdown: Arc<RwLock<CancellationToken>>,
+    /// Name in lifecycle events ("lms", or "lms-<id>" for additional servers)
+    name: &'static str,
+    /// Additional servers by id (only populated on the primary adapter)
+    servers: Arc<RwLock<BTreeMap<String, LmsServer>>>,
 }
 
 impl LmsAdapter {
     pub fn new(bus: SharedBus) -> Self {
+        let adapter = Self::with_server(bus, None);
+        // Load saved config synchronously at startup
+        adapter.load_config_sync();
+        adapter
+    }
+
+    /// Adapter for one server; `server` is the namespace of an additional server
+    fn with_server(bus: SharedBus, server: Option<&'static str>) -> Self {
         let state = Arc::new(RwLock::new(LmsState::default()));
         #[allow(clippy::expect_used)] // HTTP client creation only fails if TLS setup fails
         let client = Client::builder()
             .timeout(Duration::from_secs(10))
             .build()
             .expect("Failed to create HTTP client");
-        let rpc = LmsRpc::new(state.clone(), client);
-        let adapter = Self {
+        let rpc = LmsRpc::new(state.clone(), client, server);
+        let name = match server {
+            Some(id) => intern(format!("lms-{}", id)),
+            None => "lms",
+        };
+        Self {
             state,
             rpc,
             bus,
             shutdown: Arc::new(RwLock::new(CancellationToken::new())),
+            name,
+            servers: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// Namespace of this server (None for the primary server)
+    pub fn server_id(&self) -> Option<&'static str> {
+        self.rpc.server
+    }
+
+    /// Adapter that owns a player or zone ID, judged by its server namespace
+    pub async fn server_for(&self, id: &str) -> Result<LmsAdapter> {
+        match lms_server_of(id) {
+            None => Ok(self.clone()),
+            Some(server) if Some(server) == self.rpc.server => Ok(self.clone()),
+            Some(server) => self
+                .servers
+                .read()
+                .await
+                .get(server)
+                .map(|s| s.adapter.as_ref().clone())
+                .ok_or_else(|| anyhow!("Unknown LMS server: {}", server)),
+        }
+    }
+
+    /// Load additional servers from `lms-servers.json` (sync, for startup)
+    fn load_servers_sync(&self) {
+        let Some(content) = read_config_file(LMS_SERVERS_FILE) else {
+            return;
         };
-        // Load saved config synchronously at startup
-        adapter.load_config_sync();
-        adapter
+        let saved: Vec<SavedLmsServer> = match serde_json::from_str(&content) {
+            Ok(saved) => saved,
+            Err(e) => {
+                tracing::warn!("Failed to parse {}: {}", LMS_SERVERS_FILE, e);
+                return;
+            }
+        };
+        let Ok(mut servers) = self.servers.try_write() else {
+            return;
+        };
+        for server in saved {
+            if let Err(e) = validate_server_id(&server.id) {
+                tracing::warn!("Skipping LMS server: {}", e);
+                continue;
+            }
+            let pair = create_server(self.bus.clone(), &server.id);
+            if let Ok(mut state) = pair.adapter.state.try_write() {
+                state.host = Some(server.host.clone());
+                state.port = server.port;
+                state.username = server.username;
+                state.password = server.password;
+            }
+            tracing::info!(
+                "Loaded LMS server '{}' from disk: {}:{}",
+                server.id,
+                server.host,
+                server.port
+            );
+            servers.insert(server.id, pair);
+        }
+    }
+
+    /// Additional servers as saved in `lms-servers.json`
+    pub(crate) async fn server_configs(&self) -> Vec<SavedLmsServer> {
+        let adapters: Vec<Arc<LmsAdapter>> = self
+            .servers
+            .read()
+            .await
+            .values()
+            .map(|s| s.adapter.clone())
+            .collect();
+        let mut saved = Vec::with_capacity(adapters.len());
+        for adapter in adapters {
+            let state = adapter.state.read().await;
+            if let (Some(id), Some(host)) = (adapter.rpc.server, state.host.clone()) {
+                saved.push(SavedLmsServer {
+                    id: id.to_string(),
+                    host,
+                    port: state.port,
+                    username: state.username.clone(),
+                    password: state.password.clone(),
+                });
+            }
+        }
+        saved
+    }
+
+    /// Save additional servers to disk
+    async fn save_servers(&self) {
+        let saved = self.server_configs().await;
+
+        let path = get_config_file_path(LMS_SERVERS_FILE);
+        if let Some(parent) = path.parent() {
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                tracing::error!("Failed to create config dir: {}", e);
+            }
+        }
+        match serde_json::to_string_pretty(&saved) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(&path, json) {
+                    tracing::error!("Failed to save LMS servers: {}", e);
+                }
+            }
+            Err(e) => tracing::error!("Failed to serialize LMS servers: {}", e),
+        }
+    }
+
+    /// Add an additional server, or reconfigure it if the id exists.
+    /// The id defaults to one derived from the host. Does not start it.
+    pub async fn add_server(
+        &self,
+        id: Option<String>,
+        host: String,
+        port: Option<u16>,
+        username: Option<String>,
+        password: Option<String>,
+    ) -> Result<LmsServer> {
+        if self.rpc.server.is_some() {
+            return Err(anyhow!("Servers are added to the primary LMS adapter"));
+        }
+        let id = id.unwrap_or_else(|| server_id_for_host(&host));
+        validate_server_id(&id)?;
+
+        let port = port.unwrap_or(DEFAULT_PORT);
+        let existing = self.list_servers().await.into_iter().find(|s| {
+            s.id.as_deref() != Some(id.as_str())
+                && s.host.as_deref() == Some(host.as_str())
+                && s.port == port
+        });
+        if let Some(existing) = existing {
+            return Err(match existing.id {
+                Some(other) => anyhow!("{}:{} is already added as '{}'", host, port, other),
+                None => anyhow!("{}:{} is the primary LMS server", host, port),
+            });
+        }
+
+        let server = self
+            .servers
+            .write()
+            .await
+            .entry(id.clone())
+            .or_insert_with(|| create_server(self.bus.clone(), &id))
+            .clone();
+
+        server
+            .adapter
+            .configure(host, Some(port), username, password)
+            .await;
+        self.save_servers().await;
+        Ok(server)
+    }
+
+    /// Remove an additional server, stopping it and dropping its zones
+    pub async fn remove_server(&self, id: &str) -> Option<LmsServer> {
+        let server = self.servers.write().await.remove(id)?;
+        self.save_servers().await;
+
+        server.cli.stop().await;
+        server.adapter.stop_internal().await;
+        let player_ids: Vec<String> = {
+            let mut state = server.adapter.state.write().await;
+            state.players.drain().map(|(id, _)| id).collect()
+        };
+        for player_id in player_ids {
+            self.bus.publish(BusEvent::ZoneRemoved {
+                zone_id: server.adapter.rpc.zone_id(&player_id),
+            });
+        }
+        Some(server)
+    }
+
+    /// Additional servers
+    pub async fn servers(&self) -> Vec<LmsServer> {
+        self.servers.read().await.values().cloned().collect()
+    }
+
+    /// Startable adapters of the additional servers (polling and CLI for each)
+    pub async fn server_startables(&self) -> Vec<Arc<dyn Startable>> {
+        self.servers
+            .read()
+            .await
+            .values()
+            .flat_map(|s| {
+                [
+                    s.adapter.clone() as Arc<dyn Startable>,
+                    s.cli.clone() as Arc<dyn Startable>,
+                ]
+            })
+            .collect()
+    }
+
+    /// Status of the primary and every additional server
+    pub async fn list_servers(&self) -> Vec<LmsServerInfo> {
+        let mut adapters = vec![self.clone()];
+        adapters.extend(
+            self.servers
+                .read()
+                .await
+                .values()
+                .map(|s| s.adapter.as_ref().clone()),
+        );
+
+        let mut infos = Vec::with_capacity(adapters.len());
+        for adapter in adapters {
+            let state = adapter.state.read().await;
+            infos.push(LmsServerInfo {
+                id: adapter.rpc.server.map(str::to_string),
+                adapter: adapter.name.to_string(),
+                host: state.host.clone(),
+                port: state.port,
+                connected: state.connected,
+                player_count: state.players.len(),
+                cli_subscription_active: state.cli_subscription_active,
+            });
+        }
+        infos
+    }
+
+    /// Whether a host/port is the primary or an additional server
+    pub async fn is_known_server(&self, host: &str, port: u16) -> bool {
+        self.list_servers()
+            .await
+            .iter()
+            .any(|s| s.host.as_deref() == Some(host) && s.port == port)
     }
 
     /// Load config from disk (sync, for startup)
@@ -774,8 +1138,10 @@ impl LmsAdapter {
             state.password = password;
             state.connected = false;
         }
-        // Persist to disk
-        self.save_config().await;
+        // Persist to disk (additional servers are saved by the primary in lms-servers.json)
+        if self.rpc.server.is_none() {
+            self.save_config().await;
+        }
     }
 
     /// Check if configured
@@ -817,10 +1183,11 @@ impl LmsAdapter {
         self.rpc.get_players().await
     }
 
-    /// Get player status (delegates to shared RPC)
+    /// Get player status (delegates to shared RPC of the owning server)
     pub async fn get_player_status(&self, player_id: &str) -> Result<LmsPlayer> {
+        let server = self.server_for(player_id).await?;
         let player_id = strip_lms_prefix(player_id);
-        self.rpc.get_player_status(player_id).await
+        server.rpc.get_player_status(player_id).await
     }
 
     /// Start polling for player updates (internal - use Startable trait)
@@ -844,7 +1211,7 @@ impl LmsAdapter {
         // Create AdapterHandle and spawn run_with_retry
         let adapter = self.clone();
         let bus = self.bus.clone();
-        let handle = AdapterHandle::new(adapter, bus, shutdown);
+        let handle = AdapterHandle::new(adapter, bus, shutdown).with_name(self.name);
 
         tokio::spawn(async move { handle.run_with_retry(RetryConfig::default()).await });
 
@@ -868,13 +1235,26 @@ impl LmsAdapter {
             state.host.clone()
         };
 
-        if let Some(host) = host {
+        if let (Some(host), None) = (host, self.rpc.server) {
             self.bus.publish(BusEvent::LmsDisconnected { host });
         }
     }
 
-    /// Control player
+    /// Control player (on whichever server owns it)
     pub async fn control(&self, player_id: &str, command: &str, value: Option<i32>) -> Result<()> {
+        self.server_for(player_id)
+            .await?
+            .control_local(player_id, command, value)
+            .await
+    }
+
+    /// Control a player on this server
+    async fn control_local(
+        &self,
+        player_id: &str,
+        command: &str,
+        value: Option<i32>,
+    ) -> Result<()> {
         let player_id = strip_lms_prefix(player_id);
         let params: Vec<Value> = match command {
             // Per real-world testing (issue #68), "play" handles both start and resume.
@@ -899,6 +1279,40 @@ impl LmsAdapter {
                     json!(format!("{}{}", prefix, v)),
                 ]
             }
+            "mute" => {
+                let muted = value.unwrap_or(1) != 0;
+                vec![json!("mixer"), json!("muting"), json!(i32::from(muted))]
+            }
+            "mute_toggle" => vec![json!("mixer"), json!("muting"), json!("toggle")],
+            "seek" => {
+                let v = value.unwrap_or(0).max(0);
+                vec![json!("time"), json!(v)]
+            }
+            "seek_rel" => {
+                let v = value.unwrap_or(0);
+                // A bare "time 0" would seek to the start of the track
+                if v == 0 {
+                    return Ok(());
+                }
+                let prefix = if v > 0 { "+" } else { "" };
+                vec![json!("time"), json!(format!("{}{}", prefix, v))]
+            }
+            // 0 = off, 1 = shuffle by song
+            "shuffle" => vec![
+                json!("playlist"),
+                json!("shuffle"),
+                json!(value.unwrap_or(0)),
+            ],
+            // 0 = off, 1 = repeat song, 2 = repeat playlist
+            "repeat" => vec![
+                json!("playlist"),
+                json!("repeat"),
+                json!(value.unwrap_or(0)),
+            ],
+            // 0 = off, 1 = on
+            "power" => vec![json!("power"), json!(value.unwrap_or(0))],
+            // Seconds until the player fades out and powers off; 0 cancels
+            "sleep" => vec![json!("sleep"), json!(value.unwrap_or(0).max(0))],
             _ => return Err(anyhow!("Unknown command: {}", command)),
         };
 
@@ -943,9 +1357,22 @@ impl LmsAdapter {
         Ok(format!("{}/music/{}/{}", base_url, coverid, suffix))
     }
 
-    /// Fetch artwork image bytes
+    /// Fetch artwork image bytes from the server that owns `zone_id`
     /// If image_key is a URL, fetches directly. Otherwise treats as coverid.
     pub async fn get_artwork(
+        &self,
+        zone_id: &str,
+        image_key: &str,
+        width: Option<u32>,
+        height: Option<u32>,
+    ) -> Result<(String, Vec<u8>)> {
+        self.server_for(zone_id)
+            .await?
+            .get_artwork_local(image_key, width, height)
+            .await
+    }
+
+    async fn get_artwork_local(
         &self,
         image_key: &str,
         width: Option<u32>,
@@ -990,20 +1417,40 @@ impl LmsAdapter {
         Ok((content_type, body))
     }
 
-    /// Get cached player
+    /// Get cached player (player IDs of additional servers come back namespaced)
     pub async fn get_cached_player(&self, player_id: &str) -> Option<LmsPlayer> {
-        let player_id = strip_lms_prefix(player_id);
-        self.state.read().await.players.get(player_id).cloned()
+        let server = self.server_for(player_id).await.ok()?;
+        let mut player = server
+            .state
+            .read()
+            .await
+            .players
+            .get(strip_lms_prefix(player_id))
+            .cloned()?;
+        if let Some(id) = server.rpc.server {
+            player.playerid = format!("{}/{}", id, player.playerid);
+        }
+        Some(player)
     }
 
-    /// Get all cached players
+    /// Get all cached players of every server (additional servers' IDs are namespaced)
     pub async fn get_cached_players(&self) -> Vec<LmsPlayer> {
-        self.state.read().await.players.values().cloned().collect()
+        let mut players: Vec<LmsPlayer> =
+            self.state.read().await.players.values().cloned().collect();
+        for server in self.servers().await {
+            let id = server.adapter.rpc.server.unwrap_or_default();
+            let state = server.adapter.state.read().await;
+            players.extend(state.players.values().map(|p| LmsPlayer {
+                playerid: format!("{}/{}", id, p.playerid),
+                ..p.clone()
+            }));
+        }
+        players
     }
 
     /// Change volume (f32 for fractional step support)
     pub async fn change_volume(&self, player_id: &str, value: f32, relative: bool) -> Result<()> {
-        let player_id = strip_lms_prefix(player_id);
+        // control() strips the prefix once it has picked the server
         let command = if relative { "vol_rel" } else { "vol_abs" };
         // LMS uses integer volume 0-100, round at the last moment
         self.control(player_id, command, Some(value.round() as i32))
@@ -1029,6 +1476,23 @@ impl LmsAdapter {
         query: &str,
         player_id: Option<&str>,
         limit: Option<usize>,
+    ) -> Result<Vec<LmsSearchResult>> {
+        match player_id {
+            Some(id) => {
+                self.server_for(id)
+                    .await?
+                    .search_local(query, Some(id), limit)
+                    .await
+            }
+            None => self.search_local(query, None, limit).await,
+        }
+    }
+
+    async fn search_local(
+        &self,
+        query: &str,
+        player_id: Option<&str>,
+        limit: Option<usize>,
     ) -> Result<Vec<LmsSearchResult>> {
         let limit = limit.unwrap_or(20);
 
@@ -1355,11 +1819,23 @@ impl LmsAdapter {
         query: &str,
         player_id: &str,
         action: LmsPlayAction,
+    ) -> Result<String> {
+        self.server_for(player_id)
+            .await?
+            .search_and_play_local(query, player_id, action)
+            .await
+    }
+
+    async fn search_and_play_local(
+        &self,
+        query: &str,
+        player_id: &str,
+        action: LmsPlayAction,
     ) -> Result<String> {
         let player_id = strip_lms_prefix(player_id);
 
         // Search for content (uses globalsearch which includes all providers)
-        let results = self.search(query, Some(player_id), Some(10)).await?;
+        let results = self.search_local(query, Some(player_id), Some(10)).await?;
 
         if results.is_empty() {
             return Err(anyhow!("No results found for '{}'", query));
@@ -1465,9 +1941,68 @@ impl LmsAdapter {
     }
 }
 
+/// Map LMS content type codes (`type` tag) to display format names
+fn lms_format_name(content_type: &str) -> String {
+    match content_type.to_ascii_lowercase().as_str() {
+        "flc" | "flac" => "FLAC".to_string(),
+        "alc" => "ALAC".to_string(),
+        "aif" | "aiff" => "AIFF".to_string(),
+        "wav" => "WAV".to_string(),
+        "mp3" => "MP3".to_string(),
+        "aac" | "mp4" => "AAC".to_string(),
+        "ogg" | "ogf" => "OGG".to_string(),
+        "ops" => "OPUS".to_string(),
+        "wma" | "wmal" | "wmap" => "WMA".to_string(),
+        "dsf" | "dff" => "DSD".to_string(),
+        other => other.to_uppercase(),
+    }
+}
+
+/// Read a numeric LMS field that may be encoded as a number or a string
+/// (e.g. `"samplerate": "44100"`, `"bitrate": "1411kb/s VBR"`).
+fn lms_number(value: Option<&Value>) -> Option<u32> {
+    match value? {
+        Value::Number(n) => n.as_u64().map(|n| n as u32),
+        Value::String(s) => {
+            let digits: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Extract format metadata from a `status` playlist_loop entry
+/// (requires tags `o` type, `T` samplerate, `I` samplesize, `r` bitrate, `g` genre).
+fn parse_track_metadata(track: &Value) -> Option<TrackMetadata> {
+    let metadata = TrackMetadata {
+        format: track
+            .get("type")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(lms_format_name),
+        sample_rate: lms_number(track.get("samplerate")).filter(|r| *r > 0),
+        bit_depth: lms_number(track.get("samplesize"))
+            .filter(|b| *b > 0)
+            .map(|b| b as u8),
+        bitrate: lms_number(track.get("bitrate")).filter(|b| *b > 0),
+        genre: track
+            .get("genre")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string()),
+        ..Default::default()
+    };
+
+    if metadata.is_empty() {
+        None
+    } else {
+        Some(metadata)
+    }
+}
+
 /// Convert an LMS player to a unified Zone representation
-fn lms_player_to_zone(player: &LmsPlayer) -> Zone {
-    let zone_id = PrefixedZoneId::lms(&player.playerid).to_string();
+fn lms_player_to_zone(server: Option<&str>, player: &LmsPlayer) -> Zone {
+    let zone_id = lms_zone_id(server, &player.playerid).to_string();
     Zone {
         zone_id: zone_id.clone(),
         zone_name: player.name.clone(),
@@ -1492,7 +2027,7 @@ fn lms_player_to_zone(player: &LmsPlayer) -> Zone {
                 image_key: player.artwork_url.clone().or(player.coverid.clone()),
                 seek_position: Some(player.time),
                 duration: Some(player.duration),
-                metadata: None,
+                metadata: player.metadata.clone(),
             })
         } else {
             None
@@ -1509,6 +2044,22 @@ fn lms_player_to_zone(player: &LmsPlayer) -> Zone {
         is_pause_allowed: player.state == "playing",
         is_next_allowed: true,
         is_previous_allowed: true,
+        capabilities: ZoneCapabilities {
+            transport: true,
+            skip: true,
+            seek: true,
+            shuffle: true,
+            repeat: true,
+            volume: true,
+            volume_db: false,
+            mute: true,
+            // Sync exists server-side, but no zone command drives it
+            grouping: false,
+            source_selection: false,
+            // Players can be powered off server-side
+            standby: true,
+        },
+        is_stale: false,
     }
 }
 
@@ -1530,6 +2081,8 @@ async fn update_players_internal(
     let mut state_updates: Vec<(String, String, String)> = Vec::new();
     // VolumeChanged: (player_id, volume)
     let mut volume_updates: Vec<(String, i32)> = Vec::new();
+    // TrackMetadataChanged: (player_id, metadata)
+    let mut metadata_updates: Vec<(String, Option<TrackMetadata>)> = Vec::new();
 
     // Helper to convert empty strings to None (metadata cleared)
     let to_option = |s: &str| {
@@ -1557,6 +2110,7 @@ async fn update_players_internal(
                 player.artwork_track_id = status.artwork_track_id;
                 player.coverid = status.coverid;
                 player.artwork_url = status.artwork_url;
+                player.metadata = status.metadata;
             }
             Err(e) => {
                 tracing::warn!("Failed to get status for player {}: {}", player.playerid, e);
@@ -1564,7 +2118,7 @@ async fn update_players_internal(
         }
 
         // Check what changed for this player
-        let (now_playing_changed, state_changed, volume_changed) = {
+        let (now_playing_changed, state_changed, volume_changed, metadata_changed) = {
             let s = state.read().await;
             if let Some(old_player) = s.players.get(&player.playerid) {
                 let np_changed = old_player.title != player.title
@@ -1574,10 +2128,12 @@ async fn update_players_internal(
                     || old_player.coverid != player.coverid;
                 let state_changed = old_player.state != player.state;
                 let volume_changed = old_player.volume != player.volume;
-                (np_changed, state_changed, volume_changed)
+                // Re-send format after a track change since the aggregator clears it
+                let metadata_changed = old_player.metadata != player.metadata || np_changed;
+                (np_changed, state_changed, volume_changed, metadata_changed)
             } else {
                 // New player - will be handled by ZoneDiscovered
-                (false, false, false)
+                (false, false, false, false)
             }
         };
 
@@ -1589,6 +2145,7 @@ async fn update_players_internal(
                 artist: to_option(&player.artist),
                 album: to_option(&player.album),
                 image_key: player.artwork_url.clone().or(player.coverid.clone()),
+                duration: (player.duration > 0.0).then_some(player.duration),
             });
         }
 
@@ -1604,6 +2161,10 @@ async fn update_players_internal(
             volume_updates.push((player.playerid.clone(), player.volume));
         }
 
+        if metadata_changed {
+            metadata_updates.push((player.playerid.clone(), player.metadata.clone()));
+        }
+
         let mut s = state.write().await;
         s.players.insert(player.playerid.clone(), player);
     }
@@ -1616,11 +2177,20 @@ async fn update_players_internal(
             update.title.as_deref().unwrap_or("<cleared>")
         );
         bus.publish(BusEvent::NowPlayingChanged {
-            zone_id: PrefixedZoneId::lms(&update.player_id),
+            zone_id: rpc.zone_id(&update.player_id),
             title: update.title,
             artist: update.artist,
             album: update.album,
             image_key: update.image_key,
+            duration: update.duration,
+        });
+    }
+
+    // Emit TrackMetadataChanged after NowPlayingChanged so the format sticks to the new track
+    for (player_id, metadata) in metadata_updates {
+        bus.publish(BusEvent::TrackMetadataChanged {
+            zone_id: rpc.zone_id(&player_id),
+            metadata,
         });
     }
 
@@ -1629,7 +2199,7 @@ async fn update_players_internal(
         debug!("Polling detected state change for {}: {}", player_id, state);
         // Publish ZoneUpdated so aggregator updates state (SSE uses zone_id prefix to refresh LMS page)
         bus.publish(BusEvent::ZoneUpdated {
-            zone_id: PrefixedZoneId::lms(&player_id),
+            zone_id: rpc.zone_id(&player_id),
             display_name: player_name,
             state,
         });
@@ -1642,7 +2212,7 @@ async fn update_players_internal(
             player_id, volume
         );
         bus.publish(BusEvent::VolumeChanged {
-            output_id: PrefixedZoneId::lms(&player_id).to_string(),
+            output_id: rpc.zone_id(&player_id).to_string(),
             value: volume as f32,
             is_muted: false, // LMS doesn't expose mute via JSON-RPC
         });
@@ -1660,7 +2230,7 @@ async fn update_players_internal(
         for player_id in &added {
             if let Some(player) = state.read().await.players.get(player_id) {
                 tracing::debug!("LMS player discovered: {}", player_id);
-                let zone = lms_player_to_zone(player);
+                let zone = lms_player_to_zone(rpc.server, player);
                 bus.publish(BusEvent::ZoneDiscovered { zone });
             }
         }
@@ -1669,7 +2239,7 @@ async fn update_players_internal(
         for player_id in &removed {
             tracing::debug!("LMS player removed: {}", player_id);
             bus.publish(BusEvent::ZoneRemoved {
-                zone_id: PrefixedZoneId::lms(player_id),
+                zone_id: rpc.zone_id(player_id),
             });
         }
     }
@@ -1850,7 +2420,7 @@ async fn handle_cli_event(
             // Refresh player status on playlist changes
             match rpc.get_player_status(&player_id).await {
                 Ok(status) => {
-                    let zone_id = PrefixedZoneId::lms(&player_id);
+                    let zone_id = rpc.zone_id(&player_id);
 
                     // Update cached state and get player name for ZoneUpdated
                     let player_name = {
@@ -1866,6 +2436,7 @@ async fn handle_cli_event(
                             player.album = status.album.clone();
                             player.artwork_url = status.artwork_url.clone();
                             player.coverid = status.coverid.clone();
+                            player.metadata = status.metadata.clone();
                             player.name.clone()
                         } else {
                             player_id.clone() // Fallback to player_id if not in cache
@@ -1881,11 +2452,16 @@ async fn handle_cli_event(
 
                     if !status.title.is_empty() {
                         bus.publish(BusEvent::NowPlayingChanged {
-                            zone_id,
+                            zone_id: zone_id.clone(),
                             title: Some(status.title),
                             artist: Some(status.artist),
                             album: Some(status.album),
                             image_key: status.artwork_url.or(status.coverid),
+                            duration: (status.duration > 0.0).then_some(status.duration),
+                        });
+                        bus.publish(BusEvent::TrackMetadataChanged {
+                            zone_id,
+                            metadata: status.metadata,
                         });
                     }
                 }
@@ -1945,7 +2521,7 @@ async fn handle_cli_event(
 
                 // Publish volume changed event with prefixed output_id
                 bus.publish(BusEvent::VolumeChanged {
-                    output_id: PrefixedZoneId::lms(&player_id).to_string(),
+                    output_id: rpc.zone_id(&player_id).to_string(),
                     value: absolute_volume,
                     is_muted: false,
                 });
@@ -1961,7 +2537,7 @@ async fn handle_cli_event(
 
                 // Publish volume changed event with mute state and prefixed output_id
                 bus.publish(BusEvent::VolumeChanged {
-                    output_id: PrefixedZoneId::lms(&player_id).to_string(),
+                    output_id: rpc.zone_id(&player_id).to_string(),
                     value: current_volume as f32,
                     is_muted,
                 });
@@ -1988,7 +2564,7 @@ async fn handle_cli_event(
             // When power turns on, we don't know the actual playback state yet
             // When power turns off, playback is effectively stopped
             if !power_state {
-                let zone_id = PrefixedZoneId::lms(&player_id);
+                let zone_id = rpc.zone_id(&player_id);
                 // Publish ZoneUpdated so aggregator updates state
                 // Publish ZoneUpdated so aggregator updates state (SSE uses zone_id prefix to refresh LMS page)
                 bus.publish(BusEvent::ZoneUpdated {
@@ -2047,6 +2623,7 @@ async fn handle_cli_event(
                         title: status.title,
                         artist: status.artist,
                         album: status.album,
+                        metadata: status.metadata,
                         ..Default::default()
                     };
 
@@ -2054,7 +2631,7 @@ async fn handle_cli_event(
                     drop(s);
 
                     if is_new {
-                        let zone = lms_player_to_zone(&player);
+                        let zone = lms_player_to_zone(rpc.server, &player);
                         bus.publish(BusEvent::ZoneDiscovered { zone });
                     }
                 }
@@ -2124,9 +2701,16 @@ impl AdapterLogic for LmsAdapter {
             state.host.clone().unwrap_or_default()
         };
 
-        info!("LMS client connected to {}", host);
-        ctx.bus
-            .publish(BusEvent::LmsConnected { host: host.clone() });
+        info!("{} client connected to {}", self.name, host);
+        // LmsConnected/LmsDisconnected describe the primary server
+        if self.rpc.server.is_none() {
+            ctx.bus
+                .publish(BusEvent::LmsConnected { host: host.clone() });
+        }
+        ctx.bus.publish(BusEvent::AdapterConnected {
+            adapter: self.name.to_string(),
+            details: Some(host.clone()),
+        });
 
         // Run polling loop directly
         // CLI subscription is now handled by separate LmsCliAdapter (Issue #165)
@@ -2149,17 +2733,49 @@ impl AdapterLogic for LmsAdapter {
         }
 
         // Publish LmsDisconnected
-        ctx.bus.publish(BusEvent::LmsDisconnected { host });
+        if self.rpc.server.is_none() {
+            ctx.bus.publish(BusEvent::LmsDisconnected { host });
+        }
+        ctx.bus.publish(BusEvent::AdapterDisconnected {
+            adapter: self.name.to_string(),
+            reason: result.as_ref().err().map(|e| e.to_string()),
+        });
 
         result
     }
 
+    /// Every server's zones share the "lms" prefix, so a snapshot must cover all of
+    /// them: the primary answers for everyone and additional servers stay quiet.
+    async fn zone_snapshot(&self) -> Option<Vec<Zone>> {
+        if self.rpc.server.is_some() {
+            return None;
+        }
+        let mut zones: Vec<Zone> = {
+            let state = self.state.read().await;
+            state
+                .players
+                .values()
+                .map(|p| lms_player_to_zone(None, p))
+                .collect()
+        };
+        for server in self.servers().await {
+            let state = server.adapter.state.read().await;
+            zones.extend(
+                state
+                    .players
+                    .values()
+                    .map(|p| lms_player_to_zone(server.adapter.rpc.server, p)),
+            );
+        }
+        Some(zones)
+    }
+
     async fn handle_command(
         &self,
         zone_id: &str,
         command: AdapterCommand,
     ) -> Result<AdapterCommandResponse> {
-        // Extract player_id from zone_id (remove "lms:" prefix)
+        // Remove "lms:" prefix; control() routes on the server namespace
         let player_id = zone_id.strip_prefix("lms:").unwrap_or(zone_id);
 
         let result = match command {
@@ -2171,30 +2787,52 @@ impl AdapterLogic for LmsAdapter {
             AdapterCommand::Previous => self.control(player_id, "previous", None).await,
             AdapterCommand::VolumeAbsolute(v) => self.control(player_id, "vol_abs", Some(v)).await,
             AdapterCommand::VolumeRelative(v) => self.control(player_id, "vol_rel", Some(v)).await,
-            AdapterCommand::Mute(_) => {
-                // LMS doesn't have direct mute support via JSON-RPC
-                return Ok(AdapterCommandResponse {
-                    success: false,
-                    error: Some("Mute not supported by LMS adapter".to_string()),
-                });
+            AdapterCommand::Mute(m) => self.control(player_id, "mute", Some(i32::from(m))).await,
+            AdapterCommand::MuteToggle => self.control(player_id, "mute_toggle", None).await,
+            AdapterCommand::Seek(pos) => {
+                let pos = pos.min(i32::MAX as u32) as i32;
+                self.control(player_id, "seek", Some(pos)).await
+            }
+            AdapterCommand::SeekRelative(offset) => {
+                self.control(player_id, "seek_rel", Some(offset)).await
+            }
+            AdapterCommand::Shuffle(enabled) => {
+                self.control(player_id, "shuffle", Some(i32::from(enabled)))
+                    .await
+            }
+            AdapterCommand::Repeat(mode) => {
+                let value = match mode {
+                    RepeatMode::Off => 0,
+                    RepeatMode::One => 1,
+                    RepeatMode::All => 2,
+                };
+                self.control(player_id, "repeat", Some(value)).await
             }
         };
 
-        match result {
-            Ok(()) => Ok(AdapterCommandResponse {
-                success: true,
-                error: None,
-            }),
-            Err(e) => Ok(AdapterCommandResponse {
-                success: false,
-                error: Some(e.to_string()),
-            }),
-        }
+        Ok(AdapterCommandResponse::from_result(result))
     }
 }
 
-// Startable trait implementation via macro
-crate::impl_startable!(LmsAdapter, "lms", is_configured);
+// Startable by hand: additional servers have runtime names ("lms-<id>")
+#[async_trait]
+impl Startable for LmsAdapter {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn start(&self) -> Result<()> {
+        self.start_internal().await
+    }
+
+    async fn stop(&self) {
+        self.stop_internal().await
+    }
+
+    async fn can_start(&self) -> bool {
+        self.is_configured().await
+    }
+}
 
 // =============================================================================
 // LMS CLI Adapter - Handles real-time event subscription (Issue #165)
@@ -2220,18 +2858,25 @@ pub struct LmsCliAdapter {
     shutdown: Arc<RwLock<CancellationToken>>,
     /// Guard against duplicate start() calls
     running: Arc<RwLock<bool>>,
+    /// Name in lifecycle events ("lms-cli", or "lms-<id>-cli" for additional servers)
+    name: &'static str,
 }
 
 impl LmsCliAdapter {
     /// Create CLI adapter with shared state from LmsAdapter
     /// Use `create_lms_adapters()` factory function instead of calling directly.
     fn new(state: Arc<RwLock<LmsState>>, rpc: LmsRpc, bus: SharedBus) -> Self {
+        let name = match rpc.server {
+            Some(id) => intern(format!("lms-{}-cli", id)),
+            None => "lms-cli",
+        };
         Self {
             state,
             rpc,
             bus,
             shutdown: Arc::new(RwLock::new(CancellationToken::new())),
             running: Arc::new(RwLock::new(false)),
+            name,
         }
     }
 
@@ -2275,20 +2920,17 @@ impl AdapterLogic for LmsCliAdapter {
     async fn handle_command(
         &self,
         _zone_id: &str,
-        _command: AdapterCommand,
+        command: AdapterCommand,
     ) -> Result<AdapterCommandResponse> {
         // CLI adapter doesn't handle commands - main LmsAdapter does
-        Ok(AdapterCommandResponse {
-            success: false,
-            error: Some("CLI adapter does not handle commands".to_string()),
-        })
+        Ok(AdapterCommandResponse::unsupported(self.name, &command))
     }
 }
 
 #[async_trait]
 impl Startable for LmsCliAdapter {
     fn name(&self) -> &'static str {
-        "lms-cli"
+        self.name
     }
 
     async fn start(&self) -> Result<()> {
@@ -2317,7 +2959,7 @@ impl Startable for LmsCliAdapter {
         let adapter = self.clone();
         let bus = self.bus.clone();
         let running_flag = self.running.clone();
-        let handle = AdapterHandle::new(adapter, bus, shutdown);
+        let handle = AdapterHandle::new(adapter, bus, shutdown).with_name(self.name);
 
         tokio::spawn(async move {
             let _ = handle.run_with_retry(RetryConfig::default()).await;
@@ -2347,13 +2989,43 @@ pub fn create_lms_adapters(bus: SharedBus) -> (Arc<LmsAdapter>, Arc<LmsCliAdapte
     // Create CLI adapter with shared state
     let cli = Arc::new(LmsCliAdapter::new(lms.state.clone(), lms.rpc.clone(), bus));
 
+    // Additional servers hang off the primary adapter
+    lms.load_servers_sync();
+
     (lms, cli)
 }
 
+/// Adapter pair for an additional server (validated id)
+fn create_server(bus: SharedBus, id: &str) -> LmsServer {
+    let id = intern(id.to_string());
+    let adapter = LmsAdapter::with_server(bus.clone(), Some(id));
+    let cli = LmsCliAdapter::new(adapter.state.clone(), adapter.rpc.clone(), bus);
+    LmsServer {
+        adapter: Arc::new(adapter),
+        cli: Arc::new(cli),
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
 
+#[cfg(test)]
+pub(crate) fn capability_fixture() -> crate::adapters::traits::CapabilityFixture {
+    let player = LmsPlayer {
+        playerid: "00:04:20:aa:bb:cc".to_string(),
+        name: "Kitchen".to_string(),
+        connected: true,
+        power: true,
+        ..Default::default()
+    };
+    // On a server the adapter doesn't know, so commands fail without reaching LMS
+    crate::adapters::traits::CapabilityFixture::new(
+        LmsAdapter::new(crate::bus::create_bus()),
+        lms_player_to_zone(Some("test-lms"), &player),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2619,4 +3291,100 @@ mod tests {
             _ => panic!("Expected Mixer event, got {:?}", event),
         }
     }
+
+    // -------------------------------------------------------------------------
+    // Track Metadata Parsing Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_parse_track_metadata_string_fields() {
+        let track = json!({
+            "title": "Song",
+            "type": "flc",
+            "samplerate": "96000",
+            "samplesize": "24",
+            "bitrate": "2304kb/s VBR",
+            "genre": "Jazz"
+        });
+        let metadata = parse_track_metadata(&track).unwrap();
+        assert_eq!(metadata.format.as_deref(), Some("FLAC"));
+        assert_eq!(metadata.sample_rate, Some(96000));
+        assert_eq!(metadata.bit_depth, Some(24));
+        assert_eq!(metadata.bitrate, Some(2304));
+        assert_eq!(metadata.genre.as_deref(), Some("Jazz"));
+        assert_eq!(metadata.summary().as_deref(), Some("FLAC 24/96"));
+    }
+
+    #[test]
+    fn test_parse_track_metadata_missing() {
+        assert_eq!(parse_track_metadata(&json!({ "title": "Radio" })), None);
+        assert_eq!(parse_track_metadata(&Value::Null), None);
+    }
+
+    // -------------------------------------------------------------------------
+    // Multiple Server Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_zone_ids_namespaced_by_server() {
+        let mac = "00:04:20:aa:bb:cc";
+        assert_eq!(lms_zone_id(None, mac).as_str(), "lms:00:04:20:aa:bb:cc");
+        assert_eq!(
+            lms_zone_id(Some("upstairs"), mac).as_str(),
+            "lms:upstairs/00:04:20:aa:bb:cc"
+        );
+
+        assert_eq!(strip_lms_prefix("lms:00:04:20:aa:bb:cc"), mac);
+        assert_eq!(strip_lms_prefix("lms:upstairs/00:04:20:aa:bb:cc"), mac);
+        assert_eq!(strip_lms_prefix("upstairs/00:04:20:aa:bb:cc"), mac);
+        assert_eq!(strip_lms_prefix(mac), mac);
+
+        assert_eq!(lms_server_of("lms:00:04:20:aa:bb:cc"), None);
+        assert_eq!(
+            lms_server_of("lms:upstairs/00:04:20:aa:bb:cc"),
+            Some("upstairs")
+        );
+        assert_eq!(
+            lms_server_of("upstairs/00:04:20:aa:bb:cc"),
+            Some("upstairs")
+        );
+    }
+
+    #[test]
+    fn test_server_names_are_interned() {
+        let first = create_server(crate::bus::create_bus(), "upstairs");
+        let again = create_server(crate::bus::create_bus(), "upstairs");
+        assert_eq!(first.adapter.name(), "lms-upstairs");
+        assert_eq!(first.cli.name(), "lms-upstairs-cli");
+        assert!(std::ptr::eq(first.adapter.name(), again.adapter.name()));
+        assert!(std::ptr::eq(first.cli.name(), again.cli.name()));
+        assert!(std::ptr::eq(
+            first.adapter.server_id().unwrap(),
+            again.adapter.server_id().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_validate_server_id() {
+        assert!(validate_server_id("upstairs").is_ok());
+        assert!(validate_server_id("nas-2").is_ok());
+        assert!(validate_server_id("").is_err());
+        assert!(validate_server_id("Upstairs").is_err());
+        assert!(validate_server_id("up/stairs").is_err());
+        assert!(validate_server_id("-nas").is_err());
+        assert!(validate_server_id(&"a".repeat(33)).is_err());
+        // Would collide with the CLI adapter names
+        assert!(validate_server_id("cli").is_err());
+        assert!(validate_server_id("nas-cli").is_err());
+    }
+
+    #[test]
+    fn test_server_id_for_host() {
+        assert_eq!(server_id_for_host("192.168.1.20"), "192-168-1-20");
+        assert_eq!(server_id_for_host("NAS.local"), "nas-local");
+        assert_eq!(server_id_for_host("::1"), "1");
+        assert_eq!(server_id_for_host("..."), "server");
+        assert_eq!(server_id_for_host("media-cli"), "media-cli-lms");
+        assert!(validate_server_id(&server_id_for_host(&"x.".repeat(40))).is_ok());
+    }
 }
@@ -23,7 +23,8 @@ use crate::adapters::traits::{
     AdapterCommand, AdapterCommandResponse, AdapterContext, AdapterLogic,
 };
 use crate::bus::{
-    BusEvent, PlaybackState, PrefixedZoneId, SharedBus, VolumeControl as BusVolumeControl, Zone,
+    BusEvent, PlaybackState, PrefixedZoneId, RepeatMode, SharedBus, TrackMetadata,
+    VolumeControl as BusVolumeControl, Zone, ZoneCapabilities,
 };
 
 /// OpenHome URNs to search for - devices may advertise different services
@@ -61,6 +62,11 @@ pub struct OpenHomeDevice {
     /// VolumeSteps from Characteristics action (step = volume_max / volume_steps)
     pub volume_steps: Option<u32>,
     pub track_info: Option<TrackInfo>,
+    /// Stream format from Info Details action (codec, sample rate, bit depth)
+    pub track_metadata: Option<TrackMetadata>,
+    /// Service types from the device description (empty until it has been read)
+    #[serde(skip)]
+    pub services: Vec<String>,
     #[serde(skip)]
     pub last_seen: std::time::Instant,
     #[serde(skip)]
@@ -291,6 +297,8 @@ impl OpenHomeAdapter {
                             volume_max: None,
                             volume_steps: None,
                             track_info: None,
+                            track_metadata: None,
+                            services: Vec::new(),
                             last_seen: std::time::Instant::now(),
                             last_track_uri: None,
                         };
@@ -359,6 +367,19 @@ impl OpenHomeAdapter {
             manufacturer: Option<String>,
             #[serde(rename = "modelName")]
             model_name: Option<String>,
+            #[serde(rename = "serviceList")]
+            service_list: Option<ServiceList>,
+        }
+
+        #[derive(Deserialize)]
+        struct ServiceList {
+            service: Vec<ServiceDesc>,
+        }
+
+        #[derive(Deserialize)]
+        struct ServiceDesc {
+            #[serde(rename = "serviceType")]
+            service_type: String,
         }
 
         let root: Root = xml_from_str(&xml)?;
@@ -371,6 +392,11 @@ impl OpenHomeAdapter {
                 .unwrap_or_else(|| format!("OpenHome {}", &uuid[..8.min(uuid.len())]));
             device.manufacturer = root.device.manufacturer;
             device.model = root.device.model_name;
+            device.services = root
+                .device
+                .service_list
+                .map(|list| list.service.into_iter().map(|s| s.service_type).collect())
+                .unwrap_or_default();
 
             tracing::info!(
                 "Got OpenHome device info: {} - {} {}",
@@ -574,13 +600,40 @@ impl OpenHomeAdapter {
                                 artist,
                                 album,
                                 image_key,
+                                duration: None,
                             });
+                            // Aggregator drops format on track change; re-announce below
+                            device.track_metadata = None;
                         }
                     }
                 }
             }
         }
 
+        // Poll stream format details
+        let details = Self::soap_call(
+            http,
+            &format!("{}/Info", base_url),
+            "urn:av-openhome-org:service:Info:1",
+            "Details",
+            "",
+        )
+        .await;
+
+        if let Ok(response) = details {
+            let metadata = Self::parse_info_details(&response);
+            let mut s = state.write().await;
+            if let Some(device) = s.devices.get_mut(uuid) {
+                if metadata != device.track_metadata {
+                    device.track_metadata = metadata.clone();
+                    bus.publish(BusEvent::TrackMetadataChanged {
+                        zone_id: PrefixedZoneId::openhome(uuid),
+                        metadata,
+                    });
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -635,6 +688,32 @@ impl OpenHomeAdapter {
         Some(xml[start..end].to_string())
     }
 
+    /// Parse an Info `Details` response into format metadata.
+    /// BitRate is reported in bits per second; a zero SampleRate means nothing is playing.
+    fn parse_info_details(xml: &str) -> Option<TrackMetadata> {
+        let number = |tag: &str| {
+            Self::extract_xml_value(xml, tag)
+                .and_then(|v| v.trim().parse::<u32>().ok())
+                .filter(|n| *n > 0)
+        };
+
+        let metadata = TrackMetadata {
+            format: Self::extract_xml_value(xml, "CodecName")
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty()),
+            sample_rate: number("SampleRate"),
+            bit_depth: number("BitDepth").map(|b| b as u8),
+            bitrate: number("BitRate").map(|b| b / 1000),
+            ..Default::default()
+        };
+
+        if metadata.is_empty() {
+            None
+        } else {
+            Some(metadata)
+        }
+    }
+
     fn parse_didl_lite(xml: &str) -> Option<TrackInfo> {
         // Simple extraction from DIDL-Lite
         let title = Self::extract_xml_value(xml, "dc:title")
@@ -880,6 +959,79 @@ impl OpenHomeAdapter {
                     device.volume = Some(new_vol);
                 }
             }
+            "mute" | "mute_toggle" => {
+                let mute = match action {
+                    "mute" => value.map(|v| v != 0).unwrap_or(true),
+                    _ => {
+                        let state = self.state.read().await;
+                        !state.devices.get(uuid).map(|d| d.muted).unwrap_or(false)
+                    }
+                };
+                Self::soap_call(
+                    &self.http,
+                    &volume_url,
+                    "urn:av-openhome-org:service:Volume:1",
+                    "SetMute",
+                    &format!("<Value>{}</Value>", mute),
+                )
+                .await?;
+
+                let mut state = self.state.write().await;
+                if let Some(device) = state.devices.get_mut(uuid) {
+                    device.muted = mute;
+                }
+            }
+            "seek" | "seek_rel" => {
+                // Seek actions are scoped to the current stream
+                let response = Self::soap_call(
+                    &self.http,
+                    &transport_url,
+                    "urn:av-openhome-org:service:Transport:1",
+                    "StreamId",
+                    "",
+                )
+                .await?;
+                let stream_id = Self::extract_xml_value(&response, "StreamId")
+                    .ok_or_else(|| anyhow::anyhow!("No active stream to seek"))?;
+
+                let (soap_action, arg, seconds) = if action == "seek" {
+                    (
+                        "SeekSecondAbsolute",
+                        "SecondAbsolute",
+                        value.unwrap_or(0).max(0),
+                    )
+                } else {
+                    ("SeekSecondRelative", "SecondRelative", value.unwrap_or(0))
+                };
+                Self::soap_call(
+                    &self.http,
+                    &transport_url,
+                    "urn:av-openhome-org:service:Transport:1",
+                    soap_action,
+                    &format!(
+                        "<StreamId>{}</StreamId><{arg}>{}</{arg}>",
+                        stream_id,
+                        seconds,
+                        arg = arg
+                    ),
+                )
+                .await?;
+            }
+            "shuffle" | "repeat" => {
+                let (soap_action, arg) = if action == "shuffle" {
+                    ("SetShuffle", "Shuffle")
+                } else {
+                    ("SetRepeat", "Repeat")
+                };
+                Self::soap_call(
+                    &self.http,
+                    &transport_url,
+                    "urn:av-openhome-org:service:Transport:1",
+                    soap_action,
+                    &format!("<{arg}>{}</{arg}>", value.unwrap_or(0) != 0, arg = arg),
+                )
+                .await?;
+            }
             _ => {
                 anyhow::bail!("Unknown action: {}", action);
             }
@@ -939,8 +1091,17 @@ fn html_decode(s: &str) -> String {
         .replace("&apos;", "'")
 }
 
+impl OpenHomeDevice {
+    /// Whether the device description lists an OpenHome service, e.g. "Transport"
+    fn has_service(&self, name: &str) -> bool {
+        let prefix = format!("urn:av-openhome-org:service:{}:", name);
+        self.services.iter().any(|s| s.starts_with(&prefix))
+    }
+}
+
 /// Convert an OpenHome device to a unified Zone representation
 fn openhome_device_to_zone(device: &OpenHomeDevice) -> Zone {
+    let transport = device.has_service("Transport");
     Zone {
         zone_id: format!("openhome:{}", device.uuid),
         zone_name: device.name.clone(),
@@ -970,11 +1131,11 @@ fn openhome_device_to_zone(device: &OpenHomeDevice) -> Zone {
             image_key: t.album_art_uri.clone(),
             seek_position: None,
             duration: None,
-            metadata: None,
+            metadata: device.track_metadata.clone(),
         }),
         source: "openhome".to_string(),
         is_controllable: true,
-        is_seekable: false, // OpenHome seek support varies
+        is_seekable: transport,
         last_updated: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
@@ -983,6 +1144,22 @@ fn openhome_device_to_zone(device: &OpenHomeDevice) -> Zone {
         is_pause_allowed: device.state == "playing",
         is_next_allowed: true,
         is_previous_allowed: true,
+        // Transport and mute commands go to the Transport and Volume services; the
+        // Product service's sources and standby have no zone command
+        capabilities: ZoneCapabilities {
+            transport,
+            skip: transport,
+            seek: transport,
+            shuffle: transport,
+            repeat: transport,
+            volume: device.volume.is_some(),
+            volume_db: false,
+            mute: device.has_service("Volume"),
+            grouping: false,
+            source_selection: false,
+            standby: false,
+        },
+        is_stale: false,
     }
 }
 
@@ -1034,6 +1211,17 @@ impl AdapterLogic for OpenHomeAdapter {
         Ok(())
     }
 
+    async fn zone_snapshot(&self) -> Option<Vec<Zone>> {
+        let state = self.state.read().await;
+        Some(
+            state
+                .devices
+                .values()
+                .map(openhome_device_to_zone)
+                .collect(),
+        )
+    }
+
     async fn handle_command(
         &self,
         zone_id: &str,
@@ -1051,27 +1239,100 @@ impl AdapterLogic for OpenHomeAdapter {
             AdapterCommand::Previous => self.control(uuid, "previous", None).await,
             AdapterCommand::VolumeAbsolute(v) => self.control(uuid, "vol_abs", Some(v)).await,
             AdapterCommand::VolumeRelative(v) => self.control(uuid, "vol_rel", Some(v)).await,
-            AdapterCommand::Mute(_) => {
-                // OpenHome mute not directly supported via this adapter yet
-                return Ok(AdapterCommandResponse {
-                    success: false,
-                    error: Some("Mute not supported by OpenHome adapter".to_string()),
-                });
+            AdapterCommand::Mute(m) => self.control(uuid, "mute", Some(i32::from(m))).await,
+            AdapterCommand::MuteToggle => self.control(uuid, "mute_toggle", None).await,
+            AdapterCommand::Seek(pos) => {
+                let pos = pos.min(i32::MAX as u32) as i32;
+                self.control(uuid, "seek", Some(pos)).await
+            }
+            AdapterCommand::SeekRelative(offset) => {
+                self.control(uuid, "seek_rel", Some(offset)).await
+            }
+            AdapterCommand::Shuffle(enabled) => {
+                self.control(uuid, "shuffle", Some(i32::from(enabled)))
+                    .await
+            }
+            // OpenHome repeat is a single on/off flag covering the whole playlist
+            AdapterCommand::Repeat(RepeatMode::One) => {
+                return Ok(AdapterCommandResponse::unsupported("openhome", &command));
+            }
+            AdapterCommand::Repeat(mode) => {
+                let enabled = mode == RepeatMode::All;
+                self.control(uuid, "repeat", Some(i32::from(enabled))).await
             }
         };
 
-        match result {
-            Ok(()) => Ok(AdapterCommandResponse {
-                success: true,
-                error: None,
-            }),
-            Err(e) => Ok(AdapterCommandResponse {
-                success: false,
-                error: Some(e.to_string()),
-            }),
-        }
+        Ok(AdapterCommandResponse::from_result(result))
     }
 }
 
 // Startable trait implementation via macro
 crate::impl_startable!(OpenHomeAdapter, "openhome");
+
+#[cfg(test)]
+pub(crate) fn capability_fixture() -> crate::adapters::traits::CapabilityFixture {
+    let device = OpenHomeDevice {
+        uuid: "4c494e4e-0026-0f21-0000-000000000001".to_string(),
+        name: "Lounge".to_string(),
+        manufacturer: None,
+        model: None,
+        location: "http://127.0.0.1:9/desc.xml".to_string(),
+        state: "stopped".to_string(),
+        volume: Some(40),
+        muted: false,
+        volume_max: Some(100),
+        volume_steps: Some(100),
+        track_info: None,
+        track_metadata: None,
+        services: ["Product", "Transport", "Volume", "Info"]
+            .iter()
+            .map(|s| format!("urn:av-openhome-org:service:{}:1", s))
+            .collect(),
+        last_seen: std::time::Instant::now(),
+        last_track_uri: None,
+    };
+    // Not in the adapter's device list, so commands fail without a network call
+    crate::adapters::traits::CapabilityFixture::new(
+        OpenHomeAdapter::new(crate::bus::create_bus()),
+        openhome_device_to_zone(&device),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(services: &[&str]) -> OpenHomeDevice {
+        OpenHomeDevice {
+            uuid: "4c494e4e-0026-0f21-0000-000000000001".to_string(),
+            name: "Lounge".to_string(),
+            manufacturer: None,
+            model: None,
+            location: "http://127.0.0.1:9/desc.xml".to_string(),
+            state: "stopped".to_string(),
+            volume: Some(40),
+            muted: false,
+            volume_max: Some(100),
+            volume_steps: Some(100),
+            track_info: None,
+            track_metadata: None,
+            services: services
+                .iter()
+                .map(|s| format!("urn:av-openhome-org:service:{}:1", s))
+                .collect(),
+            last_seen: std::time::Instant::now(),
+            last_track_uri: None,
+        }
+    }
+
+    #[test]
+    fn capabilities_follow_reported_services() {
+        let zone = openhome_device_to_zone(&device(&["Product", "Volume"]));
+        assert!(!zone.capabilities.transport);
+        assert!(!zone.capabilities.seek);
+        assert!(!zone.is_seekable);
+        assert!(zone.capabilities.mute);
+        assert!(!zone.capabilities.source_selection);
+        assert!(!zone.capabilities.standby);
+    }
+}
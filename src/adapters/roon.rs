@@ -27,8 +27,8 @@ use crate::adapters::traits::{
     AdapterCommand, AdapterCommandResponse, AdapterContext, AdapterLogic,
 };
 use crate::bus::{
-    BusEvent, NowPlaying as BusNowPlaying, PlaybackState, PrefixedZoneId, SharedBus,
-    VolumeControl as BusVolumeControl, Zone as BusZone,
+    BusEvent, NowPlaying as BusNowPlaying, PlaybackState, PrefixedZoneId, RepeatMode, SharedBus,
+    VolumeControl as BusVolumeControl, Zone as BusZone, ZoneCapabilities,
 };
 use crate::config::get_config_file_path;
 use crate::knobs::KnobStore;
@@ -187,6 +187,17 @@ pub struct Zone {
     pub is_play_allowed: bool,
     pub now_playing: Option<NowPlaying>,
     pub outputs: Vec<Output>,
+    /// Shuffle/repeat/radio settings (needed to change one without resetting the others)
+    #[serde(default)]
+    pub settings: Option<ZoneSettings>,
+}
+
+/// Zone playback settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneSettings {
+    pub shuffle: bool,
+    pub repeat: RepeatMode,
+    pub auto_radio: bool,
 }
 
 /// Output information
@@ -195,6 +206,9 @@ pub struct Output {
     pub output_id: String,
     pub display_name: String,
     pub volume: Option<VolumeInfo>,
+    /// Any of the output's source controls can put the device in standby
+    #[serde(default)]
+    pub supports_standby: bool,
 }
 
 /// Volume information
@@ -507,6 +521,116 @@ impl RoonAdapter {
         Ok(())
     }
 
+    /// Toggle mute on the zone's first output
+    pub async fn mute_toggle(&self, zone_id: &str) -> Result<()> {
+        let is_muted = {
+            let state = self.state.read().await;
+            state
+                .zones
+                .get(strip_roon_prefix(zone_id))
+                .and_then(|z| z.outputs.first())
+                .and_then(|o| o.volume.as_ref())
+                .and_then(|v| v.is_muted)
+                .unwrap_or(false)
+        };
+        self.mute(zone_id, !is_muted).await
+    }
+
+    /// Put every output of the zone that supports it into standby
+    pub async fn standby(&self, zone_id: &str) -> Result<()> {
+        let zone_id = strip_roon_prefix(zone_id);
+
+        // Clone transport and collect output ids while holding lock, then release before await
+        let (transport, output_ids) = {
+            let state = self.state.read().await;
+            let transport = state
+                .transport
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("Not connected to Roon"))?;
+            let zone = state
+                .zones
+                .get(zone_id)
+                .ok_or_else(|| anyhow::anyhow!("Zone not found: {}", zone_id))?;
+            let output_ids: Vec<String> = zone
+                .outputs
+                .iter()
+                .filter(|o| o.supports_standby)
+                .map(|o| o.output_id.clone())
+                .collect();
+            (transport, output_ids)
+        };
+
+        if output_ids.is_empty() {
+            return Err(anyhow::anyhow!("Zone has no outputs with standby"));
+        }
+        for output_id in &output_ids {
+            transport.standby(output_id, None).await;
+        }
+        Ok(())
+    }
+
+    /// Seek within the current track (seconds)
+    pub async fn seek(&self, zone_id: &str, seconds: i32, relative: bool) -> Result<()> {
+        let zone_id = strip_roon_prefix(zone_id);
+
+        // Clone transport while holding lock, then release before await
+        let transport = {
+            let state = self.state.read().await;
+            state
+                .transport
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("Not connected to Roon"))?
+        };
+
+        let how = if relative {
+            transport::Seek::Relative
+        } else {
+            transport::Seek::Absolute
+        };
+        transport.seek(zone_id, &how, seconds).await;
+        Ok(())
+    }
+
+    /// Change shuffle and/or repeat, keeping the zone's other settings
+    pub async fn change_settings(
+        &self,
+        zone_id: &str,
+        shuffle: Option<bool>,
+        repeat: Option<RepeatMode>,
+    ) -> Result<()> {
+        let zone_id = strip_roon_prefix(zone_id);
+
+        // Clone transport and current settings while holding lock, then release before await
+        let (transport, current) = {
+            let state = self.state.read().await;
+            let transport = state
+                .transport
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("Not connected to Roon"))?;
+            let current = state
+                .zones
+                .get(zone_id)
+                .ok_or_else(|| anyhow::anyhow!("Zone not found: {}", zone_id))?
+                .settings
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("Zone settings not yet known"))?;
+            (transport, current)
+        };
+
+        let repeat = match repeat.unwrap_or(current.repeat) {
+            RepeatMode::Off => transport::Repeat::Disabled,
+            RepeatMode::One => transport::Repeat::LoopOne,
+            RepeatMode::All => transport::Repeat::Loop,
+        };
+        let settings = transport::Settings {
+            repeat,
+            shuffle: shuffle.unwrap_or(current.shuffle),
+            auto_radio: current.auto_radio,
+        };
+        transport.change_settings(zone_id, &settings).await;
+        Ok(())
+    }
+
     /// Get album art image
     pub async fn get_image(
         &self,
@@ -1305,6 +1429,11 @@ impl AdapterLogic for RoonAdapter {
         .await
     }
 
+    async fn zone_snapshot(&self) -> Option<Vec<BusZone>> {
+        let state = self.state.read().await;
+        Some(state.zones.values().map(roon_zone_to_bus_zone).collect())
+    }
+
     async fn handle_command(
         &self,
         zone_id: &str,
@@ -1326,18 +1455,19 @@ impl AdapterLogic for RoonAdapter {
                 self.change_volume(zone_id, delta as f32, true).await
             }
             AdapterCommand::Mute(mute) => self.mute(zone_id, mute).await,
+            AdapterCommand::MuteToggle => self.mute_toggle(zone_id).await,
+            AdapterCommand::Seek(position) => {
+                self.seek(zone_id, position.min(i32::MAX as u32) as i32, false)
+                    .await
+            }
+            AdapterCommand::SeekRelative(offset) => self.seek(zone_id, offset, true).await,
+            AdapterCommand::Shuffle(enabled) => {
+                self.change_settings(zone_id, Some(enabled), None).await
+            }
+            AdapterCommand::Repeat(mode) => self.change_settings(zone_id, None, Some(mode)).await,
         };
 
-        match result {
-            Ok(()) => Ok(AdapterCommandResponse {
-                success: true,
-                error: None,
-            }),
-            Err(e) => Ok(AdapterCommandResponse {
-                success: false,
-                error: Some(e.to_string()),
-            }),
-        }
+        Ok(AdapterCommandResponse::from_result(result))
     }
 }
 
@@ -1377,6 +1507,10 @@ fn convert_zone(roon_zone: &RoonZone) -> Zone {
                 is_muted: v.is_muted,
                 step: v.step,
             }),
+            supports_standby: o
+                .source_controls
+                .as_ref()
+                .is_some_and(|controls| controls.iter().any(|c| c.supports_standby)),
         })
         .collect();
 
@@ -1397,6 +1531,15 @@ fn convert_zone(roon_zone: &RoonZone) -> Zone {
         is_play_allowed: roon_zone.is_play_allowed,
         now_playing,
         outputs,
+        settings: Some(ZoneSettings {
+            shuffle: roon_zone.settings.shuffle,
+            repeat: match roon_zone.settings.repeat {
+                transport::Repeat::Disabled => RepeatMode::Off,
+                transport::Repeat::LoopOne => RepeatMode::One,
+                transport::Repeat::Loop => RepeatMode::All,
+            },
+            auto_radio: roon_zone.settings.auto_radio,
+        }),
     }
 }
 
@@ -1440,6 +1583,23 @@ fn roon_zone_to_bus_zone(zone: &Zone) -> BusZone {
         metadata: None,
     });
 
+    let capabilities = ZoneCapabilities {
+        transport: true,
+        skip: true,
+        seek: true,
+        shuffle: zone.settings.is_some(),
+        repeat: zone.settings.is_some(),
+        volume: volume_control.is_some(),
+        volume_db: volume_control
+            .as_ref()
+            .is_some_and(|vc| vc.scale == crate::bus::VolumeScale::Decibel),
+        mute: volume_control.is_some(),
+        // Grouping exists in Roon, but no zone command drives it
+        grouping: false,
+        source_selection: false,
+        standby: zone.outputs.iter().any(|o| o.supports_standby),
+    };
+
     BusZone {
         zone_id: format!("roon:{}", zone.zone_id),
         zone_name: zone.display_name.clone(),
@@ -1457,6 +1617,8 @@ fn roon_zone_to_bus_zone(zone: &Zone) -> BusZone {
         is_pause_allowed: zone.is_pause_allowed,
         is_next_allowed: zone.is_next_allowed,
         is_previous_allowed: zone.is_previous_allowed,
+        capabilities,
+        is_stale: false,
     }
 }
 
@@ -1731,6 +1893,7 @@ async fn run_roon_loop(
                                     artist: Some(np.artist.clone()),
                                     album: Some(np.album.clone()),
                                     image_key: np.image_key.clone(),
+                                    duration: np.length.map(|l| l as f64),
                                 });
                             }
 
@@ -1948,6 +2111,42 @@ async fn run_roon_loop(
 // Startable trait implementation via macro
 crate::impl_startable!(RoonAdapter, "roon", is_configured);
 
+#[cfg(test)]
+pub(crate) fn capability_fixture() -> crate::adapters::traits::CapabilityFixture {
+    let zone = Zone {
+        zone_id: "test-zone".to_string(),
+        display_name: "Test Zone".to_string(),
+        state: "stopped".to_string(),
+        is_next_allowed: true,
+        is_previous_allowed: true,
+        is_pause_allowed: false,
+        is_play_allowed: true,
+        now_playing: None,
+        settings: Some(ZoneSettings {
+            shuffle: false,
+            repeat: RepeatMode::Off,
+            auto_radio: false,
+        }),
+        outputs: vec![Output {
+            output_id: "output-1".to_string(),
+            display_name: "Test Output".to_string(),
+            volume: Some(VolumeInfo {
+                value: Some(-30.0),
+                min: Some(-80.0),
+                max: Some(0.0),
+                is_muted: None,
+                step: None,
+            }),
+            supports_standby: true,
+        }],
+    };
+    // Not connected to a core, so commands fail without going anywhere
+    crate::adapters::traits::CapabilityFixture::new(
+        RoonAdapter::new_disconnected(crate::bus::create_bus()),
+        roon_zone_to_bus_zone(&zone),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1968,6 +2167,7 @@ mod tests {
             is_pause_allowed: false,
             is_play_allowed: true,
             now_playing: None,
+            settings: None,
             outputs: vec![Output {
                 output_id: output_id.to_string(),
                 display_name: "Test Output".to_string(),
@@ -1978,6 +2178,7 @@ mod tests {
                     is_muted: None,
                     step: None,
                 }),
+                supports_standby: false,
             }],
         }
     }
@@ -2039,10 +2240,12 @@ mod tests {
             is_pause_allowed: false,
             is_play_allowed: true,
             now_playing: None,
+            settings: None,
             outputs: vec![Output {
                 output_id: "output-no-vol".to_string(),
                 display_name: "No Volume Output".to_string(),
                 volume: None,
+                supports_standby: false,
             }],
         };
         let bus_zone = roon_zone_to_bus_zone(&zone);
@@ -2,7 +2,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use tokio_util::sync::CancellationToken;
 
-use crate::bus::SharedBus;
+use crate::bus::{Command, RepeatMode, SharedBus, Zone};
 
 // =============================================================================
 // Startable - Uniform adapter lifecycle trait
@@ -102,13 +102,182 @@ pub enum AdapterCommand {
     VolumeAbsolute(i32),
     VolumeRelative(i32),
     Mute(bool),
+    MuteToggle,
+    /// Seek to absolute position (seconds)
+    Seek(u32),
+    /// Seek relative to current position (seconds, negative = backward)
+    SeekRelative(i32),
+    Shuffle(bool),
+    Repeat(RepeatMode),
+}
+
+impl AdapterCommand {
+    /// Short command name for logs and error messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Play => "play",
+            Self::Pause => "pause",
+            Self::PlayPause => "play_pause",
+            Self::Stop => "stop",
+            Self::Next => "next",
+            Self::Previous => "previous",
+            Self::VolumeAbsolute(_) => "volume_absolute",
+            Self::VolumeRelative(_) => "volume_relative",
+            Self::Mute(_) => "mute",
+            Self::MuteToggle => "mute_toggle",
+            Self::Seek(_) => "seek",
+            Self::SeekRelative(_) => "seek_relative",
+            Self::Shuffle(_) => "shuffle",
+            Self::Repeat(_) => "repeat",
+        }
+    }
+
+    /// Transport command for a control action name (`play`, `play_pause`, `prev`, ...)
+    pub fn from_action(action: &str) -> Option<Self> {
+        match action {
+            "play" => Some(Self::Play),
+            "pause" => Some(Self::Pause),
+            "play_pause" | "playpause" => Some(Self::PlayPause),
+            "next" => Some(Self::Next),
+            "previous" | "prev" => Some(Self::Previous),
+            "stop" => Some(Self::Stop),
+            _ => None,
+        }
+    }
+
+    /// Whether this command targets the output volume rather than transport
+    pub fn is_volume(&self) -> bool {
+        matches!(
+            self,
+            Self::VolumeAbsolute(_) | Self::VolumeRelative(_) | Self::Mute(_) | Self::MuteToggle
+        )
+    }
+}
+
+/// Convert a bus-level command into the adapter command surface.
+/// Adapters address the zone's primary output, so `output_id` is not carried over.
+/// HQPlayer DSP changes have no adapter command.
+impl TryFrom<Command> for AdapterCommand {
+    type Error = anyhow::Error;
+
+    fn try_from(command: Command) -> Result<Self> {
+        Ok(match command {
+            Command::Play => Self::Play,
+            Command::Pause => Self::Pause,
+            Command::PlayPause => Self::PlayPause,
+            Command::Stop => Self::Stop,
+            Command::Next => Self::Next,
+            Command::Previous => Self::Previous,
+            Command::VolumeAbsolute { value, .. } => Self::VolumeAbsolute(value.round() as i32),
+            Command::VolumeRelative { delta, .. } => Self::VolumeRelative(delta.round() as i32),
+            Command::Mute { muted, .. } => Self::Mute(muted),
+            Command::MuteToggle { .. } => Self::MuteToggle,
+            Command::Seek { position } => Self::Seek(position.max(0.0).round() as u32),
+            Command::SeekRelative { offset } => Self::SeekRelative(offset.round() as i32),
+            Command::Shuffle { enabled } => Self::Shuffle(enabled),
+            Command::Repeat { mode } => Self::Repeat(mode),
+            Command::HqpDsp { setting, .. } => {
+                anyhow::bail!("HQPlayer {} is not a zone command", setting)
+            }
+        })
+    }
+}
+
+/// The bus-level form of an adapter command, for publishing what was sent
+impl From<&AdapterCommand> for Command {
+    fn from(command: &AdapterCommand) -> Self {
+        match *command {
+            AdapterCommand::Play => Self::Play,
+            AdapterCommand::Pause => Self::Pause,
+            AdapterCommand::PlayPause => Self::PlayPause,
+            AdapterCommand::Stop => Self::Stop,
+            AdapterCommand::Next => Self::Next,
+            AdapterCommand::Previous => Self::Previous,
+            AdapterCommand::VolumeAbsolute(value) => Self::VolumeAbsolute {
+                value: value as f32,
+                output_id: None,
+            },
+            AdapterCommand::VolumeRelative(delta) => Self::VolumeRelative {
+                delta: delta as f32,
+                output_id: None,
+            },
+            AdapterCommand::Mute(muted) => Self::Mute {
+                muted,
+                output_id: None,
+            },
+            AdapterCommand::MuteToggle => Self::MuteToggle { output_id: None },
+            AdapterCommand::Seek(position) => Self::Seek {
+                position: f64::from(position),
+            },
+            AdapterCommand::SeekRelative(offset) => Self::SeekRelative {
+                offset: f64::from(offset),
+            },
+            AdapterCommand::Shuffle(enabled) => Self::Shuffle { enabled },
+            AdapterCommand::Repeat(mode) => Self::Repeat { mode },
+        }
+    }
+}
+
+/// Why an adapter did not execute a command
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum AdapterCommandError {
+    /// The backend has no way to perform this command
+    #[error("{command} not supported by {adapter}")]
+    Unsupported {
+        adapter: &'static str,
+        command: &'static str,
+    },
+    /// The command was sent but the backend reported an error
+    #[error("{0}")]
+    Failed(String),
 }
 
 /// Response from command execution
 #[derive(Debug, Clone)]
 pub struct AdapterCommandResponse {
     pub success: bool,
-    pub error: Option<String>,
+    pub error: Option<AdapterCommandError>,
+}
+
+impl AdapterCommandResponse {
+    /// Response for a command the adapter cannot perform
+    pub fn unsupported(adapter: &'static str, command: &AdapterCommand) -> Self {
+        Self {
+            success: false,
+            error: Some(AdapterCommandError::Unsupported {
+                adapter,
+                command: command.name(),
+            }),
+        }
+    }
+
+    /// Response from the outcome of a backend call
+    pub fn from_result(result: Result<()>) -> Self {
+        match result {
+            Ok(()) => Self {
+                success: true,
+                error: None,
+            },
+            Err(e) => Self {
+                success: false,
+                error: Some(AdapterCommandError::Failed(e.to_string())),
+            },
+        }
+    }
+
+    /// Whether the command failed because the adapter doesn't support it
+    pub fn is_unsupported(&self) -> bool {
+        matches!(self.error, Some(AdapterCommandError::Unsupported { .. }))
+    }
+
+    /// Back into a result, for callers that only care whether it worked
+    pub fn into_result(self) -> std::result::Result<(), AdapterCommandError> {
+        match self.error {
+            Some(e) => Err(e),
+            None if self.success => Ok(()),
+            None => Err(AdapterCommandError::Failed("command failed".to_string())),
+        }
+    }
 }
 
 /// Adapter-specific logic trait
@@ -137,4 +306,176 @@ pub trait AdapterLogic: Send + Sync + 'static {
     async fn init(&self) -> Result<()> {
         Ok(())
     }
+
+    /// Every zone this adapter currently has, published by AdapterHandle as a
+    /// `ZonesSnapshot` when a consumer that fell behind asks for a resync.
+    /// `None` (the default) means the adapter has no zones of its own.
+    async fn zone_snapshot(&self) -> Option<Vec<Zone>> {
+        None
+    }
+}
+
+/// A zone as an adapter publishes it, plus a way to send that adapter commands,
+/// for the test checking every adapter only advertises what it handles
+#[cfg(test)]
+pub(crate) struct CapabilityFixture {
+    pub zone: Zone,
+    pub send: Box<
+        dyn Fn(
+            AdapterCommand,
+        ) -> futures::future::BoxFuture<'static, Result<AdapterCommandResponse>>,
+    >,
+}
+
+#[cfg(test)]
+impl CapabilityFixture {
+    pub(crate) fn new<A: AdapterLogic>(adapter: A, zone: Zone) -> Self {
+        let adapter = std::sync::Arc::new(adapter);
+        let zone_id = zone.zone_id.clone();
+        Self {
+            zone,
+            send: Box::new(move |command| {
+                let adapter = adapter.clone();
+                let zone_id = zone_id.clone();
+                Box::pin(async move { adapter.handle_command(&zone_id, command).await })
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Commands behind each capability flag. Grouping and source selection have
+    /// no command yet, so advertising them is an error (the flag name). Standby is
+    /// power control outside zone commands.
+    fn capability_commands(
+        caps: &crate::bus::ZoneCapabilities,
+    ) -> std::result::Result<Vec<AdapterCommand>, &'static str> {
+        for (flag, advertised) in [
+            ("grouping", caps.grouping),
+            ("source_selection", caps.source_selection),
+        ] {
+            if advertised {
+                return Err(flag);
+            }
+        }
+
+        let mut commands = Vec::new();
+        if caps.transport {
+            commands.extend([
+                AdapterCommand::Play,
+                AdapterCommand::Pause,
+                AdapterCommand::PlayPause,
+                AdapterCommand::Stop,
+            ]);
+        }
+        if caps.skip {
+            commands.extend([AdapterCommand::Next, AdapterCommand::Previous]);
+        }
+        if caps.seek {
+            commands.extend([AdapterCommand::Seek(30), AdapterCommand::SeekRelative(10)]);
+        }
+        if caps.shuffle {
+            commands.push(AdapterCommand::Shuffle(true));
+        }
+        if caps.repeat {
+            commands.push(AdapterCommand::Repeat(RepeatMode::All));
+        }
+        if caps.volume {
+            commands.extend([
+                AdapterCommand::VolumeAbsolute(-30),
+                AdapterCommand::VolumeRelative(1),
+            ]);
+        }
+        if caps.mute {
+            commands.extend([AdapterCommand::Mute(true), AdapterCommand::MuteToggle]);
+        }
+        Ok(commands)
+    }
+
+    #[tokio::test]
+    async fn advertised_capabilities_are_handled() {
+        // Each adapter's zone is unknown to (or unreachable from) the adapter, so
+        // commands fail without touching a device, but never as unsupported
+        let fixtures = [
+            ("hqplayer", crate::adapters::hqplayer::capability_fixture()),
+            ("lms", crate::adapters::lms::capability_fixture()),
+            ("openhome", crate::adapters::openhome::capability_fixture()),
+            ("roon", crate::adapters::roon::capability_fixture()),
+            ("upnp", crate::adapters::upnp::capability_fixture()),
+        ];
+        for (adapter, fixture) in fixtures {
+            let commands = capability_commands(&fixture.zone.capabilities)
+                .unwrap_or_else(|flag| panic!("{} advertises {} with no command", adapter, flag));
+            for command in commands {
+                let response = (fixture.send)(command.clone()).await.unwrap();
+                assert!(
+                    !response.is_unsupported(),
+                    "{} advertises {} but doesn't handle it",
+                    adapter,
+                    command.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn command_conversion_rounds_numeric_params() {
+        let cmd = AdapterCommand::try_from(Command::VolumeRelative {
+            delta: -2.6,
+            output_id: None,
+        })
+        .unwrap();
+        assert!(matches!(cmd, AdapterCommand::VolumeRelative(-3)));
+
+        let cmd = AdapterCommand::try_from(Command::Seek { position: -5.0 }).unwrap();
+        assert!(matches!(cmd, AdapterCommand::Seek(0)));
+
+        let cmd = AdapterCommand::try_from(Command::Repeat {
+            mode: RepeatMode::One,
+        })
+        .unwrap();
+        assert!(matches!(cmd, AdapterCommand::Repeat(RepeatMode::One)));
+
+        assert!(AdapterCommand::try_from(Command::HqpDsp {
+            setting: "filter1x".to_string(),
+            value: "poly-sinc-gauss-long".to_string(),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn adapter_command_converts_back_to_bus_command() {
+        assert_eq!(
+            Command::from(&AdapterCommand::VolumeRelative(-3)),
+            Command::VolumeRelative {
+                delta: -3.0,
+                output_id: None,
+            }
+        );
+        assert_eq!(
+            Command::from(&AdapterCommand::Seek(90)),
+            Command::Seek { position: 90.0 }
+        );
+    }
+
+    #[test]
+    fn unsupported_response_is_typed() {
+        let resp = AdapterCommandResponse::unsupported("upnp", &AdapterCommand::Next);
+        assert!(!resp.success);
+        assert!(resp.is_unsupported());
+        assert_eq!(
+            resp.error.map(|e| e.to_string()).as_deref(),
+            Some("next not supported by upnp")
+        );
+    }
+
+    #[test]
+    fn failed_result_is_not_unsupported() {
+        let resp = AdapterCommandResponse::from_result(Err(anyhow::anyhow!("timeout")));
+        assert!(!resp.success);
+        assert!(!resp.is_unsupported());
+    }
 }
@@ -9,7 +9,8 @@ use crate::adapters::traits::{
     AdapterCommand, AdapterCommandResponse, AdapterContext, AdapterLogic,
 };
 use crate::bus::{
-    BusEvent, PlaybackState, PrefixedZoneId, SharedBus, VolumeControl as BusVolumeControl, Zone,
+    BusEvent, PlaybackState, PrefixedZoneId, SharedBus, TrackMetadata,
+    VolumeControl as BusVolumeControl, Zone, ZoneCapabilities,
 };
 use anyhow::Result;
 use async_trait::async_trait;
@@ -51,9 +52,13 @@ pub struct UPnPRenderer {
     pub state: String,
     pub volume: Option<i32>,
     pub muted: bool,
+    /// Stream format from the current track's DIDL-Lite `res` element
+    pub track_metadata: Option<TrackMetadata>,
     #[serde(skip)]
     pub last_seen: std::time::Instant,
     #[serde(skip)]
+    pub last_track_uri: Option<String>,
+    #[serde(skip)]
     pub av_transport_url: Option<String>,
     #[serde(skip)]
     pub rendering_control_url: Option<String>,
@@ -256,7 +261,9 @@ impl UPnPAdapter {
                 state: "stopped".to_string(),
                 volume: None,
                 muted: false,
+                track_metadata: None,
                 last_seen: std::time::Instant::now(),
+                last_track_uri: None,
                 av_transport_url: None,
                 rendering_control_url: None,
             };
@@ -482,6 +489,35 @@ impl UPnPAdapter {
                     }
                 }
             }
+
+            // Poll current track format (only re-parse when the track URI changes)
+            let position_info = Self::soap_call(
+                http,
+                url,
+                AV_TRANSPORT_URN,
+                "GetPositionInfo",
+                "<InstanceID>0</InstanceID>",
+            )
+            .await;
+
+            if let Ok(response) = position_info {
+                let uri = Self::extract_xml_value(&response, "TrackURI");
+                let mut s = state.write().await;
+                if let Some(renderer) = s.renderers.get_mut(uuid) {
+                    if uri != renderer.last_track_uri {
+                        renderer.last_track_uri = uri;
+                        let metadata = Self::extract_xml_value(&response, "TrackMetaData")
+                            .and_then(|didl| parse_didl_res_metadata(&html_decode(&didl)));
+                        if metadata != renderer.track_metadata {
+                            renderer.track_metadata = metadata.clone();
+                            bus.publish(BusEvent::TrackMetadataChanged {
+                                zone_id: PrefixedZoneId::upnp(uuid),
+                                metadata,
+                            });
+                        }
+                    }
+                }
+            }
         }
 
         // Poll volume
@@ -851,6 +887,60 @@ impl UPnPAdapter {
                     renderer.muted = mute;
                 }
             }
+            "mute_toggle" => {
+                let url = rc_url
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("No RenderingControl URL"))?;
+                let mute = {
+                    let state = self.state.read().await;
+                    !state.renderers.get(uuid).map(|r| r.muted).unwrap_or(false)
+                };
+                Self::soap_call(
+                    &self.http,
+                    url,
+                    RENDERING_CONTROL_URN,
+                    "SetMute",
+                    &format!("<InstanceID>0</InstanceID><Channel>Master</Channel><DesiredMute>{}</DesiredMute>", if mute { "1" } else { "0" }),
+                ).await?;
+
+                let mut state = self.state.write().await;
+                if let Some(renderer) = state.renderers.get_mut(uuid) {
+                    renderer.muted = mute;
+                }
+            }
+            "seek" | "seek_rel" => {
+                let url = av_url
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("No AVTransport URL"))?;
+                let target = if action == "seek" {
+                    value.unwrap_or(0).max(0)
+                } else {
+                    // Renderers don't seek relatively; resolve against the current position
+                    let response = Self::soap_call(
+                        &self.http,
+                        url,
+                        AV_TRANSPORT_URN,
+                        "GetPositionInfo",
+                        "<InstanceID>0</InstanceID>",
+                    )
+                    .await?;
+                    let current = Self::extract_xml_value(&response, "RelTime")
+                        .and_then(|t| parse_upnp_time(&t))
+                        .ok_or_else(|| anyhow::anyhow!("Current position unknown"))?;
+                    (current as i32 + value.unwrap_or(0)).max(0)
+                };
+                Self::soap_call(
+                    &self.http,
+                    url,
+                    AV_TRANSPORT_URN,
+                    "Seek",
+                    &format!(
+                        "<InstanceID>0</InstanceID><Unit>REL_TIME</Unit><Target>{}</Target>",
+                        format_upnp_time(target as u32)
+                    ),
+                )
+                .await?;
+            }
             _ => {
                 anyhow::bail!("Unknown action: {}", action);
             }
@@ -895,10 +985,22 @@ fn upnp_renderer_to_zone(renderer: &UPnPRenderer) -> Zone {
             // Use prefixed output_id for consistent aggregator matching
             output_id: Some(format!("upnp:{}", renderer.uuid)),
         }),
-        now_playing: None, // UPnP track info would need separate DIDL-Lite parsing
+        // Pure renderers only expose stream format here; title/artist live in the control point
+        now_playing: renderer
+            .track_metadata
+            .as_ref()
+            .map(|metadata| crate::bus::NowPlaying {
+                title: String::new(),
+                artist: String::new(),
+                album: String::new(),
+                image_key: None,
+                seek_position: None,
+                duration: None,
+                metadata: Some(metadata.clone()),
+            }),
         source: "upnp".to_string(),
         is_controllable: renderer.av_transport_url.is_some(),
-        is_seekable: false, // Pure UPnP seek support is limited
+        is_seekable: renderer.av_transport_url.is_some(),
         last_updated: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
@@ -907,6 +1009,21 @@ fn upnp_renderer_to_zone(renderer: &UPnPRenderer) -> Zone {
         is_pause_allowed: renderer.state == "playing",
         is_next_allowed: false,
         is_previous_allowed: false,
+        capabilities: ZoneCapabilities {
+            transport: renderer.av_transport_url.is_some(),
+            // No queue on a pure renderer: next/prev/shuffle/repeat live in the control point
+            skip: false,
+            seek: renderer.av_transport_url.is_some(),
+            shuffle: false,
+            repeat: false,
+            volume: renderer.rendering_control_url.is_some(),
+            volume_db: false,
+            mute: renderer.rendering_control_url.is_some(),
+            grouping: false,
+            source_selection: false,
+            standby: false,
+        },
+        is_stale: false,
     }
 }
 
@@ -961,6 +1078,17 @@ impl AdapterLogic for UPnPAdapter {
         Ok(())
     }
 
+    async fn zone_snapshot(&self) -> Option<Vec<Zone>> {
+        let state = self.state.read().await;
+        Some(
+            state
+                .renderers
+                .values()
+                .map(upnp_renderer_to_zone)
+                .collect(),
+        )
+    }
+
     async fn handle_command(
         &self,
         zone_id: &str,
@@ -974,18 +1102,6 @@ impl AdapterLogic for UPnPAdapter {
             AdapterCommand::Pause => self.control(uuid, "pause", None).await,
             AdapterCommand::PlayPause => self.control(uuid, "play_pause", None).await,
             AdapterCommand::Stop => self.control(uuid, "stop", None).await,
-            AdapterCommand::Next => {
-                return Ok(AdapterCommandResponse {
-                    success: false,
-                    error: Some("Next track not supported by pure UPnP renderers".to_string()),
-                });
-            }
-            AdapterCommand::Previous => {
-                return Ok(AdapterCommandResponse {
-                    success: false,
-                    error: Some("Previous track not supported by pure UPnP renderers".to_string()),
-                });
-            }
             AdapterCommand::VolumeAbsolute(vol) => self.control(uuid, "vol_abs", Some(vol)).await,
             AdapterCommand::VolumeRelative(delta) => {
                 self.control(uuid, "vol_rel", Some(delta)).await
@@ -994,20 +1110,171 @@ impl AdapterLogic for UPnPAdapter {
                 self.control(uuid, "mute", Some(if mute { 1 } else { 0 }))
                     .await
             }
+            AdapterCommand::MuteToggle => self.control(uuid, "mute_toggle", None).await,
+            AdapterCommand::Seek(pos) => {
+                let pos = pos.min(i32::MAX as u32) as i32;
+                self.control(uuid, "seek", Some(pos)).await
+            }
+            AdapterCommand::SeekRelative(offset) => {
+                self.control(uuid, "seek_rel", Some(offset)).await
+            }
+            // Pure renderers have no queue: next/previous and shuffle/repeat belong
+            // to the control point that pushed the URI
+            AdapterCommand::Next
+            | AdapterCommand::Previous
+            | AdapterCommand::Shuffle(_)
+            | AdapterCommand::Repeat(_) => {
+                return Ok(AdapterCommandResponse::unsupported("upnp", &command));
+            }
         };
 
-        match result {
-            Ok(()) => Ok(AdapterCommandResponse {
-                success: true,
-                error: None,
-            }),
-            Err(e) => Ok(AdapterCommandResponse {
-                success: false,
-                error: Some(e.to_string()),
-            }),
-        }
+        Ok(AdapterCommandResponse::from_result(result))
     }
 }
 
 // Startable trait implementation via macro
 crate::impl_startable!(UPnPAdapter, "upnp");
+
+/// Decode XML entities in escaped DIDL-Lite embedded in SOAP responses
+fn html_decode(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Read an attribute value from a single XML start tag
+fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let needle = format!(" {}=\"", name);
+    let start = tag.find(&needle)? + needle.len();
+    let end = tag[start..].find('"')? + start;
+    Some(tag[start..end].to_string())
+}
+
+/// Map a DIDL-Lite protocolInfo (e.g. "http-get:*:audio/x-flac:*") to a format name
+fn format_from_protocol_info(protocol_info: &str) -> Option<String> {
+    let mime = protocol_info.split(':').nth(2)?.to_ascii_lowercase();
+    let subtype = mime.strip_prefix("audio/")?;
+    let subtype = subtype.split(';').next().unwrap_or(subtype);
+    let format = match subtype.trim_start_matches("x-") {
+        "flac" => "FLAC",
+        "wav" | "wave" => "WAV",
+        "aiff" => "AIFF",
+        "mpeg" | "mp3" => "MP3",
+        "mp4" | "m4a" | "aac" => "AAC",
+        "ogg" | "vorbis" => "OGG",
+        "dsd" | "dsf" | "dff" => "DSD",
+        "l16" | "l24" => "PCM",
+        other => return Some(other.to_uppercase()),
+    };
+    Some(format.to_string())
+}
+
+/// Extract stream format from the first `<res>` element of a DIDL-Lite document.
+/// Per the ContentDirectory spec, `bitrate` is in bytes per second.
+fn parse_didl_res_metadata(didl: &str) -> Option<TrackMetadata> {
+    let start = didl.find("<res")?;
+    let end = didl[start..].find('>')? + start;
+    let tag = &didl[start..end];
+    let number = |name: &str| {
+        xml_attribute(tag, name)
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .filter(|n| *n > 0)
+    };
+
+    let metadata = TrackMetadata {
+        format: xml_attribute(tag, "protocolInfo")
+            .as_deref()
+            .and_then(format_from_protocol_info),
+        sample_rate: number("sampleFrequency"),
+        bit_depth: number("bitsPerSample").map(|b| b as u8),
+        bitrate: number("bitrate").map(|b| b * 8 / 1000),
+        ..Default::default()
+    };
+
+    if metadata.is_empty() {
+        None
+    } else {
+        Some(metadata)
+    }
+}
+
+/// Format seconds as a UPnP REL_TIME target (H:MM:SS)
+fn format_upnp_time(secs: u32) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+}
+
+/// Parse a UPnP time string (H+:MM:SS[.F+]) into whole seconds
+fn parse_upnp_time(time: &str) -> Option<u32> {
+    let mut parts = time.trim().split(':');
+    let hours: u32 = parts.next()?.parse().ok()?;
+    let minutes: u32 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(hours * 3600 + minutes * 60 + seconds as u32)
+}
+
+#[cfg(test)]
+pub(crate) fn capability_fixture() -> crate::adapters::traits::CapabilityFixture {
+    let renderer = UPnPRenderer {
+        uuid: "test-renderer".to_string(),
+        name: "Renderer".to_string(),
+        manufacturer: None,
+        model: None,
+        location: "http://127.0.0.1:9/desc.xml".to_string(),
+        state: "stopped".to_string(),
+        volume: Some(20),
+        muted: false,
+        track_metadata: None,
+        last_seen: std::time::Instant::now(),
+        last_track_uri: None,
+        av_transport_url: Some("http://127.0.0.1:9/AVTransport".to_string()),
+        rendering_control_url: Some("http://127.0.0.1:9/RenderingControl".to_string()),
+    };
+    crate::adapters::traits::CapabilityFixture::new(
+        UPnPAdapter::new(crate::bus::create_bus()),
+        upnp_renderer_to_zone(&renderer),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upnp_time_round_trip() {
+        assert_eq!(format_upnp_time(0), "0:00:00");
+        assert_eq!(format_upnp_time(3725), "1:02:05");
+        assert_eq!(parse_upnp_time("1:02:05"), Some(3725));
+        assert_eq!(parse_upnp_time("0:03:07.500"), Some(187));
+    }
+
+    #[test]
+    fn didl_res_metadata_parsed() {
+        let didl = r#"<DIDL-Lite><item><dc:title>Song</dc:title><res protocolInfo="http-get:*:audio/x-flac:*" bitrate="288000" sampleFrequency="192000" bitsPerSample="24" nrAudioChannels="2">http://host/track.flac</res></item></DIDL-Lite>"#;
+        let metadata = parse_didl_res_metadata(didl).unwrap();
+        assert_eq!(metadata.format.as_deref(), Some("FLAC"));
+        assert_eq!(metadata.sample_rate, Some(192000));
+        assert_eq!(metadata.bit_depth, Some(24));
+        assert_eq!(metadata.bitrate, Some(2304));
+        assert_eq!(metadata.summary().as_deref(), Some("FLAC 24/192"));
+    }
+
+    #[test]
+    fn didl_without_res_has_no_metadata() {
+        assert_eq!(parse_didl_res_metadata("<DIDL-Lite></DIDL-Lite>"), None);
+        assert_eq!(
+            parse_didl_res_metadata(&html_decode("&lt;res&gt;http://x&lt;/res&gt;")),
+            None
+        );
+    }
+
+    #[test]
+    fn upnp_time_rejects_garbage() {
+        assert_eq!(parse_upnp_time("NOT_IMPLEMENTED"), None);
+        assert_eq!(parse_upnp_time("1:2:3:4"), None);
+    }
+}
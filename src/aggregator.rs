@@ -1,20 +1,40 @@
 //! ZoneAggregator - Single source of truth for zone state
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, warn};
 
-use crate::bus::{BusEvent, NowPlaying, SharedBus, Zone};
+use crate::bus::{BusEvent, NowPlaying, PrefixedZoneId, SharedBus, Zone};
+
+/// Zone cache file for warm starts (under the config dir)
+pub const ZONE_CACHE_FILE: &str = "zone_cache.json";
+
+/// How often pending zone changes are written to the cache
+const CACHE_SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Cached zones not confirmed by their adapter within this window are dropped
+const STALE_ZONE_TTL: Duration = Duration::from_secs(300);
+
+/// Minimum time between resync requests; lag inside the window is resynced
+/// on the next cache tick instead, so a burst doesn't trigger a snapshot storm
+const RESYNC_MIN_INTERVAL: Duration = Duration::from_secs(5);
 
 /// ZoneAggregator maintains unified zone state from all adapters.
 /// - Subscribes to bus events
 /// - Maintains HashMap of zones by zone_id
 /// - Flushes zones when adapter stops
+/// - Asks adapters for a full snapshot when it falls behind the bus
 /// - Provides query interface for API layer
+/// - Optionally persists zones so a restart can serve them (flagged stale) right away
 pub struct ZoneAggregator {
     zones: Arc<RwLock<HashMap<String, Zone>>>,
     bus: SharedBus,
+    cache_path: Option<PathBuf>,
 }
 
 impl ZoneAggregator {
@@ -22,6 +42,23 @@ impl ZoneAggregator {
         Self {
             zones: Arc::new(RwLock::new(HashMap::new())),
             bus,
+            cache_path: None,
+        }
+    }
+
+    /// Create an aggregator that warm-starts from (and keeps writing) a zone cache.
+    ///
+    /// Cached zones are served with `is_stale` set until their adapter
+    /// rediscovers them; zones never confirmed expire after a few minutes.
+    pub fn with_cache(bus: SharedBus, cache_path: PathBuf) -> Self {
+        let zones = load_zone_cache(&cache_path);
+        if !zones.is_empty() {
+            info!("Warm start: {} cached zones (stale)", zones.len());
+        }
+        Self {
+            zones: Arc::new(RwLock::new(zones)),
+            bus,
+            cache_path: Some(cache_path),
         }
     }
 
@@ -29,13 +66,63 @@ impl ZoneAggregator {
     /// Should be spawned as a task
     pub async fn run(&self) {
         let mut rx = self.bus.subscribe();
+        let started = Instant::now();
+        let mut dirty = false;
+        let mut cache_tick = tokio::time::interval(CACHE_SAVE_INTERVAL);
+        cache_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut last_resync: Option<Instant> = None;
+        let mut resync_pending = false;
 
         info!("ZoneAggregator started");
 
-        while let Ok(event) = rx.recv().await {
+        loop {
+            let event = tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("ZoneAggregator lagged, skipped {} events", skipped);
+                        resync_pending = true;
+                        if last_resync.is_none_or(|at| at.elapsed() >= RESYNC_MIN_INTERVAL) {
+                            self.request_resync(skipped);
+                            last_resync = Some(Instant::now());
+                            resync_pending = false;
+                        }
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = cache_tick.tick() => {
+                    if resync_pending {
+                        self.request_resync(0);
+                        last_resync = Some(Instant::now());
+                        resync_pending = false;
+                    }
+                    if started.elapsed() >= STALE_ZONE_TTL && self.expire_stale_zones().await {
+                        dirty = true;
+                    }
+                    if dirty {
+                        self.save_cache().await;
+                        dirty = false;
+                    }
+                    continue;
+                }
+            };
+
+            dirty |= matches!(
+                event,
+                BusEvent::ZoneDiscovered { .. }
+                    | BusEvent::ZoneUpdated { .. }
+                    | BusEvent::ZoneRemoved { .. }
+                    | BusEvent::ZonesSnapshot { .. }
+                    | BusEvent::NowPlayingChanged { .. }
+                    | BusEvent::TrackMetadataChanged { .. }
+                    | BusEvent::VolumeChanged { .. }
+            );
+
             match event {
                 BusEvent::ZoneDiscovered { zone } => {
                     debug!("Zone discovered: {}", zone.zone_id);
+                    // A (re)discovery from the adapter replaces any cached copy
                     self.zones.write().await.insert(zone.zone_id.clone(), zone);
                 }
 
@@ -56,34 +143,80 @@ impl ZoneAggregator {
                     self.zones.write().await.remove(zone_id.as_str());
                 }
 
+                BusEvent::ZonesSnapshot { adapter, zones } => {
+                    info!("Resynced {} zones from adapter: {}", zones.len(), adapter);
+                    let prefix = format!("{}:", adapter);
+                    let mut current = self.zones.write().await;
+                    current.retain(|zone_id, _| !zone_id.starts_with(&prefix));
+                    for zone in zones {
+                        current.insert(zone.zone_id.clone(), zone);
+                    }
+                }
+
                 BusEvent::NowPlayingChanged {
                     zone_id,
                     title,
                     artist,
                     album,
                     image_key,
+                    duration,
                 } => {
                     debug!("Now playing changed: {}", zone_id);
                     if let Some(zone) = self.zones.write().await.get_mut(zone_id.as_str()) {
-                        // Preserve seek_position and duration from existing now_playing
-                        let (seek_position, duration) = zone
+                        let title = title.unwrap_or_default();
+                        let artist = artist.unwrap_or_default();
+                        let album = album.unwrap_or_default();
+
+                        // Preserve seek_position from existing now_playing, and duration
+                        // when the adapter didn't report one.
+                        // Format metadata only survives if the track itself is unchanged;
+                        // adapters follow up with TrackMetadataChanged for the new track.
+                        let (seek_position, previous_duration, metadata) = zone
                             .now_playing
                             .as_ref()
-                            .map(|np| (np.seek_position, np.duration))
-                            .unwrap_or((None, None));
+                            .map(|np| {
+                                let same_track =
+                                    np.title == title && np.artist == artist && np.album == album;
+                                (
+                                    np.seek_position,
+                                    np.duration,
+                                    np.metadata.clone().filter(|_| same_track),
+                                )
+                            })
+                            .unwrap_or((None, None, None));
+                        let duration = duration.or(previous_duration);
 
                         zone.now_playing = Some(NowPlaying {
-                            title: title.unwrap_or_default(),
-                            artist: artist.unwrap_or_default(),
-                            album: album.unwrap_or_default(),
+                            title,
+                            artist,
+                            album,
                             image_key,
                             seek_position,
                             duration,
-                            metadata: None,
+                            metadata,
                         });
                     }
                 }
 
+                BusEvent::TrackMetadataChanged { zone_id, metadata } => {
+                    debug!("Track metadata changed: {}", zone_id);
+                    if let Some(zone) = self.zones.write().await.get_mut(zone_id.as_str()) {
+                        if let Some(ref mut np) = zone.now_playing {
+                            np.metadata = metadata;
+                        } else if metadata.is_some() {
+                            zone.now_playing = Some(NowPlaying {
+                                title: String::new(),
+                                artist: String::new(),
+                                album: String::new(),
+                                image_key: None,
+                                seek_position: None,
+                                duration: None,
+                                metadata,
+                            });
+                        }
+                    }
+                }
+
                 BusEvent::VolumeChanged {
                     output_id,
                     value,
@@ -125,6 +258,12 @@ impl ZoneAggregator {
                 }
 
                 BusEvent::AdapterStopping { adapter, .. } => {
+                    // Persist before flushing so a shutdown doesn't leave an empty cache
+                    if dirty {
+                        self.save_cache().await;
+                        dirty = false;
+                    }
+
                     info!("Flushing zones for adapter: {}", adapter);
                     let prefix = format!("{}:", adapter);
 
@@ -150,6 +289,9 @@ impl ZoneAggregator {
 
                 BusEvent::ShuttingDown { .. } => {
                     info!("ZoneAggregator shutting down");
+                    if dirty {
+                        self.save_cache().await;
+                    }
                     break;
                 }
 
@@ -162,6 +304,69 @@ impl ZoneAggregator {
         info!("ZoneAggregator stopped");
     }
 
+    /// Ask every adapter to republish its zones after missing events
+    fn request_resync(&self, skipped: u64) {
+        let reason = if skipped > 0 {
+            format!("zone aggregator lagged by {} events", skipped)
+        } else {
+            "zone aggregator lagged".to_string()
+        };
+        self.bus.publish(BusEvent::ResyncRequested {
+            adapter: None,
+            reason: Some(reason),
+        });
+    }
+
+    /// Drop cached zones that no adapter has confirmed. Returns true if any were removed.
+    async fn expire_stale_zones(&self) -> bool {
+        let expired: Vec<String> = {
+            let mut zones = self.zones.write().await;
+            let ids: Vec<String> = zones
+                .values()
+                .filter(|z| z.is_stale)
+                .map(|z| z.zone_id.clone())
+                .collect();
+            for zone_id in &ids {
+                zones.remove(zone_id);
+            }
+            ids
+        };
+
+        for zone_id in &expired {
+            info!("Cached zone never reappeared, removing: {}", zone_id);
+            if let Some(zone_id) = PrefixedZoneId::parse(zone_id) {
+                self.bus.publish(BusEvent::ZoneRemoved { zone_id });
+            }
+        }
+
+        !expired.is_empty()
+    }
+
+    /// Write the current zones to the warm-start cache (no-op without a cache path)
+    async fn save_cache(&self) {
+        let Some(path) = self.cache_path.as_ref() else {
+            return;
+        };
+
+        let mut zones: Vec<Zone> = self.zones.read().await.values().cloned().collect();
+        zones.sort_by(|a, b| a.zone_id.cmp(&b.zone_id));
+
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        match serde_json::to_string_pretty(&zones) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    warn!("Failed to save zone cache: {}", e);
+                } else {
+                    debug!("Saved {} zones to cache", zones.len());
+                }
+            }
+            Err(e) => warn!("Failed to serialize zone cache: {}", e),
+        }
+    }
+
     /// Get all zones
     pub async fn get_zones(&self) -> Vec<Zone> {
         self.zones.read().await.values().cloned().collect()
@@ -198,3 +403,274 @@ impl ZoneAggregator {
         self.zones.read().await.len()
     }
 }
+
+/// Load cached zones, marking each one stale until its adapter confirms it
+fn load_zone_cache(path: &Path) -> HashMap<String, Zone> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return HashMap::new();
+    };
+
+    match serde_json::from_str::<Vec<Zone>>(&content) {
+        Ok(zones) => zones
+            .into_iter()
+            .filter(|z| PrefixedZoneId::parse(&z.zone_id).is_some())
+            .map(|mut z| {
+                z.is_stale = true;
+                if let Some(ref mut np) = z.now_playing {
+                    np.seek_position = None;
+                }
+                (z.zone_id.clone(), z)
+            })
+            .collect(),
+        Err(e) => {
+            warn!("Failed to parse zone cache: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::{
+        create_bus, EventBus, PlaybackState, PrefixedZoneId, TrackMetadata, ZoneCapabilities,
+    };
+    use std::time::Duration;
+
+    fn test_zone(zone_id: &str) -> Zone {
+        Zone {
+            zone_id: zone_id.to_string(),
+            zone_name: "Test Zone".to_string(),
+            state: PlaybackState::Playing,
+            volume_control: None,
+            now_playing: None,
+            source: "lms".to_string(),
+            is_controllable: true,
+            is_seekable: true,
+            last_updated: 0,
+            is_play_allowed: false,
+            is_pause_allowed: true,
+            is_next_allowed: true,
+            is_previous_allowed: true,
+            capabilities: ZoneCapabilities::default(),
+            is_stale: false,
+        }
+    }
+
+    fn now_playing(title: &str) -> BusEvent {
+        BusEvent::NowPlayingChanged {
+            zone_id: PrefixedZoneId::lms("aa"),
+            title: Some(title.to_string()),
+            artist: Some("Artist".to_string()),
+            album: Some("Album".to_string()),
+            image_key: None,
+            duration: None,
+        }
+    }
+
+    /// Wait until the aggregator's view of a zone passes `check`
+    async fn wait_for(
+        aggregator: &ZoneAggregator,
+        zone_id: &str,
+        check: impl Fn(Option<&Zone>) -> bool,
+    ) {
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while !check(aggregator.get_zone(zone_id).await.as_ref()) {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("aggregator never reached the expected state");
+    }
+
+    /// Wait until everything published before this has been handled: a seek
+    /// marker on lms:aa is applied after the events queued ahead of it
+    async fn sync(bus: &SharedBus, aggregator: &ZoneAggregator, marker: i64) {
+        bus.publish(BusEvent::SeekPositionChanged {
+            zone_id: PrefixedZoneId::lms("aa"),
+            position: marker,
+        });
+        wait_for(aggregator, "lms:aa", |z| {
+            z.and_then(|z| z.now_playing.as_ref())
+                .is_some_and(|np| np.seek_position == Some(marker as f64))
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn track_metadata_follows_current_track() {
+        let bus = create_bus();
+        let aggregator = Arc::new(ZoneAggregator::new(bus.clone()));
+        let runner = aggregator.clone();
+        let handle = tokio::spawn(async move { runner.run().await });
+        while bus.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let metadata = TrackMetadata {
+            format: Some("FLAC".to_string()),
+            sample_rate: Some(96_000),
+            bit_depth: Some(24),
+            ..Default::default()
+        };
+
+        bus.publish(BusEvent::ZoneDiscovered {
+            zone: test_zone("lms:aa"),
+        });
+        bus.publish(now_playing("One"));
+        bus.publish(BusEvent::TrackMetadataChanged {
+            zone_id: PrefixedZoneId::lms("aa"),
+            metadata: Some(metadata.clone()),
+        });
+        sync(&bus, &aggregator, 1).await;
+
+        let np = aggregator.get_now_playing("lms:aa").await.unwrap();
+        assert_eq!(np.metadata, Some(metadata.clone()));
+
+        // Repeated now-playing for the same track keeps the format
+        bus.publish(now_playing("One"));
+        sync(&bus, &aggregator, 2).await;
+        let np = aggregator.get_now_playing("lms:aa").await.unwrap();
+        assert_eq!(np.metadata, Some(metadata));
+
+        // A new track drops stale format details
+        bus.publish(now_playing("Two"));
+        sync(&bus, &aggregator, 3).await;
+        let np = aggregator.get_now_playing("lms:aa").await.unwrap();
+        assert_eq!(np.title, "Two");
+        assert_eq!(np.metadata, None);
+
+        bus.publish(BusEvent::ShuttingDown { reason: None });
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn warm_start_serves_cached_zones_until_rediscovered() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ZONE_CACHE_FILE);
+        let mut cached = test_zone("lms:aa");
+        cached.now_playing = Some(NowPlaying {
+            title: "Cached".to_string(),
+            artist: String::new(),
+            album: String::new(),
+            image_key: None,
+            seek_position: Some(42.0),
+            duration: Some(180.0),
+            metadata: None,
+        });
+        std::fs::write(&path, serde_json::to_string(&vec![cached]).unwrap()).unwrap();
+
+        let bus = create_bus();
+        let aggregator = Arc::new(ZoneAggregator::with_cache(bus.clone(), path.clone()));
+
+        let zone = aggregator.get_zone("lms:aa").await.unwrap();
+        assert!(zone.is_stale);
+        let np = zone.now_playing.unwrap();
+        assert_eq!(np.title, "Cached");
+        assert_eq!(np.seek_position, None);
+
+        let runner = aggregator.clone();
+        let handle = tokio::spawn(async move { runner.run().await });
+        while bus.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let mut fresh = test_zone("lms:aa");
+        fresh.zone_name = "Kitchen".to_string();
+        bus.publish(BusEvent::ZoneDiscovered { zone: fresh });
+        wait_for(&aggregator, "lms:aa", |z| z.is_some_and(|z| !z.is_stale)).await;
+
+        let zone = aggregator.get_zone("lms:aa").await.unwrap();
+        assert!(!zone.is_stale);
+        assert_eq!(zone.zone_name, "Kitchen");
+
+        // Shutdown persists the confirmed zone set
+        bus.publish(BusEvent::ShuttingDown { reason: None });
+        handle.await.unwrap();
+        let saved: Vec<Zone> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].zone_name, "Kitchen");
+    }
+
+    #[tokio::test]
+    async fn adapter_flush_keeps_cached_zones_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ZONE_CACHE_FILE);
+
+        let bus = create_bus();
+        let aggregator = Arc::new(ZoneAggregator::with_cache(bus.clone(), path.clone()));
+        let runner = aggregator.clone();
+        let handle = tokio::spawn(async move { runner.run().await });
+        while bus.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        bus.publish(BusEvent::ZoneDiscovered {
+            zone: test_zone("lms:aa"),
+        });
+        bus.publish(BusEvent::AdapterStopping {
+            adapter: "lms".to_string(),
+            reason: None,
+        });
+        bus.publish(BusEvent::ShuttingDown { reason: None });
+        handle.await.unwrap();
+
+        assert!(aggregator.get_zone("lms:aa").await.is_none());
+        let restarted = ZoneAggregator::with_cache(bus, path);
+        assert!(restarted.get_zone("lms:aa").await.unwrap().is_stale);
+    }
+
+    #[tokio::test]
+    async fn lag_requests_resync_and_snapshot_replaces_adapter_zones() {
+        let bus = Arc::new(EventBus::new(4));
+        let aggregator = Arc::new(ZoneAggregator::new(bus.clone()));
+        let runner = aggregator.clone();
+        let handle = tokio::spawn(async move { runner.run().await });
+        while bus.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        let mut rx = bus.subscribe();
+
+        bus.publish(BusEvent::ZoneDiscovered {
+            zone: test_zone("lms:aa"),
+        });
+        bus.publish(BusEvent::ZoneDiscovered {
+            zone: test_zone("roon:1"),
+        });
+        wait_for(&aggregator, "roon:1", |z| z.is_some()).await;
+
+        // Overflow the channel before the aggregator gets to run
+        for position in 0..10 {
+            bus.publish(BusEvent::SeekPositionChanged {
+                zone_id: PrefixedZoneId::lms("aa"),
+                position,
+            });
+        }
+
+        let requested = tokio::time::timeout(Duration::from_secs(1), async {
+            loop {
+                if let Ok(BusEvent::ResyncRequested { adapter, .. }) = rx.recv().await {
+                    return adapter;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(requested, None);
+
+        // The LMS adapter answers: aa went away while we weren't listening, bb appeared
+        bus.publish(BusEvent::ZonesSnapshot {
+            adapter: "lms".to_string(),
+            zones: vec![test_zone("lms:bb")],
+        });
+        wait_for(&aggregator, "lms:bb", |z| z.is_some()).await;
+
+        assert!(aggregator.get_zone("lms:aa").await.is_none());
+        assert!(aggregator.get_zone("lms:bb").await.is_some());
+        assert!(aggregator.get_zone("roon:1").await.is_some());
+
+        bus.publish(BusEvent::ShuttingDown { reason: None });
+        handle.await.unwrap();
+    }
+}
@@ -1,27 +1,47 @@
 //! HTTP API handlers
 
+use crate::adapters::external::{ExternalAdapters, EXTERNAL_PREFIX};
 use crate::adapters::hqplayer::{HqpAdapter, HqpInstanceManager, HqpZoneLinkService};
 use crate::adapters::lms::LmsAdapter;
 use crate::adapters::openhome::OpenHomeAdapter;
 use crate::adapters::roon::RoonAdapter;
 use crate::adapters::upnp::UPnPAdapter;
-use crate::adapters::Startable;
+use crate::adapters::{AdapterCommand, AdapterCommandResponse, AdapterLogic, Startable};
 use crate::aggregator::ZoneAggregator;
-use crate::bus::SharedBus;
-use crate::coordinator::AdapterCoordinator;
-use crate::knobs::KnobStore;
+use crate::audit::{
+    audit_action, audit_command, request_origin, request_surface, AuditFilter, AuditLog,
+    CommandAudit,
+};
+use crate::auth::{AuthStore, Scope};
+use crate::bus::{
+    Command, CommandOrigin, CommandResponse, ControlSurface, SequencedEvent, SharedBus,
+};
+use crate::coordinator::{AdapterCoordinator, AdapterStatus};
+use crate::fades::{fade_levels, FadeSettings, Fades, FADE_STEP_INTERVAL};
+use crate::history::{HistoryFilter, HistoryStore};
+use crate::knobs::{KnobStore, VolumeCoalescer};
+use crate::metrics::Metrics;
+use crate::rooms::{RoomRole, RoomStore, ROOM_PREFIX};
+use crate::rules::RulesEngine;
+use crate::scenes::{CaptureRequest, Scene, SceneStore};
+use crate::scheduler::Scheduler;
+use crate::scrobbler::{Scrobbler, ScrobblerConfig};
+use crate::sleep_timer::{SleepRequest, SleepTimers};
+use crate::tls::OverTls;
+use crate::volume_policy::{VolumePolicies, VolumePolicyStore};
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{rejection::ExtensionRejection, ConnectInfo, Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::{
         sse::{Event, KeepAlive, Sse},
         IntoResponse,
     },
-    Json,
+    Extension, Json,
 };
 use futures::stream::Stream;
 use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
+use std::net::SocketAddr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -29,6 +49,15 @@ use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::StreamExt;
 use tokio_util::sync::CancellationToken;
 
+/// Volume actually applied by `AppState::set_volume`
+#[derive(Debug, Clone, Serialize)]
+pub struct VolumeOutcome {
+    /// Level sent to the zone (None when a relative step was sent with the level unknown)
+    pub value: Option<f32>,
+    /// Why the requested level was clamped
+    pub reason: Option<String>,
+}
+
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState {
@@ -39,7 +68,17 @@ pub struct AppState {
     pub lms: Arc<LmsAdapter>,
     pub openhome: Arc<OpenHomeAdapter>,
     pub upnp: Arc<UPnPAdapter>,
+    pub external: Arc<ExternalAdapters>,
     pub knobs: KnobStore,
+    pub rooms: Arc<RoomStore>,
+    pub history: Arc<HistoryStore>,
+    pub scrobbler: Arc<Scrobbler>,
+    pub scheduler: Arc<Scheduler>,
+    pub rules: Arc<RulesEngine>,
+    pub volume_policy: Arc<VolumePolicyStore>,
+    pub fades: Arc<Fades>,
+    pub sleep_timers: Arc<SleepTimers>,
+    pub scenes: Arc<SceneStore>,
     pub bus: SharedBus,
     pub aggregator: Arc<ZoneAggregator>,
     pub coordinator: Arc<AdapterCoordinator>,
@@ -49,6 +88,14 @@ pub struct AppState {
     pub shutdown: CancellationToken,
     /// Count of active SSE connections (for shutdown diagnostics)
     pub sse_connections: Arc<AtomicUsize>,
+    /// Counters exported on `/metrics`
+    pub metrics: Arc<Metrics>,
+    /// API tokens and whether they're required
+    pub auth: Arc<AuthStore>,
+    /// Control actions and where they came from
+    pub audit: Arc<AuditLog>,
+    /// Pending knob volume targets, sent at most once per window per zone
+    pub volume_coalescer: Arc<VolumeCoalescer>,
 }
 
 impl AppState {
@@ -61,7 +108,17 @@ impl AppState {
         lms: Arc<LmsAdapter>,
         openhome: Arc<OpenHomeAdapter>,
         upnp: Arc<UPnPAdapter>,
+        external: Arc<ExternalAdapters>,
         knobs: KnobStore,
+        rooms: Arc<RoomStore>,
+        history: Arc<HistoryStore>,
+        scrobbler: Arc<Scrobbler>,
+        scheduler: Arc<Scheduler>,
+        rules: Arc<RulesEngine>,
+        volume_policy: Arc<VolumePolicyStore>,
+        fades: Arc<Fades>,
+        sleep_timers: Arc<SleepTimers>,
+        scenes: Arc<SceneStore>,
         bus: SharedBus,
         aggregator: Arc<ZoneAggregator>,
         coordinator: Arc<AdapterCoordinator>,
@@ -77,7 +134,17 @@ impl AppState {
             lms,
             openhome,
             upnp,
+            external,
             knobs,
+            rooms,
+            history,
+            scrobbler,
+            scheduler,
+            rules,
+            volume_policy,
+            fades,
+            sleep_timers,
+            scenes,
             bus,
             aggregator,
             coordinator,
@@ -85,9 +152,20 @@ impl AppState {
             start_time,
             shutdown,
             sse_connections: Arc::new(AtomicUsize::new(0)),
+            metrics: Arc::new(Metrics::new()),
+            auth: Arc::new(AuthStore::new()),
+            audit: Arc::new(AuditLog::new()),
+            volume_coalescer: Arc::new(VolumeCoalescer::new()),
         }
     }
 
+    /// All adapters the coordinator can start and stop, including extra LMS servers
+    pub async fn startables(&self) -> Vec<Arc<dyn Startable>> {
+        let mut adapters: Vec<Arc<dyn Startable>> = self.startable_adapters.to_vec();
+        adapters.extend(self.lms.server_startables().await);
+        adapters
+    }
+
     /// Get the count of active SSE connections
     pub fn active_sse_connections(&self) -> usize {
         self.sse_connections.load(Ordering::Relaxed)
@@ -114,8 +192,50 @@ impl AppState {
         use crate::knobs::image::jpeg_to_rgb565;
 
         // Fetch raw image from appropriate adapter
-        let raw_image = if zone_id.starts_with("lms:") {
-            let (content_type, data) = self.lms.get_artwork(image_key, width, height).await?;
+        let raw_image = self.fetch_image(zone_id, image_key, width, height).await;
+        self.metrics.record_image(zone_id, raw_image.is_ok());
+        let raw_image = raw_image?;
+
+        // Convert to RGB565 if requested (for ESP32 LCD displays)
+        if format == Some("rgb565") {
+            // Use square dimensions when only one side specified (matches adapter behavior)
+            let (target_w, target_h) = match (width, height) {
+                (Some(w), Some(h)) => (w, h),
+                (Some(w), None) => (w, w),
+                (None, Some(h)) => (h, h),
+                (None, None) => (240, 240),
+            };
+
+            match jpeg_to_rgb565(&raw_image.data, target_w, target_h) {
+                Ok(rgb565) => Ok(ImageData {
+                    content_type: "application/octet-stream".to_string(),
+                    data: rgb565.data,
+                }),
+                Err(_) => {
+                    // Fall back to original on conversion error
+                    Ok(raw_image)
+                }
+            }
+        } else {
+            Ok(raw_image)
+        }
+    }
+
+    /// Fetch the original image from the adapter owning the zone
+    async fn fetch_image(
+        &self,
+        zone_id: &str,
+        image_key: &str,
+        width: Option<u32>,
+        height: Option<u32>,
+    ) -> anyhow::Result<crate::bus::ImageData> {
+        use crate::bus::ImageData;
+
+        let image = if zone_id.starts_with("lms:") {
+            let (content_type, data) = self
+                .lms
+                .get_artwork(zone_id, image_key, width, height)
+                .await?;
             ImageData { content_type, data }
         } else if zone_id.starts_with("openhome:") {
             let img = self.openhome.get_image(image_key).await?;
@@ -137,29 +257,498 @@ impl AppState {
             anyhow::bail!("Unknown zone type for image: {}", zone_id)
         };
 
-        // Convert to RGB565 if requested (for ESP32 LCD displays)
-        if format == Some("rgb565") {
-            // Use square dimensions when only one side specified (matches adapter behavior)
-            let (target_w, target_h) = match (width, height) {
-                (Some(w), Some(h)) => (w, h),
-                (Some(w), None) => (w, w),
-                (None, Some(h)) => (h, h),
-                (None, None) => (240, 240),
-            };
+        Ok(image)
+    }
 
-            match jpeg_to_rgb565(&raw_image.data, target_w, target_h) {
-                Ok(rgb565) => Ok(ImageData {
-                    content_type: "application/octet-stream".to_string(),
-                    data: rgb565.data,
-                }),
-                Err(_) => {
-                    // Fall back to original on conversion error
-                    Ok(raw_image)
+    /// Send a command to any zone, routed by zone_id prefix.
+    /// Unprefixed IDs are treated as Roon zones (legacy knob convention).
+    /// Returns an error only when no adapter owns the zone.
+    async fn dispatch_command(
+        &self,
+        zone_id: &str,
+        command: AdapterCommand,
+        origin: &CommandOrigin,
+    ) -> anyhow::Result<AdapterCommandResponse> {
+        let role = if command.is_volume() {
+            RoomRole::Volume
+        } else {
+            RoomRole::Transport
+        };
+        let resolved = self.resolve_zone_id(zone_id, role).await?;
+
+        // Volume always goes through the safety policy
+        match command {
+            AdapterCommand::VolumeAbsolute(value) => {
+                let result = self
+                    .apply_volume(zone_id, &resolved, value as f32, false)
+                    .await;
+                return Ok(AdapterCommandResponse::from_result(result.map(|_| ())));
+            }
+            AdapterCommand::VolumeRelative(delta) => {
+                let result = self
+                    .apply_volume(zone_id, &resolved, delta as f32, true)
+                    .await;
+                return Ok(AdapterCommandResponse::from_result(result.map(|_| ())));
+            }
+            AdapterCommand::Play | AdapterCommand::PlayPause => {
+                let stopped = self
+                    .get_zone(&resolved)
+                    .await
+                    .is_some_and(|z| z.state == crate::bus::PlaybackState::Stopped);
+                if stopped {
+                    if let Err(e) = self.enforce_start_cap(zone_id, origin).await {
+                        tracing::warn!("Failed to apply start volume cap to {}: {}", zone_id, e);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        self.route_command(&resolved, command).await
+    }
+
+    /// Send a command from a control surface, fading the volume around pause,
+    /// stop, skips and resume when fades are enabled for the zone and surface.
+    ///
+    /// The command and its result are published on the bus with their origin.
+    /// Returns an error only when no adapter owns the zone.
+    pub async fn dispatch_command_from(
+        &self,
+        zone_id: &str,
+        command: AdapterCommand,
+        origin: CommandOrigin,
+    ) -> anyhow::Result<AdapterCommandResponse> {
+        // Volume is published by set_volume, with the policy's reason if clamped
+        match command {
+            AdapterCommand::VolumeAbsolute(value) => {
+                let result = self.set_volume(zone_id, value as f32, false, origin).await;
+                return Ok(AdapterCommandResponse::from_result(result.map(|_| ())));
+            }
+            AdapterCommand::VolumeRelative(delta) => {
+                let result = self.set_volume(zone_id, delta as f32, true, origin).await;
+                return Ok(AdapterCommandResponse::from_result(result.map(|_| ())));
+            }
+            _ => {}
+        }
+
+        let audit =
+            CommandAudit::begin(&self.bus, zone_id, Command::from(&command), origin.clone());
+        let result = self.fade_or_dispatch(zone_id, command, &origin).await;
+        audit.finish(
+            match &result {
+                Ok(response) => response.clone().into_result().map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            },
+            None,
+        );
+        result
+    }
+
+    async fn fade_or_dispatch(
+        &self,
+        zone_id: &str,
+        command: AdapterCommand,
+        origin: &CommandOrigin,
+    ) -> anyhow::Result<AdapterCommandResponse> {
+        let surface = origin.surface;
+        let settings = self.fades.settings().await;
+        let is_transport = matches!(
+            command,
+            AdapterCommand::Play
+                | AdapterCommand::PlayPause
+                | AdapterCommand::Pause
+                | AdapterCommand::Stop
+                | AdapterCommand::Next
+                | AdapterCommand::Previous
+        );
+        let Some(duration) = settings
+            .duration_for(&[zone_id], surface)
+            .filter(|_| is_transport)
+        else {
+            return self.dispatch_command(zone_id, command, origin).await;
+        };
+
+        let transport = self.resolve_zone_id(zone_id, RoomRole::Transport).await?;
+        let volume_zone = self.resolve_zone_id(zone_id, RoomRole::Volume).await?;
+        let Some(control) = self
+            .volume_control(&volume_zone)
+            .await
+            .filter(|vc| !vc.is_muted)
+        else {
+            return self.dispatch_command(zone_id, command, origin).await;
+        };
+        let zone_state = self.get_zone(&transport).await.map(|z| z.state);
+        let playing = zone_state == Some(crate::bus::PlaybackState::Playing);
+
+        self.fade_command(
+            zone_id,
+            &transport,
+            &volume_zone,
+            command,
+            control,
+            zone_state,
+            playing,
+            duration,
+            &settings,
+            origin,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn fade_command(
+        &self,
+        zone_id: &str,
+        transport: &str,
+        volume_zone: &str,
+        command: AdapterCommand,
+        control: crate::bus::VolumeControl,
+        zone_state: Option<crate::bus::PlaybackState>,
+        playing: bool,
+        duration: Duration,
+        settings: &FadeSettings,
+        origin: &CommandOrigin,
+    ) -> anyhow::Result<AdapterCommandResponse> {
+        use crate::bus::PlaybackState;
+
+        let fade = self.fades.begin(volume_zone, control.value);
+        let level = fade.level;
+        let floor = control.min;
+
+        // Each branch returns the level to put back unless the fade ended where it
+        // should, so no exit leaves the zone at its floor
+        let (result, restore) = match command {
+            // Fade out, act once silent, then put the level back for next time
+            AdapterCommand::Pause | AdapterCommand::Stop | AdapterCommand::PlayPause if playing => {
+                self.run_fade(volume_zone, level, floor, duration, &fade)
+                    .await;
+                (self.route_command(transport, command).await, Some(level))
+            }
+            // Dip around a track change
+            AdapterCommand::Next | AdapterCommand::Previous if playing => {
+                let completed = self
+                    .run_fade(volume_zone, level, floor, duration, &fade)
+                    .await;
+                let result = self.route_command(transport, command).await;
+                let restored = match &result {
+                    Ok(_) if completed => {
+                        self.run_fade(volume_zone, floor, level, duration, &fade)
+                            .await
+                    }
+                    _ => false,
+                };
+                (result, (!restored).then_some(level))
+            }
+            // Start silent and fade in (never above the start cap)
+            AdapterCommand::Play | AdapterCommand::PlayPause if settings.fade_in && !playing => {
+                let mut target = level;
+                if zone_state == Some(PlaybackState::Stopped) {
+                    let policy = self.volume_policy.policy_for(&[zone_id, volume_zone]).await;
+                    target = policy.start_level(level).unwrap_or(level);
+                }
+                match self.send_volume(volume_zone, floor, false).await {
+                    Ok(()) => {
+                        let result = self.route_command(transport, command).await;
+                        let faded = match &result {
+                            Ok(response) if response.success => {
+                                self.run_fade(volume_zone, floor, target, duration, &fade)
+                                    .await
+                            }
+                            _ => false,
+                        };
+                        (result, (!faded).then_some(target))
+                    }
+                    Err(e) => (Err(e), None),
                 }
             }
+            command => {
+                // Cutting a fade short still puts back the level it started from
+                let took_over = level != control.value;
+                (
+                    self.dispatch_command(zone_id, command, origin).await,
+                    took_over.then_some(level),
+                )
+            }
+        };
+
+        // A fade taken over by a newer fade or a volume command leaves the level to it
+        if let Some(restore) = restore.filter(|_| !fade.token.is_cancelled()) {
+            if let Err(e) = self.send_volume(volume_zone, restore, false).await {
+                tracing::warn!("Failed to restore volume of {}: {}", volume_zone, e);
+            }
+        }
+        self.fades.finish(volume_zone, &fade);
+        result
+    }
+
+    /// Step the volume from `from` to `to`. Returns false if the fade was cancelled.
+    async fn run_fade(
+        &self,
+        zone_id: &str,
+        from: f32,
+        to: f32,
+        duration: Duration,
+        fade: &crate::fades::FadeHandle,
+    ) -> bool {
+        for level in fade_levels(from, to, duration) {
+            tokio::select! {
+                _ = fade.token.cancelled() => return false,
+                _ = tokio::time::sleep(FADE_STEP_INTERVAL) => {}
+            }
+            if let Err(e) = self.send_volume(zone_id, level, false).await {
+                tracing::warn!("Fade on {} stopped: {}", zone_id, e);
+                return false;
+            }
+        }
+        !fade.token.is_cancelled()
+    }
+
+    /// Send a command to the adapter owning an already-resolved zone id
+    async fn route_command(
+        &self,
+        zone_id: &str,
+        command: AdapterCommand,
+    ) -> anyhow::Result<AdapterCommandResponse> {
+        let action = command.name();
+        let started = Instant::now();
+        let result = self.send_to_adapter(zone_id, command).await;
+        let ok = matches!(&result, Ok(response) if response.success);
+        self.metrics
+            .record_command(zone_id, action, started.elapsed(), ok);
+        result
+    }
+
+    async fn send_to_adapter(
+        &self,
+        zone_id: &str,
+        command: AdapterCommand,
+    ) -> anyhow::Result<AdapterCommandResponse> {
+        if let Some(raw) = zone_id.strip_prefix("lms:") {
+            self.lms.handle_command(raw, command).await
+        } else if let Some(raw) = zone_id.strip_prefix("openhome:") {
+            self.openhome.handle_command(raw, command).await
+        } else if let Some(raw) = zone_id.strip_prefix("upnp:") {
+            self.upnp.handle_command(raw, command).await
+        } else if zone_id.starts_with(EXTERNAL_PREFIX) {
+            self.external.handle_command(zone_id, command).await
+        } else if let Some(raw) = zone_id.strip_prefix("hqplayer:") {
+            let adapter = match self.hqp_instances.get_for_zone(raw).await {
+                Some(adapter) => adapter,
+                None if self.hqplayer.get_status().await.host.as_deref() == Some(raw) => {
+                    self.hqplayer.clone()
+                }
+                None => anyhow::bail!("HQPlayer instance not found: {}", raw),
+            };
+            adapter.handle_command(command).await
+        } else if zone_id.starts_with("roon:") || !zone_id.contains(':') {
+            self.roon.handle_command(zone_id, command).await
         } else {
-            Ok(raw_image)
+            anyhow::bail!("Unknown zone type: {}", zone_id)
+        }
+    }
+
+    /// Set a zone's volume, applying its volume safety policy first.
+    ///
+    /// Every volume path should come through here. The request is published on the
+    /// bus with its origin; requests that break the policy are clamped rather than
+    /// refused, and their `CommandResult` carries the reason.
+    pub async fn set_volume(
+        &self,
+        zone_id: &str,
+        value: f32,
+        relative: bool,
+        origin: CommandOrigin,
+    ) -> anyhow::Result<VolumeOutcome> {
+        let command = if relative {
+            Command::VolumeRelative {
+                delta: value,
+                output_id: None,
+            }
+        } else {
+            Command::VolumeAbsolute {
+                value,
+                output_id: None,
+            }
+        };
+        let audit = CommandAudit::begin(&self.bus, zone_id, command, origin);
+        let result = match self.resolve_zone_id(zone_id, RoomRole::Volume).await {
+            Ok(resolved) => self.apply_volume(zone_id, &resolved, value, relative).await,
+            Err(e) => Err(e),
+        };
+        match &result {
+            Ok(outcome) => audit.finish(Ok(()), outcome.reason.clone()),
+            Err(e) => audit.finish(Err(e.to_string()), None),
+        }
+        result
+    }
+
+    async fn apply_volume(
+        &self,
+        zone_id: &str,
+        resolved: &str,
+        value: f32,
+        relative: bool,
+    ) -> anyhow::Result<VolumeOutcome> {
+        // A volume command takes over from any fade in progress
+        self.fades.cancel(resolved);
+
+        let policy = self.volume_policy.policy_for(&[zone_id, resolved]).await;
+        let control = self.volume_control(resolved).await;
+        let current = control.as_ref().map(|vc| vc.value);
+        let range = control.as_ref().map(|vc| (vc.min, vc.max));
+
+        let Some(requested) = (if relative {
+            current.map(|c| c + value)
+        } else {
+            Some(value)
+        }) else {
+            // Level unknown: only a bounded relative step can be checked
+            if value > 0.0 && policy.max_volume.is_some() {
+                anyhow::bail!(
+                    "Volume of {} is unknown; cannot enforce its ceiling",
+                    zone_id
+                );
+            }
+            let delta = policy.max_step.map_or(value, |step| value.min(step));
+            self.send_volume(resolved, delta, true).await?;
+            return Ok(VolumeOutcome {
+                value: None,
+                reason: None,
+            });
+        };
+
+        let decision = policy.apply(requested, current, range);
+        if let Some(reason) = &decision.reason {
+            tracing::warn!(
+                "Volume for {} clamped from {} to {}: {}",
+                zone_id,
+                requested,
+                decision.value,
+                reason
+            );
+        }
+        // Always the checked level: a relative step applied to a level that moved
+        // since (stale cache, steps in flight) could end up above the ceiling
+        self.send_volume(resolved, decision.value, false).await?;
+
+        Ok(VolumeOutcome {
+            value: Some(decision.value),
+            reason: decision.reason,
+        })
+    }
+
+    /// Lower a zone's volume to its policy's start cap, if it is above it
+    pub async fn enforce_start_cap(
+        &self,
+        zone_id: &str,
+        origin: &CommandOrigin,
+    ) -> anyhow::Result<()> {
+        let resolved = self.resolve_zone_id(zone_id, RoomRole::Volume).await?;
+        let policy = self
+            .volume_policy
+            .policy_for(&[zone_id, resolved.as_str()])
+            .await;
+        let Some(current) = self.volume_control(&resolved).await.map(|vc| vc.value) else {
+            return Ok(());
+        };
+        if let Some(level) = policy.start_level(current) {
+            self.send_volume(&resolved, level, false).await?;
+            self.report_volume_clamp(
+                zone_id,
+                level,
+                &format!("playback started at {}, start cap is {}", current, level),
+                origin,
+            );
+        }
+        Ok(())
+    }
+
+    /// Current volume control of an adapter zone (unprefixed ids are Roon zones)
+    pub(crate) async fn volume_control(&self, zone_id: &str) -> Option<crate::bus::VolumeControl> {
+        let zone = match self.aggregator.get_zone(zone_id).await {
+            Some(zone) => Some(zone),
+            None if !zone_id.contains(':') => {
+                self.aggregator.get_zone(&format!("roon:{}", zone_id)).await
+            }
+            None => None,
+        };
+        zone.and_then(|z| z.volume_control)
+    }
+
+    /// Pass a volume level straight to the adapter (no policy)
+    pub(crate) async fn send_volume(
+        &self,
+        zone_id: &str,
+        value: f32,
+        relative: bool,
+    ) -> anyhow::Result<()> {
+        let direct =
+            zone_id.starts_with("lms:") || zone_id.starts_with("roon:") || !zone_id.contains(':');
+        if direct {
+            let started = Instant::now();
+            let result = if zone_id.starts_with("lms:") {
+                self.lms.change_volume(zone_id, value, relative).await
+            } else {
+                // Roon takes fractional steps (e.g. 0.5 dB)
+                self.roon.change_volume(zone_id, value, relative).await
+            };
+            let action = if relative {
+                "volume_relative"
+            } else {
+                "volume_absolute"
+            };
+            self.metrics
+                .record_command(zone_id, action, started.elapsed(), result.is_ok());
+            result
+        } else {
+            let command = if relative {
+                AdapterCommand::VolumeRelative(value.round() as i32)
+            } else {
+                AdapterCommand::VolumeAbsolute(value.round() as i32)
+            };
+            self.route_command(zone_id, command).await?.into_result()?;
+            Ok(())
+        }
+    }
+
+    /// Publish a volume change made by the policy (on behalf of the command that
+    /// triggered it), with the reason
+    fn report_volume_clamp(&self, zone_id: &str, value: f32, reason: &str, origin: &CommandOrigin) {
+        let command = Command::VolumeAbsolute {
+            value,
+            output_id: None,
+        };
+        CommandAudit::begin(&self.bus, zone_id, command, origin.clone())
+            .finish(Ok(()), Some(reason.to_string()));
+    }
+
+    /// All zones, with room members replaced by their merged room zone
+    pub async fn get_zones(&self) -> Vec<crate::bus::Zone> {
+        self.rooms.merge(self.aggregator.get_zones().await).await
+    }
+
+    /// Get a zone by id, including merged `room:` zones
+    pub async fn get_zone(&self, zone_id: &str) -> Option<crate::bus::Zone> {
+        if zone_id.starts_with(ROOM_PREFIX) {
+            self.get_zones()
+                .await
+                .into_iter()
+                .find(|z| z.zone_id == zone_id)
+        } else {
+            self.aggregator.get_zone(zone_id).await
+        }
+    }
+
+    /// Map a `room:` zone id to the member zone that handles `role`.
+    /// Other zone ids are returned unchanged.
+    pub async fn resolve_zone_id(&self, zone_id: &str, role: RoomRole) -> anyhow::Result<String> {
+        if !zone_id.starts_with(ROOM_PREFIX) {
+            return Ok(zone_id.to_string());
         }
+        self.rooms
+            .resolve(zone_id, role, self.aggregator.get_zones().await)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Room has no available zones: {}", zone_id))
     }
 }
 
@@ -200,6 +789,8 @@ pub struct StatusResponse {
     pub openhome_devices: usize,
     pub upnp_devices: usize,
     pub bus_subscribers: usize,
+    /// Per-adapter supervision: connection state, failures, last error, retries
+    pub adapters: Vec<AdapterStatus>,
 }
 
 /// GET /status - Service health check
@@ -209,6 +800,13 @@ pub async fn status_handler(State(state): State<AppState>) -> Json<StatusRespons
     let lms_status = state.lms.get_status().await;
     let openhome_status = state.openhome.get_status().await;
     let upnp_status = state.upnp.get_status().await;
+    let mut adapters: Vec<AdapterStatus> = state
+        .coordinator
+        .adapter_status()
+        .await
+        .into_values()
+        .collect();
+    adapters.sort_by(|a, b| a.prefix.cmp(&b.prefix));
 
     Json(StatusResponse {
         service: "unified-hifi-control",
@@ -221,9 +819,59 @@ pub async fn status_handler(State(state): State<AppState>) -> Json<StatusRespons
         openhome_devices: openhome_status.device_count,
         upnp_devices: upnp_status.renderer_count,
         bus_subscribers: state.bus.subscriber_count(),
+        adapters,
     })
 }
 
+/// GET /metrics - Prometheus text exposition
+pub async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            crate::metrics::CONTENT_TYPE,
+        )],
+        crate::metrics::render(&state).await,
+    )
+}
+
+/// Adapter restart request
+#[derive(Deserialize)]
+pub struct AdapterRestartRequest {
+    pub adapter: String,
+}
+
+/// POST /adapters/restart - Stop and start a single adapter
+pub async fn adapter_restart_handler(
+    State(state): State<AppState>,
+    Json(req): Json<AdapterRestartRequest>,
+) -> impl IntoResponse {
+    let Some(adapter) = state
+        .startables()
+        .await
+        .into_iter()
+        .find(|a| a.name() == req.adapter)
+    else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Unknown adapter: {}", req.adapter),
+            }),
+        )
+            .into_response();
+    };
+
+    match state.coordinator.restart(&adapter).await {
+        Ok(()) => Json(serde_json::json!({ "ok": true, "adapter": req.adapter })).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
 // =============================================================================
 // Roon handlers
 // =============================================================================
@@ -271,9 +919,14 @@ pub struct ControlRequest {
 /// POST /roon/control - Control playback
 pub async fn roon_control_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<ControlRequest>,
 ) -> impl IntoResponse {
-    match state.roon.control(&req.zone_id, &req.action).await {
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let zone_id = prefixed_zone_id("roon", &req.zone_id);
+    let control = state.roon.control(&req.zone_id, &req.action);
+    match audit_action(&state.bus, &zone_id, &req.action, origin, control).await {
         Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
         Err(e) => (
             StatusCode::BAD_REQUEST,
@@ -299,14 +952,30 @@ pub struct VolumeRequest {
 /// POST /roon/volume - Change volume
 pub async fn roon_volume_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<VolumeRequest>,
 ) -> impl IntoResponse {
-    match state
-        .roon
-        .change_volume(&req.zone_id, req.value, req.relative)
-        .await
-    {
-        Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
+    let origin = http_origin(&state, &headers, connect_info).await;
+    volume_response(
+        state
+            .set_volume(&req.zone_id, req.value, req.relative, origin)
+            .await,
+    )
+}
+
+/// JSON response for a volume change made through the volume policy
+fn volume_response(result: anyhow::Result<VolumeOutcome>) -> axum::response::Response {
+    match result {
+        Ok(outcome) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "ok": true,
+                "value": outcome.value,
+                "reason": outcome.reason
+            })),
+        )
+            .into_response(),
         Err(e) => (
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
@@ -317,6 +986,34 @@ pub async fn roon_volume_handler(
     }
 }
 
+/// Volume actions of the per-adapter control endpoints, as (value, relative)
+fn volume_action(action: &str, value: Option<i32>) -> Option<(f32, bool)> {
+    match action {
+        "vol_abs" | "volume" => value.map(|v| (v as f32, false)),
+        "vol_rel" => value.map(|v| (v as f32, true)),
+        _ => None,
+    }
+}
+
+/// Add the adapter prefix to a zone id if it doesn't have it yet
+fn prefixed_zone_id(prefix: &str, id: &str) -> String {
+    if id.starts_with(prefix) && id[prefix.len()..].starts_with(':') {
+        id.to_string()
+    } else {
+        format!("{}:{}", prefix, id)
+    }
+}
+
+/// Where a control request came from, for the audit log
+async fn http_origin(
+    state: &AppState,
+    headers: &HeaderMap,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+) -> CommandOrigin {
+    let socket_addr = connect_info.ok().map(|c| c.0);
+    request_origin(&state.knobs, request_surface(headers), headers, socket_addr).await
+}
+
 /// Query params for image request
 #[derive(Deserialize)]
 pub struct ImageQuery {
@@ -783,9 +1480,17 @@ pub struct HqpControlRequest {
 /// POST /hqplayer/control - Control HQPlayer playback
 pub async fn hqp_control_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<HqpControlRequest>,
 ) -> impl IntoResponse {
-    match state.hqplayer.control(&req.action).await {
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let zone_id = match hqp_zone_id(&state).await {
+        Some(zone_id) => zone_id,
+        None => "hqplayer".to_string(),
+    };
+    let control = state.hqplayer.control(&req.action);
+    match audit_action(&state.bus, &zone_id, &req.action, origin, control).await {
         Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
         Err(e) => (
             StatusCode::BAD_REQUEST,
@@ -806,18 +1511,36 @@ pub struct HqpVolumeRequest {
 /// POST /hqplayer/volume - Change HQPlayer volume
 pub async fn hqp_volume_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<HqpVolumeRequest>,
 ) -> impl IntoResponse {
-    match state.hqplayer.set_volume(req.value).await {
-        Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
-        Err(e) => (
+    let Some(zone_id) = hqp_zone_id(&state).await else {
+        return (
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: e.to_string(),
+                error: "HQPlayer not configured".to_string(),
             }),
         )
-            .into_response(),
-    }
+            .into_response();
+    };
+    let origin = http_origin(&state, &headers, connect_info).await;
+    volume_response(
+        state
+            .set_volume(&zone_id, req.value as f32, false, origin)
+            .await,
+    )
+}
+
+/// Zone id of the default HQPlayer, None when it isn't configured
+pub(crate) async fn hqp_zone_id(state: &AppState) -> Option<String> {
+    let host = state.hqplayer.get_status().await.host?;
+    // Zones are named after the instance when it has one
+    let raw = match state.hqplayer.get_instance_name().await {
+        Some(name) if state.hqp_instances.get(&name).await.is_some() => name,
+        _ => host,
+    };
+    Some(prefixed_zone_id("hqplayer", &raw))
 }
 
 /// HQPlayer setting request (legacy - uses name/value with u32)
@@ -830,27 +1553,39 @@ pub struct HqpSettingRequest {
 /// POST /hqplayer/setting - Change HQPlayer pipeline setting (legacy endpoint)
 pub async fn hqp_setting_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<HqpSettingRequest>,
 ) -> impl IntoResponse {
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let zone_id = hqp_zone_id(&state)
+        .await
+        .unwrap_or_else(|| "hqplayer".to_string());
     // Legacy endpoint - convert numeric value to string for name-based lookups
     let value_str = req.value.to_string();
-    let result = match req.name.as_str() {
-        "mode" => state.hqplayer.set_mode(&value_str).await,
-        "filter" => {
-            // Sets both 1x and Nx to the same filter - propagate first error if any
-            match state.hqplayer.set_filter_1x(&value_str).await {
-                Ok(()) => state.hqplayer.set_filter_nx(&value_str).await,
-                Err(e) => Err(e),
+    let change = async {
+        match req.name.as_str() {
+            "mode" => state.hqplayer.set_mode(&value_str).await,
+            "filter" => {
+                // Sets both 1x and Nx to the same filter - propagate first error if any
+                match state.hqplayer.set_filter_1x(&value_str).await {
+                    Ok(()) => state.hqplayer.set_filter_nx(&value_str).await,
+                    Err(e) => Err(e),
+                }
             }
+            "filter1x" => state.hqplayer.set_filter_1x(&value_str).await,
+            "filterNx" | "filternx" => state.hqplayer.set_filter_nx(&value_str).await,
+            "shaper" => state.hqplayer.set_shaper(&value_str).await,
+            "samplerate" | "rate" => state.hqplayer.set_rate(req.value).await,
+            _ => Err(anyhow::anyhow!("Unknown setting: {}", req.name)),
         }
-        "filter1x" => state.hqplayer.set_filter_1x(&value_str).await,
-        "filterNx" | "filternx" => state.hqplayer.set_filter_nx(&value_str).await,
-        "shaper" => state.hqplayer.set_shaper(&value_str).await,
-        "samplerate" | "rate" => state.hqplayer.set_rate(req.value).await,
-        _ => Err(anyhow::anyhow!("Unknown setting: {}", req.name)),
+    };
+    let command = Command::HqpDsp {
+        setting: req.name.clone(),
+        value: value_str.clone(),
     };
 
-    match result {
+    match audit_command(&state.bus, &zone_id, command, origin, change).await {
         Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
         Err(e) => (
             StatusCode::BAD_REQUEST,
@@ -872,6 +1607,8 @@ pub struct HqpPipelineRequest {
 /// POST /hqp/pipeline - Change HQPlayer pipeline setting (iOS compatible)
 pub async fn hqp_pipeline_update_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<HqpPipelineRequest>,
 ) -> impl IntoResponse {
     // Convert value to string - all settings now use name-based lookups
@@ -914,17 +1651,27 @@ pub async fn hqp_pipeline_update_handler(
             .into_response();
     }
 
-    let result = match req.setting.as_str() {
-        "mode" => state.hqplayer.set_mode(&value_str).await,
-        "filter1x" => state.hqplayer.set_filter_1x(&value_str).await,
-        "filterNx" | "filternx" => state.hqplayer.set_filter_nx(&value_str).await,
-        "shaper" => state.hqplayer.set_shaper(&value_str).await,
-        "samplerate" => state.hqplayer.set_rate(rate_value).await,
-        "dither" => state.hqplayer.set_shaper(&value_str).await, // dither uses same API
-        _ => Err(anyhow::anyhow!("Unknown setting: {}", req.setting)),
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let zone_id = hqp_zone_id(&state)
+        .await
+        .unwrap_or_else(|| "hqplayer".to_string());
+    let change = async {
+        match req.setting.as_str() {
+            "mode" => state.hqplayer.set_mode(&value_str).await,
+            "filter1x" => state.hqplayer.set_filter_1x(&value_str).await,
+            "filterNx" | "filternx" => state.hqplayer.set_filter_nx(&value_str).await,
+            "shaper" => state.hqplayer.set_shaper(&value_str).await,
+            "samplerate" => state.hqplayer.set_rate(rate_value).await,
+            "dither" => state.hqplayer.set_shaper(&value_str).await, // dither uses same API
+            _ => Err(anyhow::anyhow!("Unknown setting: {}", req.setting)),
+        }
+    };
+    let command = Command::HqpDsp {
+        setting: req.setting.clone(),
+        value: value_str.clone(),
     };
 
-    match result {
+    match audit_command(&state.bus, &zone_id, command, origin, change).await {
         Ok(()) => {
             // After setting, fetch and return the fresh pipeline state
             // This ensures the UI gets the updated state immediately
@@ -969,10 +1716,21 @@ pub struct HqpProfileRequest {
 /// POST /hqplayer/profile - Load a profile
 pub async fn hqp_load_profile_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<HqpProfileRequest>,
 ) -> impl IntoResponse {
-    match state.hqplayer.load_profile(&req.profile).await {
-        Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let zone_id = hqp_zone_id(&state)
+        .await
+        .unwrap_or_else(|| "hqplayer".to_string());
+    let command = Command::HqpDsp {
+        setting: "profile".to_string(),
+        value: req.profile.clone(),
+    };
+    let change = state.hqplayer.load_profile(&req.profile);
+    match audit_command(&state.bus, &zone_id, command, origin, change).await {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
         Err(e) => (
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
@@ -1029,9 +1787,20 @@ pub struct HqpMatrixProfileRequest {
 /// POST /hqplayer/matrix/profile - Set matrix profile
 pub async fn hqp_set_matrix_profile_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<HqpMatrixProfileRequest>,
 ) -> impl IntoResponse {
-    match state.hqplayer.set_matrix_profile(req.profile).await {
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let zone_id = hqp_zone_id(&state)
+        .await
+        .unwrap_or_else(|| "hqplayer".to_string());
+    let command = Command::HqpDsp {
+        setting: "matrix_profile".to_string(),
+        value: req.profile.to_string(),
+    };
+    let change = state.hqplayer.set_matrix_profile(req.profile);
+    match audit_command(&state.bus, &zone_id, command, origin, change).await {
         Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
         Err(e) => (
             StatusCode::BAD_REQUEST,
@@ -1092,13 +1861,18 @@ pub struct LmsControlRequest {
 /// POST /lms/control - Control LMS player
 pub async fn lms_control_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<LmsControlRequest>,
 ) -> impl IntoResponse {
-    match state
-        .lms
-        .control(&req.player_id, &req.action, req.value)
-        .await
-    {
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let zone_id = prefixed_zone_id("lms", &req.player_id);
+    if let Some((value, relative)) = volume_action(&req.action, req.value) {
+        return volume_response(state.set_volume(&zone_id, value, relative, origin).await);
+    }
+
+    let control = state.lms.control(&req.player_id, &req.action, req.value);
+    match audit_action(&state.bus, &zone_id, &req.action, origin, control).await {
         Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
         Err(e) => (
             StatusCode::BAD_REQUEST,
@@ -1122,22 +1896,17 @@ pub struct LmsVolumeRequest {
 /// POST /lms/volume - Change LMS player volume
 pub async fn lms_volume_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<LmsVolumeRequest>,
 ) -> impl IntoResponse {
-    match state
-        .lms
-        .change_volume(&req.player_id, req.value, req.relative)
-        .await
-    {
-        Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
-            .into_response(),
-    }
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let zone_id = prefixed_zone_id("lms", &req.player_id);
+    volume_response(
+        state
+            .set_volume(&zone_id, req.value, req.relative, origin)
+            .await,
+    )
 }
 
 /// LMS discovery request query params
@@ -1148,15 +1917,35 @@ pub struct LmsDiscoverRequest {
 }
 
 /// GET /lms/discover - Discover LMS servers on the local network via UDP broadcast
-pub async fn lms_discover_handler(Query(params): Query<LmsDiscoverRequest>) -> impl IntoResponse {
+///
+/// Each result is marked `configured` when it is already the primary or an
+/// additional server, so clients can offer to add the others.
+pub async fn lms_discover_handler(
+    State(state): State<AppState>,
+    Query(params): Query<LmsDiscoverRequest>,
+) -> impl IntoResponse {
     use crate::adapters::discover_lms_servers;
 
     match discover_lms_servers(params.timeout_ms).await {
-        Ok(servers) => (
-            StatusCode::OK,
-            Json(serde_json::json!({ "discovered": servers })),
-        )
-            .into_response(),
+        Ok(servers) => {
+            let mut discovered = Vec::with_capacity(servers.len());
+            for server in servers {
+                let configured = state
+                    .lms
+                    .is_known_server(&server.host, server.json_port)
+                    .await;
+                let mut value = serde_json::to_value(&server).unwrap_or_default();
+                if let Some(obj) = value.as_object_mut() {
+                    obj.insert("configured".to_string(), configured.into());
+                }
+                discovered.push(value);
+            }
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({ "discovered": discovered })),
+            )
+                .into_response()
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -1167,6 +1956,125 @@ pub async fn lms_discover_handler(Query(params): Query<LmsDiscoverRequest>) -> i
     }
 }
 
+/// GET /lms/servers - Primary and additional LMS servers
+pub async fn lms_servers_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(serde_json::json!({ "servers": state.lms.list_servers().await }))
+}
+
+/// Additional LMS server request
+#[derive(Deserialize)]
+pub struct LmsAddServerRequest {
+    /// Zone namespace; derived from the host when omitted
+    #[serde(default)]
+    pub id: Option<String>,
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// POST /lms/servers - Add (or reconfigure) an additional LMS server
+///
+/// The server follows the LMS enabled setting: it is started right away
+/// unless LMS is disabled.
+pub async fn lms_add_server_handler(
+    State(state): State<AppState>,
+    Json(req): Json<LmsAddServerRequest>,
+) -> impl IntoResponse {
+    let server = match add_lms_server(&state, req).await {
+        Ok(server) => server,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+                .into_response()
+        }
+    };
+
+    Json(serde_json::json!({
+        "ok": true,
+        "id": server.adapter.server_id(),
+        "adapter": server.adapter.name(),
+    }))
+    .into_response()
+}
+
+/// DELETE /lms/servers/{id} - Remove an additional LMS server and its zones
+pub async fn lms_remove_server_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if !remove_lms_server(&state, &id).await {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Unknown LMS server: {}", id),
+            }),
+        )
+            .into_response();
+    }
+
+    Json(serde_json::json!({ "ok": true })).into_response()
+}
+
+/// Add or reconfigure an additional LMS server, then start it (new servers) or
+/// reconnect it (changed ones) when LMS is enabled
+pub(crate) async fn add_lms_server(
+    state: &AppState,
+    req: LmsAddServerRequest,
+) -> anyhow::Result<crate::adapters::lms::LmsServer> {
+    let known: Vec<&'static str> = state
+        .lms
+        .servers()
+        .await
+        .iter()
+        .filter_map(|s| s.adapter.server_id())
+        .collect();
+
+    let server = state
+        .lms
+        .add_server(req.id, req.host, req.port, req.username, req.password)
+        .await?;
+
+    let adapters: [Arc<dyn Startable>; 2] = [server.adapter.clone(), server.cli.clone()];
+    let is_new = server
+        .adapter
+        .server_id()
+        .is_none_or(|id| !known.contains(&id));
+    let enabled = state.coordinator.is_enabled("lms").await;
+
+    if is_new {
+        for adapter in &adapters {
+            state.coordinator.register(adapter.name(), enabled).await;
+        }
+        state.coordinator.start_all_enabled(&adapters).await;
+    } else if enabled {
+        // Reconnect with the new settings
+        for adapter in &adapters {
+            if let Err(e) = state.coordinator.restart(adapter).await {
+                tracing::warn!("Failed to restart {}: {}", adapter.name(), e);
+            }
+        }
+    }
+    Ok(server)
+}
+
+/// Remove an additional LMS server and its adapters; false if it isn't known
+pub(crate) async fn remove_lms_server(state: &AppState, id: &str) -> bool {
+    let Some(server) = state.lms.remove_server(id).await else {
+        return false;
+    };
+    state.coordinator.unregister(server.adapter.name()).await;
+    state.coordinator.unregister(server.cli.name()).await;
+    true
+}
+
 // =============================================================================
 // SSE Events
 // =============================================================================
@@ -1186,6 +2094,7 @@ impl Drop for SseConnectionGuard {
 
 pub async fn events_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     // Track this connection
     let count = state.sse_connections.fetch_add(1, Ordering::Relaxed) + 1;
@@ -1195,25 +2104,49 @@ pub async fn events_handler(
         counter: state.sse_connections.clone(),
     };
     let shutdown = state.shutdown.clone();
-    let rx = state.bus.subscribe();
+
+    // Browsers send Last-Event-ID automatically when an EventSource reconnects
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+
+    let (backlog, rx) = match last_event_id {
+        Some(last_seq) => {
+            let resume = state.bus.resume(last_seq);
+            let mut backlog: Vec<Result<Event, Infallible>> =
+                Vec::with_capacity(resume.missed.len() + 1);
+            if resume.resync_required {
+                tracing::debug!(
+                    "SSE client at event {} is outside the replay buffer, requesting resync",
+                    last_seq
+                );
+                backlog.push(Ok(resync_event(Some(state.bus.last_seq()))));
+            } else {
+                tracing::debug!(
+                    "SSE client resuming after event {} ({} missed)",
+                    last_seq,
+                    resume.missed.len()
+                );
+            }
+            backlog.extend(resume.missed.iter().filter_map(sequenced_to_sse).map(Ok));
+            (backlog, resume.rx)
+        }
+        None => (Vec::new(), state.bus.subscribe_sequenced()),
+    };
 
     // Create stream that terminates on shutdown
     // Use futures::StreamExt::take_until via UFCS (tokio_stream doesn't have it)
-    let base_stream = BroadcastStream::new(rx);
+    let live = BroadcastStream::new(rx).filter_map(|result| match result {
+        Ok(event) => sequenced_to_sse(&event).map(Ok),
+        // We fell behind the broadcast channel - tell the client to refetch
+        Err(_) => Some(Ok(resync_event(None))),
+    });
+    let base_stream = tokio_stream::iter(backlog).chain(live);
     let with_shutdown =
         futures::StreamExt::take_until(base_stream, async move { shutdown.cancelled().await });
 
     let stream = with_shutdown
-        .filter_map(|result| match result {
-            Ok(event) => {
-                // Serialize event to JSON
-                match serde_json::to_string(&event) {
-                    Ok(json) => Some(Ok(Event::default().data(json))),
-                    Err(_) => None,
-                }
-            }
-            Err(_) => None, // Skip lagged messages
-        })
         // Use map + flatten to attach guard lifetime to stream
         // When stream ends, guard is dropped (decrementing counter)
         .map(move |item| {
@@ -1228,63 +2161,740 @@ pub async fn events_handler(
     )
 }
 
+/// Serialize a sequenced bus event as an SSE event carrying its sequence id
+fn sequenced_to_sse(event: &SequencedEvent) -> Option<Event> {
+    serde_json::to_string(&event.event)
+        .ok()
+        .map(|json| Event::default().id(event.seq.to_string()).data(json))
+}
+
+/// Marker telling the client its missed events can't be replayed and it must
+/// refetch full state. Carries the current head id when known so the next
+/// reconnect resumes from here.
+fn resync_event(head: Option<u64>) -> Event {
+    let event = Event::default().data(r#"{"type":"ResyncRequired"}"#);
+    match head {
+        Some(seq) => event.id(seq.to_string()),
+        None => event,
+    }
+}
+
+// =============================================================================
+// OpenHome handlers
+// =============================================================================
+
+/// GET /openhome/status - OpenHome discovery status
+pub async fn openhome_status_handler(
+    State(state): State<AppState>,
+) -> Json<crate::adapters::openhome::OpenHomeStatus> {
+    Json(state.openhome.get_status().await)
+}
+
+/// GET /openhome/zones - List all discovered OpenHome devices
+pub async fn openhome_zones_handler(
+    State(state): State<AppState>,
+) -> Json<ZonesWrapper<crate::adapters::openhome::OpenHomeZone>> {
+    Json(ZonesWrapper {
+        zones: state.openhome.get_zones().await,
+    })
+}
+
+/// GET /openhome/zone/:zone_id/now_playing - Get now playing for zone
+pub async fn openhome_now_playing_handler(
+    State(state): State<AppState>,
+    Path(zone_id): Path<String>,
+) -> impl IntoResponse {
+    match state.openhome.get_now_playing(&zone_id).await {
+        Some(np) => (StatusCode::OK, Json(np)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Zone not found: {}", zone_id),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// OpenHome control request
+#[derive(Deserialize)]
+pub struct OpenHomeControlRequest {
+    pub zone_id: String,
+    pub action: String,
+    #[serde(default)]
+    pub value: Option<i32>,
+}
+
+/// POST /openhome/control - Control OpenHome device
+pub async fn openhome_control_handler(
+    State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
+    Json(req): Json<OpenHomeControlRequest>,
+) -> impl IntoResponse {
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let zone_id = prefixed_zone_id("openhome", &req.zone_id);
+    if let Some((value, relative)) = volume_action(&req.action, req.value) {
+        return volume_response(state.set_volume(&zone_id, value, relative, origin).await);
+    }
+
+    let control = state.openhome.control(&req.zone_id, &req.action, req.value);
+    match audit_action(&state.bus, &zone_id, &req.action, origin, control).await {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+// =============================================================================
+// UPnP handlers
+// =============================================================================
+
+/// GET /upnp/status - UPnP discovery status
+pub async fn upnp_status_handler(
+    State(state): State<AppState>,
+) -> Json<crate::adapters::upnp::UPnPStatus> {
+    Json(state.upnp.get_status().await)
+}
+
+/// GET /upnp/zones - List all discovered UPnP renderers
+pub async fn upnp_zones_handler(
+    State(state): State<AppState>,
+) -> Json<ZonesWrapper<crate::adapters::upnp::UPnPZone>> {
+    Json(ZonesWrapper {
+        zones: state.upnp.get_zones().await,
+    })
+}
+
+/// GET /upnp/zone/:zone_id/now_playing - Get now playing for renderer
+pub async fn upnp_now_playing_handler(
+    State(state): State<AppState>,
+    Path(zone_id): Path<String>,
+) -> impl IntoResponse {
+    match state.upnp.get_now_playing(&zone_id).await {
+        Some(np) => (StatusCode::OK, Json(np)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Renderer not found: {}", zone_id),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// UPnP control request
+#[derive(Deserialize)]
+pub struct UPnPControlRequest {
+    pub zone_id: String,
+    pub action: String,
+    #[serde(default)]
+    pub value: Option<i32>,
+}
+
+/// POST /upnp/control - Control UPnP renderer
+pub async fn upnp_control_handler(
+    State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
+    Json(req): Json<UPnPControlRequest>,
+) -> impl IntoResponse {
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let zone_id = prefixed_zone_id("upnp", &req.zone_id);
+    if let Some((value, relative)) = volume_action(&req.action, req.value) {
+        return volume_response(state.set_volume(&zone_id, value, relative, origin).await);
+    }
+
+    let control = state.upnp.control(&req.zone_id, &req.action, req.value);
+    match audit_action(&state.bus, &zone_id, &req.action, origin, control).await {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+// =============================================================================
+// Zone command handlers
+// =============================================================================
+
+/// POST /zones/{zone_id}/command - Send a source-agnostic command to any zone
+///
+/// Body is a serialized `Command`, e.g. `{"action":"Seek","params":{"position":90}}`.
+pub async fn zone_command_handler(
+    State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
+    Path(zone_id): Path<String>,
+    Json(command): Json<Command>,
+) -> impl IntoResponse {
+    // Always the API surface, so fades follow the API setting
+    let origin = CommandOrigin {
+        surface: ControlSurface::Api,
+        ..http_origin(&state, &headers, connect_info).await
+    };
+    let adapter_command = match AdapterCommand::try_from(command.clone()) {
+        Ok(adapter_command) => adapter_command,
+        Err(e) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+                .into_response()
+        }
+    };
+    let (status, success, error) = match state
+        .dispatch_command_from(&zone_id, adapter_command, origin)
+        .await
+    {
+        Ok(resp) if resp.success => (StatusCode::OK, true, None),
+        Ok(resp) => (
+            command_error_status(&state, &zone_id, resp.is_unsupported()).await,
+            false,
+            resp.error.map(|e| e.to_string()),
+        ),
+        Err(e) => (
+            command_error_status(&state, &zone_id, false).await,
+            false,
+            Some(e.to_string()),
+        ),
+    };
+
+    let response = CommandResponse {
+        zone_id,
+        command,
+        success,
+        error,
+        reason: None,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+    };
+
+    (status, Json(response)).into_response()
+}
+
+/// Status for a failed zone command: 404 for a zone nobody reports, 422 for a
+/// command the zone can't perform, 502 when its adapter failed to carry it out
+async fn command_error_status(state: &AppState, zone_id: &str, unsupported: bool) -> StatusCode {
+    let known = match state.get_zone(zone_id).await {
+        Some(_) => true,
+        // Unprefixed ids are Roon zones
+        None if !zone_id.contains(':') => {
+            state.get_zone(&format!("roon:{}", zone_id)).await.is_some()
+        }
+        None => false,
+    };
+    if !known {
+        StatusCode::NOT_FOUND
+    } else if unsupported {
+        StatusCode::UNPROCESSABLE_ENTITY
+    } else {
+        StatusCode::BAD_GATEWAY
+    }
+}
+
+// =============================================================================
+// Room handlers
+// =============================================================================
+
+/// Adapter zone available for room membership
+#[derive(Serialize)]
+struct RoomMemberCandidate {
+    zone_id: String,
+    zone_name: String,
+    source: String,
+}
+
+/// GET /rooms - List rooms and the adapter zones they can be built from
+pub async fn rooms_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let rooms = state.rooms.list().await;
+    let mut zones: Vec<RoomMemberCandidate> = state
+        .aggregator
+        .get_zones()
+        .await
+        .into_iter()
+        .map(|z| RoomMemberCandidate {
+            zone_id: z.zone_id,
+            zone_name: z.zone_name,
+            source: z.source,
+        })
+        .collect();
+    zones.sort_by(|a, b| a.zone_name.cmp(&b.zone_name));
+    Json(serde_json::json!({ "rooms": rooms, "zones": zones }))
+}
+
+/// POST /rooms - Create or update a room
+pub async fn room_save_handler(
+    State(state): State<AppState>,
+    Json(room): Json<crate::rooms::Room>,
+) -> impl IntoResponse {
+    match state.rooms.save_room(room).await {
+        Ok(room) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "ok": true,
+                "zone_id": room.zone_id(),
+                "room": room
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Room delete request
+#[derive(Deserialize)]
+pub struct RoomDeleteRequest {
+    pub id: String,
+}
+
+/// POST /rooms/delete - Delete a room (members reappear as individual zones)
+pub async fn room_delete_handler(
+    State(state): State<AppState>,
+    Json(req): Json<RoomDeleteRequest>,
+) -> impl IntoResponse {
+    let was_removed = state.rooms.remove(&req.id).await;
+    Json(serde_json::json!({
+        "ok": true,
+        "id": req.id,
+        "was_removed": was_removed
+    }))
+}
+
+// =============================================================================
+// History handlers
+// =============================================================================
+
+/// Query params for GET /history
+#[derive(Debug, Default, Deserialize)]
+pub struct HistoryParams {
+    /// Zone id, zone name or room zone id ("room:<id>")
+    pub zone_id: Option<String>,
+    pub source: Option<String>,
+    /// Case-insensitive artist substring
+    pub artist: Option<String>,
+    /// Start of the time range (RFC 3339 or unix milliseconds)
+    pub since: Option<String>,
+    /// End of the time range (RFC 3339 or unix milliseconds)
+    pub until: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Parse an RFC 3339 timestamp or unix milliseconds
+fn parse_history_time(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    let value = value.trim();
+    if let Ok(ms) = value.parse::<i64>() {
+        return chrono::DateTime::from_timestamp_millis(ms)
+            .ok_or_else(|| format!("Timestamp out of range: {}", value));
+    }
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .map_err(|_| format!("Invalid time '{}' (expected RFC 3339 or unix ms)", value))
+}
+
+/// Build a history filter from query params (rooms expand to their member zones)
+pub async fn history_filter(
+    state: &AppState,
+    params: HistoryParams,
+) -> Result<HistoryFilter, String> {
+    let zones = match params.zone_id {
+        Some(zone_id) if zone_id.starts_with(ROOM_PREFIX) => state
+            .rooms
+            .get(&zone_id)
+            .await
+            .map(|room| room.zone_ids)
+            .ok_or_else(|| format!("Room not found: {}", zone_id))?,
+        Some(zone_id) => vec![zone_id],
+        None => Vec::new(),
+    };
+
+    Ok(HistoryFilter {
+        zones,
+        source: params.source,
+        artist: params.artist,
+        since: params
+            .since
+            .as_deref()
+            .map(parse_history_time)
+            .transpose()?,
+        until: params
+            .until
+            .as_deref()
+            .map(parse_history_time)
+            .transpose()?,
+        limit: params.limit,
+    })
+}
+
+/// GET /history - Listening history, newest first
+pub async fn history_handler(
+    State(state): State<AppState>,
+    Query(params): Query<HistoryParams>,
+) -> impl IntoResponse {
+    match history_filter(&state, params).await {
+        Ok(filter) => {
+            let entries = state.history.query(&filter).await;
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "count": entries.len(),
+                    "entries": entries
+                })),
+            )
+                .into_response()
+        }
+        Err(error) => (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response(),
+    }
+}
+
+// =============================================================================
+// Audit handlers
+// =============================================================================
+
+/// Query params for GET /audit
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditParams {
+    pub zone_id: Option<String>,
+    /// Control surface ("knob", "web", "api", "mcp", "scheduler", ...)
+    pub surface: Option<String>,
+    /// Case-insensitive substring of the knob id or name, session or client address
+    pub origin: Option<String>,
+    /// Start of the time range (RFC 3339 or unix milliseconds)
+    pub since: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Build an audit filter from query params
+fn audit_filter(params: AuditParams) -> Result<AuditFilter, String> {
+    let surface = params
+        .surface
+        .map(|surface| {
+            serde_json::from_value::<ControlSurface>(serde_json::Value::String(surface.clone()))
+                .map_err(|_| format!("Unknown surface: {}", surface))
+        })
+        .transpose()?;
+
+    Ok(AuditFilter {
+        zone_id: params.zone_id,
+        surface,
+        origin: params.origin,
+        since: params
+            .since
+            .as_deref()
+            .map(parse_history_time)
+            .transpose()?,
+        limit: params.limit,
+    })
+}
+
+/// GET /audit - Commands sent to zones and where they came from, newest first
+pub async fn audit_handler(
+    State(state): State<AppState>,
+    Query(params): Query<AuditParams>,
+) -> impl IntoResponse {
+    match audit_filter(params) {
+        Ok(filter) => {
+            let entries = state.audit.query(&filter).await;
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "count": entries.len(),
+                    "entries": entries
+                })),
+            )
+                .into_response()
+        }
+        Err(error) => (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response(),
+    }
+}
+
+// =============================================================================
+// Scrobbler handlers
+// =============================================================================
+
+/// GET /scrobbler/status - Scrobbler settings (without the token) and queue state
+pub async fn scrobbler_status_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.scrobbler.status().await)
+}
+
+/// Scrobbler configuration request (omitted fields keep their current value)
+#[derive(Deserialize)]
+pub struct ScrobblerConfigRequest {
+    pub enabled: bool,
+    pub url: Option<String>,
+    pub token: Option<String>,
+}
+
+/// POST /scrobbler/config - Update scrobbler settings
+pub async fn scrobbler_config_handler(
+    State(state): State<AppState>,
+    Json(req): Json<ScrobblerConfigRequest>,
+) -> impl IntoResponse {
+    let current = state.scrobbler.config().await;
+    let config = ScrobblerConfig {
+        enabled: req.enabled,
+        url: req
+            .url
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty())
+            .unwrap_or(current.url),
+        token: req
+            .token
+            .map(|t| t.trim().to_string())
+            .unwrap_or(current.token),
+    };
+
+    match state.scrobbler.set_config(config).await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "ok": true,
+                "status": state.scrobbler.status().await
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+// =============================================================================
+// Schedule handlers
+// =============================================================================
+
+/// GET /schedules - Schedules with their next and last run
+pub async fn schedules_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(serde_json::json!({ "schedules": state.scheduler.statuses().await }))
+}
+
+/// POST /schedules - Create or update a schedule
+pub async fn schedule_save_handler(
+    State(state): State<AppState>,
+    Json(schedule): Json<crate::scheduler::Schedule>,
+) -> impl IntoResponse {
+    match state.scheduler.save_schedule(schedule).await {
+        Ok(schedule) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "ok": true,
+                "schedule": schedule
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Schedule delete/run request
+#[derive(Deserialize)]
+pub struct ScheduleIdRequest {
+    pub id: String,
+}
+
+/// POST /schedules/delete - Delete a schedule
+pub async fn schedule_delete_handler(
+    State(state): State<AppState>,
+    Json(req): Json<ScheduleIdRequest>,
+) -> impl IntoResponse {
+    let was_removed = state.scheduler.remove(&req.id).await;
+    Json(serde_json::json!({
+        "ok": true,
+        "id": req.id,
+        "was_removed": was_removed
+    }))
+}
+
+/// POST /schedules/run - Run a schedule's action now
+pub async fn schedule_run_handler(
+    State(state): State<AppState>,
+    Json(req): Json<ScheduleIdRequest>,
+) -> impl IntoResponse {
+    let Some(schedule) = state.scheduler.get(&req.id).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Schedule not found: {}", req.id),
+            }),
+        )
+            .into_response();
+    };
+
+    let run = state.scheduler.run_schedule(&state, &schedule).await;
+    Json(serde_json::json!({ "ok": run.ok, "run": run })).into_response()
+}
+
+// =============================================================================
+// Rule handlers
+// =============================================================================
+
+/// GET /rules - Automation rules and their recent firings
+pub async fn rules_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "rules": state.rules.list().await,
+        "firings": state.rules.firings().await,
+        "events": crate::rules::RULE_EVENTS,
+    }))
+}
+
+/// POST /rules - Create or update a rule
+pub async fn rule_save_handler(
+    State(state): State<AppState>,
+    Json(rule): Json<crate::rules::Rule>,
+) -> impl IntoResponse {
+    match state.rules.save_rule(rule).await {
+        Ok(rule) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "ok": true,
+                "rule": rule
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Rule delete request
+#[derive(Deserialize)]
+pub struct RuleIdRequest {
+    pub id: String,
+}
+
+/// POST /rules/delete - Delete a rule
+pub async fn rule_delete_handler(
+    State(state): State<AppState>,
+    Json(req): Json<RuleIdRequest>,
+) -> impl IntoResponse {
+    let was_removed = state.rules.remove(&req.id).await;
+    Json(serde_json::json!({
+        "ok": true,
+        "id": req.id,
+        "was_removed": was_removed
+    }))
+}
+
+// =============================================================================
+// Volume policy handlers
+// =============================================================================
+
+/// GET /volume/policy - Volume safety policy (default and per-zone overrides)
+pub async fn volume_policy_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.volume_policy.get().await)
+}
+
+/// POST /volume/policy - Replace the volume safety policy
+pub async fn volume_policy_save_handler(
+    State(state): State<AppState>,
+    Json(policies): Json<VolumePolicies>,
+) -> impl IntoResponse {
+    match state.volume_policy.set(policies).await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "ok": true,
+                "policy": state.volume_policy.get().await
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
 // =============================================================================
-// OpenHome handlers
+// Fade handlers
 // =============================================================================
 
-/// GET /openhome/status - OpenHome discovery status
-pub async fn openhome_status_handler(
-    State(state): State<AppState>,
-) -> Json<crate::adapters::openhome::OpenHomeStatus> {
-    Json(state.openhome.get_status().await)
-}
-
-/// GET /openhome/zones - List all discovered OpenHome devices
-pub async fn openhome_zones_handler(
-    State(state): State<AppState>,
-) -> Json<ZonesWrapper<crate::adapters::openhome::OpenHomeZone>> {
-    Json(ZonesWrapper {
-        zones: state.openhome.get_zones().await,
-    })
+/// GET /fades - Volume fade settings
+pub async fn fades_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.fades.settings().await)
 }
 
-/// GET /openhome/zone/:zone_id/now_playing - Get now playing for zone
-pub async fn openhome_now_playing_handler(
+/// POST /fades - Replace the volume fade settings
+pub async fn fades_save_handler(
     State(state): State<AppState>,
-    Path(zone_id): Path<String>,
+    Json(settings): Json<FadeSettings>,
 ) -> impl IntoResponse {
-    match state.openhome.get_now_playing(&zone_id).await {
-        Some(np) => (StatusCode::OK, Json(np)).into_response(),
-        None => (
-            StatusCode::NOT_FOUND,
+    match state.fades.set_settings(settings).await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "ok": true,
+                "fades": state.fades.settings().await
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: format!("Zone not found: {}", zone_id),
+                error: e.to_string(),
             }),
         )
             .into_response(),
     }
 }
 
-/// OpenHome control request
-#[derive(Deserialize)]
-pub struct OpenHomeControlRequest {
-    pub zone_id: String,
-    pub action: String,
-    #[serde(default)]
-    pub value: Option<i32>,
+// =============================================================================
+// Sleep timer handlers
+// =============================================================================
+
+/// GET /sleep - Running sleep timers
+pub async fn sleep_timers_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(serde_json::json!({ "timers": state.sleep_timers.list().await }))
 }
 
-/// POST /openhome/control - Control OpenHome device
-pub async fn openhome_control_handler(
+/// POST /sleep - Set (or replace) a zone's sleep timer
+pub async fn sleep_timer_set_handler(
     State(state): State<AppState>,
-    Json(req): Json<OpenHomeControlRequest>,
+    Json(req): Json<SleepRequest>,
 ) -> impl IntoResponse {
-    match state
-        .openhome
-        .control(&req.zone_id, &req.action, req.value)
-        .await
-    {
-        Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
+    match state.sleep_timers.set(&state, req).await {
+        Ok(timer) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "ok": true, "timer": timer })),
+        )
+            .into_response(),
         Err(e) => (
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
@@ -1295,63 +2905,120 @@ pub async fn openhome_control_handler(
     }
 }
 
+/// Sleep timer cancel request
+#[derive(Deserialize)]
+pub struct SleepCancelRequest {
+    pub zone_id: String,
+}
+
+/// POST /sleep/cancel - Cancel a zone's sleep timer
+pub async fn sleep_timer_cancel_handler(
+    State(state): State<AppState>,
+    Json(req): Json<SleepCancelRequest>,
+) -> impl IntoResponse {
+    let was_cancelled = state.sleep_timers.cancel(&state, &req.zone_id).await;
+    Json(serde_json::json!({
+        "ok": true,
+        "zone_id": req.zone_id,
+        "was_cancelled": was_cancelled
+    }))
+}
+
 // =============================================================================
-// UPnP handlers
+// Scene handlers
 // =============================================================================
 
-/// GET /upnp/status - UPnP discovery status
-pub async fn upnp_status_handler(
-    State(state): State<AppState>,
-) -> Json<crate::adapters::upnp::UPnPStatus> {
-    Json(state.upnp.get_status().await)
+/// GET /scenes - Saved scenes
+pub async fn scenes_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(serde_json::json!({ "scenes": state.scenes.list().await }))
 }
 
-/// GET /upnp/zones - List all discovered UPnP renderers
-pub async fn upnp_zones_handler(
+/// POST /scenes - Create or update a scene
+pub async fn scene_save_handler(
     State(state): State<AppState>,
-) -> Json<ZonesWrapper<crate::adapters::upnp::UPnPZone>> {
-    Json(ZonesWrapper {
-        zones: state.upnp.get_zones().await,
-    })
+    Json(scene): Json<Scene>,
+) -> impl IntoResponse {
+    match state.scenes.save_scene(scene).await {
+        Ok(scene) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "ok": true, "scene": scene })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
 }
 
-/// GET /upnp/zone/:zone_id/now_playing - Get now playing for renderer
-pub async fn upnp_now_playing_handler(
+/// POST /scenes/capture - Save the current state of some zones as a scene
+pub async fn scene_capture_handler(
     State(state): State<AppState>,
-    Path(zone_id): Path<String>,
+    Json(req): Json<CaptureRequest>,
 ) -> impl IntoResponse {
-    match state.upnp.get_now_playing(&zone_id).await {
-        Some(np) => (StatusCode::OK, Json(np)).into_response(),
-        None => (
-            StatusCode::NOT_FOUND,
+    match state.scenes.capture(&state, req).await {
+        Ok(scene) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "ok": true, "scene": scene })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: format!("Renderer not found: {}", zone_id),
+                error: e.to_string(),
             }),
         )
             .into_response(),
     }
 }
 
-/// UPnP control request
+/// Scene delete/recall request
 #[derive(Deserialize)]
-pub struct UPnPControlRequest {
-    pub zone_id: String,
-    pub action: String,
-    #[serde(default)]
-    pub value: Option<i32>,
+pub struct SceneIdRequest {
+    pub id: String,
 }
 
-/// POST /upnp/control - Control UPnP renderer
-pub async fn upnp_control_handler(
+/// POST /scenes/delete - Delete a scene
+pub async fn scene_delete_handler(
     State(state): State<AppState>,
-    Json(req): Json<UPnPControlRequest>,
+    Json(req): Json<SceneIdRequest>,
 ) -> impl IntoResponse {
-    match state
-        .upnp
-        .control(&req.zone_id, &req.action, req.value)
-        .await
-    {
-        Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
+    let was_removed = state.scenes.remove(&req.id).await;
+    Json(serde_json::json!({
+        "ok": true,
+        "id": req.id,
+        "was_removed": was_removed
+    }))
+}
+
+/// POST /scenes/recall - Apply a scene to all of its zones
+pub async fn scene_recall_handler(
+    State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
+    Json(req): Json<SceneIdRequest>,
+) -> impl IntoResponse {
+    if state.scenes.get(&req.id).await.is_none() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Scene not found: {}", req.id),
+            }),
+        )
+            .into_response();
+    }
+
+    let origin = CommandOrigin {
+        surface: ControlSurface::Api,
+        ..http_origin(&state, &headers, connect_info).await
+    };
+    match state.scenes.recall(&state, &req.id, &origin).await {
+        Ok(recall) => {
+            Json(serde_json::json!({ "ok": recall.ok, "recall": recall })).into_response()
+        }
         Err(e) => (
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
@@ -1730,6 +3397,8 @@ pub async fn hqp_instance_profiles_handler(
 /// POST /hqp/instances/:name/profile - Load a profile on a specific HQPlayer instance
 pub async fn hqp_instance_load_profile_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Path(name): Path<String>,
     Json(req): Json<HqpProfileRequest>,
 ) -> impl IntoResponse {
@@ -1746,7 +3415,14 @@ pub async fn hqp_instance_load_profile_handler(
         }
     };
 
-    match adapter.load_profile(&req.profile).await {
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let command = Command::HqpDsp {
+        setting: "profile".to_string(),
+        value: req.profile.clone(),
+    };
+    let change = adapter.load_profile(&req.profile);
+    let zone_id = format!("hqplayer:{}", name);
+    match audit_command(&state.bus, &zone_id, command, origin, change).await {
         Ok(()) => (
             StatusCode::OK,
             Json(serde_json::json!({"ok": true, "instance": name, "profile": req.profile})),
@@ -1812,6 +3488,8 @@ pub struct HqpInstanceMatrixProfileRequest {
 /// POST /hqp/instances/:name/matrix/profile - Set matrix profile on a specific instance
 pub async fn hqp_instance_set_matrix_profile_handler(
     State(state): State<AppState>,
+    connect_info: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Path(name): Path<String>,
     Json(req): Json<HqpInstanceMatrixProfileRequest>,
 ) -> impl IntoResponse {
@@ -1828,7 +3506,14 @@ pub async fn hqp_instance_set_matrix_profile_handler(
         }
     };
 
-    match adapter.set_matrix_profile(req.value).await {
+    let origin = http_origin(&state, &headers, connect_info).await;
+    let command = Command::HqpDsp {
+        setting: "matrix_profile".to_string(),
+        value: req.value.to_string(),
+    };
+    let change = adapter.set_matrix_profile(req.value);
+    let zone_id = format!("hqplayer:{}", name);
+    match audit_command(&state.bus, &zone_id, command, origin, change).await {
         Ok(()) => (
             StatusCode::OK,
             Json(serde_json::json!({"ok": true, "instance": name, "value": req.value})),
@@ -2013,7 +3698,7 @@ pub struct AppSettings {
     pub adapters: AdapterSettings,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct AdapterSettings {
     #[serde(default = "default_true")]
     pub roon: bool,
@@ -2079,7 +3764,7 @@ pub fn load_app_settings() -> AppSettings {
     settings
 }
 
-fn save_app_settings(settings: &AppSettings) -> bool {
+pub(crate) fn save_app_settings(settings: &AppSettings) -> bool {
     let path = settings_path();
     if let Some(parent) = path.parent() {
         let _ = std::fs::create_dir_all(parent);
@@ -2121,11 +3806,19 @@ pub async fn api_settings_post_handler(
     }
 
     // Compare adapter enabled states and start/stop as needed
-    let old_adapters = &old_settings.adapters;
-    let new_adapters = &new_settings.adapters;
+    apply_adapter_settings(&state, &old_settings.adapters, &new_settings.adapters).await;
+
+    Json(serde_json::json!({"ok": true}))
+}
 
+/// Start or stop adapters whose enabled setting changed
+pub async fn apply_adapter_settings(
+    state: &AppState,
+    old_adapters: &AdapterSettings,
+    new_adapters: &AdapterSettings,
+) {
     // Helper to process adapter state changes
-    let adapters_list = state.startable_adapters.clone();
+    let adapters_list = state.startables().await;
     let coord = state.coordinator.clone();
 
     // Check each adapter for state changes
@@ -2155,23 +3848,266 @@ pub async fn api_settings_post_handler(
         // Update coordinator state
         coord.set_enabled(name, now_enabled).await;
 
-        // Find the adapter and start/stop it
-        if let Some(adapter) = adapters_list.iter().find(|a| a.name() == name) {
+        // The LMS toggle also covers its CLI adapter and any extra servers
+        let prefix = format!("{}-", name);
+        let targets = adapters_list
+            .iter()
+            .filter(|a| a.name() == name || (name == "lms" && a.name().starts_with(&prefix)));
+
+        for adapter in targets {
+            let adapter_name = adapter.name();
+            if adapter_name != name {
+                coord.set_enabled(adapter_name, now_enabled).await;
+            }
+
             if now_enabled {
-                tracing::info!("Dynamically enabling adapter: {}", name);
+                tracing::info!("Dynamically enabling adapter: {}", adapter_name);
                 if adapter.can_start().await {
                     if let Err(e) = adapter.start().await {
-                        tracing::warn!("Failed to start adapter {}: {}", name, e);
+                        tracing::warn!("Failed to start adapter {}: {}", adapter_name, e);
                     }
                 }
             } else {
-                tracing::info!("Dynamically disabling adapter: {}", name);
+                tracing::info!("Dynamically disabling adapter: {}", adapter_name);
                 adapter.stop().await;
             }
         }
     }
+}
 
-    Json(serde_json::json!({"ok": true}))
+// =============================================================================
+// Authentication
+// =============================================================================
+
+/// Auth status as seen by the caller
+#[derive(Serialize)]
+pub struct AuthStatusResponse {
+    pub enabled: bool,
+    /// Knob pairing is open
+    pub knob_enrollment: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub knob_enrollment_until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Scope of the token sent with the request, if it's valid
+    pub scope: Option<Scope>,
+}
+
+/// GET /auth/status - Whether auth is on, and what the caller may do
+pub async fn auth_status_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let scope = match crate::auth::request_token(&headers, true) {
+        Some(secret) => state.auth.authenticate(&secret).await,
+        None => None,
+    };
+    let knob_enrollment_until = state.auth.knob_pairing_until().await;
+    Json(AuthStatusResponse {
+        enabled: state.auth.is_enabled().await,
+        knob_enrollment: knob_enrollment_until.is_some(),
+        knob_enrollment_until,
+        scope,
+    })
+}
+
+/// Login request
+#[derive(Deserialize)]
+pub struct AuthLoginRequest {
+    pub token: String,
+}
+
+/// POST /auth/login - Check a token and keep it in the UI session cookie
+pub async fn auth_login_handler(
+    State(state): State<AppState>,
+    tls: Result<Extension<OverTls>, ExtensionRejection>,
+    Json(req): Json<AuthLoginRequest>,
+) -> impl IntoResponse {
+    let token = req.token.trim();
+    match state.auth.authenticate(token).await {
+        Some(scope) => (
+            [(
+                axum::http::header::SET_COOKIE,
+                crate::auth::session_cookie(token, tls.is_ok()),
+            )],
+            Json(serde_json::json!({"ok": true, "scope": scope})),
+        )
+            .into_response(),
+        None => (
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse {
+                error: "Invalid token".to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// POST /auth/logout - Clear the UI session cookie
+pub async fn auth_logout_handler(
+    tls: Result<Extension<OverTls>, ExtensionRejection>,
+) -> impl IntoResponse {
+    (
+        [(
+            axum::http::header::SET_COOKIE,
+            crate::auth::clear_session_cookie(tls.is_ok()),
+        )],
+        Json(serde_json::json!({"ok": true})),
+    )
+}
+
+/// GET /auth/tokens - List tokens (without secrets)
+pub async fn auth_tokens_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(serde_json::json!({ "tokens": state.auth.tokens().await }))
+}
+
+/// Create token request
+#[derive(Deserialize)]
+pub struct AuthCreateTokenRequest {
+    pub name: String,
+    pub scope: Scope,
+}
+
+/// POST /auth/tokens - Create a token; the secret is only returned here
+pub async fn auth_create_token_handler(
+    State(state): State<AppState>,
+    Json(req): Json<AuthCreateTokenRequest>,
+) -> impl IntoResponse {
+    match state.auth.create_token(&req.name, req.scope).await {
+        Ok((token, secret)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({"token": token, "secret": secret})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// DELETE /auth/tokens/{id} - Revoke a token
+pub async fn auth_revoke_token_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.auth.revoke(&id).await {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Auth settings request
+#[derive(Deserialize)]
+pub struct AuthSettingsRequest {
+    pub enabled: bool,
+    /// Open knob pairing for ten minutes (false closes it)
+    #[serde(default)]
+    pub knob_enrollment: bool,
+    /// Knob id to approve while pairing, for a knob the bridge hasn't seen yet
+    #[serde(default)]
+    pub knob_id: Option<String>,
+    /// Admin token to log in with when turning auth on
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// POST /auth/settings - Turn auth on/off and open/close knob pairing
+///
+/// Turning auth on needs an admin token in the body; the caller is logged in with
+/// it so the UI isn't locked out by its own change.
+pub async fn auth_settings_handler(
+    State(state): State<AppState>,
+    tls: Result<Extension<OverTls>, ExtensionRejection>,
+    Json(req): Json<AuthSettingsRequest>,
+) -> impl IntoResponse {
+    let bad_request =
+        |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response();
+
+    let login = if req.enabled && !state.auth.is_enabled().await {
+        let token = req.token.as_deref().map(str::trim).unwrap_or_default();
+        if state.auth.authenticate(token).await != Some(Scope::Admin) {
+            return bad_request("Enabling authentication needs an admin token".to_string());
+        }
+        Some(token.to_string())
+    } else {
+        None
+    };
+
+    if let Err(e) = state.auth.configure(req.enabled).await {
+        return bad_request(e.to_string());
+    }
+    state
+        .auth
+        .set_knob_pairing(req.knob_enrollment, req.knob_id.as_deref())
+        .await;
+
+    let body = Json(serde_json::json!({"ok": true}));
+    match login {
+        Some(token) => (
+            [(
+                axum::http::header::SET_COOKIE,
+                crate::auth::session_cookie(&token, tls.is_ok()),
+            )],
+            body,
+        )
+            .into_response(),
+        None => body.into_response(),
+    }
+}
+
+// =============================================================================
+// Backup / restore
+// =============================================================================
+
+/// GET /api/backup - Download all bridge state as one JSON bundle
+pub async fn backup_handler() -> impl IntoResponse {
+    let backup = crate::backup::create_backup();
+    let filename = format!(
+        "attachment; filename=\"unified-hifi-backup-{}.json\"",
+        backup.created_at.format("%Y%m%d-%H%M%S")
+    );
+    (
+        [(axum::http::header::CONTENT_DISPOSITION, filename)],
+        Json(backup),
+    )
+}
+
+/// Restore query parameters
+#[derive(Deserialize)]
+pub struct RestoreQuery {
+    /// Report what would change without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// POST /api/restore?dry_run=true - Validate, migrate and apply a backup bundle
+pub async fn restore_handler(
+    State(state): State<AppState>,
+    Query(params): Query<RestoreQuery>,
+    Json(body): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    let result = match serde_json::from_value::<crate::backup::Backup>(body) {
+        Ok(backup) => crate::backup::restore(&state, backup, params.dry_run).await,
+        Err(e) => Err(anyhow::anyhow!("Not a backup: {}", e)),
+    };
+    match result {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
 }
 
 #[cfg(test)]
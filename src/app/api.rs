@@ -17,6 +17,38 @@ pub struct AppStatus {
     pub git_sha: String,
     pub uptime_secs: u64,
     pub bus_subscribers: usize,
+    #[serde(default)]
+    pub adapters: Vec<AdapterDiagnostics>,
+}
+
+/// Mirrors `coordinator::StateTransition`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AdapterTransition {
+    pub at: String,
+    pub state: String,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+/// Mirrors `coordinator::AdapterStatus` (with its health flattened in)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AdapterDiagnostics {
+    pub prefix: String,
+    pub enabled: bool,
+    pub running: bool,
+    /// "stopped", "running", "connected", "disconnected" or "retrying"
+    pub state: String,
+    pub since: Option<String>,
+    pub consecutive_failures: u32,
+    pub attempts: u32,
+    pub manual_restarts: u32,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<String>,
+    pub last_event_at: Option<String>,
+    pub next_retry_at: Option<String>,
+    pub retry_delay_secs: Option<u64>,
+    #[serde(default)]
+    pub transitions: Vec<AdapterTransition>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
@@ -74,6 +106,28 @@ pub struct Zone {
     pub zone_name: String,
     pub source: Option<String>,
     pub dsp: Option<ZoneDsp>,
+    /// Controls the zone supports (absent from older servers: assume everything)
+    pub capabilities: Option<ZoneCapabilities>,
+    /// Cached from before a restart and not yet confirmed by its adapter
+    #[serde(default)]
+    pub is_stale: bool,
+}
+
+/// Mirrors `bus::ZoneCapabilities`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ZoneCapabilities {
+    pub transport: bool,
+    pub skip: bool,
+    pub seek: bool,
+    pub shuffle: bool,
+    pub repeat: bool,
+    pub volume: bool,
+    pub volume_db: bool,
+    pub mute: bool,
+    pub grouping: bool,
+    pub source_selection: bool,
+    pub standby: bool,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
@@ -101,6 +155,234 @@ pub struct NowPlaying {
     pub volume_step: Option<f32>,
     pub is_previous_allowed: bool,
     pub is_next_allowed: bool,
+    /// Stream format label (e.g. "FLAC 24/192", "DSD128")
+    pub format: Option<String>,
+    /// Running sleep timer
+    #[serde(default)]
+    pub sleep_timer: Option<SleepTimer>,
+}
+
+/// Mirrors `sleep_timer::SleepTimerStatus`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct SleepTimer {
+    pub zone_id: String,
+    /// "timer", "end_of_track" or "end_of_album"
+    pub mode: String,
+    pub remaining_secs: Option<u64>,
+}
+
+// =============================================================================
+// Room Types
+// =============================================================================
+
+/// Mirrors `rooms::Room` - several adapter zones presented as one
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Room {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub zone_ids: Vec<String>,
+    #[serde(default)]
+    pub transport_zone: Option<String>,
+    #[serde(default)]
+    pub volume_zone: Option<String>,
+    #[serde(default)]
+    pub metadata_zone: Option<String>,
+}
+
+/// Adapter zone that can be added to a room
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct RoomCandidate {
+    pub zone_id: String,
+    pub zone_name: String,
+    pub source: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct RoomsResponse {
+    pub rooms: Vec<Room>,
+    pub zones: Vec<RoomCandidate>,
+}
+
+// =============================================================================
+// Scene Types
+// =============================================================================
+
+/// Mirrors `scenes::SceneZone` (only the fields the UI shows)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct SceneZone {
+    pub zone_id: String,
+    #[serde(default)]
+    pub volume: Option<f32>,
+    #[serde(default)]
+    pub muted: Option<bool>,
+}
+
+/// Mirrors `scenes::Scene`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Scene {
+    pub id: String,
+    pub name: String,
+    pub zones: Vec<SceneZone>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScenesResponse {
+    pub scenes: Vec<Scene>,
+}
+
+// =============================================================================
+// Scrobbler Types
+// =============================================================================
+
+/// Mirrors `scrobbler::ScrobblerStatus`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScrobblerStatus {
+    pub enabled: bool,
+    pub url: String,
+    pub has_token: bool,
+    pub queued: usize,
+    pub submitted: u64,
+    pub last_submitted_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// POST /scrobbler/config body (omitted fields keep their current value)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScrobblerConfigRequest {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+// =============================================================================
+// Schedule Types
+// =============================================================================
+
+/// Mirrors `scheduler::ScheduleWhen`
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleWhen {
+    Daily {
+        time: String,
+        /// Weekday names ("mon".."sun"); empty = every day
+        #[serde(default)]
+        days: Vec<String>,
+    },
+    Cron {
+        expr: String,
+    },
+}
+
+/// Mirrors `scheduler::ScheduleAction`
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleAction {
+    Play {
+        query: String,
+        #[serde(default)]
+        source: Option<String>,
+        #[serde(default)]
+        action: Option<String>,
+    },
+    Volume {
+        value: f32,
+    },
+    HqpProfile {
+        profile: String,
+    },
+    Pause,
+    Stop,
+}
+
+/// Mirrors `scheduler::AlarmRamp` (percent of the zone's volume range)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AlarmRamp {
+    pub start_percent: f32,
+    pub target_percent: f32,
+    pub ramp_minutes: u32,
+}
+
+/// Mirrors `scheduler::Schedule`
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Schedule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    #[serde(default)]
+    pub zone_id: String,
+    pub when: ScheduleWhen,
+    pub action: ScheduleAction,
+    #[serde(default)]
+    pub alarm: Option<AlarmRamp>,
+}
+
+/// Mirrors `scheduler::ScheduleRun`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScheduleRun {
+    pub at: String,
+    pub ok: bool,
+    pub message: String,
+}
+
+/// Mirrors `scheduler::ScheduleStatus`
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ScheduleStatus {
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    pub next_run: Option<String>,
+    pub last_run: Option<ScheduleRun>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct SchedulesResponse {
+    pub schedules: Vec<ScheduleStatus>,
+}
+
+// =============================================================================
+// Volume Policy Types
+// =============================================================================
+
+/// Mirrors `volume_policy::VolumePolicy`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct VolumePolicy {
+    pub max_volume: Option<f32>,
+    pub max_step: Option<f32>,
+    pub start_cap: Option<f32>,
+}
+
+/// Mirrors `volume_policy::VolumePolicies`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct VolumePolicies {
+    #[serde(default)]
+    pub default: VolumePolicy,
+    #[serde(default)]
+    pub zones: std::collections::BTreeMap<String, VolumePolicy>,
+}
+
+/// Mirrors `fades::ZoneFade`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ZoneFade {
+    pub enabled: bool,
+    pub duration_ms: Option<u64>,
+}
+
+/// Mirrors `fades::FadeSettings` (surfaces as their snake_case names)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct FadeSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub fade_in: bool,
+    #[serde(default)]
+    pub surfaces: std::collections::BTreeSet<String>,
+    #[serde(default)]
+    pub zones: std::collections::BTreeMap<String, ZoneFade>,
 }
 
 // =============================================================================
@@ -141,6 +423,42 @@ pub struct LmsPlayer {
     pub volume: i32,
 }
 
+/// One LMS server from /lms/servers (the primary has no id)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct LmsServerInfo {
+    pub id: Option<String>,
+    pub adapter: String,
+    pub host: Option<String>,
+    pub port: u16,
+    pub connected: bool,
+    pub player_count: usize,
+    #[serde(default)]
+    pub cli_subscription_active: bool,
+}
+
+/// Wrapper for /lms/servers response
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct LmsServersResponse {
+    pub servers: Vec<LmsServerInfo>,
+}
+
+/// A server found by /lms/discover
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct DiscoveredLmsServer {
+    pub host: String,
+    pub json_port: u16,
+    pub name: String,
+    /// Already the primary or an additional server
+    #[serde(default)]
+    pub configured: bool,
+}
+
+/// Wrapper for /lms/discover response
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct LmsDiscoverResponse {
+    pub discovered: Vec<DiscoveredLmsServer>,
+}
+
 // =============================================================================
 // HQPlayer Types
 // =============================================================================
@@ -297,10 +615,159 @@ pub struct FetchFirmwareResponse {
     pub error: Option<String>,
 }
 
+// =============================================================================
+// Audit Log
+// =============================================================================
+
+/// Mirrors `bus::CommandOrigin`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuditOrigin {
+    pub surface: String,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl AuditOrigin {
+    /// "knob Kitchen from 192.168.1.20", "scheduler Wake up", "api"
+    pub fn label(&self) -> String {
+        let mut label = self.surface.clone();
+        if let Some(who) = self.name.as_ref().or(self.id.as_ref()) {
+            label.push(' ');
+            label.push_str(who);
+        }
+        if let Some(ip) = &self.client_ip {
+            label.push_str(" from ");
+            label.push_str(ip);
+        }
+        label
+    }
+}
+
+/// Mirrors `bus::Command` (`{"action": ..., "params": {...}}`)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuditCommand {
+    pub action: String,
+    #[serde(default)]
+    pub params: Option<serde_json::Value>,
+}
+
+/// Mirrors `audit::AuditEntry`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    pub at: String,
+    pub zone_id: String,
+    pub command: AuditCommand,
+    pub origin: Option<AuditOrigin>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuditResponse {
+    pub entries: Vec<AuditEntry>,
+}
+
+// =============================================================================
+// Authentication
+// =============================================================================
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuthStatus {
+    pub enabled: bool,
+    /// Knob pairing is open
+    pub knob_enrollment: bool,
+    #[serde(default)]
+    pub knob_enrollment_until: Option<String>,
+    /// Scope of the session (None when not logged in)
+    pub scope: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ApiTokenInfo {
+    pub id: String,
+    pub name: String,
+    pub scope: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub knob_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ApiTokensResponse {
+    pub tokens: Vec<ApiTokenInfo>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateTokenRequest {
+    pub name: String,
+    pub scope: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct CreateTokenResponse {
+    pub token: Option<ApiTokenInfo>,
+    /// Only returned when the token is created
+    pub secret: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AuthSettingsRequest {
+    pub enabled: bool,
+    pub knob_enrollment: bool,
+    /// Admin token to log in with when turning auth on
+    pub token: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LoginRequest {
+    pub token: String,
+}
+
+/// Login/settings response: `ok` on success, `error` otherwise
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuthResponse {
+    #[serde(default)]
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
 // =============================================================================
 // Client-side fetch helpers (for use in effects/resources)
 // =============================================================================
 
+/// Load `url` as a new page (client-side only), e.g. after logging in or out
+#[cfg(target_arch = "wasm32")]
+pub fn load_page(url: &str) {
+    if let Some(window) = web_sys::window() {
+        let _ = window.location().set_href(url);
+    }
+}
+
+/// SSR stub - no-op
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_page(_url: &str) {}
+
+/// Send the browser to the login page, coming back here afterwards
+#[cfg(target_arch = "wasm32")]
+fn redirect_to_login() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let location = window.location();
+    let here = format!(
+        "{}{}",
+        location.pathname().unwrap_or_default(),
+        location.search().unwrap_or_default()
+    );
+    if !here.starts_with("/login") {
+        load_page(&format!("/login?next={}", urlencoding::encode(&here)));
+    }
+}
+
 /// Fetch JSON from a URL (client-side only)
 #[cfg(target_arch = "wasm32")]
 pub async fn fetch_json<T: for<'de> Deserialize<'de>>(url: &str) -> Result<T, String> {
@@ -320,6 +787,12 @@ pub async fn fetch_json<T: for<'de> Deserialize<'de>>(url: &str) -> Result<T, St
 
     let resp: Response = resp_value.dyn_into().map_err(|_| "Not a Response")?;
 
+    // API authentication is on and the session is missing or revoked
+    if resp.status() == 401 {
+        redirect_to_login();
+        return Err("Not logged in".to_string());
+    }
+
     let json = JsFuture::from(resp.json().map_err(|e| format!("{:?}", e))?)
         .await
         .map_err(|e| format!("{:?}", e))?;
@@ -415,3 +888,34 @@ pub async fn post_json_no_response<T: Serialize>(url: &str, body: &T) -> Result<
 pub async fn post_json_no_response<T: Serialize>(_url: &str, _body: &T) -> Result<(), String> {
     Err("post_json_no_response is only available in browser".to_string())
 }
+
+/// Send a DELETE request (client-side only)
+#[cfg(target_arch = "wasm32")]
+pub async fn delete_request(url: &str) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{Request, RequestInit, Response};
+
+    let window = web_sys::window().ok_or("No window")?;
+    let opts = RequestInit::new();
+    opts.set_method("DELETE");
+
+    let request = Request::new_with_str_and_init(url, &opts).map_err(|e| format!("{:?}", e))?;
+
+    let resp_value = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let resp: Response = resp_value.dyn_into().map_err(|_| "Not a Response")?;
+    if resp.ok() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", resp.status()))
+    }
+}
+
+/// SSR stub - returns error (should not be called during SSR)
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn delete_request(_url: &str) -> Result<(), String> {
+    Err("delete_request is only available in browser".to_string())
+}
@@ -79,6 +79,7 @@ pub fn Nav(props: NavProps) -> Element {
                     if !hide_knobs {
                         Link { class: nav_link_class("knobs"), to: Route::Knobs {}, "Knobs" }
                     }
+                    Link { class: nav_link_class("schedules"), to: Route::Schedules {}, "Schedules" }
                     Link { class: nav_link_class("settings"), to: Route::Settings {}, "Settings" }
                 }
 
@@ -117,6 +118,7 @@ pub fn Nav(props: NavProps) -> Element {
                     if !hide_knobs {
                         Link { class: nav_link_class("knobs"), to: Route::Knobs {}, onclick: move |_| menu_open.set(false), "Knobs" }
                     }
+                    Link { class: nav_link_class("schedules"), to: Route::Schedules {}, onclick: move |_| menu_open.set(false), "Schedules" }
                     Link { class: nav_link_class("settings"), to: Route::Settings {}, onclick: move |_| menu_open.set(false), "Settings" }
                 }
             }
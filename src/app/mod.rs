@@ -13,7 +13,7 @@ pub mod settings_context;
 pub mod sse;
 pub mod theme;
 
-use pages::{HqPlayer, Knobs, Lms, Settings, Zones};
+use pages::{Diagnostics, HqPlayer, Knobs, Lms, Login, Schedules, Settings, Zones};
 use settings_context::use_settings_provider;
 use sse::use_sse_provider;
 use theme::use_theme_provider;
@@ -46,6 +46,12 @@ pub enum Route {
     Lms {},
     #[route("/knobs")]
     Knobs {},
+    #[route("/schedules")]
+    Schedules {},
     #[route("/settings")]
     Settings {},
+    #[route("/diagnostics")]
+    Diagnostics {},
+    #[route("/login")]
+    Login {},
 }
@@ -0,0 +1,213 @@
+//! Diagnostics page component.
+//!
+//! Per-adapter supervision: connection state, failures, last error, retry
+//! schedule and recent state changes, with a restart button per adapter.
+
+use dioxus::prelude::*;
+
+use crate::app::api::{AdapterDiagnostics, AppStatus};
+use crate::app::components::{ErrorAlert, Layout};
+use crate::app::sse::{use_sse, SseEvent};
+
+/// State changes shown per adapter (newest first)
+const SHOWN_TRANSITIONS: usize = 8;
+
+/// Adapter restart request body
+#[derive(Clone, serde::Serialize)]
+struct AdapterRestartRequest {
+    adapter: String,
+}
+
+/// "2026-10-17T06:30:12.123Z" -> "2026-10-17 06:30:12 UTC"
+fn short_time(time: &str) -> String {
+    format!("{} UTC", time.get(..19).unwrap_or(time).replace('T', " "))
+}
+
+fn state_class(state: &str) -> &'static str {
+    match state {
+        "connected" | "running" => "status-ok",
+        "retrying" | "disconnected" => "status-err",
+        _ => "text-muted",
+    }
+}
+
+/// Diagnostics page component.
+#[component]
+pub fn Diagnostics() -> Element {
+    let sse = use_sse();
+
+    let mut status = use_resource(|| async {
+        crate::app::api::fetch_json::<AppStatus>("/status")
+            .await
+            .ok()
+    });
+
+    let mut error = use_signal(|| None::<String>);
+    let mut notice = use_signal(|| None::<String>);
+
+    // Refresh on adapter lifecycle events
+    use_effect(move || {
+        let _ = (sse.event_count)();
+        if matches!(
+            (sse.last_event)(),
+            Some(
+                SseEvent::AdapterStarting
+                    | SseEvent::AdapterRetrying
+                    | SseEvent::AdapterConnected
+                    | SseEvent::AdapterDisconnected
+                    | SseEvent::AdapterStopped
+                    | SseEvent::RoonConnected
+                    | SseEvent::RoonDisconnected
+                    | SseEvent::LmsConnected
+                    | SseEvent::LmsDisconnected
+            )
+        ) {
+            status.restart();
+        }
+    });
+
+    let restart = move |adapter: String| {
+        error.set(None);
+        notice.set(None);
+        spawn(async move {
+            match crate::app::api::post_json::<AdapterRestartRequest, serde_json::Value>(
+                "/adapters/restart",
+                &AdapterRestartRequest {
+                    adapter: adapter.clone(),
+                },
+            )
+            .await
+            {
+                Ok(resp) => {
+                    if let Some(e) = resp.get("error").and_then(|e| e.as_str()) {
+                        error.set(Some(e.to_string()));
+                    } else {
+                        notice.set(Some(format!("Restarted {adapter}")));
+                    }
+                }
+                Err(e) => error.set(Some(format!("Restart failed: {e}"))),
+            }
+            status.restart();
+        });
+    };
+
+    let adapters: Vec<AdapterDiagnostics> = status
+        .read()
+        .clone()
+        .flatten()
+        .map(|s| s.adapters)
+        .unwrap_or_default();
+
+    rsx! {
+        Layout {
+            title: "Diagnostics".to_string(),
+            nav_active: "settings".to_string(),
+
+            h1 { class: "text-2xl font-bold mb-6", "Diagnostics" }
+
+            if let Some(err) = error() {
+                ErrorAlert {
+                    message: err,
+                    on_dismiss: move |_| error.set(None),
+                }
+            }
+            if let Some(msg) = notice() {
+                p { class: "text-sm text-muted mb-4", "{msg}" }
+            }
+
+            section { id: "adapters", class: "mb-8",
+                div { class: "mb-4",
+                    h2 { class: "text-xl font-semibold", "Adapters" }
+                    p { class: "text-muted text-sm", "Connection state, failures and retries since the server started" }
+                }
+                if adapters.is_empty() {
+                    div { class: "card p-6",
+                        p { class: "text-muted", "No adapter status available." }
+                    }
+                }
+                for adapter in adapters.iter() {
+                    {
+                        let name = adapter.prefix.clone();
+                        let since = adapter
+                            .since
+                            .as_deref()
+                            .map(|t| format!(" since {}", short_time(t)))
+                            .unwrap_or_default();
+                        let last_event = adapter
+                            .last_event_at
+                            .as_deref()
+                            .map(short_time)
+                            .unwrap_or_else(|| "—".to_string());
+                        let last_error = adapter.last_error.as_ref().map(|e| {
+                            match adapter.last_error_at.as_deref() {
+                                Some(at) => format!("{} ({})", e, short_time(at)),
+                                None => e.clone(),
+                            }
+                        });
+                        let next_retry = adapter.next_retry_at.as_deref().map(|at| {
+                            let delay = adapter
+                                .retry_delay_secs
+                                .map(|s| format!(" (backoff {s}s)"))
+                                .unwrap_or_default();
+                            format!("{}{}", short_time(at), delay)
+                        });
+                        let transitions: Vec<String> = adapter
+                            .transitions
+                            .iter()
+                            .rev()
+                            .take(SHOWN_TRANSITIONS)
+                            .map(|t| match &t.detail {
+                                Some(detail) => format!("{} {} — {}", short_time(&t.at), t.state, detail),
+                                None => format!("{} {}", short_time(&t.at), t.state),
+                            })
+                            .collect();
+                        rsx! {
+                            div { key: "{adapter.prefix}", class: "card p-6 mb-4",
+                                div { class: "flex items-center justify-between gap-4 mb-3",
+                                    div {
+                                        p { class: "font-semibold", "{adapter.prefix}" }
+                                        if adapter.enabled {
+                                            p { class: "text-sm",
+                                                span { class: state_class(&adapter.state), "{adapter.state}" }
+                                                span { class: "text-muted", "{since}" }
+                                            }
+                                        } else {
+                                            p { class: "text-sm text-muted", "disabled" }
+                                        }
+                                    }
+                                    button {
+                                        class: "btn btn-outline btn-sm",
+                                        disabled: !adapter.enabled,
+                                        onclick: move |_| restart(name.clone()),
+                                        "Restart"
+                                    }
+                                }
+                                div { class: "grid gap-2 grid-cols-1 md:grid-cols-2 text-sm",
+                                    p { span { class: "text-muted", "Failures in a row: " } "{adapter.consecutive_failures}" }
+                                    p { span { class: "text-muted", "Attempts: " } "{adapter.attempts} ({adapter.manual_restarts} manual restarts)" }
+                                    p { span { class: "text-muted", "Last event: " } "{last_event}" }
+                                    if let Some(retry) = next_retry {
+                                        p { span { class: "text-muted", "Next retry: " } "{retry}" }
+                                    }
+                                }
+                                if let Some(err) = last_error {
+                                    p { class: "text-sm mt-2",
+                                        span { class: "text-muted", "Last error: " }
+                                        span { class: "status-err", "{err}" }
+                                    }
+                                }
+                                if !transitions.is_empty() {
+                                    ul { class: "text-sm text-muted mt-3",
+                                        for line in transitions.iter() {
+                                            li { "{line}" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
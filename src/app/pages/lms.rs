@@ -4,7 +4,9 @@
 
 use dioxus::prelude::*;
 
-use crate::app::api::{AppSettings, LmsConfig, LmsPlayer, LmsPlayersResponse};
+use crate::app::api::{
+    AppSettings, LmsConfig, LmsDiscoverResponse, LmsPlayer, LmsPlayersResponse, LmsServersResponse,
+};
 use crate::app::components::Layout;
 use crate::app::sse::use_sse;
 
@@ -19,6 +21,13 @@ struct LmsConfigureRequest {
     password: Option<String>,
 }
 
+/// Additional LMS server request
+#[derive(Clone, serde::Serialize)]
+struct LmsAddServerRequest {
+    host: String,
+    port: u16,
+}
+
 /// LMS control request
 #[derive(Clone, serde::Serialize)]
 struct LmsControlRequest {
@@ -256,6 +265,8 @@ pub fn Lms() -> Element {
                 }
             }
 
+            AdditionalServers { on_change: move |_| players.restart() }
+
             // Players section
             section { id: "lms-players", class: "mb-8",
                 div { class: "mb-4",
@@ -294,6 +305,165 @@ pub fn Lms() -> Element {
     }
 }
 
+/// Additional servers: list, remove, and add from network discovery
+#[component]
+fn AdditionalServers(on_change: EventHandler<()>) -> Element {
+    let mut status = use_signal(|| None::<String>);
+    let mut discovering = use_signal(|| false);
+    let mut discovered = use_signal(|| None::<LmsDiscoverResponse>);
+
+    let mut servers = use_resource(|| async {
+        crate::app::api::fetch_json::<LmsServersResponse>("/lms/servers")
+            .await
+            .ok()
+            .map(|r| r.servers)
+    });
+
+    let discover = move |_| {
+        discovering.set(true);
+        status.set(None);
+        spawn(async move {
+            match crate::app::api::fetch_json::<LmsDiscoverResponse>("/lms/discover").await {
+                Ok(resp) => discovered.set(Some(resp)),
+                Err(e) => status.set(Some(format!("Error: {}", e))),
+            }
+            discovering.set(false);
+        });
+    };
+
+    let add = move |(host, port): (String, u16)| {
+        spawn(async move {
+            let req = LmsAddServerRequest { host, port };
+            match crate::app::api::post_json::<_, serde_json::Value>("/lms/servers", &req).await {
+                Ok(resp) => {
+                    if let Some(err) = resp.get("error").and_then(|e| e.as_str()) {
+                        status.set(Some(format!("Error: {}", err)));
+                    } else {
+                        status.set(None);
+                        // Mark the server as configured in the discovery list
+                        if let Some(resp) = discovered.write().as_mut() {
+                            for server in resp.discovered.iter_mut() {
+                                if server.host == req.host && server.json_port == req.port {
+                                    server.configured = true;
+                                }
+                            }
+                        }
+                        servers.restart();
+                        on_change.call(());
+                    }
+                }
+                Err(e) => status.set(Some(format!("Error: {}", e))),
+            }
+        });
+    };
+
+    let remove = move |id: String| {
+        spawn(async move {
+            match crate::app::api::delete_request(&format!("/lms/servers/{}", id)).await {
+                Ok(()) => {
+                    servers.restart();
+                    on_change.call(());
+                }
+                Err(e) => status.set(Some(format!("Error: {}", e))),
+            }
+        });
+    };
+
+    let extra: Vec<_> = servers
+        .read()
+        .clone()
+        .flatten()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|s| s.id.is_some())
+        .collect();
+
+    rsx! {
+        section { id: "lms-servers", class: "mb-8",
+            div { class: "mb-4",
+                h2 { class: "text-xl font-semibold", "Additional Servers" }
+                p { class: "text-muted text-sm",
+                    "Players on other servers appear alongside the main server's, with zone ids namespaced by server"
+                }
+            }
+            div { class: "card p-6",
+                if extra.is_empty() {
+                    p { class: "text-muted mb-4", "No additional servers." }
+                } else {
+                    ul { class: "mb-4",
+                        for server in extra {
+                            {
+                                let id = server.id.clone().unwrap_or_default();
+                                let remove_id = id.clone();
+                                rsx! {
+                                    li { key: "{id}", class: "flex items-center gap-2 py-2",
+                                        span { class: "font-medium", "{id}" }
+                                        span { class: "text-muted text-sm",
+                                            "{server.host.as_deref().unwrap_or(\"\")}:{server.port}"
+                                        }
+                                        if server.connected {
+                                            span { class: "status-ok text-sm", "✓ {server.player_count} player(s)" }
+                                        } else {
+                                            span { class: "status-err text-sm", "✗ Not connected" }
+                                        }
+                                        button {
+                                            class: "btn btn-ghost ml-auto",
+                                            onclick: move |_| remove(remove_id.clone()),
+                                            "Remove"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div { class: "flex items-center gap-4",
+                    button {
+                        class: "btn btn-outline",
+                        disabled: discovering(),
+                        onclick: discover,
+                        if discovering() { "Searching..." } else { "Find Servers" }
+                    }
+                    if let Some(ref s) = status() {
+                        span { class: "status-err", "{s}" }
+                    }
+                }
+
+                if let Some(resp) = discovered() {
+                    if resp.discovered.is_empty() {
+                        p { class: "text-muted mt-4", "No servers found on the network." }
+                    } else {
+                        ul { class: "mt-4",
+                            for server in resp.discovered {
+                                {
+                                    let host = server.host.clone();
+                                    let port = server.json_port;
+                                    rsx! {
+                                        li { key: "{server.host}:{server.json_port}", class: "flex items-center gap-2 py-2",
+                                            span { class: "font-medium", "{server.name}" }
+                                            span { class: "text-muted text-sm", "{server.host}:{server.json_port}" }
+                                            if server.configured {
+                                                span { class: "text-muted text-sm ml-auto", "Added" }
+                                            } else {
+                                                button {
+                                                    class: "btn btn-primary ml-auto",
+                                                    onclick: move |_| add((host.clone(), port)),
+                                                    "Add"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Player card component
 #[component]
 fn PlayerCard(player: LmsPlayer, on_control: EventHandler<(String, String)>) -> Element {
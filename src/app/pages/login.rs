@@ -0,0 +1,93 @@
+//! Login page component.
+//!
+//! Shown when API authentication is on and the browser has no session. A valid
+//! token is kept in an HttpOnly cookie, then the page the user came from is loaded.
+
+use dioxus::prelude::*;
+
+use crate::app::api::{AuthResponse, LoginRequest};
+use crate::app::components::Layout;
+
+/// Page to return to after logging in (`?next=`), limited to this site
+#[cfg(target_arch = "wasm32")]
+fn next_page() -> String {
+    let search = web_sys::window()
+        .and_then(|w| w.location().search().ok())
+        .unwrap_or_default();
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("next="))
+        .and_then(|v| urlencoding::decode(v).ok())
+        .map(|v| v.into_owned())
+        .filter(|v| v.starts_with('/') && !v.starts_with("//"))
+        .unwrap_or_else(|| "/".to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn next_page() -> String {
+    "/".to_string()
+}
+
+/// Login page component.
+#[component]
+pub fn Login() -> Element {
+    let mut token = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut busy = use_signal(|| false);
+
+    let login = move |evt: Event<FormData>| {
+        evt.prevent_default();
+        let req = LoginRequest { token: token() };
+        busy.set(true);
+        spawn(async move {
+            match crate::app::api::post_json::<_, AuthResponse>("/auth/login", &req).await {
+                Ok(resp) if resp.ok => crate::app::api::load_page(&next_page()),
+                Ok(resp) => error.set(Some(
+                    resp.error.unwrap_or_else(|| "Login failed".to_string()),
+                )),
+                Err(e) => error.set(Some(format!("Login failed: {e}"))),
+            }
+            busy.set(false);
+        });
+    };
+
+    rsx! {
+        Layout {
+            title: "Log in".to_string(),
+            nav_active: "".to_string(),
+
+            h1 { class: "text-2xl font-bold mb-6", "Log in" }
+
+            div { class: "card p-6 max-w-md",
+                form { class: "space-y-4", onsubmit: login,
+                    div {
+                        label { class: "block text-sm font-medium mb-1", "Access token" }
+                        input {
+                            class: "input w-full",
+                            r#type: "password",
+                            autocomplete: "current-password",
+                            placeholder: "uhc_...",
+                            value: "{token}",
+                            oninput: move |evt| token.set(evt.value()),
+                        }
+                    }
+                    div { class: "flex items-center gap-4",
+                        button {
+                            class: "btn btn-primary",
+                            r#type: "submit",
+                            disabled: busy() || token().trim().is_empty(),
+                            "Log in"
+                        }
+                        if let Some(e) = error() {
+                            span { class: "status-err text-sm", "{e}" }
+                        }
+                    }
+                }
+                p { class: "mt-4 text-sm text-muted",
+                    "Tokens are created in Settings → Access by an admin."
+                }
+            }
+        }
+    }
+}
@@ -2,14 +2,20 @@
 //!
 //! These pages use Dioxus signals and server functions instead of inline JavaScript.
 
+mod diagnostics;
 mod hqplayer;
 mod knobs;
 mod lms;
+mod login;
+mod schedules;
 mod settings;
 mod zones;
 
+pub use diagnostics::Diagnostics;
 pub use hqplayer::HqPlayer;
 pub use knobs::Knobs;
 pub use lms::Lms;
+pub use login::Login;
+pub use schedules::Schedules;
 pub use settings::Settings;
 pub use zones::Zones;
@@ -0,0 +1,503 @@
+//! Schedules page component.
+//!
+//! Create, toggle, run and delete scheduled zone actions (alarms, bedtime pause, ...).
+
+use crate::app::api::{
+    AlarmRamp, Schedule, ScheduleAction, ScheduleStatus, ScheduleWhen, SchedulesResponse, Zone,
+    ZonesResponse,
+};
+use crate::app::components::{ErrorAlert, Layout};
+use dioxus::prelude::*;
+
+const WEEKDAYS: [(&str, &str); 7] = [
+    ("mon", "Mon"),
+    ("tue", "Tue"),
+    ("wed", "Wed"),
+    ("thu", "Thu"),
+    ("fri", "Fri"),
+    ("sat", "Sat"),
+    ("sun", "Sun"),
+];
+
+/// Schedule id request body
+#[derive(Clone, serde::Serialize)]
+struct ScheduleIdRequest {
+    id: String,
+}
+
+/// "2026-10-19T06:30:00+02:00" -> "2026-10-19 06:30"
+fn short_time(time: &str) -> String {
+    time.get(..16).unwrap_or(time).replace('T', " ")
+}
+
+fn describe_when(when: &ScheduleWhen) -> String {
+    match when {
+        ScheduleWhen::Daily { time, days } if days.is_empty() => format!("Daily at {time}"),
+        ScheduleWhen::Daily { time, days } => format!("{} at {time}", days.join(", ")),
+        ScheduleWhen::Cron { expr } => format!("Cron: {expr}"),
+    }
+}
+
+fn describe_action(schedule: &Schedule) -> String {
+    let action = match &schedule.action {
+        ScheduleAction::Play { query, .. } => format!("Play \"{query}\""),
+        ScheduleAction::Volume { value } => format!("Set volume to {value}"),
+        ScheduleAction::HqpProfile { profile } => format!("Load HQPlayer profile {profile}"),
+        ScheduleAction::Pause => "Pause".to_string(),
+        ScheduleAction::Stop => "Stop".to_string(),
+    };
+    match &schedule.alarm {
+        Some(alarm) => format!(
+            "{action}, ramping {}% → {}% over {} min",
+            alarm.start_percent, alarm.target_percent, alarm.ramp_minutes
+        ),
+        None => action,
+    }
+}
+
+/// Schedules page component.
+#[component]
+pub fn Schedules() -> Element {
+    let mut schedules = use_resource(|| async {
+        crate::app::api::fetch_json::<SchedulesResponse>("/schedules")
+            .await
+            .ok()
+    });
+    let zones = use_resource(|| async {
+        crate::app::api::fetch_json::<ZonesResponse>("/zones")
+            .await
+            .ok()
+            .map(|r| r.zones)
+            .unwrap_or_default()
+    });
+
+    let mut error = use_signal(|| None::<String>);
+    let mut notice = use_signal(|| None::<String>);
+
+    let zone_list: Vec<Zone> = zones.read().clone().unwrap_or_default();
+    let names = zone_list.clone();
+    let zone_name = move |zone_id: &str| {
+        names
+            .iter()
+            .find(|z| z.zone_id == zone_id)
+            .map(|z| z.zone_name.clone())
+            .unwrap_or_else(|| zone_id.to_string())
+    };
+
+    let save = move |schedule: Schedule| {
+        error.set(None);
+        spawn(async move {
+            match crate::app::api::post_json::<Schedule, serde_json::Value>("/schedules", &schedule)
+                .await
+            {
+                Ok(resp) => {
+                    if let Some(e) = resp.get("error").and_then(|e| e.as_str()) {
+                        error.set(Some(e.to_string()));
+                    } else {
+                        schedules.restart();
+                    }
+                }
+                Err(e) => error.set(Some(format!("Saving schedule failed: {e}"))),
+            }
+        });
+    };
+
+    let delete = move |id: String| {
+        spawn(async move {
+            let _ = crate::app::api::post_json_no_response(
+                "/schedules/delete",
+                &ScheduleIdRequest { id },
+            )
+            .await;
+            schedules.restart();
+        });
+    };
+
+    let run_now = move |id: String| {
+        notice.set(None);
+        spawn(async move {
+            match crate::app::api::post_json::<ScheduleIdRequest, serde_json::Value>(
+                "/schedules/run",
+                &ScheduleIdRequest { id },
+            )
+            .await
+            {
+                Ok(resp) => {
+                    let message = resp
+                        .get("run")
+                        .and_then(|r| r.get("message"))
+                        .or_else(|| resp.get("error"))
+                        .and_then(|m| m.as_str())
+                        .unwrap_or_default()
+                        .to_string();
+                    if resp.get("ok").and_then(|ok| ok.as_bool()) == Some(true) {
+                        notice.set(Some(message));
+                    } else {
+                        error.set(Some(message));
+                    }
+                }
+                Err(e) => error.set(Some(format!("Running schedule failed: {e}"))),
+            }
+            schedules.restart();
+        });
+    };
+
+    let list: Vec<ScheduleStatus> = schedules
+        .read()
+        .clone()
+        .flatten()
+        .map(|r| r.schedules)
+        .unwrap_or_default();
+
+    rsx! {
+        Layout {
+            title: "Schedules".to_string(),
+            nav_active: "schedules".to_string(),
+
+            h1 { class: "text-2xl font-bold mb-6", "Schedules" }
+
+            if let Some(err) = error() {
+                ErrorAlert {
+                    message: err,
+                    on_dismiss: move |_| error.set(None),
+                }
+            }
+            if let Some(msg) = notice() {
+                p { class: "text-sm text-muted mb-4", "{msg}" }
+            }
+
+            section { id: "schedules", class: "mb-8",
+                div { class: "mb-4",
+                    h2 { class: "text-xl font-semibold", "Scheduled actions" }
+                    p { class: "text-muted text-sm", "Times are in the server's local time zone" }
+                }
+                div { class: "card p-6",
+                    if list.is_empty() {
+                        p { class: "text-muted", "No schedules yet." }
+                    }
+                    for status in list.iter() {
+                        {
+                            let schedule = status.schedule.clone();
+                            let id = schedule.id.clone();
+                            let run_id = id.clone();
+                            let target = if schedule.zone_id.is_empty() {
+                                String::new()
+                            } else {
+                                format!(" on {}", zone_name(&schedule.zone_id))
+                            };
+                            let when = describe_when(&schedule.when);
+                            let action = describe_action(&schedule);
+                            let next = status
+                                .next_run
+                                .as_deref()
+                                .map(short_time)
+                                .unwrap_or_else(|| "—".to_string());
+                            let last = status.last_run.as_ref().map(|run| {
+                                let outcome = if run.ok { "ok" } else { "failed" };
+                                format!("Last run {} ({outcome}): {}", short_time(&run.at), run.message)
+                            });
+                            let toggled = Schedule {
+                                enabled: !schedule.enabled,
+                                ..schedule.clone()
+                            };
+                            let toggle_label = if schedule.enabled { "Disable" } else { "Enable" };
+                            rsx! {
+                                div { key: "{schedule.id}", class: "flex items-center justify-between gap-4 mb-4",
+                                    div { class: "min-w-0",
+                                        p { class: "font-semibold",
+                                            "{schedule.name}"
+                                            if !schedule.enabled {
+                                                span { class: "text-sm text-muted ml-2", "(disabled)" }
+                                            }
+                                        }
+                                        p { class: "text-sm", "{when}: {action}{target}" }
+                                        p { class: "text-sm text-muted", "Next run: {next}" }
+                                        if let Some(last) = last {
+                                            p { class: "text-sm text-muted truncate", "{last}" }
+                                        }
+                                    }
+                                    div { class: "flex gap-2",
+                                        button {
+                                            class: "btn btn-outline btn-sm",
+                                            onclick: move |_| run_now(run_id.clone()),
+                                            "Run now"
+                                        }
+                                        button {
+                                            class: "btn btn-outline btn-sm",
+                                            onclick: move |_| save(toggled.clone()),
+                                            "{toggle_label}"
+                                        }
+                                        button {
+                                            class: "btn btn-outline btn-sm",
+                                            onclick: move |_| delete(id.clone()),
+                                            "Delete"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            section { id: "schedule-new", class: "mb-8",
+                div { class: "mb-4",
+                    h2 { class: "text-xl font-semibold", "New schedule" }
+                }
+                div { class: "card p-6",
+                    ScheduleForm { zones: zone_list, on_save: save }
+                }
+            }
+        }
+    }
+}
+
+/// Form for creating a schedule
+#[component]
+fn ScheduleForm(zones: Vec<Zone>, on_save: EventHandler<Schedule>) -> Element {
+    let mut name = use_signal(String::new);
+    let mut zone_id = use_signal(String::new);
+    let mut when_kind = use_signal(|| "daily".to_string());
+    let mut time = use_signal(|| "07:00".to_string());
+    let mut days = use_signal(Vec::<String>::new);
+    let mut cron = use_signal(String::new);
+    let mut action_kind = use_signal(|| "play".to_string());
+    let mut query = use_signal(String::new);
+    let mut source = use_signal(|| "library".to_string());
+    let mut volume = use_signal(String::new);
+    let mut profile = use_signal(String::new);
+    let mut alarm = use_signal(|| false);
+    let mut start_percent = use_signal(|| "5".to_string());
+    let mut target_percent = use_signal(|| "30".to_string());
+    let mut ramp_minutes = use_signal(|| "10".to_string());
+
+    let submit = move |_| {
+        let when = if when_kind() == "cron" {
+            ScheduleWhen::Cron { expr: cron() }
+        } else {
+            ScheduleWhen::Daily {
+                time: time(),
+                days: days(),
+            }
+        };
+        let action = match action_kind().as_str() {
+            "volume" => ScheduleAction::Volume {
+                value: volume().trim().parse().unwrap_or_default(),
+            },
+            "hqp_profile" => ScheduleAction::HqpProfile { profile: profile() },
+            "pause" => ScheduleAction::Pause,
+            "stop" => ScheduleAction::Stop,
+            _ => ScheduleAction::Play {
+                query: query(),
+                source: Some(source()),
+                action: None,
+            },
+        };
+        let ramp = (alarm() && action_kind() == "play").then(|| AlarmRamp {
+            start_percent: start_percent().trim().parse().unwrap_or(5.0),
+            target_percent: target_percent().trim().parse().unwrap_or(30.0),
+            ramp_minutes: ramp_minutes().trim().parse().unwrap_or(10),
+        });
+        on_save.call(Schedule {
+            id: String::new(),
+            name: name(),
+            enabled: true,
+            zone_id: zone_id(),
+            when,
+            action,
+            alarm: ramp,
+        });
+        name.set(String::new());
+    };
+
+    rsx! {
+        div { class: "flex flex-col gap-3",
+            input {
+                class: "input",
+                r#type: "text",
+                placeholder: "Schedule name",
+                value: "{name}",
+                oninput: move |evt| name.set(evt.value()),
+            }
+
+            label { class: "flex flex-col gap-1 text-sm",
+                span { class: "text-muted", "Zone" }
+                select {
+                    class: "input",
+                    onchange: move |evt| zone_id.set(evt.value()),
+                    option { value: "", selected: zone_id().is_empty(), "Select a zone" }
+                    for zone in zones.iter() {
+                        option {
+                            value: "{zone.zone_id}",
+                            selected: zone.zone_id == zone_id(),
+                            "{zone.zone_name}"
+                        }
+                    }
+                }
+            }
+
+            div { class: "grid gap-3 grid-cols-1 md:grid-cols-2",
+                label { class: "flex flex-col gap-1 text-sm",
+                    span { class: "text-muted", "Repeat" }
+                    select {
+                        class: "input",
+                        onchange: move |evt| when_kind.set(evt.value()),
+                        option { value: "daily", selected: when_kind() == "daily", "Daily / weekdays" }
+                        option { value: "cron", selected: when_kind() == "cron", "Cron expression" }
+                    }
+                }
+                if when_kind() == "cron" {
+                    label { class: "flex flex-col gap-1 text-sm",
+                        span { class: "text-muted", "Cron (minute hour day month weekday)" }
+                        input {
+                            class: "input",
+                            r#type: "text",
+                            placeholder: "30 6 * * 1-5",
+                            value: "{cron}",
+                            oninput: move |evt| cron.set(evt.value()),
+                        }
+                    }
+                } else {
+                    label { class: "flex flex-col gap-1 text-sm",
+                        span { class: "text-muted", "Time" }
+                        input {
+                            class: "input",
+                            r#type: "time",
+                            value: "{time}",
+                            oninput: move |evt| time.set(evt.value()),
+                        }
+                    }
+                }
+            }
+            if when_kind() == "daily" {
+                div { class: "flex flex-wrap gap-3",
+                    for (day, day_label) in WEEKDAYS {
+                        label { key: "{day}", class: "flex items-center gap-1 text-sm",
+                            input {
+                                r#type: "checkbox",
+                                checked: days().iter().any(|d| d == day),
+                                onchange: move |evt| {
+                                    days.with_mut(|d| {
+                                        d.retain(|x| x != day);
+                                        if evt.checked() {
+                                            d.push(day.to_string());
+                                        }
+                                    });
+                                },
+                            }
+                            "{day_label}"
+                        }
+                    }
+                    span { class: "text-sm text-muted", "(none = every day)" }
+                }
+            }
+
+            label { class: "flex flex-col gap-1 text-sm",
+                span { class: "text-muted", "Action" }
+                select {
+                    class: "input",
+                    onchange: move |evt| action_kind.set(evt.value()),
+                    option { value: "play", selected: action_kind() == "play", "Play search result" }
+                    option { value: "volume", selected: action_kind() == "volume", "Set volume" }
+                    option { value: "hqp_profile", selected: action_kind() == "hqp_profile", "Load HQPlayer profile" }
+                    option { value: "pause", selected: action_kind() == "pause", "Pause" }
+                    option { value: "stop", selected: action_kind() == "stop", "Stop" }
+                }
+            }
+
+            match action_kind().as_str() {
+                "play" => rsx! {
+                    div { class: "grid gap-3 grid-cols-1 md:grid-cols-2",
+                        input {
+                            class: "input",
+                            r#type: "text",
+                            placeholder: "Search (album, artist, playlist...)",
+                            value: "{query}",
+                            oninput: move |evt| query.set(evt.value()),
+                        }
+                        select {
+                            class: "input",
+                            onchange: move |evt| source.set(evt.value()),
+                            option { value: "library", selected: source() == "library", "Library" }
+                            option { value: "tidal", selected: source() == "tidal", "TIDAL (Roon)" }
+                            option { value: "qobuz", selected: source() == "qobuz", "Qobuz (Roon)" }
+                        }
+                    }
+                    label { class: "flex items-center gap-2 text-sm",
+                        input {
+                            r#type: "checkbox",
+                            checked: alarm(),
+                            onchange: move |evt| alarm.set(evt.checked()),
+                        }
+                        "Alarm: start quietly and ramp up the volume"
+                    }
+                    if alarm() {
+                        div { class: "grid gap-3 grid-cols-1 md:grid-cols-3",
+                            label { class: "flex flex-col gap-1 text-sm",
+                                span { class: "text-muted", "Start volume (%)" }
+                                input {
+                                    class: "input",
+                                    r#type: "number",
+                                    min: "0",
+                                    max: "100",
+                                    value: "{start_percent}",
+                                    oninput: move |evt| start_percent.set(evt.value()),
+                                }
+                            }
+                            label { class: "flex flex-col gap-1 text-sm",
+                                span { class: "text-muted", "Target volume (%)" }
+                                input {
+                                    class: "input",
+                                    r#type: "number",
+                                    min: "0",
+                                    max: "100",
+                                    value: "{target_percent}",
+                                    oninput: move |evt| target_percent.set(evt.value()),
+                                }
+                            }
+                            label { class: "flex flex-col gap-1 text-sm",
+                                span { class: "text-muted", "Ramp (minutes)" }
+                                input {
+                                    class: "input",
+                                    r#type: "number",
+                                    min: "1",
+                                    max: "120",
+                                    value: "{ramp_minutes}",
+                                    oninput: move |evt| ramp_minutes.set(evt.value()),
+                                }
+                            }
+                        }
+                    }
+                },
+                "volume" => rsx! {
+                    input {
+                        class: "input",
+                        r#type: "number",
+                        placeholder: "Volume (in the zone's scale, e.g. -30 dB or 40)",
+                        value: "{volume}",
+                        oninput: move |evt| volume.set(evt.value()),
+                    }
+                },
+                "hqp_profile" => rsx! {
+                    input {
+                        class: "input",
+                        r#type: "text",
+                        placeholder: "Profile name",
+                        value: "{profile}",
+                        oninput: move |evt| profile.set(evt.value()),
+                    }
+                },
+                _ => rsx! {},
+            }
+
+            div {
+                button {
+                    class: "btn btn-primary",
+                    disabled: name().trim().is_empty(),
+                    onclick: submit,
+                    "Save schedule"
+                }
+            }
+        }
+    }
+}
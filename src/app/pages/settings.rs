@@ -4,11 +4,17 @@
 
 use dioxus::prelude::*;
 
-use crate::app::api::{AdapterSettings, AppSettings, HqpStatus, LmsConfig, RoonStatus};
+use crate::app::api::{
+    AdapterSettings, ApiTokensResponse, AppSettings, AuditResponse, AuthResponse,
+    AuthSettingsRequest, AuthStatus, CreateTokenRequest, CreateTokenResponse, FadeSettings,
+    HqpStatus, LmsConfig, RoonStatus, ScrobblerConfigRequest, ScrobblerStatus, VolumePolicies,
+    VolumePolicy, ZoneFade, ZonesResponse,
+};
 use crate::app::components::Layout;
 use crate::app::settings_context::use_settings;
 use crate::app::sse::use_sse;
 use crate::app::theme::{use_theme, Theme};
+use crate::app::Route;
 
 /// OpenHome status response
 #[derive(Clone, Debug, Default, serde::Deserialize, PartialEq)]
@@ -145,7 +151,10 @@ pub fn Settings() -> Element {
             section { class: "mb-8",
                 div { class: "mb-4",
                     h2 { class: "text-xl font-semibold", "Features" }
-                    p { class: "text-muted text-sm", "Zone sources and page visibility" }
+                    p { class: "text-muted text-sm",
+                        "Zone sources and page visibility · "
+                        Link { class: "link", to: Route::Diagnostics {}, "Adapter diagnostics" }
+                    }
                 }
 
                 div { class: "card p-6",
@@ -343,6 +352,77 @@ pub fn Settings() -> Element {
                 }
             }
 
+            // Volume safety section
+            section { class: "mb-8",
+                div { class: "mb-4",
+                    h2 { class: "text-xl font-semibold", "Volume Safety" }
+                    p { class: "text-muted text-sm",
+                        "Limits applied to every volume change, in each zone's own scale (dB or %). Leave blank for no limit."
+                    }
+                }
+                VolumeSafetySettings {}
+            }
+
+            // Fades section
+            section { class: "mb-8",
+                div { class: "mb-4",
+                    h2 { class: "text-xl font-semibold", "Fades" }
+                    p { class: "text-muted text-sm",
+                        "Fade out before pause, stop and track skips, and fade in on resume"
+                    }
+                }
+                FadeSettingsCard {}
+            }
+
+            // Scrobbling section
+            section { class: "mb-8",
+                div { class: "mb-4",
+                    h2 { class: "text-xl font-semibold", "Scrobbling" }
+                    p { class: "text-muted text-sm",
+                        "Submit plays from every zone to ListenBrainz or a compatible server"
+                    }
+                }
+                ScrobblerSettings {}
+            }
+
+            // Access section
+            section { class: "mb-8",
+                div { class: "mb-4",
+                    h2 { class: "text-xl font-semibold", "Access" }
+                    p { class: "text-muted text-sm",
+                        "Require tokens for the API, the web UI, knobs and MCP"
+                    }
+                }
+                AccessSettings {}
+            }
+
+            // Audit log section
+            section { class: "mb-8",
+                div { class: "mb-4",
+                    h2 { class: "text-xl font-semibold", "Audit Log" }
+                    p { class: "text-muted text-sm",
+                        "Recent commands sent to zones and which knob, client, schedule or rule sent them"
+                    }
+                }
+                AuditLogCard {}
+            }
+
+            // Backup section
+            section { class: "mb-8",
+                div { class: "mb-4",
+                    h2 { class: "text-xl font-semibold", "Backup" }
+                    p { class: "text-muted text-sm",
+                        "Settings, adapter connections, knobs, rooms, scenes, schedules and rules in one file"
+                    }
+                }
+                div { class: "card p-6",
+                    a { class: "btn-outline", href: "/api/backup", download: "", "Download backup" }
+                    p { class: "mt-4 text-sm text-muted",
+                        "The file includes server passwords and the Roon pairing. Restore it with POST /api/restore (see docs/backup.md)."
+                    }
+                }
+            }
+
             // Theme Settings section
             section { class: "mb-8",
                 div { class: "mb-4",
@@ -374,3 +454,790 @@ pub fn Settings() -> Element {
         }
     }
 }
+
+/// ListenBrainz scrobbler settings card
+#[component]
+fn ScrobblerSettings() -> Element {
+    let mut status = use_resource(|| async {
+        crate::app::api::fetch_json::<ScrobblerStatus>("/scrobbler/status")
+            .await
+            .ok()
+    });
+
+    let mut enabled = use_signal(|| false);
+    let mut url = use_signal(String::new);
+    let mut token = use_signal(String::new);
+    let mut message = use_signal(|| None::<String>);
+
+    use_effect(move || {
+        if let Some(Some(s)) = status.read().as_ref() {
+            enabled.set(s.enabled);
+            url.set(s.url.clone());
+        }
+    });
+
+    let save = move |_| {
+        let req = ScrobblerConfigRequest {
+            enabled: enabled(),
+            url: Some(url()),
+            // Blank keeps the saved token
+            token: Some(token()).filter(|t| !t.trim().is_empty()),
+        };
+        spawn(async move {
+            match crate::app::api::post_json::<_, serde_json::Value>("/scrobbler/config", &req)
+                .await
+            {
+                Ok(resp) => {
+                    if let Some(e) = resp.get("error").and_then(|e| e.as_str()) {
+                        message.set(Some(e.to_string()));
+                    } else {
+                        token.set(String::new());
+                        message.set(Some("Saved".to_string()));
+                        status.restart();
+                    }
+                }
+                Err(e) => message.set(Some(format!("Saving failed: {e}"))),
+            }
+        });
+    };
+
+    let st = status.read().clone().flatten().unwrap_or_default();
+    let token_placeholder = if st.has_token {
+        "Token saved - leave blank to keep"
+    } else {
+        "ListenBrainz user token"
+    };
+
+    rsx! {
+        div { class: "card p-6 space-y-4",
+            label { class: "flex items-center gap-2",
+                input {
+                    r#type: "checkbox",
+                    class: "checkbox",
+                    checked: enabled(),
+                    onchange: move |_| enabled.toggle(),
+                }
+                span { "Enable scrobbling" }
+            }
+            div {
+                label { class: "block text-sm font-medium mb-1", "Server URL" }
+                input {
+                    class: "input w-full",
+                    r#type: "text",
+                    value: "{url}",
+                    oninput: move |evt| url.set(evt.value()),
+                }
+            }
+            div {
+                label { class: "block text-sm font-medium mb-1", "User token" }
+                input {
+                    class: "input w-full",
+                    r#type: "password",
+                    placeholder: "{token_placeholder}",
+                    value: "{token}",
+                    oninput: move |evt| token.set(evt.value()),
+                }
+            }
+            div { class: "flex items-center gap-4",
+                button { class: "btn btn-primary", onclick: save, "Save" }
+                if let Some(msg) = message() {
+                    span { class: "text-sm text-muted", "{msg}" }
+                }
+            }
+            p { class: "text-sm text-muted",
+                "Submitted: {st.submitted} · Queued: {st.queued}"
+            }
+            if let Some(err) = st.last_error.clone() {
+                p { class: "text-sm text-error", "Last error: {err}" }
+            }
+        }
+    }
+}
+
+/// Volume safety policy card (default plus per-zone overrides)
+#[component]
+fn VolumeSafetySettings() -> Element {
+    let loaded = use_resource(|| async {
+        crate::app::api::fetch_json::<VolumePolicies>("/volume/policy")
+            .await
+            .ok()
+    });
+    let zones = use_resource(|| async {
+        crate::app::api::fetch_json::<ZonesResponse>("/zones")
+            .await
+            .ok()
+            .map(|r| r.zones)
+            .unwrap_or_default()
+    });
+
+    let mut policies = use_signal(VolumePolicies::default);
+    let mut new_zone = use_signal(String::new);
+    let mut message = use_signal(|| None::<String>);
+
+    use_effect(move || {
+        if let Some(Some(p)) = loaded.read().as_ref() {
+            policies.set(p.clone());
+        }
+    });
+
+    let save = move |_| {
+        let req = policies();
+        spawn(async move {
+            match crate::app::api::post_json::<_, serde_json::Value>("/volume/policy", &req).await {
+                Ok(resp) => {
+                    if let Some(e) = resp.get("error").and_then(|e| e.as_str()) {
+                        message.set(Some(e.to_string()));
+                    } else {
+                        message.set(Some("Saved".to_string()));
+                    }
+                }
+                Err(e) => message.set(Some(format!("Saving failed: {e}"))),
+            }
+        });
+    };
+
+    let all_zones = zones.read().clone().unwrap_or_default();
+    let zone_name = move |zone_id: &str| {
+        zones
+            .read()
+            .as_ref()
+            .and_then(|zs| zs.iter().find(|z| z.zone_id == zone_id))
+            .map(|z| z.zone_name.clone())
+            .unwrap_or_else(|| zone_id.to_string())
+    };
+    let current = policies();
+    let available: Vec<_> = all_zones
+        .iter()
+        .filter(|z| !current.zones.contains_key(&z.zone_id))
+        .cloned()
+        .collect();
+
+    rsx! {
+        div { class: "card p-6 space-y-4",
+            div { class: "overflow-x-auto",
+                table { class: "w-full text-sm",
+                    thead {
+                        tr { class: "border-b border-default text-left",
+                            th { class: "py-2 px-3", "Zone" }
+                            th { class: "py-2 px-3", "Max volume" }
+                            th { class: "py-2 px-3", "Max step up" }
+                            th { class: "py-2 px-3", "Start cap" }
+                            th { class: "py-2 px-3" }
+                        }
+                    }
+                    tbody {
+                        tr { class: "border-b border-default",
+                            td { class: "py-2 px-3 font-medium", "All zones (default)" }
+                            PolicyInputs {
+                                policy: current.default.clone(),
+                                on_change: move |p| policies.write().default = p,
+                            }
+                            td { class: "py-2 px-3" }
+                        }
+                        for (zone_id, policy) in current.zones.clone() {
+                            tr { key: "{zone_id}", class: "border-b border-default",
+                                td { class: "py-2 px-3", "{zone_name(&zone_id)}" }
+                                PolicyInputs {
+                                    policy: policy.clone(),
+                                    on_change: {
+                                        let zone_id = zone_id.clone();
+                                        move |p| {
+                                            policies.write().zones.insert(zone_id.clone(), p);
+                                        }
+                                    },
+                                }
+                                td { class: "py-2 px-3",
+                                    button {
+                                        class: "btn btn-outline btn-sm",
+                                        onclick: {
+                                            let zone_id = zone_id.clone();
+                                            move |_| {
+                                                policies.write().zones.remove(&zone_id);
+                                            }
+                                        },
+                                        "Remove"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if !available.is_empty() {
+                div { class: "flex items-center gap-2",
+                    select {
+                        class: "input",
+                        value: "{new_zone}",
+                        onchange: move |evt| new_zone.set(evt.value()),
+                        option { value: "", "Add a zone override..." }
+                        for zone in available {
+                            option { key: "{zone.zone_id}", value: "{zone.zone_id}", "{zone.zone_name}" }
+                        }
+                    }
+                    button {
+                        class: "btn btn-outline",
+                        disabled: new_zone().is_empty(),
+                        onclick: move |_| {
+                            let zone_id = new_zone();
+                            let default = policies().default;
+                            policies.write().zones.insert(zone_id, default);
+                            new_zone.set(String::new());
+                        },
+                        "Add"
+                    }
+                }
+            }
+            div { class: "flex items-center gap-4",
+                button { class: "btn btn-primary", onclick: save, "Save" }
+                if let Some(msg) = message() {
+                    span { class: "text-sm text-muted", "{msg}" }
+                }
+            }
+        }
+    }
+}
+
+/// Max volume / max step / start cap inputs for one policy row
+#[component]
+fn PolicyInputs(policy: VolumePolicy, on_change: EventHandler<VolumePolicy>) -> Element {
+    let field = |value: Option<f32>| value.map(|v| v.to_string()).unwrap_or_default();
+    let parse = |text: String| text.trim().parse::<f32>().ok();
+
+    rsx! {
+        td { class: "py-2 px-3",
+            input {
+                class: "input w-24",
+                r#type: "number",
+                step: "any",
+                aria_label: "Max volume",
+                value: "{field(policy.max_volume)}",
+                onchange: {
+                    let policy = policy.clone();
+                    move |evt: FormEvent| {
+                        on_change.call(VolumePolicy {
+                            max_volume: parse(evt.value()),
+                            ..policy.clone()
+                        })
+                    }
+                },
+            }
+        }
+        td { class: "py-2 px-3",
+            input {
+                class: "input w-24",
+                r#type: "number",
+                step: "any",
+                min: "0",
+                aria_label: "Max step up",
+                value: "{field(policy.max_step)}",
+                onchange: {
+                    let policy = policy.clone();
+                    move |evt: FormEvent| {
+                        on_change.call(VolumePolicy {
+                            max_step: parse(evt.value()),
+                            ..policy.clone()
+                        })
+                    }
+                },
+            }
+        }
+        td { class: "py-2 px-3",
+            input {
+                class: "input w-24",
+                r#type: "number",
+                step: "any",
+                aria_label: "Start cap",
+                value: "{field(policy.start_cap)}",
+                onchange: {
+                    let policy = policy.clone();
+                    move |evt: FormEvent| {
+                        on_change.call(VolumePolicy {
+                            start_cap: parse(evt.value()),
+                            ..policy.clone()
+                        })
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Control surfaces fades can be enabled for (server `bus::ControlSurface`)
+const FADE_SURFACES: &[(&str, &str)] = &[
+    ("web", "Web UI"),
+    ("knob", "Knobs"),
+    ("mcp", "MCP"),
+    ("api", "HTTP API"),
+    ("scheduler", "Schedules"),
+    ("rules", "Rules"),
+];
+
+/// Volume fade settings card
+#[component]
+fn FadeSettingsCard() -> Element {
+    let loaded = use_resource(|| async {
+        crate::app::api::fetch_json::<FadeSettings>("/fades")
+            .await
+            .ok()
+    });
+    let zones = use_resource(|| async {
+        crate::app::api::fetch_json::<ZonesResponse>("/zones")
+            .await
+            .ok()
+            .map(|r| r.zones)
+            .unwrap_or_default()
+    });
+
+    let mut settings = use_signal(FadeSettings::default);
+    let mut new_zone = use_signal(String::new);
+    let mut message = use_signal(|| None::<String>);
+
+    use_effect(move || {
+        if let Some(Some(s)) = loaded.read().as_ref() {
+            settings.set(s.clone());
+        }
+    });
+
+    let save = move |_| {
+        let req = settings();
+        spawn(async move {
+            match crate::app::api::post_json::<_, serde_json::Value>("/fades", &req).await {
+                Ok(resp) => {
+                    if let Some(e) = resp.get("error").and_then(|e| e.as_str()) {
+                        message.set(Some(e.to_string()));
+                    } else {
+                        message.set(Some("Saved".to_string()));
+                    }
+                }
+                Err(e) => message.set(Some(format!("Saving failed: {e}"))),
+            }
+        });
+    };
+
+    let current = settings();
+    let all_zones = zones.read().clone().unwrap_or_default();
+    let zone_name = |zone_id: &str| {
+        all_zones
+            .iter()
+            .find(|z| z.zone_id == zone_id)
+            .map(|z| z.zone_name.clone())
+            .unwrap_or_else(|| zone_id.to_string())
+    };
+    let overrides: Vec<(String, String, ZoneFade)> = current
+        .zones
+        .iter()
+        .map(|(id, fade)| (id.clone(), zone_name(id), fade.clone()))
+        .collect();
+    let available: Vec<_> = all_zones
+        .iter()
+        .filter(|z| !current.zones.contains_key(&z.zone_id))
+        .cloned()
+        .collect();
+
+    rsx! {
+        div { class: "card p-6 space-y-4",
+            label { class: "flex items-center gap-2",
+                input {
+                    r#type: "checkbox",
+                    class: "checkbox",
+                    checked: current.enabled,
+                    onchange: move |_| {
+                        let enabled = !settings().enabled;
+                        settings.write().enabled = enabled;
+                    },
+                }
+                span { "Fade all zones (unless overridden below)" }
+            }
+            label { class: "flex items-center gap-2",
+                input {
+                    r#type: "checkbox",
+                    class: "checkbox",
+                    checked: current.fade_in,
+                    onchange: move |_| {
+                        let fade_in = !settings().fade_in;
+                        settings.write().fade_in = fade_in;
+                    },
+                }
+                span { "Fade in on resume" }
+            }
+            div {
+                label { class: "block text-sm font-medium mb-1", "Duration (ms)" }
+                input {
+                    class: "input w-32",
+                    r#type: "number",
+                    min: "100",
+                    max: "5000",
+                    step: "100",
+                    value: "{current.duration_ms}",
+                    onchange: move |evt| {
+                        if let Ok(ms) = evt.value().trim().parse::<u64>() {
+                            settings.write().duration_ms = ms;
+                        }
+                    },
+                }
+            }
+            div {
+                p { class: "text-sm font-medium mb-1", "Fade commands from" }
+                div { class: "flex flex-wrap gap-4",
+                    for (key, name) in FADE_SURFACES.iter().copied() {
+                        label { key: "{key}", class: "flex items-center gap-2",
+                            input {
+                                r#type: "checkbox",
+                                class: "checkbox",
+                                checked: current.surfaces.contains(key),
+                                onchange: move |_| {
+                                    let mut s = settings.write();
+                                    if !s.surfaces.remove(key) {
+                                        s.surfaces.insert(key.to_string());
+                                    }
+                                },
+                            }
+                            span { "{name}" }
+                        }
+                    }
+                }
+            }
+            if !overrides.is_empty() {
+                div { class: "space-y-2",
+                    p { class: "text-sm font-medium", "Zone overrides" }
+                    for (zone_id, name, fade) in overrides {
+                        div { key: "{zone_id}", class: "flex items-center gap-4",
+                            label { class: "flex items-center gap-2 flex-1",
+                                input {
+                                    r#type: "checkbox",
+                                    class: "checkbox",
+                                    checked: fade.enabled,
+                                    onchange: {
+                                        let zone_id = zone_id.clone();
+                                        move |_| {
+                                            if let Some(z) = settings.write().zones.get_mut(&zone_id) {
+                                                z.enabled = !z.enabled;
+                                            }
+                                        }
+                                    },
+                                }
+                                span { "{name}" }
+                            }
+                            button {
+                                class: "btn btn-outline btn-sm",
+                                onclick: {
+                                    let zone_id = zone_id.clone();
+                                    move |_| {
+                                        settings.write().zones.remove(&zone_id);
+                                    }
+                                },
+                                "Remove"
+                            }
+                        }
+                    }
+                }
+            }
+            if !available.is_empty() {
+                div { class: "flex items-center gap-2",
+                    select {
+                        class: "input",
+                        value: "{new_zone}",
+                        onchange: move |evt| new_zone.set(evt.value()),
+                        option { value: "", "Add a zone override..." }
+                        for zone in available {
+                            option { key: "{zone.zone_id}", value: "{zone.zone_id}", "{zone.zone_name}" }
+                        }
+                    }
+                    button {
+                        class: "btn btn-outline",
+                        disabled: new_zone().is_empty(),
+                        onclick: move |_| {
+                            let zone_id = new_zone();
+                            let enabled = !settings().enabled;
+                            settings.write().zones.insert(
+                                zone_id,
+                                ZoneFade {
+                                    enabled,
+                                    duration_ms: None,
+                                },
+                            );
+                            new_zone.set(String::new());
+                        },
+                        "Add"
+                    }
+                }
+            }
+            div { class: "flex items-center gap-4",
+                button { class: "btn btn-primary", onclick: save, "Save" }
+                if let Some(msg) = message() {
+                    span { class: "text-sm text-muted", "{msg}" }
+                }
+            }
+        }
+    }
+}
+
+/// Most recent audited commands, newest first
+#[component]
+fn AuditLogCard() -> Element {
+    let mut entries = use_resource(|| async {
+        crate::app::api::fetch_json::<AuditResponse>("/audit?limit=50")
+            .await
+            .ok()
+            .map(|r| r.entries)
+    });
+
+    let entry_list = entries.read().clone().flatten().unwrap_or_default();
+
+    rsx! {
+        div { class: "card p-6 space-y-4",
+            if entry_list.is_empty() {
+                p { class: "text-muted", "No commands recorded yet." }
+            } else {
+                div { class: "overflow-x-auto",
+                    table { class: "w-full text-sm",
+                        thead {
+                            tr { class: "border-b border-default text-left",
+                                th { class: "py-2 px-3", "Time" }
+                                th { class: "py-2 px-3", "Zone" }
+                                th { class: "py-2 px-3", "Command" }
+                                th { class: "py-2 px-3", "From" }
+                                th { class: "py-2 px-3", "Result" }
+                            }
+                        }
+                        tbody {
+                            for (i, entry) in entry_list.into_iter().enumerate() {
+                                {
+                                    let time = entry.at.get(..19).unwrap_or(&entry.at).replace('T', " ");
+                                    let command = match &entry.command.params {
+                                        Some(params) => format!("{} {}", entry.command.action, params),
+                                        None => entry.command.action.clone(),
+                                    };
+                                    let origin = entry.origin.as_ref().map(|o| o.label()).unwrap_or_default();
+                                    let result = match (entry.success, entry.error, entry.reason) {
+                                        (false, Some(error), _) => error,
+                                        (false, None, _) => "failed".to_string(),
+                                        (true, _, Some(reason)) => reason,
+                                        (true, _, None) => "ok".to_string(),
+                                    };
+                                    rsx! {
+                                        tr { key: "{i}", class: "border-b border-default",
+                                            td { class: "py-2 px-3 text-muted whitespace-nowrap", "{time}" }
+                                            td { class: "py-2 px-3", "{entry.zone_id}" }
+                                            td { class: "py-2 px-3", "{command}" }
+                                            td { class: "py-2 px-3 text-muted", "{origin}" }
+                                            td {
+                                                class: if entry.success { "py-2 px-3" } else { "py-2 px-3 status-err" },
+                                                "{result}"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            button { class: "btn btn-outline", onclick: move |_| entries.restart(), "Refresh" }
+        }
+    }
+}
+
+/// API authentication card: on/off, knob enrollment and tokens
+#[component]
+fn AccessSettings() -> Element {
+    let mut status = use_resource(|| async {
+        crate::app::api::fetch_json::<AuthStatus>("/auth/status")
+            .await
+            .ok()
+    });
+    let mut tokens = use_resource(|| async {
+        crate::app::api::fetch_json::<ApiTokensResponse>("/auth/tokens")
+            .await
+            .ok()
+            .map(|r| r.tokens)
+    });
+
+    let mut name = use_signal(String::new);
+    let mut scope = use_signal(|| "control".to_string());
+    // Name, scope and secret of the token just created (shown once)
+    let mut created = use_signal(|| None::<(String, String, String)>);
+    let mut message = use_signal(|| None::<String>);
+
+    let st = status.read().clone().flatten().unwrap_or_default();
+    let enabled = st.enabled;
+    let knob_enrollment = st.knob_enrollment;
+
+    let configure = move |enabled: bool, knob_enrollment: bool, token: Option<String>| {
+        let req = AuthSettingsRequest {
+            enabled,
+            knob_enrollment,
+            token,
+        };
+        spawn(async move {
+            match crate::app::api::post_json::<_, AuthResponse>("/auth/settings", &req).await {
+                Ok(resp) if resp.ok => {
+                    message.set(None);
+                    created.set(None);
+                    status.restart();
+                    tokens.restart();
+                }
+                Ok(resp) => message.set(resp.error),
+                Err(e) => message.set(Some(format!("Saving failed: {e}"))),
+            }
+        });
+    };
+
+    let create = move |_| {
+        let req = CreateTokenRequest {
+            name: name(),
+            scope: scope(),
+        };
+        spawn(async move {
+            match crate::app::api::post_json::<_, CreateTokenResponse>("/auth/tokens", &req).await {
+                Ok(CreateTokenResponse {
+                    secret: Some(secret),
+                    ..
+                }) => {
+                    created.set(Some((req.name, req.scope, secret)));
+                    name.set(String::new());
+                    message.set(None);
+                    tokens.restart();
+                }
+                Ok(resp) => message.set(resp.error),
+                Err(e) => message.set(Some(format!("Creating token failed: {e}"))),
+            }
+        });
+    };
+
+    let revoke = move |id: String| {
+        spawn(async move {
+            match crate::app::api::delete_request(&format!("/auth/tokens/{}", id)).await {
+                Ok(()) => tokens.restart(),
+                Err(e) => message.set(Some(format!("Revoking failed: {e}"))),
+            }
+        });
+    };
+
+    let logout = move |_| {
+        spawn(async move {
+            let _ = crate::app::api::post_json_no_response("/auth/logout", &()).await;
+            crate::app::api::load_page("/login");
+        });
+    };
+
+    let token_list = tokens.read().clone().flatten().unwrap_or_default();
+
+    rsx! {
+        div { class: "card p-6 space-y-4",
+            if enabled {
+                p {
+                    span { class: "status-ok", "✓ Tokens required" }
+                    if let Some(ref s) = st.scope {
+                        span { class: "text-muted text-sm", " · logged in with a {s} token" }
+                    }
+                }
+            } else {
+                p { class: "text-muted",
+                    "Off: anyone on the network can control the bridge. Create an admin token to turn it on."
+                }
+            }
+
+            label { class: "flex items-center gap-2",
+                input {
+                    r#type: "checkbox",
+                    class: "checkbox",
+                    checked: knob_enrollment,
+                    onchange: move |_| configure(enabled, !knob_enrollment, None),
+                }
+                span { "Pair knobs for 10 minutes (knobs this bridge has already seen)" }
+            }
+            if let Some(ref until) = st.knob_enrollment_until {
+                p { class: "text-muted text-sm",
+                    "Pairing open until {until.get(11..19).unwrap_or_default()} UTC"
+                }
+            }
+
+            if !token_list.is_empty() {
+                table { class: "w-full",
+                    tbody {
+                        for token in token_list {
+                            {
+                                let revoke_id = token.id.clone();
+                                let created_on = token.created_at.get(..10).unwrap_or_default().to_string();
+                                rsx! {
+                                    tr { key: "{token.id}", class: "border-b border-default",
+                                        td { class: "py-2 px-3", "{token.name}" }
+                                        td { class: "py-2 px-3 text-muted text-sm", "{token.scope}" }
+                                        td { class: "py-2 px-3 text-muted text-sm", "{created_on}" }
+                                        td { class: "py-2 px-3 text-right",
+                                            button {
+                                                class: "btn btn-ghost",
+                                                onclick: move |_| revoke(revoke_id.clone()),
+                                                "Revoke"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "flex flex-wrap items-end gap-4",
+                div {
+                    label { class: "block text-sm font-medium mb-1", "New token" }
+                    input {
+                        class: "input",
+                        r#type: "text",
+                        placeholder: "Name, e.g. Home Assistant",
+                        value: "{name}",
+                        oninput: move |evt| name.set(evt.value()),
+                    }
+                }
+                select {
+                    class: "input",
+                    "aria-label": "Token scope",
+                    value: "{scope}",
+                    onchange: move |evt| scope.set(evt.value()),
+                    option { value: "read", selected: scope() == "read", "Read only" }
+                    option { value: "control", selected: scope() == "control", "Control" }
+                    option { value: "admin", selected: scope() == "admin", "Admin" }
+                }
+                button {
+                    class: "btn btn-outline",
+                    disabled: name().trim().is_empty(),
+                    onclick: create,
+                    "Create"
+                }
+            }
+
+            if let Some((token_name, token_scope, secret)) = created() {
+                div { class: "space-y-2",
+                    p { class: "text-sm",
+                        "Token for {token_name} - copy it now, it won't be shown again:"
+                    }
+                    code { class: "block p-2 break-all", "{secret}" }
+                    if !enabled && token_scope == "admin" {
+                        button {
+                            class: "btn btn-primary",
+                            onclick: move |_| configure(true, knob_enrollment, Some(secret.clone())),
+                            "Require tokens (log in with this one)"
+                        }
+                    }
+                }
+            }
+
+            if enabled {
+                div { class: "flex items-center gap-4",
+                    button {
+                        class: "btn btn-outline",
+                        onclick: move |_| configure(false, knob_enrollment, None),
+                        "Turn off"
+                    }
+                    button { class: "btn btn-ghost", onclick: logout, "Log out" }
+                }
+            }
+
+            if let Some(msg) = message() {
+                p { class: "text-sm status-err", "{msg}" }
+            }
+        }
+    }
+}
@@ -2,7 +2,10 @@
 //!
 //! Shows all available zones using Dioxus resources.
 
-use crate::app::api::{HqpMatrixProfilesResponse, HqpProfile, NowPlaying, Zone, ZonesResponse};
+use crate::app::api::{
+    HqpMatrixProfilesResponse, HqpProfile, NowPlaying, Room, RoomsResponse, ScenesResponse, Zone,
+    ZonesResponse,
+};
 use crate::app::components::{ErrorAlert, HqpControlsCompact, Layout, VolumeControlsCompact};
 use crate::app::sse::{use_sse, SseEvent};
 use dioxus::prelude::*;
@@ -101,7 +104,10 @@ pub fn Zones() -> Element {
             match evt {
                 // Zone-scoped events: fetch only the specific zone that changed
                 // ZoneUpdated includes state changes (play/pause) that affect is_playing
-                SseEvent::NowPlayingChanged { .. } | SseEvent::ZoneUpdated { .. } => {
+                SseEvent::NowPlayingChanged { .. }
+                | SseEvent::TrackMetadataChanged { .. }
+                | SseEvent::ZoneUpdated { .. }
+                | SseEvent::SleepTimerChanged { .. } => {
                     if let Some(zone_id) = evt.zone_id() {
                         let zone_id = zone_id.to_string();
                         spawn(async move {
@@ -255,9 +261,10 @@ pub fn Zones() -> Element {
         for zones in groups.values_mut() {
             zones.sort_by(|a, b| a.zone_name.cmp(&b.zone_name));
         }
-        // Sort groups in a sensible order: Roon, LMS, OpenHome, UPnP, then others
+        // Sort groups in a sensible order: Rooms, Roon, LMS, OpenHome, UPnP, then others
         let priority = |s: &str| -> i32 {
             match s.to_lowercase().as_str() {
+                "room" => -1,
                 "roon" => 0,
                 "lms" => 1,
                 "openhome" => 2,
@@ -320,6 +327,453 @@ pub fn Zones() -> Element {
             section { id: "zones",
                 {content}
             }
+
+            // Rooms: merge duplicate zones of one physical system
+            section { id: "rooms", class: "mt-8",
+                h2 { class: "text-lg font-semibold mb-4", "Rooms" }
+                div { class: "card p-6",
+                    RoomsEditor { on_change: move |_| zones.restart() }
+                }
+            }
+
+            // Scenes: multi-zone snapshots recalled in one go
+            section { id: "scenes", class: "mt-8",
+                h2 { class: "text-lg font-semibold mb-4", "Scenes" }
+                div { class: "card p-6",
+                    ScenesEditor { zones: zones_list_signal() }
+                }
+            }
+        }
+    }
+}
+
+/// Rooms editor - binds zones from different adapters into one logical zone
+#[component]
+fn RoomsEditor(on_change: EventHandler<()>) -> Element {
+    let mut rooms = use_resource(|| async {
+        crate::app::api::fetch_json::<RoomsResponse>("/rooms")
+            .await
+            .ok()
+    });
+
+    let mut name = use_signal(String::new);
+    let mut members = use_signal(Vec::<String>::new);
+    let mut transport_zone = use_signal(String::new);
+    let mut volume_zone = use_signal(String::new);
+    let mut metadata_zone = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let data = rooms.read().clone().flatten().unwrap_or_default();
+    let candidates = data.zones.clone();
+    let zone_name = move |zone_id: &str| {
+        candidates
+            .iter()
+            .find(|z| z.zone_id == zone_id)
+            .map(|z| format!("{} ({})", z.zone_name, z.source))
+            .unwrap_or_else(|| zone_id.to_string())
+    };
+
+    // Zones already in a room can't join another one
+    let taken: Vec<String> = data.rooms.iter().flat_map(|r| r.zone_ids.clone()).collect();
+    let available: Vec<_> = data
+        .zones
+        .iter()
+        .filter(|z| !taken.contains(&z.zone_id))
+        .cloned()
+        .collect();
+    let selected = members();
+    let selected_options: Vec<(String, String)> = selected
+        .iter()
+        .map(|id| (id.clone(), zone_name(id)))
+        .collect();
+
+    let save = move |_| {
+        let optional = |v: String| if v.is_empty() { None } else { Some(v) };
+        let room = Room {
+            id: String::new(),
+            name: name(),
+            zone_ids: members(),
+            transport_zone: optional(transport_zone()),
+            volume_zone: optional(volume_zone()),
+            metadata_zone: optional(metadata_zone()),
+        };
+        error.set(None);
+        spawn(async move {
+            match crate::app::api::post_json::<Room, serde_json::Value>("/rooms", &room).await {
+                Ok(resp) => {
+                    if let Some(e) = resp.get("error").and_then(|e| e.as_str()) {
+                        error.set(Some(e.to_string()));
+                    } else {
+                        name.set(String::new());
+                        members.set(Vec::new());
+                        transport_zone.set(String::new());
+                        volume_zone.set(String::new());
+                        metadata_zone.set(String::new());
+                        rooms.restart();
+                        on_change.call(());
+                    }
+                }
+                Err(e) => error.set(Some(format!("Saving room failed: {e}"))),
+            }
+        });
+    };
+
+    let delete = move |id: String| {
+        spawn(async move {
+            #[derive(serde::Serialize)]
+            struct DeleteRequest {
+                id: String,
+            }
+            let _ = crate::app::api::post_json_no_response("/rooms/delete", &DeleteRequest { id })
+                .await;
+            rooms.restart();
+            on_change.call(());
+        });
+    };
+
+    rsx! {
+        p { class: "text-sm text-muted mb-4",
+            "Combine zones that are the same physical device (e.g. a streamer seen by Roon, OpenHome and UPnP) into one room."
+        }
+
+        if let Some(err) = error() {
+            ErrorAlert {
+                message: err,
+                on_dismiss: move |_| error.set(None),
+            }
+        }
+
+        // Existing rooms
+        for room in data.rooms.iter() {
+            {
+                let id = room.id.clone();
+                let member_names = room
+                    .zone_ids
+                    .iter()
+                    .map(|z| zone_name(z))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                rsx! {
+                    div { key: "{room.id}", class: "flex items-center justify-between gap-4 mb-3",
+                        div { class: "min-w-0",
+                            p { class: "font-semibold", "{room.name}" }
+                            p { class: "text-sm text-muted truncate", "{member_names}" }
+                        }
+                        button {
+                            class: "btn btn-outline btn-sm",
+                            onclick: move |_| delete(id.clone()),
+                            "Delete"
+                        }
+                    }
+                }
+            }
+        }
+
+        // New room form
+        div { class: "flex flex-col gap-3 mt-4",
+            input {
+                class: "input",
+                r#type: "text",
+                placeholder: "Room name",
+                value: "{name}",
+                oninput: move |evt| name.set(evt.value()),
+            }
+            if available.is_empty() {
+                p { class: "text-sm text-muted", "No zones available to add." }
+            }
+            for zone in available.iter() {
+                {
+                    let zone_id = zone.zone_id.clone();
+                    let checked = selected.contains(&zone_id);
+                    rsx! {
+                        label { key: "{zone.zone_id}", class: "flex items-center gap-2 text-sm",
+                            input {
+                                r#type: "checkbox",
+                                checked: checked,
+                                onchange: move |evt| {
+                                    let zone_id = zone_id.clone();
+                                    members.with_mut(|m| {
+                                        if evt.checked() {
+                                            if !m.contains(&zone_id) {
+                                                m.push(zone_id);
+                                            }
+                                        } else {
+                                            m.retain(|z| *z != zone_id);
+                                        }
+                                    });
+                                },
+                            }
+                            "{zone.zone_name} ({zone.source})"
+                        }
+                    }
+                }
+            }
+            if selected.len() > 1 {
+                div { class: "grid gap-3 grid-cols-1 md:grid-cols-3",
+                    RoomAuthoritySelect { label: "Transport", options: selected_options.clone(), value: transport_zone }
+                    RoomAuthoritySelect { label: "Volume", options: selected_options.clone(), value: volume_zone }
+                    RoomAuthoritySelect { label: "Now playing", options: selected_options.clone(), value: metadata_zone }
+                }
+            }
+            div {
+                button {
+                    class: "btn btn-primary",
+                    disabled: name().trim().is_empty() || selected.is_empty(),
+                    onclick: save,
+                    "Save room"
+                }
+            }
+        }
+    }
+}
+
+/// Scenes editor - capture the current state of some zones, recall or delete scenes
+#[component]
+fn ScenesEditor(zones: Vec<Zone>) -> Element {
+    let mut scenes = use_resource(|| async {
+        crate::app::api::fetch_json::<ScenesResponse>("/scenes")
+            .await
+            .ok()
+    });
+
+    let mut name = use_signal(String::new);
+    let mut members = use_signal(Vec::<String>::new);
+    let mut include_playing = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+    let mut status = use_signal(|| None::<String>);
+
+    let data = scenes.read().clone().flatten().unwrap_or_default();
+    let selected = members();
+    let zone_names = zones.clone();
+    let zone_name = move |zone_id: &str| {
+        zone_names
+            .iter()
+            .find(|z| z.zone_id == zone_id)
+            .map(|z| z.zone_name.clone())
+            .unwrap_or_else(|| zone_id.to_string())
+    };
+
+    #[derive(serde::Serialize)]
+    struct CaptureRequest {
+        name: String,
+        zone_ids: Vec<String>,
+        include_playing: bool,
+    }
+
+    #[derive(serde::Serialize)]
+    struct SceneIdRequest {
+        id: String,
+    }
+
+    let capture = move |_| {
+        let request = CaptureRequest {
+            name: name(),
+            zone_ids: members(),
+            include_playing: include_playing(),
+        };
+        error.set(None);
+        status.set(None);
+        spawn(async move {
+            match crate::app::api::post_json::<CaptureRequest, serde_json::Value>(
+                "/scenes/capture",
+                &request,
+            )
+            .await
+            {
+                Ok(resp) => {
+                    if let Some(e) = resp.get("error").and_then(|e| e.as_str()) {
+                        error.set(Some(e.to_string()));
+                    } else {
+                        name.set(String::new());
+                        members.set(Vec::new());
+                        include_playing.set(false);
+                        scenes.restart();
+                    }
+                }
+                Err(e) => error.set(Some(format!("Capturing scene failed: {e}"))),
+            }
+        });
+    };
+
+    let recall = move |id: String| {
+        error.set(None);
+        status.set(None);
+        spawn(async move {
+            match crate::app::api::post_json::<SceneIdRequest, serde_json::Value>(
+                "/scenes/recall",
+                &SceneIdRequest { id: id.clone() },
+            )
+            .await
+            {
+                Ok(resp) => {
+                    if let Some(e) = resp.get("error").and_then(|e| e.as_str()) {
+                        error.set(Some(e.to_string()));
+                    } else if resp.get("ok").and_then(|v| v.as_bool()) == Some(true) {
+                        status.set(Some(format!("Recalled {id}")));
+                    } else {
+                        let failed = resp["recall"]["steps"]
+                            .as_array()
+                            .map(|steps| {
+                                steps
+                                    .iter()
+                                    .filter(|s| s["ok"] == false)
+                                    .filter_map(|s| {
+                                        Some(format!(
+                                            "{} {}: {}",
+                                            s["zone_id"].as_str()?,
+                                            s["step"].as_str()?,
+                                            s["error"].as_str().unwrap_or("failed")
+                                        ))
+                                    })
+                                    .collect::<Vec<_>>()
+                                    .join("; ")
+                            })
+                            .unwrap_or_default();
+                        error.set(Some(format!("Scene recalled with errors: {failed}")));
+                    }
+                }
+                Err(e) => error.set(Some(format!("Recalling scene failed: {e}"))),
+            }
+        });
+    };
+
+    let delete = move |id: String| {
+        spawn(async move {
+            let _ =
+                crate::app::api::post_json_no_response("/scenes/delete", &SceneIdRequest { id })
+                    .await;
+            scenes.restart();
+        });
+    };
+
+    rsx! {
+        p { class: "text-sm text-muted mb-4",
+            "Save the volume, mute and HQPlayer settings of several zones, and bring them all back with one click."
+        }
+
+        if let Some(err) = error() {
+            ErrorAlert {
+                message: err,
+                on_dismiss: move |_| error.set(None),
+            }
+        }
+        if let Some(msg) = status() {
+            p { class: "text-sm text-muted mb-3", "{msg}" }
+        }
+
+        // Saved scenes
+        for scene in data.scenes.iter() {
+            {
+                let recall_id = scene.id.clone();
+                let delete_id = scene.id.clone();
+                let member_names = scene
+                    .zones
+                    .iter()
+                    .map(|z| zone_name(&z.zone_id))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                rsx! {
+                    div { key: "{scene.id}", class: "flex items-center justify-between gap-4 mb-3",
+                        div { class: "min-w-0",
+                            p { class: "font-semibold", "{scene.name}" }
+                            p { class: "text-sm text-muted truncate", "{member_names}" }
+                        }
+                        div { class: "flex gap-2",
+                            button {
+                                class: "btn btn-primary btn-sm",
+                                onclick: move |_| recall(recall_id.clone()),
+                                "Recall"
+                            }
+                            button {
+                                class: "btn btn-outline btn-sm",
+                                onclick: move |_| delete(delete_id.clone()),
+                                "Delete"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Capture form
+        div { class: "flex flex-col gap-3 mt-4",
+            input {
+                class: "input",
+                r#type: "text",
+                placeholder: "Scene name",
+                value: "{name}",
+                oninput: move |evt| name.set(evt.value()),
+            }
+            for zone in zones.iter() {
+                {
+                    let zone_id = zone.zone_id.clone();
+                    let checked = selected.contains(&zone_id);
+                    rsx! {
+                        label { key: "{zone.zone_id}", class: "flex items-center gap-2 text-sm",
+                            input {
+                                r#type: "checkbox",
+                                checked: checked,
+                                onchange: move |evt| {
+                                    let zone_id = zone_id.clone();
+                                    members.with_mut(|m| {
+                                        if evt.checked() {
+                                            if !m.contains(&zone_id) {
+                                                m.push(zone_id);
+                                            }
+                                        } else {
+                                            m.retain(|z| *z != zone_id);
+                                        }
+                                    });
+                                },
+                            }
+                            "{zone.zone_name}"
+                        }
+                    }
+                }
+            }
+            label { class: "flex items-center gap-2 text-sm",
+                input {
+                    r#type: "checkbox",
+                    checked: include_playing(),
+                    onchange: move |evt| include_playing.set(evt.checked()),
+                }
+                "Also play what is playing now when recalled"
+            }
+            div {
+                button {
+                    class: "btn btn-primary",
+                    disabled: name().trim().is_empty() || selected.is_empty(),
+                    onclick: capture,
+                    "Capture scene"
+                }
+            }
+        }
+    }
+}
+
+/// Picks which member zone is authoritative for one aspect of a room ("" = automatic)
+#[component]
+fn RoomAuthoritySelect(
+    label: &'static str,
+    options: Vec<(String, String)>,
+    value: Signal<String>,
+) -> Element {
+    let mut value = value;
+    rsx! {
+        label { class: "flex flex-col gap-1 text-sm",
+            span { class: "text-muted", "{label}" }
+            select {
+                class: "input",
+                onchange: move |evt| value.set(evt.value()),
+                option { value: "", selected: value().is_empty(), "Automatic" }
+                for (zone_id, zone_name) in options.iter() {
+                    option {
+                        value: "{zone_id}",
+                        selected: *zone_id == value(),
+                        "{zone_name}"
+                    }
+                }
+            }
         }
     }
 }
@@ -341,10 +795,17 @@ fn ZoneCard(
     let zone_id_next = zone_id.clone();
     let zone_id_vol_down = zone_id.clone();
     let zone_id_vol_up = zone_id.clone();
+    let zone_id_sleep = zone_id.clone();
 
     let np = now_playing.as_ref();
     let is_playing = np.map(|n| n.is_playing).unwrap_or(false);
 
+    // Hide controls the zone can't honour (older servers send no capabilities)
+    let caps = zone.capabilities.as_ref();
+    let can_transport = caps.map(|c| c.transport).unwrap_or(true);
+    let can_skip = caps.map(|c| c.skip).unwrap_or(true);
+    let can_volume = caps.map(|c| c.volume).unwrap_or(true);
+
     let has_hqp = zone
         .dsp
         .as_ref()
@@ -384,6 +845,17 @@ fn ZoneCard(
             }
         })
         .unwrap_or_default();
+    let format = np.and_then(|n| n.format.clone());
+
+    // Sleep timer countdown (as of the last fetch)
+    let sleep_label = np.and_then(|n| n.sleep_timer.as_ref()).map(|t| {
+        match (t.mode.as_str(), t.remaining_secs) {
+            ("end_of_album", _) => "Sleep at end of album".to_string(),
+            ("end_of_track", None) => "Sleep at end of track".to_string(),
+            (_, Some(secs)) => format!("Sleep in {} min", secs.div_ceil(60)),
+            (_, None) => "Sleep timer".to_string(),
+        }
+    });
 
     // HQP matrix info
     let has_matrix = hqp_matrix
@@ -421,6 +893,24 @@ fn ZoneCard(
                         if has_hqp {
                             span { class: "badge badge-primary", "HQP" }
                         }
+                        if let Some(format) = format {
+                            span { class: "badge badge-secondary", "{format}" }
+                        }
+                        if let Some(label) = sleep_label {
+                            button {
+                                class: "badge badge-secondary",
+                                title: "Cancel sleep timer",
+                                onclick: move |_| on_control.call((zone_id_sleep.clone(), "sleep_cancel".to_string())),
+                                "{label} ✕"
+                            }
+                        }
+                        if zone.is_stale {
+                            span {
+                                class: "badge badge-secondary",
+                                title: "Last known state - waiting for the adapter to reconnect",
+                                "Cached"
+                            }
+                        }
                     }
 
                     // Now playing info
@@ -446,43 +936,51 @@ fn ZoneCard(
 
             // Transport controls
             div { class: "flex flex-wrap items-center gap-2 mt-4",
-                button {
-                    class: "btn btn-ghost",
-                    "aria-label": "Previous track",
-                    onclick: move |_| on_control.call((zone_id_prev.clone(), "previous".to_string())),
-                    svg { class: "w-5 h-5", fill: "currentColor", view_box: "0 0 24 24",
-                        path { d: "M6 6h2v12H6zm3.5 6l8.5 6V6z" }
-                    }
-                }
-                button {
-                    class: "btn btn-primary",
-                    "aria-label": if is_playing { "Pause" } else { "Play" },
-                    onclick: move |_| on_control.call((zone_id_play.clone(), "play_pause".to_string())),
-                    if is_playing {
+                if can_skip {
+                    button {
+                        class: "btn btn-ghost",
+                        "aria-label": "Previous track",
+                        onclick: move |_| on_control.call((zone_id_prev.clone(), "previous".to_string())),
                         svg { class: "w-5 h-5", fill: "currentColor", view_box: "0 0 24 24",
-                            path { d: "M6 19h4V5H6v14zm8-14v14h4V5h-4z" }
+                            path { d: "M6 6h2v12H6zm3.5 6l8.5 6V6z" }
                         }
-                    } else {
-                        svg { class: "w-5 h-5", fill: "currentColor", view_box: "0 0 24 24",
-                            path { d: "M8 5v14l11-7z" }
+                    }
+                }
+                if can_transport {
+                    button {
+                        class: "btn btn-primary",
+                        "aria-label": if is_playing { "Pause" } else { "Play" },
+                        onclick: move |_| on_control.call((zone_id_play.clone(), "play_pause".to_string())),
+                        if is_playing {
+                            svg { class: "w-5 h-5", fill: "currentColor", view_box: "0 0 24 24",
+                                path { d: "M6 19h4V5H6v14zm8-14v14h4V5h-4z" }
+                            }
+                        } else {
+                            svg { class: "w-5 h-5", fill: "currentColor", view_box: "0 0 24 24",
+                                path { d: "M8 5v14l11-7z" }
+                            }
                         }
                     }
                 }
-                button {
-                    class: "btn btn-ghost",
-                    "aria-label": "Next track",
-                    onclick: move |_| on_control.call((zone_id_next.clone(), "next".to_string())),
-                    svg { class: "w-5 h-5", fill: "currentColor", view_box: "0 0 24 24",
-                        path { d: "M6 18l8.5-6L6 6v12zM16 6v12h2V6h-2z" }
+                if can_skip {
+                    button {
+                        class: "btn btn-ghost",
+                        "aria-label": "Next track",
+                        onclick: move |_| on_control.call((zone_id_next.clone(), "next".to_string())),
+                        svg { class: "w-5 h-5", fill: "currentColor", view_box: "0 0 24 24",
+                            path { d: "M6 18l8.5-6L6 6v12zM16 6v12h2V6h-2z" }
+                        }
                     }
                 }
 
-                VolumeControlsCompact {
-                    volume: volume,
-                    volume_type: volume_type,
-                    volume_step: volume_step,
-                    on_vol_down: move |_| on_control.call((zone_id_vol_down.clone(), "vol_down".to_string())),
-                    on_vol_up: move |_| on_control.call((zone_id_vol_up.clone(), "vol_up".to_string())),
+                if can_volume {
+                    VolumeControlsCompact {
+                        volume: volume,
+                        volume_type: volume_type,
+                        volume_step: volume_step,
+                        on_vol_down: move |_| on_control.call((zone_id_vol_down.clone(), "vol_down".to_string())),
+                        on_vol_up: move |_| on_control.call((zone_id_vol_up.clone(), "vol_up".to_string())),
+                    }
                 }
             }
         }
@@ -50,12 +50,17 @@ pub enum SseEvent {
     NowPlayingChanged {
         payload: ZonePayload,
     },
+    TrackMetadataChanged {
+        payload: ZonePayload,
+    },
     VolumeChanged {
         payload: VolumePayload,
     },
     SeekPositionChanged {
         payload: ZonePayload,
     },
+    // Full zone list from an adapter after a server-side resync
+    ZonesSnapshot,
 
     // HQPlayer events
     HqpConnected,
@@ -78,6 +83,21 @@ pub enum SseEvent {
     UpnpRendererFound,
     UpnpRendererLost,
 
+    // Adapter lifecycle (diagnostics)
+    AdapterStarting,
+    AdapterRetrying,
+    AdapterConnected,
+    AdapterDisconnected,
+    AdapterStopped,
+
+    // Sleep timer set, cleared or ran out
+    SleepTimerChanged {
+        payload: ZonePayload,
+    },
+
+    // Server could not replay missed events; refetch everything
+    ResyncRequired,
+
     // Catch-all for unknown events
     #[serde(other)]
     Unknown,
@@ -90,7 +110,9 @@ impl SseEvent {
             SseEvent::ZoneUpdated { payload } => Some(&payload.zone_id),
             SseEvent::ZoneRemoved { payload } => Some(&payload.zone_id),
             SseEvent::NowPlayingChanged { payload } => Some(&payload.zone_id),
+            SseEvent::TrackMetadataChanged { payload } => Some(&payload.zone_id),
             SseEvent::SeekPositionChanged { payload } => Some(&payload.zone_id),
+            SseEvent::SleepTimerChanged { payload } => Some(&payload.zone_id),
             _ => None,
         }
     }
@@ -113,9 +135,12 @@ impl SseContext {
         matches!(
             self.last_event.read().as_ref(),
             Some(
-                SseEvent::ZoneUpdated { .. }
+                SseEvent::ResyncRequired
+                    | SseEvent::ZonesSnapshot
+                    | SseEvent::ZoneUpdated { .. }
                     | SseEvent::ZoneRemoved { .. }
                     | SseEvent::NowPlayingChanged { .. }
+                    | SseEvent::TrackMetadataChanged { .. }
                     | SseEvent::SeekPositionChanged { .. }
                     | SseEvent::VolumeChanged { .. }
                     | SseEvent::RoonConnected
@@ -129,7 +154,7 @@ impl SseContext {
     pub fn should_refresh_roon(&self) -> bool {
         matches!(
             self.last_event.read().as_ref(),
-            Some(SseEvent::RoonConnected | SseEvent::RoonDisconnected)
+            Some(SseEvent::ResyncRequired | SseEvent::RoonConnected | SseEvent::RoonDisconnected)
         )
     }
 
@@ -137,7 +162,8 @@ impl SseContext {
         matches!(
             self.last_event.read().as_ref(),
             Some(
-                SseEvent::HqpConnected
+                SseEvent::ResyncRequired
+                    | SseEvent::HqpConnected
                     | SseEvent::HqpDisconnected
                     | SseEvent::HqpStateChanged
                     | SseEvent::HqpPipelineChanged
@@ -148,7 +174,9 @@ impl SseContext {
     pub fn should_refresh_lms(&self) -> bool {
         let event = self.last_event.read();
         match event.as_ref() {
-            Some(SseEvent::LmsConnected | SseEvent::LmsDisconnected) => true,
+            Some(SseEvent::ResyncRequired | SseEvent::LmsConnected | SseEvent::LmsDisconnected) => {
+                true
+            }
             // ZoneUpdated with lms: prefix indicates LMS player state change
             Some(SseEvent::ZoneUpdated { payload }) => payload.zone_id.starts_with("lms:"),
             _ => false,
@@ -159,7 +187,8 @@ impl SseContext {
         matches!(
             self.last_event.read().as_ref(),
             Some(
-                SseEvent::RoonConnected
+                SseEvent::ResyncRequired
+                    | SseEvent::RoonConnected
                     | SseEvent::RoonDisconnected
                     | SseEvent::OpenHomeDeviceFound
                     | SseEvent::OpenHomeDeviceLost
@@ -173,7 +202,9 @@ impl SseContext {
         matches!(
             self.last_event.read().as_ref(),
             Some(
-                SseEvent::ZoneUpdated { .. }
+                SseEvent::ResyncRequired
+                    | SseEvent::ZonesSnapshot
+                    | SseEvent::ZoneUpdated { .. }
                     | SseEvent::ZoneRemoved { .. }
                     | SseEvent::RoonConnected
                     | SseEvent::RoonDisconnected
@@ -0,0 +1,618 @@
+//! Command audit log - who changed what, from where
+//!
+//! Every control action is published as a `CommandReceived` / `CommandResult`
+//! pair carrying a [`CommandOrigin`]: the knob, MCP session, HTTP client or
+//! internal service that sent it. `AuditRecorder` follows the results on the bus
+//! and `AuditLog` appends them to a JSON Lines file in the data dir, rotating it
+//! once it grows past [`MAX_FILE_BYTES`].
+
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::adapters::AdapterCommand;
+use crate::bus::{BusEvent, Command, CommandOrigin, CommandResponse, ControlSurface, SharedBus};
+use crate::config::get_data_dir;
+use crate::knobs::{extract_client_ip, KnobStore};
+
+/// Audit file (JSON Lines, under the data dir); rotated files are `audit.1.jsonl`, ...
+pub const AUDIT_FILE: &str = "audit.jsonl";
+
+/// The file is rotated once it grows past this
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Rotated files kept next to the current one
+const ROTATED_FILES: usize = 3;
+
+/// Entries kept in memory for queries
+const MAX_ENTRIES: usize = 10_000;
+
+/// Default number of entries returned by a query
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Upper bound on entries returned by a query
+pub const MAX_LIMIT: usize = 1000;
+
+/// One control action and its outcome
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    pub zone_id: String,
+    pub command: Command,
+    /// None for commands published without an origin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<CommandOrigin>,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Why the command was changed (e.g. volume clamped by policy)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl AuditEntry {
+    fn from_result(
+        response: &CommandResponse,
+        request_id: Option<String>,
+        origin: Option<CommandOrigin>,
+    ) -> Self {
+        Self {
+            at: DateTime::from_timestamp_millis(response.timestamp as i64).unwrap_or_else(Utc::now),
+            zone_id: response.zone_id.clone(),
+            command: response.command.clone(),
+            origin,
+            success: response.success,
+            error: response.error.clone(),
+            reason: response.reason.clone(),
+            request_id,
+        }
+    }
+}
+
+/// Query filter for [`AuditLog::query`]
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub zone_id: Option<String>,
+    pub surface: Option<ControlSurface>,
+    /// Case-insensitive substring of the origin (knob id or name, session, client address)
+    pub origin: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    /// Maximum entries (defaults to [`DEFAULT_LIMIT`], capped at [`MAX_LIMIT`])
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        if self.zone_id.as_ref().is_some_and(|z| *z != entry.zone_id) {
+            return false;
+        }
+        if let Some(surface) = self.surface {
+            if entry.origin.as_ref().map(|o| o.surface) != Some(surface) {
+                return false;
+            }
+        }
+        if let Some(ref needle) = self.origin {
+            let needle = needle.to_lowercase();
+            let found = entry.origin.as_ref().is_some_and(|o| {
+                [&o.id, &o.name, &o.client_ip, &o.user_agent]
+                    .into_iter()
+                    .flatten()
+                    .any(|field| field.to_lowercase().contains(&needle))
+            });
+            if !found {
+                return false;
+            }
+        }
+        if self.since.is_some_and(|since| entry.at < since) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Persistent, rotating command audit log
+pub struct AuditLog {
+    entries: RwLock<VecDeque<AuditEntry>>,
+    path: Option<PathBuf>,
+    /// Size of the current file, so it can be rotated
+    file_bytes: AtomicU64,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuditLog {
+    /// Open the audit file in the data dir
+    pub fn new() -> Self {
+        Self::open(get_data_dir().join(AUDIT_FILE))
+    }
+
+    /// Open an audit file at a specific path (loads existing and rotated entries)
+    pub fn open(path: PathBuf) -> Self {
+        let entries = load_audit(&path);
+        if !entries.is_empty() {
+            debug!("Loaded {} audit entries", entries.len());
+        }
+        let file_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Self {
+            entries: RwLock::new(entries),
+            path: Some(path),
+            file_bytes: AtomicU64::new(file_bytes),
+        }
+    }
+
+    /// Audit log kept only in memory (nothing is written to disk)
+    pub fn in_memory() -> Self {
+        Self {
+            entries: RwLock::new(VecDeque::new()),
+            path: None,
+            file_bytes: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a command result
+    pub async fn record(&self, entry: AuditEntry) {
+        let mut entries = self.entries.write().await;
+        self.append(&entry);
+        entries.push_back(entry);
+        while entries.len() > MAX_ENTRIES {
+            entries.pop_front();
+        }
+    }
+
+    /// Entries matching the filter, newest first
+    pub async fn query(&self, filter: &AuditFilter) -> Vec<AuditEntry> {
+        let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        self.entries
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|e| filter.matches(e))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    fn append(&self, entry: &AuditEntry) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        if self.file_bytes.load(Ordering::Relaxed) >= MAX_FILE_BYTES {
+            rotate(path);
+            self.file_bytes.store(0, Ordering::Relaxed);
+        }
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let result = serde_json::to_string(entry)
+            .map_err(std::io::Error::other)
+            .and_then(|line| {
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| writeln!(file, "{}", line))
+                    .map(|()| line.len() as u64 + 1)
+            });
+        match result {
+            Ok(written) => {
+                self.file_bytes.fetch_add(written, Ordering::Relaxed);
+            }
+            Err(e) => warn!("Failed to append audit entry: {}", e),
+        }
+    }
+}
+
+/// `audit.jsonl` -> `audit.<n>.jsonl`
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    path.with_extension(format!("{}.jsonl", n))
+}
+
+/// Shift the rotated files up by one, dropping the oldest, and start a new file
+fn rotate(path: &Path) {
+    for n in (1..ROTATED_FILES).rev() {
+        let from = rotated_path(path, n);
+        if from.exists() {
+            let _ = std::fs::rename(&from, rotated_path(path, n + 1));
+        }
+    }
+    match std::fs::rename(path, rotated_path(path, 1)) {
+        Ok(()) => debug!("Rotated audit log {}", path.display()),
+        Err(e) => warn!("Failed to rotate audit log: {}", e),
+    }
+}
+
+/// Load the newest `MAX_ENTRIES` entries, oldest rotated file first
+fn load_audit(path: &Path) -> VecDeque<AuditEntry> {
+    let files = (1..=ROTATED_FILES)
+        .rev()
+        .map(|n| rotated_path(path, n))
+        .chain(std::iter::once(path.to_path_buf()));
+
+    let mut entries = VecDeque::new();
+    let mut invalid = 0;
+    for file in files {
+        let Ok(content) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str::<AuditEntry>(line) {
+                Ok(entry) => {
+                    entries.push_back(entry);
+                    if entries.len() > MAX_ENTRIES {
+                        entries.pop_front();
+                    }
+                }
+                Err(_) => invalid += 1,
+            }
+        }
+    }
+    if invalid > 0 {
+        warn!("Skipped {} unreadable audit lines", invalid);
+    }
+    entries
+}
+
+// =============================================================================
+// Publishing
+// =============================================================================
+
+/// A command published as received, waiting for its result
+#[must_use = "finish the audit to publish the command's result"]
+pub struct CommandAudit {
+    bus: SharedBus,
+    zone_id: String,
+    command: Command,
+    origin: CommandOrigin,
+    request_id: String,
+}
+
+impl CommandAudit {
+    /// Publish `CommandReceived` for a command about to be sent
+    pub fn begin(bus: &SharedBus, zone_id: &str, command: Command, origin: CommandOrigin) -> Self {
+        let request_id = format!("{:016x}", rand::random::<u64>());
+        bus.publish(BusEvent::CommandReceived {
+            zone_id: zone_id.to_string(),
+            command: command.clone(),
+            request_id: Some(request_id.clone()),
+            origin: Some(origin.clone()),
+        });
+        Self {
+            bus: bus.clone(),
+            zone_id: zone_id.to_string(),
+            command,
+            origin,
+            request_id,
+        }
+    }
+
+    /// Publish the matching `CommandResult`
+    pub fn finish(self, result: Result<(), String>, reason: Option<String>) {
+        let success = result.is_ok();
+        if !success || reason.is_some() {
+            debug!(
+                "{:?} on {} from {}: {}",
+                self.command,
+                self.zone_id,
+                self.origin.describe(),
+                result.as_ref().err().or(reason.as_ref()).map_or("", |s| s)
+            );
+        }
+        self.bus.publish(BusEvent::CommandResult {
+            response: CommandResponse {
+                zone_id: self.zone_id,
+                command: self.command,
+                success,
+                error: result.err(),
+                reason,
+                timestamp: Utc::now().timestamp_millis() as u64,
+            },
+            request_id: Some(self.request_id),
+            origin: Some(self.origin),
+        });
+    }
+}
+
+/// Run a transport action sent straight to an adapter (e.g. `roon.control`),
+/// publishing it like any other command. Actions that aren't transport commands
+/// run unpublished.
+pub async fn audit_action(
+    bus: &SharedBus,
+    zone_id: &str,
+    action: &str,
+    origin: CommandOrigin,
+    run: impl Future<Output = anyhow::Result<()>>,
+) -> anyhow::Result<()> {
+    let Some(command) = AdapterCommand::from_action(action) else {
+        return run.await;
+    };
+    audit_command(bus, zone_id, Command::from(&command), origin, run).await
+}
+
+/// Run a change that bypasses the zone adapters (e.g. an HQPlayer DSP setting),
+/// publishing it as `command`
+pub async fn audit_command(
+    bus: &SharedBus,
+    zone_id: &str,
+    command: Command,
+    origin: CommandOrigin,
+    run: impl Future<Output = anyhow::Result<()>>,
+) -> anyhow::Result<()> {
+    let audit = CommandAudit::begin(bus, zone_id, command, origin);
+    let result = run.await;
+    audit.finish(result.as_ref().map_err(|e| e.to_string()).copied(), None);
+    result
+}
+
+/// Surface of an HTTP request: knobs send `X-Knob-Id`, browsers send `Sec-Fetch-Site`
+pub fn request_surface(headers: &HeaderMap) -> ControlSurface {
+    if headers.contains_key("x-knob-id") {
+        ControlSurface::Knob
+    } else if headers.contains_key("sec-fetch-site") {
+        ControlSurface::Web
+    } else {
+        ControlSurface::Api
+    }
+}
+
+/// Origin of an HTTP request: client address, user agent and, for knobs, the
+/// knob id and its name from the knob store
+pub async fn request_origin(
+    knobs: &KnobStore,
+    surface: ControlSurface,
+    headers: &HeaderMap,
+    socket_addr: Option<SocketAddr>,
+) -> CommandOrigin {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let id = header("x-knob-id");
+    let name = match &id {
+        Some(knob_id) => knobs
+            .get(knob_id)
+            .await
+            .map(|k| k.name)
+            .filter(|n| !n.is_empty()),
+        None => None,
+    };
+    CommandOrigin {
+        id,
+        name,
+        client_ip: extract_client_ip(headers, socket_addr),
+        user_agent: header("user-agent"),
+        ..CommandOrigin::new(surface)
+    }
+}
+
+// =============================================================================
+// Recorder
+// =============================================================================
+
+/// Follows command results on the bus and records them in the audit log
+pub struct AuditRecorder {
+    bus: SharedBus,
+    log: Arc<AuditLog>,
+}
+
+impl AuditRecorder {
+    pub fn new(bus: SharedBus, log: Arc<AuditLog>) -> Self {
+        Self { bus, log }
+    }
+
+    /// Start the recorder's event loop
+    /// Should be spawned as a task
+    pub async fn run(&self) {
+        let mut rx = self.bus.subscribe();
+
+        info!("AuditRecorder started");
+
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("AuditRecorder lagged, skipped {} events", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            match event {
+                BusEvent::CommandResult {
+                    response,
+                    request_id,
+                    origin,
+                } => {
+                    self.log
+                        .record(AuditEntry::from_result(&response, request_id, origin))
+                        .await;
+                }
+                BusEvent::ShuttingDown { .. } => break,
+                _ => {}
+            }
+        }
+
+        info!("AuditRecorder stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::create_bus;
+
+    fn entry(zone_id: &str, origin: CommandOrigin, minute: u32) -> AuditEntry {
+        AuditEntry {
+            at: DateTime::parse_from_rfc3339(&format!("2026-01-10T20:{:02}:00Z", minute))
+                .unwrap()
+                .with_timezone(&Utc),
+            zone_id: zone_id.to_string(),
+            command: Command::VolumeRelative {
+                delta: 2.0,
+                output_id: None,
+            },
+            origin: Some(origin),
+            success: true,
+            error: None,
+            reason: None,
+            request_id: None,
+        }
+    }
+
+    fn knob(id: &str, name: &str) -> CommandOrigin {
+        CommandOrigin {
+            id: Some(id.to_string()),
+            name: Some(name.to_string()),
+            ..CommandOrigin::new(ControlSurface::Knob)
+        }
+    }
+
+    #[tokio::test]
+    async fn query_filters_and_orders_newest_first() {
+        let log = AuditLog::in_memory();
+        log.record(entry("roon:1", knob("a1b2", "Kitchen"), 1))
+            .await;
+        log.record(entry(
+            "roon:1",
+            CommandOrigin::service(ControlSurface::Scheduler, "wake-up", Some("Wake up")),
+            2,
+        ))
+        .await;
+        log.record(entry("lms:aa", knob("c3d4", "Bedroom"), 3))
+            .await;
+
+        let all = log.query(&AuditFilter::default()).await;
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].zone_id, "lms:aa");
+
+        let filter = AuditFilter {
+            zone_id: Some("roon:1".to_string()),
+            surface: Some(ControlSurface::Knob),
+            ..Default::default()
+        };
+        let found = log.query(&filter).await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].origin.as_ref().unwrap().id.as_deref(),
+            Some("a1b2")
+        );
+
+        let filter = AuditFilter {
+            origin: Some("bedroom".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(log.query(&filter).await.len(), 1);
+
+        let filter = AuditFilter {
+            since: Some(all[1].at),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let found = log.query(&filter).await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].zone_id, "lms:aa");
+    }
+
+    #[tokio::test]
+    async fn file_rotates_and_entries_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(AUDIT_FILE);
+
+        let log = AuditLog::open(path.clone());
+        log.record(entry("roon:1", knob("a1b2", "Kitchen"), 1))
+            .await;
+        // Pretend the file is full so the next entry starts a new one
+        log.file_bytes.store(MAX_FILE_BYTES, Ordering::Relaxed);
+        log.record(entry("lms:aa", knob("c3d4", "Bedroom"), 2))
+            .await;
+
+        assert!(rotated_path(&path, 1).exists());
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+
+        let reopened = AuditLog::open(path);
+        let entries = reopened.query(&AuditFilter::default()).await;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].zone_id, "lms:aa");
+    }
+
+    #[tokio::test]
+    async fn command_audit_publishes_origin_with_result() {
+        let bus = create_bus();
+        let mut rx = bus.subscribe();
+
+        let audit = CommandAudit::begin(
+            &bus,
+            "roon:1",
+            Command::Pause,
+            CommandOrigin::new(ControlSurface::Mcp),
+        );
+        audit.finish(Err("zone offline".to_string()), None);
+
+        let BusEvent::CommandReceived {
+            request_id: received_id,
+            origin,
+            ..
+        } = rx.recv().await.unwrap()
+        else {
+            panic!("expected CommandReceived");
+        };
+        assert_eq!(origin.unwrap().surface, ControlSurface::Mcp);
+
+        let BusEvent::CommandResult {
+            response,
+            request_id,
+            origin,
+        } = rx.recv().await.unwrap()
+        else {
+            panic!("expected CommandResult");
+        };
+        assert_eq!(request_id, received_id);
+        assert!(!response.success);
+        assert_eq!(response.error.as_deref(), Some("zone offline"));
+
+        let entry = AuditEntry::from_result(&response, request_id, origin);
+        assert_eq!(entry.origin.unwrap().surface, ControlSurface::Mcp);
+    }
+
+    #[test]
+    fn request_surface_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(request_surface(&headers), ControlSurface::Api);
+        headers.insert("sec-fetch-site", "same-origin".parse().unwrap());
+        assert_eq!(request_surface(&headers), ControlSurface::Web);
+        headers.insert("x-knob-id", "a1b2".parse().unwrap());
+        assert_eq!(request_surface(&headers), ControlSurface::Knob);
+    }
+
+    #[test]
+    fn origin_description() {
+        let mut origin = knob("a1b2", "Kitchen");
+        origin.client_ip = Some("192.168.1.20".to_string());
+        assert_eq!(origin.describe(), "knob Kitchen (a1b2) from 192.168.1.20");
+        assert_eq!(
+            CommandOrigin::service(ControlSurface::Rules, "night-mode", None).describe(),
+            "rules night-mode"
+        );
+        assert_eq!(
+            CommandOrigin::new(ControlSurface::SleepTimer).describe(),
+            "sleep_timer"
+        );
+    }
+}
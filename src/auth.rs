@@ -0,0 +1,756 @@
+//! API authentication with scoped tokens
+//!
+//! Off by default: until enabled in settings every request is let through, so
+//! existing knobs and scripts keep working. Once enabled, every route needs a token
+//! whose scope covers it:
+//!
+//! - `read`: GET requests, SSE, `/metrics`
+//! - `control`: transport, volume, HQPlayer DSP, scenes, sleep timers, MCP
+//! - `admin`: everything else (settings, adapter config, tokens, backup/restore)
+//!
+//! Tokens are sent as `Authorization: Bearer <token>`. The web UI logs in with a
+//! token once and gets it back as an HttpOnly cookie, which also covers the SSE
+//! stream. MCP only accepts the header.
+//!
+//! Knobs enroll while an admin has knob pairing open (for [`KNOB_PAIRING_WINDOW`]):
+//! a request from a knob that is already known to the knob store, or whose id the
+//! admin named when opening pairing, is refused with a new control token in
+//! `X-Knob-Token`; the knob repeats it with the token. Other knob ids get a plain 401.
+//!
+//! Tokens are stored in `auth.json` (config dir) as SHA-256 hashes; the token
+//! itself is only shown when it's created.
+
+use anyhow::{bail, Result};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::Json;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::api::AppState;
+use crate::config_store::ConfigFile;
+
+/// Auth settings and tokens (config dir)
+pub const AUTH_FILE: &str = "auth.json";
+
+/// Cookie the web UI session is kept in
+pub const SESSION_COOKIE: &str = "uhc_token";
+
+/// Response header carrying a token issued to a knob on first contact
+pub const KNOB_TOKEN_HEADER: &str = "x-knob-token";
+
+/// How long knob pairing stays open once an admin opens it
+pub const KNOB_PAIRING_WINDOW: Duration = Duration::minutes(10);
+
+const TOKEN_PREFIX: &str = "uhc_";
+const MAX_NAME_LEN: usize = 64;
+
+/// What a token may do; each scope includes the ones below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    Read,
+    Control,
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Control => "control",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+/// A stored token
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scope: Scope,
+    /// SHA-256 of the token, hex
+    pub hash: String,
+    pub created_at: DateTime<Utc>,
+    /// Knob the token was issued to on first contact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub knob_id: Option<String>,
+}
+
+/// A token as shown by the API (without its hash)
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenInfo {
+    pub id: String,
+    pub name: String,
+    pub scope: Scope,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub knob_id: Option<String>,
+}
+
+impl From<&ApiToken> for TokenInfo {
+    fn from(token: &ApiToken) -> Self {
+        Self {
+            id: token.id.clone(),
+            name: token.name.clone(),
+            scope: token.scope,
+            created_at: token.created_at,
+            knob_id: token.knob_id.clone(),
+        }
+    }
+}
+
+/// Contents of `auth.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuthSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+}
+
+/// Knob pairing opened by an admin (kept in memory only, so a restart closes it)
+#[derive(Debug, Clone)]
+struct KnobPairing {
+    until: DateTime<Utc>,
+    /// Knob id the admin approved, in addition to knobs the knob store knows
+    knob_id: Option<String>,
+}
+
+fn hash_token(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+fn new_token(name: String, scope: Scope, knob_id: Option<String>) -> (ApiToken, String) {
+    let secret = format!(
+        "{}{}",
+        TOKEN_PREFIX,
+        hex::encode(rand::random::<[u8; 16]>())
+    );
+    let token = ApiToken {
+        id: hex::encode(rand::random::<[u8; 4]>()),
+        name,
+        scope,
+        hash: hash_token(&secret),
+        created_at: Utc::now(),
+        knob_id,
+    };
+    (token, secret)
+}
+
+/// Persisted auth settings and tokens
+pub struct AuthStore {
+    settings: RwLock<AuthSettings>,
+    pairing: RwLock<Option<KnobPairing>>,
+    file: ConfigFile,
+}
+
+impl Default for AuthStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuthStore {
+    /// Create the store, loading saved settings from the config dir
+    pub fn new() -> Self {
+        let file = ConfigFile::new(AUTH_FILE, "auth settings");
+        Self {
+            settings: RwLock::new(Self::read(&file)),
+            pairing: RwLock::new(None),
+            file,
+        }
+    }
+
+    /// Saved settings; a damaged file locks everything out
+    fn read(file: &ConfigFile) -> AuthSettings {
+        match file.read::<AuthSettings>() {
+            Ok(Some(settings)) => {
+                info!(
+                    "Loaded auth settings ({}, {} tokens)",
+                    if settings.enabled {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    },
+                    settings.tokens.len()
+                );
+                settings
+            }
+            Ok(None) => AuthSettings::default(),
+            Err(e) => {
+                // Fail closed: a damaged file must not silently turn auth off
+                warn!("{:#}, rejecting all tokens", e);
+                AuthSettings {
+                    enabled: true,
+                    tokens: Vec::new(),
+                }
+            }
+        }
+    }
+
+    /// Store that is never written to disk
+    pub fn in_memory() -> Self {
+        Self {
+            settings: RwLock::new(AuthSettings::default()),
+            pairing: RwLock::new(None),
+            file: ConfigFile::in_memory("auth settings"),
+        }
+    }
+
+    /// Re-read settings from the config dir (after a restore)
+    pub async fn reload(&self) {
+        self.file.reload(&self.settings, Self::read).await;
+    }
+
+    pub async fn is_enabled(&self) -> bool {
+        self.settings.read().await.enabled
+    }
+
+    /// When knob pairing closes, if it's open
+    pub async fn knob_pairing_until(&self) -> Option<DateTime<Utc>> {
+        self.pairing
+            .read()
+            .await
+            .as_ref()
+            .map(|p| p.until)
+            .filter(|until| *until > Utc::now())
+    }
+
+    /// Open knob pairing for [`KNOB_PAIRING_WINDOW`], optionally approving one
+    /// knob id that the knob store doesn't know yet, or close it
+    pub async fn set_knob_pairing(&self, open: bool, knob_id: Option<&str>) {
+        let pairing = open.then(|| KnobPairing {
+            until: Utc::now() + KNOB_PAIRING_WINDOW,
+            knob_id: knob_id
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(String::from),
+        });
+        match &pairing {
+            Some(p) => info!(
+                "Knob pairing open until {}{}",
+                p.until.format("%H:%M:%S"),
+                p.knob_id
+                    .as_deref()
+                    .map(|id| format!(" (approved {})", id))
+                    .unwrap_or_default()
+            ),
+            None => debug!("Knob pairing closed"),
+        }
+        *self.pairing.write().await = pairing;
+    }
+
+    /// All tokens, oldest first
+    pub async fn tokens(&self) -> Vec<TokenInfo> {
+        self.settings
+            .read()
+            .await
+            .tokens
+            .iter()
+            .map(TokenInfo::from)
+            .collect()
+    }
+
+    /// Scope of a token, if it's valid
+    pub async fn authenticate(&self, secret: &str) -> Option<Scope> {
+        let hash = hash_token(secret);
+        self.settings
+            .read()
+            .await
+            .tokens
+            .iter()
+            .find(|t| t.hash == hash)
+            .map(|t| t.scope)
+    }
+
+    /// Create a token; returns it with its secret, which is not stored
+    pub async fn create_token(&self, name: &str, scope: Scope) -> Result<(TokenInfo, String)> {
+        let name = name.trim();
+        if name.is_empty() {
+            bail!("Token name is required");
+        }
+        if name.len() > MAX_NAME_LEN {
+            bail!("Token name is longer than {} characters", MAX_NAME_LEN);
+        }
+
+        let (token, secret) = new_token(name.to_string(), scope, None);
+        let info = TokenInfo::from(&token);
+
+        let mut settings = self.settings.write().await;
+        settings.tokens.push(token);
+        self.file.save(&*settings);
+        info!("Created {} token \"{}\"", scope.as_str(), name);
+        Ok((info, secret))
+    }
+
+    /// Delete a token. The last admin token can't be revoked while auth is on.
+    pub async fn revoke(&self, id: &str) -> Result<()> {
+        let mut settings = self.settings.write().await;
+        let Some(index) = settings.tokens.iter().position(|t| t.id == id) else {
+            bail!("Token not found: {}", id);
+        };
+        let admins = settings
+            .tokens
+            .iter()
+            .filter(|t| t.scope == Scope::Admin)
+            .count();
+        if settings.enabled && settings.tokens[index].scope == Scope::Admin && admins == 1 {
+            bail!("Can't revoke the last admin token while authentication is enabled");
+        }
+
+        let token = settings.tokens.remove(index);
+        self.file.save(&*settings);
+        info!("Revoked token \"{}\"", token.name);
+        Ok(())
+    }
+
+    /// Turn auth on or off.
+    /// Turning it on needs an admin token, so the UI can't be locked out.
+    pub async fn configure(&self, enabled: bool) -> Result<()> {
+        let mut settings = self.settings.write().await;
+        if enabled && !settings.tokens.iter().any(|t| t.scope == Scope::Admin) {
+            bail!("Create an admin token before enabling authentication");
+        }
+        if settings.enabled != enabled {
+            info!(
+                "API authentication {}",
+                if enabled { "enabled" } else { "disabled" }
+            );
+        }
+        settings.enabled = enabled;
+        self.file.save(&*settings);
+        Ok(())
+    }
+
+    /// Issue a control token to a knob while pairing is open. Only knobs the knob
+    /// store already knows (`known`) or the id the admin approved are enrolled.
+    /// None otherwise, or if the knob already has a token.
+    pub async fn enroll_knob(&self, knob_id: &str, known: bool) -> Option<String> {
+        let approved = match self.pairing.read().await.as_ref() {
+            Some(p) if p.until > Utc::now() => known || p.knob_id.as_deref() == Some(knob_id),
+            _ => false,
+        };
+        if !approved {
+            return None;
+        }
+
+        let mut settings = self.settings.write().await;
+        if settings
+            .tokens
+            .iter()
+            .any(|t| t.knob_id.as_deref() == Some(knob_id))
+        {
+            return None;
+        }
+
+        let (token, secret) = new_token(
+            format!("Knob {}", knob_id),
+            Scope::Control,
+            Some(knob_id.to_string()),
+        );
+        settings.tokens.push(token);
+        self.file.save(&*settings);
+        info!("Issued token to knob {}", knob_id);
+        Some(secret)
+    }
+}
+
+// =============================================================================
+// Route scopes
+// =============================================================================
+
+/// Reachable without a token: login, and what the login page needs to render
+const PUBLIC_ROUTES: &[&str] = &[
+    "/login",
+    "/auth/status",
+    "/auth/login",
+    "/auth/logout",
+    "/favicon.ico",
+    "/apple-touch-icon.png",
+    "/tailwind.css",
+    "/dx-components-theme.css",
+    "/assets/**",
+    // dx serve hot reload (development only)
+    "/_dioxus/**",
+];
+
+/// Non-GET routes that only operate zones and DSP
+const CONTROL_ROUTES: &[&str] = &[
+    "/control",
+    "/knob/control",
+    "/knob/config",
+    "/config/*",
+    "/zones/*/command",
+    "/roon/control",
+    "/roon/volume",
+    "/roon/play",
+    "/roon/play_item",
+    "/lms/control",
+    "/lms/volume",
+    "/openhome/control",
+    "/upnp/control",
+    "/hqplayer/control",
+    "/hqplayer/volume",
+    "/hqplayer/setting",
+    "/hqplayer/profile",
+    "/hqplayer/matrix/profile",
+    "/hqp/pipeline",
+    "/hqp/profiles/load",
+    "/hqp/instances/*/profile",
+    "/hqp/instances/*/matrix/profile",
+    "/scenes/recall",
+    "/schedules/run",
+    "/sleep",
+    "/sleep/cancel",
+];
+
+/// Non-GET routes that don't change anything
+const READ_POSTS: &[&str] = &["/roon/browse"];
+
+/// GET routes that expose secrets
+const ADMIN_READS: &[&str] = &["/api/backup", "/audit", "/auth/tokens"];
+
+/// `*` matches one path segment, a trailing `**` any remainder
+fn route_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern_segments = pattern.trim_start_matches('/').split('/');
+    let mut path_segments = path.trim_start_matches('/').split('/');
+    loop {
+        match (pattern_segments.next(), path_segments.next()) {
+            (Some("**"), _) => return true,
+            (Some(p), Some(s)) if p == "*" || p == s => continue,
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+fn matches_any(patterns: &[&str], path: &str) -> bool {
+    patterns.iter().any(|p| route_matches(p, path))
+}
+
+/// Scope a request needs, or None for public routes.
+/// Unlisted changes need admin, so a new route is never left open by accident.
+pub fn required_scope(method: &Method, path: &str) -> Option<Scope> {
+    if method == Method::OPTIONS || matches_any(PUBLIC_ROUTES, path) {
+        return None;
+    }
+    if path == "/mcp" {
+        return Some(Scope::Control);
+    }
+    if method == Method::GET || method == Method::HEAD {
+        return Some(if matches_any(ADMIN_READS, path) {
+            Scope::Admin
+        } else {
+            Scope::Read
+        });
+    }
+    if matches_any(CONTROL_ROUTES, path) {
+        Some(Scope::Control)
+    } else if matches_any(READ_POSTS, path) {
+        Some(Scope::Read)
+    } else {
+        Some(Scope::Admin)
+    }
+}
+
+// =============================================================================
+// Request credentials
+// =============================================================================
+
+/// Token sent with a request: bearer header, or the UI session cookie
+pub fn request_token(headers: &HeaderMap, allow_cookie: bool) -> Option<String> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim().to_string());
+    if bearer.is_some() || !allow_cookie {
+        return bearer;
+    }
+
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .find_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            (name == SESSION_COOKIE).then(|| value.to_string())
+        })
+}
+
+/// `Set-Cookie` value that logs the UI in with `secret`. `secure` (the request
+/// came over HTTPS) keeps browsers from sending it back over plain HTTP.
+pub fn session_cookie(secret: &str, secure: bool) -> String {
+    format!(
+        "{}={}; Path=/; HttpOnly; SameSite=Strict; Max-Age=31536000{}",
+        SESSION_COOKIE,
+        secret,
+        secure_attribute(secure)
+    )
+}
+
+/// `Set-Cookie` value that logs the UI out
+pub fn clear_session_cookie(secure: bool) -> String {
+    format!(
+        "{}=; Path=/; HttpOnly; SameSite=Strict; Max-Age=0{}",
+        SESSION_COOKIE,
+        secure_attribute(secure)
+    )
+}
+
+fn secure_attribute(secure: bool) -> &'static str {
+    if secure {
+        "; Secure"
+    } else {
+        ""
+    }
+}
+
+/// Knob a request comes from (knob firmware sends `X-Knob-Id` on every request)
+fn request_knob_id(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .get("x-knob-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_NAME_LEN)
+}
+
+fn reject(status: StatusCode, message: String) -> Response {
+    let mut response = (status, Json(serde_json::json!({ "error": message }))).into_response();
+    if status == StatusCode::UNAUTHORIZED {
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    }
+    response
+}
+
+fn wants_html(request: &Request) -> bool {
+    request.method() == Method::GET
+        && request
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("text/html"))
+}
+
+/// Middleware checking every request against the route's scope
+pub async fn require_auth(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if !state.auth.is_enabled().await {
+        return next.run(request).await;
+    }
+    let Some(required) = required_scope(request.method(), request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    // MCP clients must send the header; a browser session isn't enough
+    let allow_cookie = request.uri().path() != "/mcp";
+    if let Some(secret) = request_token(request.headers(), allow_cookie) {
+        return match state.auth.authenticate(&secret).await {
+            Some(scope) if scope >= required => next.run(request).await,
+            Some(scope) => reject(
+                StatusCode::FORBIDDEN,
+                format!(
+                    "Token scope is {}, this needs {}",
+                    scope.as_str(),
+                    required.as_str()
+                ),
+            ),
+            None => reject(StatusCode::UNAUTHORIZED, "Invalid token".to_string()),
+        };
+    }
+
+    // Enrollment hands out the token but never serves the request itself
+    if required <= Scope::Control {
+        if let Some(knob_id) = request_knob_id(&request) {
+            let known = state.knobs.get(&knob_id).await.is_some();
+            if let Some(secret) = state.auth.enroll_knob(&knob_id, known).await {
+                let mut response = reject(
+                    StatusCode::UNAUTHORIZED,
+                    format!(
+                        "Knob enrolled; repeat the request with the token in {}",
+                        KNOB_TOKEN_HEADER
+                    ),
+                );
+                if let Ok(value) = HeaderValue::from_str(&secret) {
+                    response.headers_mut().insert(KNOB_TOKEN_HEADER, value);
+                }
+                return response;
+            }
+        }
+    }
+
+    if wants_html(&request) {
+        let next_url = request
+            .uri()
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or("/");
+        return Redirect::to(&format!("/login?next={}", urlencoding::encode(next_url)))
+            .into_response();
+    }
+    reject(
+        StatusCode::UNAUTHORIZED,
+        "Authentication required".to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_cookie_is_secure_over_https() {
+        assert!(!session_cookie("abc", false).contains("Secure"));
+        assert!(session_cookie("abc", true).ends_with("; Secure"));
+        assert!(!clear_session_cookie(false).contains("Secure"));
+        assert!(clear_session_cookie(true).ends_with("; Secure"));
+    }
+
+    #[test]
+    fn test_required_scope() {
+        let get = Method::GET;
+        let post = Method::POST;
+
+        assert_eq!(required_scope(&get, "/login"), None);
+        assert_eq!(required_scope(&get, "/assets/app.wasm"), None);
+        assert_eq!(required_scope(&Method::OPTIONS, "/control"), None);
+
+        assert_eq!(required_scope(&get, "/zones"), Some(Scope::Read));
+        assert_eq!(required_scope(&get, "/events"), Some(Scope::Read));
+        assert_eq!(required_scope(&get, "/settings"), Some(Scope::Read));
+        assert_eq!(required_scope(&get, "/api/backup"), Some(Scope::Admin));
+        assert_eq!(required_scope(&get, "/audit"), Some(Scope::Admin));
+
+        assert_eq!(required_scope(&post, "/control"), Some(Scope::Control));
+        assert_eq!(
+            required_scope(&post, "/zones/roon:1/command"),
+            Some(Scope::Control)
+        );
+        assert_eq!(
+            required_scope(&post, "/hqp/instances/den/matrix/profile"),
+            Some(Scope::Control)
+        );
+        assert_eq!(required_scope(&get, "/mcp"), Some(Scope::Control));
+        assert_eq!(required_scope(&post, "/roon/browse"), Some(Scope::Read));
+
+        assert_eq!(required_scope(&post, "/api/settings"), Some(Scope::Admin));
+        assert_eq!(required_scope(&post, "/hqp/instances"), Some(Scope::Admin));
+        assert_eq!(
+            required_scope(&Method::DELETE, "/hqp/instances/den"),
+            Some(Scope::Admin)
+        );
+        assert_eq!(required_scope(&post, "/some/new/route"), Some(Scope::Admin));
+    }
+
+    #[test]
+    fn test_route_matches() {
+        assert!(route_matches("/config/*", "/config/knob-1"));
+        assert!(!route_matches("/config/*", "/config"));
+        assert!(!route_matches("/config/*", "/config/a/b"));
+        assert!(route_matches("/assets/**", "/assets/a/b.js"));
+        assert!(!route_matches("/sleep", "/sleep/cancel"));
+    }
+
+    #[test]
+    fn test_request_token() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            "theme=dark; uhc_token=uhc_abc".parse().unwrap(),
+        );
+        assert_eq!(request_token(&headers, true).as_deref(), Some("uhc_abc"));
+        assert_eq!(request_token(&headers, false), None);
+
+        headers.insert(header::AUTHORIZATION, "Bearer uhc_def".parse().unwrap());
+        assert_eq!(request_token(&headers, false).as_deref(), Some("uhc_def"));
+    }
+
+    #[tokio::test]
+    async fn test_create_authenticate_revoke() {
+        let store = AuthStore::in_memory();
+        assert!(store.create_token("  ", Scope::Read).await.is_err());
+
+        let (info, secret) = store.create_token("Phone", Scope::Control).await.unwrap();
+        assert!(secret.starts_with(TOKEN_PREFIX));
+        assert_eq!(store.authenticate(&secret).await, Some(Scope::Control));
+        assert_eq!(store.authenticate("uhc_wrong").await, None);
+
+        store.revoke(&info.id).await.unwrap();
+        assert_eq!(store.authenticate(&secret).await, None);
+        assert!(store.revoke(&info.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_enabling_needs_an_admin_token() {
+        let store = AuthStore::in_memory();
+        store.create_token("Viewer", Scope::Read).await.unwrap();
+        assert!(store.configure(true).await.is_err());
+        assert!(!store.is_enabled().await);
+
+        let (admin, _) = store.create_token("Me", Scope::Admin).await.unwrap();
+        store.configure(true).await.unwrap();
+        assert!(store.is_enabled().await);
+
+        // The last admin token stays while auth is on
+        assert!(store.revoke(&admin.id).await.is_err());
+        store.configure(false).await.unwrap();
+        store.revoke(&admin.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_knob_enrolls_once() {
+        let store = AuthStore::in_memory();
+        store.set_knob_pairing(true, None).await;
+
+        let secret = store.enroll_knob("knob-1", true).await.unwrap();
+        assert_eq!(store.authenticate(&secret).await, Some(Scope::Control));
+        assert!(store.enroll_knob("knob-1", true).await.is_none());
+
+        store.set_knob_pairing(false, None).await;
+        assert!(store.enroll_knob("knob-2", true).await.is_none());
+
+        let tokens = store.tokens().await;
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].knob_id.as_deref(), Some("knob-1"));
+    }
+
+    #[tokio::test]
+    async fn test_knob_enrollment_needs_pairing_and_approval() {
+        let store = AuthStore::in_memory();
+        assert!(store.knob_pairing_until().await.is_none());
+
+        // Closed by default
+        assert!(store.enroll_knob("knob-1", true).await.is_none());
+
+        // Unknown ids are only enrolled when the admin approved them
+        store.set_knob_pairing(true, Some("knob-2")).await;
+        assert!(store.knob_pairing_until().await.is_some());
+        assert!(store.enroll_knob("stranger", false).await.is_none());
+        assert!(store.enroll_knob("knob-2", false).await.is_some());
+
+        // The window expires
+        if let Some(pairing) = store.pairing.write().await.as_mut() {
+            pairing.until = Utc::now() - Duration::seconds(1);
+        }
+        assert!(store.knob_pairing_until().await.is_none());
+        assert!(store.enroll_knob("knob-1", true).await.is_none());
+    }
+
+    #[test]
+    fn test_old_enrollment_setting_is_ignored() {
+        let settings: AuthSettings =
+            serde_json::from_str(r#"{"enabled": true, "knob_enrollment": true}"#).unwrap();
+        assert!(settings.enabled);
+        assert!(settings.tokens.is_empty());
+    }
+}
@@ -0,0 +1,556 @@
+//! Backup and restore of bridge state
+//!
+//! `GET /api/backup` bundles the bridge's config files (settings, adapter
+//! connections, HQPlayer instances and zone links, knobs, Roon pairing, rooms,
+//! scenes, schedules, rules, ...) into one versioned JSON document, for moving an
+//! install (e.g. Synology package to Docker) or recovering after a disk failure.
+//!
+//! `POST /api/restore` validates a bundle, brings files from older formats up to
+//! date with the same migrations used at startup, writes them to the config dir and
+//! reloads what can be reloaded live. Files that adapters only read when they start
+//! (connections, Roon pairing) take effect after a restart; the report says which.
+//! A dry run writes nothing and reports what would change.
+//!
+//! Caches and history (zone cache, scrobble queue, listening history) are not
+//! included. Bundles contain credentials and the Roon pairing token.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use tracing::{info, warn};
+
+use crate::adapters::external::ExternalAdapterConfig;
+use crate::adapters::hqplayer::HqpInstanceConfig;
+use crate::api::{apply_adapter_settings, load_app_settings, AppSettings, AppState};
+use crate::auth::AuthSettings;
+use crate::config::{get_config_file_path, migrate_hqp_config_value, read_config_file};
+use crate::fades::FadeSettings;
+use crate::knobs::store::Knob;
+use crate::rooms::Room;
+use crate::rules::Rule;
+use crate::scenes::Scene;
+use crate::scheduler::Schedule;
+use crate::scrobbler::ScrobblerConfig;
+use crate::volume_policy::VolumePolicies;
+
+/// Value of the `format` field, to recognise bundles
+pub const BACKUP_FORMAT: &str = "unified-hifi-backup";
+
+/// Current bundle version
+pub const BACKUP_VERSION: u32 = 1;
+
+/// A backup bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backup {
+    pub format: String,
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// Bridge version that made the bundle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_version: Option<String>,
+    /// Config file name -> file content
+    pub files: BTreeMap<String, Value>,
+}
+
+/// When a restored file takes effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    /// Reloaded right away
+    Live,
+    /// Read when adapters start; needs a restart
+    Restart,
+}
+
+/// What restoring does to one file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreAction {
+    Create,
+    Replace,
+    Unchanged,
+}
+
+/// One file in a restore report
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreChange {
+    pub file: String,
+    pub action: RestoreAction,
+    pub effect: Effect,
+}
+
+/// Outcome (or, for a dry run, plan) of a restore
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub dry_run: bool,
+    /// Version of the bundle as uploaded
+    pub version: u32,
+    /// Files converted from older formats
+    pub migrations: Vec<String>,
+    pub changes: Vec<RestoreChange>,
+    /// Some changed files only take effect after a restart
+    pub restart_required: bool,
+}
+
+/// A config file included in backups
+struct BackupFile {
+    name: &'static str,
+    /// Checks that content parses as what the owning store expects
+    check: fn(&Value) -> Result<()>,
+    effect: Effect,
+}
+
+fn check<T: DeserializeOwned>(value: &Value) -> Result<()> {
+    T::deserialize(value)?;
+    Ok(())
+}
+
+/// Parse a list and put every entry through the checks its store runs on save
+fn check_items<T: DeserializeOwned>(
+    value: &Value,
+    normalize: fn(&mut T) -> Result<()>,
+) -> Result<()> {
+    for (i, mut item) in Vec::<T>::deserialize(value)?.into_iter().enumerate() {
+        normalize(&mut item).with_context(|| format!("entry {}", i + 1))?;
+    }
+    Ok(())
+}
+
+fn check_rooms(value: &Value) -> Result<()> {
+    check_items(value, Room::normalize)
+}
+
+fn check_scenes(value: &Value) -> Result<()> {
+    check_items(value, Scene::normalize)
+}
+
+fn check_schedules(value: &Value) -> Result<()> {
+    check_items(value, Schedule::normalize)
+}
+
+fn check_rules(value: &Value) -> Result<()> {
+    check_items(value, Rule::normalize)
+}
+
+/// Same limits the volume policy store enforces when it loads the file
+fn check_volume_policy(value: &Value) -> Result<()> {
+    VolumePolicies::deserialize(value)?.validate()
+}
+
+/// Files in a backup, in restore order
+const BACKUP_FILES: &[BackupFile] = &[
+    BackupFile {
+        name: "app-settings.json",
+        check: check::<AppSettings>,
+        effect: Effect::Live,
+    },
+    BackupFile {
+        name: "roon_state.json",
+        check: check::<Map<String, Value>>,
+        effect: Effect::Restart,
+    },
+    BackupFile {
+        name: "lms-config.json",
+        check: check::<Map<String, Value>>,
+        effect: Effect::Restart,
+    },
+    BackupFile {
+        name: "lms-servers.json",
+        check: check::<Vec<Map<String, Value>>>,
+        effect: Effect::Restart,
+    },
+    BackupFile {
+        name: "hqp-config.json",
+        check: check::<Vec<HqpInstanceConfig>>,
+        effect: Effect::Restart,
+    },
+    BackupFile {
+        name: "hqp-zone-links.json",
+        check: check::<HashMap<String, String>>,
+        effect: Effect::Restart,
+    },
+    BackupFile {
+        name: "external_adapters.json",
+        check: check::<Vec<ExternalAdapterConfig>>,
+        effect: Effect::Restart,
+    },
+    BackupFile {
+        name: "scrobbler.json",
+        check: check::<ScrobblerConfig>,
+        effect: Effect::Restart,
+    },
+    BackupFile {
+        name: "auth.json",
+        check: check::<AuthSettings>,
+        effect: Effect::Live,
+    },
+    BackupFile {
+        name: "knobs.json",
+        check: check::<HashMap<String, Knob>>,
+        effect: Effect::Live,
+    },
+    BackupFile {
+        name: "rooms.json",
+        check: check_rooms,
+        effect: Effect::Live,
+    },
+    BackupFile {
+        name: "scenes.json",
+        check: check_scenes,
+        effect: Effect::Live,
+    },
+    BackupFile {
+        name: "schedules.json",
+        check: check_schedules,
+        effect: Effect::Live,
+    },
+    BackupFile {
+        name: "rules.json",
+        check: check_rules,
+        effect: Effect::Live,
+    },
+    BackupFile {
+        name: "fades.json",
+        check: check::<FadeSettings>,
+        effect: Effect::Live,
+    },
+    BackupFile {
+        name: "volume_policy.json",
+        check: check_volume_policy,
+        effect: Effect::Live,
+    },
+];
+
+/// Node.js-era file names and the files that replaced them
+const RENAMED_FILES: &[(&str, &str)] = &[("roon-config.json", "roon_state.json")];
+
+fn backup_file(name: &str) -> Option<&'static BackupFile> {
+    BACKUP_FILES.iter().find(|f| f.name == name)
+}
+
+/// Current content of a config file, if it exists and is valid JSON
+fn read_json(name: &str) -> Option<Value> {
+    let content = read_config_file(name)?;
+    match serde_json::from_str(&content) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Backup: skipping unreadable {}: {}", name, e);
+            None
+        }
+    }
+}
+
+/// Bundle the current config files
+pub fn create_backup() -> Backup {
+    let files = BACKUP_FILES
+        .iter()
+        .filter_map(|f| read_json(f.name).map(|value| (f.name.to_string(), value)))
+        .collect();
+
+    Backup {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: Utc::now(),
+        app_version: Some(env!("UHC_VERSION").to_string()),
+        files,
+    }
+}
+
+/// Bring a bundle up to the current version and file formats.
+/// Returns a note for every file that was converted.
+fn migrate(backup: &mut Backup) -> Result<Vec<String>> {
+    if backup.format != BACKUP_FORMAT {
+        bail!("Not a backup (format is \"{}\")", backup.format);
+    }
+    if backup.version > BACKUP_VERSION {
+        bail!(
+            "Backup version {} was made by a newer release (this one reads up to {})",
+            backup.version,
+            BACKUP_VERSION
+        );
+    }
+
+    // Version 1 is the first bundle version, so only file formats need upgrading.
+    // These mirror the startup migrations in `config`, so a bundle assembled from
+    // an old install restores like the install itself would have started.
+    let mut notes = Vec::new();
+
+    for (old, new) in RENAMED_FILES {
+        if let Some(value) = backup.files.remove(*old) {
+            if backup.files.contains_key(*new) {
+                notes.push(format!("{}: ignored, {} is also present", old, new));
+            } else {
+                backup.files.insert(new.to_string(), value);
+                notes.push(format!("{}: restored as {}", old, new));
+            }
+        }
+    }
+
+    if let Some(value) = backup.files.get_mut("hqp-config.json") {
+        if let Some(migrated) = migrate_hqp_config_value(value) {
+            *value = migrated;
+            notes.push("hqp-config.json: converted from Node.js format".to_string());
+        } else if let Value::Object(single) = value {
+            // Single-instance format from before multi-instance support
+            let mut instance = single.clone();
+            instance
+                .entry("name")
+                .or_insert_with(|| Value::String("default".to_string()));
+            *value = Value::Array(vec![Value::Object(instance)]);
+            notes.push("hqp-config.json: converted to multi-instance format".to_string());
+        }
+    }
+
+    // Node.js wrote camelCase keys; AppSettings reads both and writes snake_case
+    if let Some(value) = backup.files.get_mut("app-settings.json") {
+        if let Ok(settings) = AppSettings::deserialize(&*value) {
+            if let Ok(normalized) = serde_json::to_value(&settings) {
+                if normalized != *value {
+                    *value = normalized;
+                    notes.push("app-settings.json: converted to current format".to_string());
+                }
+            }
+        }
+    }
+
+    Ok(notes)
+}
+
+/// Every problem with the bundle's files
+fn validate(backup: &Backup) -> Result<()> {
+    let errors: Vec<String> = backup
+        .files
+        .iter()
+        .filter_map(|(name, value)| match backup_file(name) {
+            None => Some(format!("{}: not a file this bridge backs up", name)),
+            Some(file) => (file.check)(value)
+                .err()
+                .map(|e| format!("{}: {:#}", name, e)),
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid backup\n  {}", errors.join("\n  ")))
+    }
+}
+
+/// Migrate and validate a bundle, then compare it with the current files
+fn plan(backup: &mut Backup) -> Result<(Vec<String>, Vec<RestoreChange>)> {
+    let migrations = migrate(backup)?;
+    validate(backup)?;
+
+    let changes = BACKUP_FILES
+        .iter()
+        .filter_map(|file| {
+            let value = backup.files.get(file.name)?;
+            let action = match read_json(file.name) {
+                None => RestoreAction::Create,
+                Some(current) if current == *value => RestoreAction::Unchanged,
+                Some(_) => RestoreAction::Replace,
+            };
+            Some(RestoreChange {
+                file: file.name.to_string(),
+                action,
+                effect: file.effect,
+            })
+        })
+        .collect();
+
+    Ok((migrations, changes))
+}
+
+fn write_file(name: &str, value: &Value) -> Result<()> {
+    let path = get_config_file_path(name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(value)?)
+        .map_err(|e| anyhow!("Failed to write {}: {}", name, e))
+}
+
+/// Restore a bundle. Files missing from the bundle are left alone.
+pub async fn restore(state: &AppState, mut backup: Backup, dry_run: bool) -> Result<RestoreReport> {
+    let version = backup.version;
+    let (migrations, changes) = plan(&mut backup)?;
+    let restart_required = changes
+        .iter()
+        .any(|c| c.action != RestoreAction::Unchanged && c.effect == Effect::Restart);
+
+    let report = RestoreReport {
+        dry_run,
+        version,
+        migrations,
+        changes,
+        restart_required,
+    };
+    if dry_run {
+        return Ok(report);
+    }
+
+    let old_settings = load_app_settings();
+    for change in report
+        .changes
+        .iter()
+        .filter(|c| c.action != RestoreAction::Unchanged)
+    {
+        if let Some(value) = backup.files.get(&change.file) {
+            write_file(&change.file, value)?;
+        }
+    }
+
+    for change in report
+        .changes
+        .iter()
+        .filter(|c| c.action != RestoreAction::Unchanged && c.effect == Effect::Live)
+    {
+        match change.file.as_str() {
+            "app-settings.json" => {
+                let new_settings = load_app_settings();
+                apply_adapter_settings(state, &old_settings.adapters, &new_settings.adapters).await;
+            }
+            "auth.json" => state.auth.reload().await,
+            "knobs.json" => state.knobs.reload().await,
+            "rooms.json" => state.rooms.reload().await,
+            "scenes.json" => state.scenes.reload().await,
+            "schedules.json" => state.scheduler.reload().await,
+            "rules.json" => state.rules.reload().await,
+            "fades.json" => state.fades.reload().await,
+            "volume_policy.json" => state.volume_policy.reload().await,
+            _ => {}
+        }
+    }
+
+    info!(
+        "Restored backup ({} file(s) changed{})",
+        report
+            .changes
+            .iter()
+            .filter(|c| c.action != RestoreAction::Unchanged)
+            .count(),
+        if report.restart_required {
+            ", restart required"
+        } else {
+            ""
+        }
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(files: Value) -> Backup {
+        serde_json::from_value(serde_json::json!({
+            "format": BACKUP_FORMAT,
+            "version": BACKUP_VERSION,
+            "created_at": "2026-01-01T00:00:00Z",
+            "files": files,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_every_backup_file_name_is_unique() {
+        for (i, file) in BACKUP_FILES.iter().enumerate() {
+            assert!(
+                BACKUP_FILES[..i].iter().all(|f| f.name != file.name),
+                "{} listed twice",
+                file.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_migrate_rejects_foreign_and_newer_bundles() {
+        let mut foreign = bundle(serde_json::json!({}));
+        foreign.format = "something-else".to_string();
+        assert!(migrate(&mut foreign).is_err());
+
+        let mut newer = bundle(serde_json::json!({}));
+        newer.version = BACKUP_VERSION + 1;
+        let err = migrate(&mut newer).unwrap_err().to_string();
+        assert!(err.contains("newer release"), "{}", err);
+    }
+
+    #[test]
+    fn test_migrate_nodejs_files() {
+        let mut backup = bundle(serde_json::json!({
+            "roon-config.json": {"paired_core_id": "abc"},
+            "hqp-config.json": {"host": "192.168.1.20", "port": 8088},
+            "app-settings.json": {"hideKnobsPage": true, "adapters": {"roon": true}},
+        }));
+
+        let notes = migrate(&mut backup).unwrap();
+        assert_eq!(notes.len(), 3, "{:?}", notes);
+
+        assert!(!backup.files.contains_key("roon-config.json"));
+        assert_eq!(backup.files["roon_state.json"]["paired_core_id"], "abc");
+
+        let hqp = &backup.files["hqp-config.json"][0];
+        assert_eq!(hqp["name"], "default");
+        assert_eq!(hqp["port"], 4321);
+        assert_eq!(hqp["web_port"], 8088);
+
+        assert_eq!(backup.files["app-settings.json"]["hide_knobs_page"], true);
+        validate(&backup).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_single_instance_hqp_config() {
+        let mut backup = bundle(serde_json::json!({
+            "hqp-config.json": {"host": "h", "port": 4321, "web_port": 8088},
+        }));
+
+        let notes = migrate(&mut backup).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(backup.files["hqp-config.json"][0]["name"], "default");
+        validate(&backup).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_leaves_current_files_alone() {
+        let mut backup = bundle(serde_json::json!({
+            "hqp-config.json": [{"name": "den", "host": "h", "port": 4321, "web_port": 8088}],
+        }));
+        let before = backup.files.clone();
+
+        assert!(migrate(&mut backup).unwrap().is_empty());
+        assert_eq!(backup.files, before);
+    }
+
+    #[test]
+    fn test_validate_names_bad_files() {
+        let backup = bundle(serde_json::json!({
+            "rooms.json": {"not": "a list"},
+            "passwords.txt": "secret",
+            "volume_policy.json": {"default": {"max_volume": 70}},
+        }));
+
+        let err = validate(&backup).unwrap_err().to_string();
+        assert!(err.contains("rooms.json:"), "{}", err);
+        assert!(err.contains("passwords.txt: not a file"), "{}", err);
+        assert!(!err.contains("volume_policy.json"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_runs_store_checks() {
+        let backup = bundle(serde_json::json!({
+            "scenes.json": [{"name": "Evening", "zones": []}],
+            "volume_policy.json": {"default": {"max_volume": 40, "start_cap": 60}},
+        }));
+
+        let err = validate(&backup).unwrap_err().to_string();
+        assert!(
+            err.contains("scenes.json: entry 1: a scene needs"),
+            "{}",
+            err
+        );
+        assert!(err.contains("volume_policy.json: start_cap"), "{}", err);
+    }
+}
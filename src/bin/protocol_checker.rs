@@ -199,6 +199,7 @@ enum BusEvent {
         artist: Option<String>,
         album: Option<String>,
         image_key: Option<String>,
+        duration: Option<f64>,
     },
     SeekPositionChanged {
         zone_id: String,
@@ -506,6 +507,7 @@ fn generate_example(schema_type: &str) {
                     artist: Some("Test Artist".to_string()),
                     album: Some("Test Album".to_string()),
                     image_key: Some("img-key".to_string()),
+                    duration: Some(245.0),
                 })
                 .unwrap()
             );
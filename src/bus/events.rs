@@ -52,12 +52,18 @@ impl PrefixedZoneId {
         Self(format!("hqplayer:{}", raw_id.as_ref()))
     }
 
+    /// Create a zone ID for an external adapter (`prefix` is "ext-<name>")
+    pub fn external(prefix: &str, raw_id: impl AsRef<str>) -> Self {
+        Self(format!("{}:{}", prefix, raw_id.as_ref()))
+    }
+
     /// Parse a prefixed zone ID from a string.
     /// Returns None if the string doesn't contain a valid prefix.
     pub fn parse(s: impl AsRef<str>) -> Option<Self> {
         let s = s.as_ref();
         let valid_prefixes = ["roon:", "lms:", "openhome:", "upnp:", "hqplayer:"];
-        if valid_prefixes.iter().any(|p| s.starts_with(p)) {
+        let external = s.starts_with("ext-") && s.contains(':');
+        if external || valid_prefixes.iter().any(|p| s.starts_with(p)) {
             Some(Self(s.to_string()))
         } else {
             None
@@ -146,6 +152,47 @@ pub struct Zone {
 
     /// Whether previous track command is allowed
     pub is_previous_allowed: bool,
+
+    /// What the zone's backend supports (static, unlike the `is_*_allowed` flags)
+    #[serde(default)]
+    pub capabilities: ZoneCapabilities,
+
+    /// Served from the warm-start cache and not yet confirmed by its adapter
+    #[serde(default)]
+    pub is_stale: bool,
+}
+
+/// Capability descriptor for a zone, populated by its adapter.
+///
+/// Describes what the zone can do at all, so frontends can hide controls that
+/// would fail. Per-track state (e.g. "next is allowed right now") stays on the
+/// `is_*_allowed` flags of [`Zone`]. Apart from `standby`, a flag is only set
+/// when the adapter's `handle_command` carries it out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ZoneCapabilities {
+    /// Play, pause and stop
+    pub transport: bool,
+    /// Next/previous track
+    pub skip: bool,
+    /// Seek within the current track
+    pub seek: bool,
+    /// Shuffle on/off
+    pub shuffle: bool,
+    /// Repeat modes
+    pub repeat: bool,
+    /// Volume can be changed
+    pub volume: bool,
+    /// Volume is expressed in dB rather than a 0-100 scale
+    pub volume_db: bool,
+    /// Mute/unmute
+    pub mute: bool,
+    /// Zone can be grouped/synced with other zones
+    pub grouping: bool,
+    /// Input/source selection
+    pub source_selection: bool,
+    /// Standby / power control (LMS `power`, Roon output standby); driven by the
+    /// sleep timer rather than a zone command
+    pub standby: bool,
 }
 
 /// Playback state enumeration
@@ -255,7 +302,7 @@ pub struct NowPlaying {
 }
 
 /// Additional track metadata
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct TrackMetadata {
     /// Audio format (e.g., "FLAC", "DSD", "MQA")
     pub format: Option<String>,
@@ -282,6 +329,59 @@ pub struct TrackMetadata {
     pub disc_number: Option<u32>,
 }
 
+impl TrackMetadata {
+    /// Whether no format details are known
+    pub fn is_empty(&self) -> bool {
+        self.format.is_none()
+            && self.sample_rate.is_none()
+            && self.bit_depth.is_none()
+            && self.bitrate.is_none()
+    }
+
+    /// Short human-readable format label, e.g. "FLAC 24/192", "DSD128", "MP3 320k".
+    ///
+    /// Returns `None` when nothing useful is known.
+    pub fn summary(&self) -> Option<String> {
+        let format = self
+            .format
+            .as_deref()
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(str::to_uppercase);
+
+        // DSD is conventionally labelled by its multiple of 44.1kHz (DSD64 = 2.8224MHz)
+        let is_dsd =
+            format.as_deref().is_some_and(|f| f.starts_with("DSD")) || self.bit_depth == Some(1);
+        if is_dsd {
+            return Some(match self.sample_rate {
+                Some(rate) if rate >= 2_822_400 => format!("DSD{}", rate / 44_100),
+                _ => format.unwrap_or_else(|| "DSD".to_string()),
+            });
+        }
+
+        let rate_khz = self.sample_rate.filter(|r| *r > 0).map(|r| {
+            if r % 1000 == 0 {
+                (r / 1000).to_string()
+            } else {
+                format!("{:.1}", r as f64 / 1000.0)
+            }
+        });
+
+        let detail = match (self.bit_depth.filter(|b| *b > 0), rate_khz) {
+            (Some(bits), Some(rate)) => Some(format!("{}/{}", bits, rate)),
+            (None, Some(rate)) => Some(format!("{}kHz", rate)),
+            _ => self.bitrate.filter(|b| *b > 0).map(|b| format!("{}k", b)),
+        };
+
+        match (format, detail) {
+            (Some(f), Some(d)) => Some(format!("{} {}", f, d)),
+            (Some(f), None) => Some(f),
+            (None, Some(d)) => Some(d),
+            (None, None) => None,
+        }
+    }
+}
+
 /// Image data returned from adapters
 #[derive(Debug, Clone)]
 pub struct ImageData {
@@ -384,6 +484,11 @@ pub enum Command {
 
     /// Set repeat mode
     Repeat { mode: RepeatMode },
+
+    /// Change an HQPlayer DSP setting (mode, filter, shaper, rate) or load a
+    /// profile. Sent straight to the instance rather than through its zone's
+    /// adapter; published so the audit log records who changed it.
+    HqpDsp { setting: String, value: String },
 }
 
 /// Repeat mode options
@@ -410,10 +515,101 @@ pub struct CommandResponse {
     /// Error message if command failed
     pub error: Option<String>,
 
+    /// Why the command was changed before execution (e.g. volume clamped by policy)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
     /// Timestamp of execution
     pub timestamp: u64,
 }
 
+/// Where a command came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlSurface {
+    /// Web UI
+    Web,
+    /// Hardware knob
+    Knob,
+    /// MCP tools
+    Mcp,
+    /// Generic HTTP API (`/zones/{id}/command`)
+    Api,
+    Scheduler,
+    Rules,
+    SleepTimer,
+    /// Start cap applied when playback starts outside the bridge
+    VolumePolicy,
+}
+
+impl ControlSurface {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Web => "web",
+            Self::Knob => "knob",
+            Self::Mcp => "mcp",
+            Self::Api => "api",
+            Self::Scheduler => "scheduler",
+            Self::Rules => "rules",
+            Self::SleepTimer => "sleep_timer",
+            Self::VolumePolicy => "volume_policy",
+        }
+    }
+}
+
+/// Where a control action came from, carried on `CommandReceived` and `CommandResult`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommandOrigin {
+    pub surface: ControlSurface,
+    /// Knob id, MCP session id, or the schedule/rule/service that acted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Knob name from the knob store, MCP client name, or schedule/rule name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Address of the HTTP client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+impl CommandOrigin {
+    /// Origin with just a surface
+    pub fn new(surface: ControlSurface) -> Self {
+        Self {
+            surface,
+            id: None,
+            name: None,
+            client_ip: None,
+            user_agent: None,
+        }
+    }
+
+    /// Internal service acting on its own (schedule, rule, sleep timer)
+    pub fn service(surface: ControlSurface, id: &str, name: Option<&str>) -> Self {
+        Self {
+            id: Some(id.to_string()),
+            name: name.filter(|n| !n.is_empty()).map(str::to_string),
+            ..Self::new(surface)
+        }
+    }
+
+    /// Short description for logs and the audit table, e.g. "knob Kitchen (a1b2)"
+    pub fn describe(&self) -> String {
+        let mut text = self.surface.as_str().to_string();
+        match (&self.name, &self.id) {
+            (Some(name), Some(id)) => text.push_str(&format!(" {} ({})", name, id)),
+            (Some(label), None) | (None, Some(label)) => text.push_str(&format!(" {}", label)),
+            (None, None) => {}
+        }
+        if let Some(ip) = &self.client_ip {
+            text.push_str(&format!(" from {}", ip));
+        }
+        text
+    }
+}
+
 // =============================================================================
 // Bus Events
 // =============================================================================
@@ -456,6 +652,15 @@ pub enum BusEvent {
         zone_id: PrefixedZoneId,
     },
 
+    /// Every zone an adapter currently has, in answer to `ResyncRequested`.
+    /// Replaces whatever consumers hold for the adapter.
+    ZonesSnapshot {
+        /// Adapter identifier (zone id prefix)
+        adapter: String,
+        /// Full zone information
+        zones: Vec<Zone>,
+    },
+
     // =========================================================================
     // Now Playing Events
     // =========================================================================
@@ -471,6 +676,18 @@ pub enum BusEvent {
         album: Option<String>,
         /// Image key for album art
         image_key: Option<String>,
+        /// Track length in seconds, if the adapter knows it
+        #[serde(default)]
+        duration: Option<f64>,
+    },
+
+    /// Stream format metadata changed for a zone (codec, sample rate, bit depth).
+    /// `None` clears metadata when the adapter can no longer report it.
+    TrackMetadataChanged {
+        /// Zone identifier (must be prefixed, e.g., "roon:xxx")
+        zone_id: PrefixedZoneId,
+        /// Format details for the current track
+        metadata: Option<TrackMetadata>,
     },
 
     /// Seek position changed (for progress updates)
@@ -498,6 +715,9 @@ pub enum BusEvent {
         command: Command,
         /// Optional request ID for correlation
         request_id: Option<String>,
+        /// Who sent the command
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        origin: Option<CommandOrigin>,
     },
 
     /// Result of a command execution
@@ -506,6 +726,9 @@ pub enum BusEvent {
         response: CommandResponse,
         /// Request ID for correlation (if provided in CommandReceived)
         request_id: Option<String>,
+        /// Who sent the command (as in CommandReceived)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        origin: Option<CommandOrigin>,
     },
 
     // =========================================================================
@@ -549,6 +772,26 @@ pub enum BusEvent {
         reason: Option<String>,
     },
 
+    /// An adapter's run loop is starting (first attempt or a retry)
+    AdapterStarting {
+        /// Adapter identifier
+        adapter: String,
+        /// 1 for the first attempt, counting up across retries
+        attempt: u32,
+    },
+
+    /// An adapter's run loop failed and will be retried after a backoff
+    AdapterRetrying {
+        /// Adapter identifier
+        adapter: String,
+        /// Error the adapter failed with
+        error: String,
+        /// Failures in a row (reset after a stable run)
+        consecutive_failures: u32,
+        /// Delay before the next attempt
+        retry_in_secs: u64,
+    },
+
     // =========================================================================
     // System Events
     // =========================================================================
@@ -558,12 +801,34 @@ pub enum BusEvent {
         reason: Option<String>,
     },
 
+    /// A consumer fell behind the bus and missed events; adapters answer
+    /// with a `ZonesSnapshot`
+    ResyncRequested {
+        /// Only this adapter should answer (None = every adapter)
+        adapter: Option<String>,
+        /// Who asked and why
+        reason: Option<String>,
+    },
+
     /// Health check event (can be used for monitoring)
     HealthCheck {
         /// Timestamp
         timestamp: u64,
     },
 
+    // =========================================================================
+    // Timer Events
+    // =========================================================================
+    /// A zone's sleep timer was set, cleared or ran out
+    SleepTimerChanged {
+        /// Zone the timer belongs to (adapter zone or `room:` zone)
+        zone_id: String,
+        /// "timer", "end_of_track" or "end_of_album"; None once the timer is gone
+        mode: Option<String>,
+        /// Seconds left, when known
+        remaining_secs: Option<u64>,
+    },
+
     // =========================================================================
     // Legacy Events (for backward compatibility)
     // =========================================================================
@@ -614,7 +879,9 @@ impl BusEvent {
             Self::ZoneDiscovered { .. } => "zone_discovered",
             Self::ZoneUpdated { .. } => "zone_updated",
             Self::ZoneRemoved { .. } => "zone_removed",
+            Self::ZonesSnapshot { .. } => "zones_snapshot",
             Self::NowPlayingChanged { .. } => "now_playing_changed",
+            Self::TrackMetadataChanged { .. } => "track_metadata_changed",
             Self::SeekPositionChanged { .. } => "seek_position_changed",
             Self::VolumeChanged { .. } => "volume_changed",
             Self::CommandReceived { .. } => "command_received",
@@ -624,8 +891,12 @@ impl BusEvent {
             Self::ZonesFlushed { .. } => "zones_flushed",
             Self::AdapterConnected { .. } => "adapter_connected",
             Self::AdapterDisconnected { .. } => "adapter_disconnected",
+            Self::AdapterStarting { .. } => "adapter_starting",
+            Self::AdapterRetrying { .. } => "adapter_retrying",
             Self::ShuttingDown { .. } => "shutting_down",
+            Self::ResyncRequested { .. } => "resync_requested",
             Self::HealthCheck { .. } => "health_check",
+            Self::SleepTimerChanged { .. } => "sleep_timer_changed",
             Self::RoonConnected { .. } => "roon_connected",
             Self::RoonDisconnected => "roon_disconnected",
             Self::HqpConnected { .. } => "hqp_connected",
@@ -646,6 +917,7 @@ impl BusEvent {
             Self::ZoneDiscovered { .. }
                 | Self::ZoneUpdated { .. }
                 | Self::ZoneRemoved { .. }
+                | Self::ZonesSnapshot { .. }
                 | Self::ZonesFlushed { .. }
         )
     }
@@ -655,6 +927,7 @@ impl BusEvent {
         matches!(
             self,
             Self::NowPlayingChanged { .. }
+                | Self::TrackMetadataChanged { .. }
                 | Self::SeekPositionChanged { .. }
                 | Self::VolumeChanged { .. }
         )
@@ -676,6 +949,8 @@ impl BusEvent {
                 | Self::AdapterStopped { .. }
                 | Self::AdapterConnected { .. }
                 | Self::AdapterDisconnected { .. }
+                | Self::AdapterStarting { .. }
+                | Self::AdapterRetrying { .. }
         )
     }
 
@@ -731,6 +1006,8 @@ mod tests {
                 is_pause_allowed: false,
                 is_next_allowed: true,
                 is_previous_allowed: true,
+                capabilities: ZoneCapabilities::default(),
+                is_stale: false,
             },
         };
         assert_eq!(event.event_type(), "zone_discovered");
@@ -738,6 +1015,65 @@ mod tests {
         assert!(!event.is_legacy_event());
     }
 
+    #[test]
+    fn test_zone_capabilities_default_when_missing() {
+        // Zones serialized before capabilities existed (e.g. cached state) still parse
+        let json = r#"{
+            "zone_id": "lms:aa",
+            "zone_name": "Kitchen",
+            "state": "stopped",
+            "volume_control": null,
+            "now_playing": null,
+            "source": "lms",
+            "is_controllable": true,
+            "is_seekable": false,
+            "last_updated": 0,
+            "is_play_allowed": true,
+            "is_pause_allowed": true,
+            "is_next_allowed": true,
+            "is_previous_allowed": true
+        }"#;
+        let zone: Zone = serde_json::from_str(json).unwrap();
+        assert_eq!(zone.capabilities, ZoneCapabilities::default());
+    }
+
+    #[test]
+    fn test_track_metadata_summary() {
+        let pcm = TrackMetadata {
+            format: Some("flac".to_string()),
+            sample_rate: Some(192_000),
+            bit_depth: Some(24),
+            ..Default::default()
+        };
+        assert_eq!(pcm.summary().as_deref(), Some("FLAC 24/192"));
+
+        let cd = TrackMetadata {
+            format: Some("FLAC".to_string()),
+            sample_rate: Some(44_100),
+            bit_depth: Some(16),
+            ..Default::default()
+        };
+        assert_eq!(cd.summary().as_deref(), Some("FLAC 16/44.1"));
+
+        let dsd = TrackMetadata {
+            format: Some("DSD".to_string()),
+            sample_rate: Some(5_644_800),
+            bit_depth: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(dsd.summary().as_deref(), Some("DSD128"));
+
+        let lossy = TrackMetadata {
+            format: Some("MP3".to_string()),
+            bitrate: Some(320),
+            ..Default::default()
+        };
+        assert_eq!(lossy.summary().as_deref(), Some("MP3 320k"));
+
+        assert!(TrackMetadata::default().is_empty());
+        assert_eq!(TrackMetadata::default().summary(), None);
+    }
+
     #[test]
     fn test_command_serialization() {
         let cmd = Command::VolumeAbsolute {
@@ -757,6 +1093,7 @@ mod tests {
             artist: Some("Test Artist".to_string()),
             album: Some("Test Album".to_string()),
             image_key: None,
+            duration: None,
         };
         let json = serde_json::to_string(&event).unwrap();
         assert!(json.contains("now_playing_changed") || json.contains("NowPlayingChanged"));
@@ -790,9 +1127,11 @@ mod tests {
         assert!(PrefixedZoneId::parse("openhome:abc").is_some());
         assert!(PrefixedZoneId::parse("upnp:abc").is_some());
         assert!(PrefixedZoneId::parse("hqplayer:abc").is_some());
+        assert!(PrefixedZoneId::parse("ext-jukebox:abc").is_some());
 
         // Invalid - no prefix
         assert!(PrefixedZoneId::parse("abc123").is_none());
         assert!(PrefixedZoneId::parse("unknown:abc").is_none());
+        assert!(PrefixedZoneId::parse("ext-jukebox").is_none());
     }
 }
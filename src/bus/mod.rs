@@ -2,17 +2,56 @@
 //!
 //! Uses tokio::sync::broadcast for pub/sub pattern.
 //! Events are typed and can carry payloads.
+//!
+//! Every published event is stamped with a monotonically increasing sequence
+//! id and kept in a bounded replay ring, so SSE clients that drop their
+//! connection can resume from the last id they saw (see [`EventBus::resume`]).
 
-use std::sync::Arc;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::broadcast;
 
 pub mod events;
 pub use events::*;
 
+/// Default number of events kept for replay to reconnecting clients
+pub const DEFAULT_REPLAY_CAPACITY: usize = 1024;
+
+/// A bus event stamped with its sequence id
+#[derive(Debug, Clone, Serialize)]
+pub struct SequencedEvent {
+    pub seq: u64,
+    pub event: BusEvent,
+}
+
+/// Result of resuming a stream from a client's last seen sequence id
+pub struct Resume {
+    /// Events published after the client's last id, oldest first
+    pub missed: Vec<SequencedEvent>,
+    /// The gap could not be filled from the replay ring (client must resync)
+    pub resync_required: bool,
+    /// Live receiver positioned immediately after `missed`
+    pub rx: broadcast::Receiver<SequencedEvent>,
+}
+
+/// Sequence counter and replay ring, guarded together so that
+/// sequence order, ring order and broadcast order always agree.
+struct ReplayState {
+    next_seq: u64,
+    ring: VecDeque<SequencedEvent>,
+    capacity: usize,
+}
+
 /// Event bus handle for publishing and subscribing
 #[derive(Clone)]
 pub struct EventBus {
     sender: broadcast::Sender<BusEvent>,
+    sequenced: broadcast::Sender<SequencedEvent>,
+    replay: Arc<Mutex<ReplayState>>,
+    published: Arc<AtomicU64>,
 }
 
 impl Default for EventBus {
@@ -24,13 +63,57 @@ impl Default for EventBus {
 impl EventBus {
     /// Create a new event bus with specified capacity
     pub fn new(capacity: usize) -> Self {
+        Self::with_replay_capacity(capacity, DEFAULT_REPLAY_CAPACITY)
+    }
+
+    /// Create a new event bus with explicit channel and replay ring capacities
+    pub fn with_replay_capacity(capacity: usize, replay_capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
-        Self { sender }
+        let (sequenced, _) = broadcast::channel(capacity);
+        // Seed sequence ids from wall-clock millis so ids keep increasing across
+        // restarts; a client holding an id from a previous run then falls
+        // outside the replay ring and is told to resync instead of silently
+        // matching unrelated events.
+        let next_seq = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+            + 1;
+        Self {
+            sender,
+            sequenced,
+            replay: Arc::new(Mutex::new(ReplayState {
+                next_seq,
+                ring: VecDeque::with_capacity(replay_capacity),
+                capacity: replay_capacity,
+            })),
+            published: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn replay_state(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        // Poisoning can only come from a panic mid-publish; the ring is still usable
+        self.replay.lock().unwrap_or_else(|e| e.into_inner())
     }
 
     /// Publish an event to all subscribers
     pub fn publish(&self, event: BusEvent) {
+        let mut replay = self.replay_state();
+        let seq = replay.next_seq;
+        replay.next_seq += 1;
+        let sequenced = SequencedEvent {
+            seq,
+            event: event.clone(),
+        };
+        if replay.capacity > 0 {
+            if replay.ring.len() >= replay.capacity {
+                replay.ring.pop_front();
+            }
+            replay.ring.push_back(sequenced.clone());
+        }
+        self.published.fetch_add(1, Ordering::Relaxed);
         // Ignore send errors (no subscribers)
+        let _ = self.sequenced.send(sequenced);
         let _ = self.sender.send(event);
     }
 
@@ -39,9 +122,74 @@ impl EventBus {
         self.sender.subscribe()
     }
 
+    /// Subscribe to all events with their sequence ids
+    pub fn subscribe_sequenced(&self) -> broadcast::Receiver<SequencedEvent> {
+        self.sequenced.subscribe()
+    }
+
+    /// Sequence id of the most recently published event
+    pub fn last_seq(&self) -> u64 {
+        self.replay_state().next_seq - 1
+    }
+
+    /// Resume a sequenced subscription after `last_seq`.
+    ///
+    /// Returns the events the client missed plus a live receiver that picks up
+    /// exactly where they end. If `last_seq` is older than the replay ring (or
+    /// unknown to this process), `resync_required` is set and `missed` is empty.
+    pub fn resume(&self, last_seq: u64) -> Resume {
+        // Hold the lock while subscribing so no event lands between the
+        // replayed slice and the live receiver
+        let replay = self.replay_state();
+        let rx = self.sequenced.subscribe();
+        let head = replay.next_seq - 1;
+        let oldest = replay
+            .ring
+            .front()
+            .map(|e| e.seq)
+            .unwrap_or(replay.next_seq);
+
+        if last_seq == head {
+            return Resume {
+                missed: Vec::new(),
+                resync_required: false,
+                rx,
+            };
+        }
+        if last_seq > head || last_seq.saturating_add(1) < oldest {
+            return Resume {
+                missed: Vec::new(),
+                resync_required: true,
+                rx,
+            };
+        }
+
+        let missed = replay
+            .ring
+            .iter()
+            .filter(|e| e.seq > last_seq)
+            .cloned()
+            .collect();
+        Resume {
+            missed,
+            resync_required: false,
+            rx,
+        }
+    }
+
+    /// Number of events published since the bus was created
+    pub fn published(&self) -> u64 {
+        self.published.load(Ordering::Relaxed)
+    }
+
+    /// Events still queued for the slowest subscriber (its receive lag)
+    pub fn queue_depth(&self) -> usize {
+        self.sender.len().max(self.sequenced.len())
+    }
+
     /// Get the number of current subscribers
     pub fn subscriber_count(&self) -> usize {
-        self.sender.receiver_count()
+        self.sender.receiver_count() + self.sequenced.receiver_count()
     }
 }
 
@@ -93,4 +241,90 @@ mod tests {
             BusEvent::RoonDisconnected
         ));
     }
+
+    #[tokio::test]
+    async fn test_sequence_ids_increase() {
+        let bus = create_bus();
+        let mut rx = bus.subscribe_sequenced();
+
+        bus.publish(BusEvent::RoonDisconnected);
+        bus.publish(BusEvent::HealthCheck { timestamp: 0 });
+
+        let first = rx.recv().await.unwrap();
+        let second = rx.recv().await.unwrap();
+        assert_eq!(second.seq, first.seq + 1);
+        assert_eq!(bus.last_seq(), second.seq);
+    }
+
+    #[tokio::test]
+    async fn test_queue_depth_tracks_slowest_subscriber() {
+        let bus = create_bus();
+        let mut rx = bus.subscribe();
+
+        bus.publish(BusEvent::RoonDisconnected);
+        bus.publish(BusEvent::HealthCheck { timestamp: 0 });
+        assert_eq!(bus.published(), 2);
+        assert_eq!(bus.queue_depth(), 2);
+
+        rx.recv().await.unwrap();
+        rx.recv().await.unwrap();
+        assert_eq!(bus.queue_depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_resume_replays_missed_events() {
+        let bus = create_bus();
+        bus.publish(BusEvent::RoonDisconnected);
+        let seen = bus.last_seq();
+        bus.publish(BusEvent::HealthCheck { timestamp: 0 });
+        bus.publish(BusEvent::LmsDisconnected {
+            host: "lms".to_string(),
+        });
+
+        let mut resume = bus.resume(seen);
+        assert!(!resume.resync_required);
+        assert_eq!(resume.missed.len(), 2);
+        assert_eq!(resume.missed[0].seq, seen + 1);
+        assert!(matches!(
+            resume.missed[1].event,
+            BusEvent::LmsDisconnected { .. }
+        ));
+
+        // Live receiver continues right after the replayed slice
+        bus.publish(BusEvent::RoonDisconnected);
+        assert_eq!(resume.rx.recv().await.unwrap().seq, seen + 3);
+    }
+
+    #[test]
+    fn test_resume_up_to_date() {
+        let bus = create_bus();
+        bus.publish(BusEvent::HealthCheck { timestamp: 0 });
+
+        let resume = bus.resume(bus.last_seq());
+        assert!(!resume.resync_required);
+        assert!(resume.missed.is_empty());
+    }
+
+    #[test]
+    fn test_resume_gap_too_old_requires_resync() {
+        let bus = EventBus::with_replay_capacity(16, 2);
+        bus.publish(BusEvent::HealthCheck { timestamp: 0 });
+        let seen = bus.last_seq();
+        for _ in 0..3 {
+            bus.publish(BusEvent::HealthCheck { timestamp: 0 });
+        }
+
+        let resume = bus.resume(seen);
+        assert!(resume.resync_required);
+        assert!(resume.missed.is_empty());
+    }
+
+    #[test]
+    fn test_resume_unknown_future_id_requires_resync() {
+        let bus = create_bus();
+        bus.publish(BusEvent::HealthCheck { timestamp: 0 });
+
+        let resume = bus.resume(bus.last_seq() + 100);
+        assert!(resume.resync_required);
+    }
 }
@@ -16,12 +16,44 @@ pub struct Config {
 
     #[serde(default)]
     pub lms: Option<LmsConfig>,
+
+    #[serde(default)]
+    pub tls: TlsConfig,
 }
 
 fn default_port() -> u16 {
     8088
 }
 
+/// HTTPS listener, in addition to plain HTTP on `port`
+/// (UHC_TLS__ENABLED, UHC_TLS__PORT, UHC_TLS__CERT, UHC_TLS__KEY)
+#[derive(Debug, Deserialize)]
+pub struct TlsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_tls_port")]
+    pub port: u16,
+    /// PEM certificate chain; a self-signed certificate is generated when unset
+    pub cert: Option<std::path::PathBuf>,
+    /// PEM private key for `cert`
+    pub key: Option<std::path::PathBuf>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_tls_port(),
+            cert: None,
+            key: None,
+        }
+    }
+}
+
+fn default_tls_port() -> u16 {
+    8443
+}
+
 #[derive(Debug, Default, Deserialize)]
 pub struct RoonConfig {
     pub extension_id: Option<String>,
@@ -480,6 +512,29 @@ mod tests {
         );
     }
 
+    #[test]
+    #[serial]
+    fn test_tls_env_config() {
+        let _g1 = EnvGuard::set("UHC_CONFIG_DIR", "/tmp/uhc-test-nonexistent");
+        let config = load_config().expect("config should load");
+        assert!(!config.tls.enabled, "HTTPS should be off by default");
+        assert_eq!(config.tls.port, 8443);
+
+        let _g2 = EnvGuard::set("UHC_TLS__ENABLED", "true");
+        let _g3 = EnvGuard::set("UHC_TLS__PORT", "9443");
+        let _g4 = EnvGuard::set("UHC_TLS__CERT", "/etc/uhc/cert.pem");
+
+        let config = load_config().expect("config should load");
+
+        assert!(config.tls.enabled);
+        assert_eq!(config.tls.port, 9443);
+        assert_eq!(
+            config.tls.cert.as_deref(),
+            Some(std::path::Path::new("/etc/uhc/cert.pem"))
+        );
+        assert!(config.tls.key.is_none());
+    }
+
     #[test]
     #[serial]
     fn test_invalid_port_uses_default() {
@@ -680,48 +735,55 @@ fn migrate_hqp_config(data_dir: &std::path::Path) {
         Err(_) => return,
     };
 
-    // Check if it's Node.js format (single object without web_port field)
-    // Node.js format: {"host":"...", "port":8088, "username":"...", "password":"..."}
-    // Rust format: {"host":"...", "port":4321, "web_port":8088, ...} or array format
-    if let Ok(value) = serde_json::from_str::<serde_json::Value>(&content) {
-        // Skip if already migrated (has web_port or is array format)
-        if value.is_array() {
-            return;
-        }
-        if value.get("web_port").is_some() {
-            return;
-        }
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return;
+    };
+    let Some(rust_config) = migrate_hqp_config_value(&value) else {
+        return;
+    };
 
-        // It's Node.js single-object format - convert it
-        if let Some(obj) = value.as_object() {
-            let host = obj.get("host").and_then(|v| v.as_str()).unwrap_or("");
-            let nodejs_port = obj.get("port").and_then(|v| v.as_u64()).unwrap_or(8088) as u16;
-            let username = obj.get("username").and_then(|v| v.as_str());
-            let password = obj.get("password").and_then(|v| v.as_str());
-
-            // In Node.js, "port" is the web UI port (8088)
-            // In Rust, "port" is the native protocol port (4321), "web_port" is web UI
-            let rust_config = serde_json::json!([{
-                "name": "default",
-                "host": host,
-                "port": 4321,  // Native protocol port
-                "web_port": nodejs_port,  // Node.js port becomes web_port
-                "username": username,
-                "password": password
-            }]);
-
-            if let Ok(json) = serde_json::to_string_pretty(&rust_config) {
-                match std::fs::write(&hqp_path, &json) {
-                    Ok(()) => {
-                        tracing::info!(
-                            "Migrated HQPlayer config from Node.js format (port {} → web_port {})",
-                            nodejs_port,
-                            nodejs_port
-                        );
-                    }
-                    Err(e) => tracing::warn!("Failed to write migrated HQP config: {}", e),
-                }
+    let nodejs_port = &rust_config[0]["web_port"];
+
+    if let Ok(json) = serde_json::to_string_pretty(&rust_config) {
+        match std::fs::write(&hqp_path, &json) {
+            Ok(()) => {
+                tracing::info!(
+                    "Migrated HQPlayer config from Node.js format (port {} → web_port {})",
+                    nodejs_port,
+                    nodejs_port
+                );
             }
+            Err(e) => tracing::warn!("Failed to write migrated HQP config: {}", e),
         }
     }
 }
+
+/// Convert a Node.js-format HQPlayer config to the Rust format.
+/// Returns None when the value is already in the Rust format.
+pub fn migrate_hqp_config_value(value: &serde_json::Value) -> Option<serde_json::Value> {
+    // Check if it's Node.js format (single object without web_port field)
+    // Node.js format: {"host":"...", "port":8088, "username":"...", "password":"..."}
+    // Rust format: {"host":"...", "port":4321, "web_port":8088, ...} or array format
+    // Skip if already migrated (has web_port or is array format)
+    if value.is_array() || value.get("web_port").is_some() {
+        return None;
+    }
+
+    // It's Node.js single-object format - convert it
+    let obj = value.as_object()?;
+    let host = obj.get("host").and_then(|v| v.as_str()).unwrap_or("");
+    let nodejs_port = obj.get("port").and_then(|v| v.as_u64()).unwrap_or(8088) as u16;
+    let username = obj.get("username").and_then(|v| v.as_str());
+    let password = obj.get("password").and_then(|v| v.as_str());
+
+    // In Node.js, "port" is the web UI port (8088)
+    // In Rust, "port" is the native protocol port (4321), "web_port" is web UI
+    Some(serde_json::json!([{
+        "name": "default",
+        "host": host,
+        "port": 4321,  // Native protocol port
+        "web_port": nodejs_port,  // Node.js port becomes web_port
+        "username": username,
+        "password": password
+    }]))
+}
@@ -0,0 +1,899 @@
+//! Declarative configuration file
+//!
+//! One optional file declares adapters, the LMS servers, HQPlayer instances, zone
+//! links, knob defaults and volume policy, for deployments managed as code. It is
+//! `unified-hifi.toml` (or `.yaml`, `.yml`, `.json`) in the config dir, or the path
+//! in `UHC_CONFIG_FILE`.
+//!
+//! The file is validated at startup and an invalid file stops the bridge. It is then
+//! polled for changes: a valid edit is applied without restarting, an invalid one is
+//! logged and ignored until it is fixed. `--check-config` validates a file without
+//! starting anything.
+//!
+//! Each section that is present is authoritative for what it covers and is written
+//! through to the usual stores (`app-settings.json`, `hqp-config.json`, ...), so the
+//! web UI shows the declared state. Sections left out stay editable in the UI. On
+//! reload only sections that changed are applied.
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+use crate::adapters::hqplayer::{
+    load_hqp_configs, HqpInstanceConfig, DEFAULT_PORT as HQP_PORT, DEFAULT_WEB_PORT as HQP_WEB_PORT,
+};
+use crate::adapters::lms::{
+    server_id_for_host, validate_server_id, SavedLmsServer, DEFAULT_PORT as LMS_PORT,
+};
+use crate::api::{
+    add_lms_server, apply_adapter_settings, remove_lms_server, save_app_settings, AdapterSettings,
+    AppSettings, AppState, LmsAddServerRequest,
+};
+use crate::config::get_config_dir;
+use crate::knobs::store::KnobConfig;
+use crate::volume_policy::{VolumePolicies, VolumePolicy};
+
+/// Environment variable overriding the config file path
+pub const CONFIG_FILE_ENV: &str = "UHC_CONFIG_FILE";
+
+/// File name (without extension) looked up in the config dir
+const CONFIG_FILE_STEM: &str = "unified-hifi";
+
+/// Supported formats, in lookup order
+const CONFIG_FILE_EXTENSIONS: &[&str] = &["toml", "yaml", "yml", "json"];
+
+/// How often the file is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Path of the config file, if one is in use
+pub fn config_file_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(CONFIG_FILE_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    let dir = get_config_dir();
+    CONFIG_FILE_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", CONFIG_FILE_STEM, ext)))
+        .find(|path| path.exists())
+}
+
+/// Adapter toggles; adapters left out keep their current setting
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredAdapters {
+    pub roon: Option<bool>,
+    pub lms: Option<bool>,
+    pub openhome: Option<bool>,
+    pub upnp: Option<bool>,
+    pub hqplayer: Option<bool>,
+}
+
+impl DeclaredAdapters {
+    fn apply_to(&self, current: &AdapterSettings) -> AdapterSettings {
+        AdapterSettings {
+            roon: self.roon.unwrap_or(current.roon),
+            lms: self.lms.unwrap_or(current.lms),
+            openhome: self.openhome.unwrap_or(current.openhome),
+            upnp: self.upnp.unwrap_or(current.upnp),
+            hqplayer: self.hqplayer.unwrap_or(current.hqplayer),
+        }
+    }
+}
+
+/// Primary LMS server
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredLms {
+    pub host: String,
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+/// Additional LMS server (`lms-servers.json`)
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredLmsServer {
+    /// Zone namespace (`lms:<id>/<player_id>`); derived from the host when unset
+    pub id: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl DeclaredLmsServer {
+    fn server_id(&self) -> String {
+        self.id
+            .clone()
+            .unwrap_or_else(|| server_id_for_host(&self.host))
+    }
+
+    /// The server as the LMS adapter saves it
+    fn to_saved(&self) -> SavedLmsServer {
+        SavedLmsServer {
+            id: self.server_id(),
+            host: self.host.clone(),
+            port: self.port.unwrap_or(LMS_PORT),
+            username: self.username.clone(),
+            password: self.password.clone(),
+        }
+    }
+}
+
+/// HQPlayer instance
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredHqpInstance {
+    pub name: String,
+    pub host: String,
+    pub port: Option<u16>,
+    pub web_port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl DeclaredHqpInstance {
+    /// The instance as `HqpInstanceManager` saves it
+    fn to_instance_config(&self) -> HqpInstanceConfig {
+        HqpInstanceConfig {
+            name: self.name.clone(),
+            host: self.host.clone(),
+            port: self.port.unwrap_or(HQP_PORT),
+            web_port: self.web_port.unwrap_or(HQP_WEB_PORT),
+            username: self.username.clone(),
+            password: self.password.clone(),
+        }
+    }
+}
+
+/// Zone routed through an HQPlayer instance
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredZoneLink {
+    pub zone_id: String,
+    pub instance: String,
+}
+
+/// Knob settings
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredKnobs {
+    /// Config for newly registered knobs, same keys as the knob config API.
+    /// Settings left out use the built-in defaults.
+    #[serde(default)]
+    pub defaults: Map<String, Value>,
+}
+
+/// Volume limits (see `volume_policy`)
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredPolicy {
+    pub max_volume: Option<f32>,
+    pub max_step: Option<f32>,
+    pub start_cap: Option<f32>,
+}
+
+impl DeclaredPolicy {
+    fn to_policy(&self) -> VolumePolicy {
+        VolumePolicy {
+            max_volume: self.max_volume,
+            max_step: self.max_step,
+            start_cap: self.start_cap,
+        }
+    }
+}
+
+/// Volume limits for one zone
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredZonePolicy {
+    pub zone_id: String,
+    pub max_volume: Option<f32>,
+    pub max_step: Option<f32>,
+    pub start_cap: Option<f32>,
+}
+
+/// Volume policy: the default plus per-zone overrides
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredVolumePolicy {
+    #[serde(default)]
+    pub default: DeclaredPolicy,
+    #[serde(default)]
+    pub zones: Vec<DeclaredZonePolicy>,
+}
+
+impl DeclaredVolumePolicy {
+    fn to_policies(&self) -> VolumePolicies {
+        VolumePolicies {
+            default: self.default.to_policy(),
+            zones: self
+                .zones
+                .iter()
+                .map(|z| {
+                    let policy = VolumePolicy {
+                        max_volume: z.max_volume,
+                        max_step: z.max_step,
+                        start_cap: z.start_cap,
+                    };
+                    (z.zone_id.clone(), policy)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Contents of the config file; every section is optional
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredConfig {
+    pub adapters: Option<DeclaredAdapters>,
+    pub lms: Option<DeclaredLms>,
+    pub lms_servers: Option<Vec<DeclaredLmsServer>>,
+    pub hqplayer: Option<Vec<DeclaredHqpInstance>>,
+    pub zone_links: Option<Vec<DeclaredZoneLink>>,
+    pub knobs: Option<DeclaredKnobs>,
+    pub volume_policy: Option<DeclaredVolumePolicy>,
+}
+
+impl DeclaredConfig {
+    /// Parse and validate a config file
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(anyhow!("{}: file not found", path.display()));
+        }
+        let config: Self = ::config::Config::builder()
+            .add_source(::config::File::from(path).required(true))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .with_context(|| format!("{}: invalid config", path.display()))?;
+
+        let errors = config.validate();
+        if !errors.is_empty() {
+            return Err(anyhow!(
+                "{}: invalid config\n  {}",
+                path.display(),
+                errors.join("\n  ")
+            ));
+        }
+        Ok(config)
+    }
+
+    /// Every problem found, each prefixed with the path of the offending value
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if let Some(lms) = &self.lms {
+            if lms.host.trim().is_empty() {
+                errors.push("lms.host: must not be empty".to_string());
+            }
+            if lms.port == Some(0) {
+                errors.push("lms.port: must not be 0".to_string());
+            }
+        }
+
+        if let Some(servers) = &self.lms_servers {
+            for (i, server) in servers.iter().enumerate() {
+                if server.host.trim().is_empty() {
+                    errors.push(format!("lms_servers[{}].host: must not be empty", i));
+                }
+                if server.port == Some(0) {
+                    errors.push(format!("lms_servers[{}].port: must not be 0", i));
+                }
+                let id = server.server_id();
+                if let Err(e) = validate_server_id(&id) {
+                    errors.push(format!("lms_servers[{}].id: {}", i, e));
+                } else if servers[..i].iter().any(|o| o.server_id() == id) {
+                    errors.push(format!(
+                        "lms_servers[{}].id: duplicate server \"{}\"",
+                        i, id
+                    ));
+                }
+            }
+        }
+
+        if let Some(instances) = &self.hqplayer {
+            for (i, instance) in instances.iter().enumerate() {
+                if instance.name.trim().is_empty() {
+                    errors.push(format!("hqplayer[{}].name: must not be empty", i));
+                } else if instances[..i].iter().any(|o| o.name == instance.name) {
+                    errors.push(format!(
+                        "hqplayer[{}].name: duplicate instance \"{}\"",
+                        i, instance.name
+                    ));
+                }
+                if instance.host.trim().is_empty() {
+                    errors.push(format!("hqplayer[{}].host: must not be empty", i));
+                }
+                for (field, port) in [("port", instance.port), ("web_port", instance.web_port)] {
+                    if port == Some(0) {
+                        errors.push(format!("hqplayer[{}].{}: must not be 0", i, field));
+                    }
+                }
+            }
+        }
+
+        if let Some(links) = &self.zone_links {
+            for (i, link) in links.iter().enumerate() {
+                if !link.zone_id.contains(':') {
+                    errors.push(format!(
+                        "zone_links[{}].zone_id: \"{}\" is not a prefixed zone id (e.g. \"roon:...\")",
+                        i, link.zone_id
+                    ));
+                } else if links[..i].iter().any(|o| o.zone_id == link.zone_id) {
+                    errors.push(format!(
+                        "zone_links[{}].zone_id: \"{}\" is linked twice",
+                        i, link.zone_id
+                    ));
+                }
+                // Instances declared here are checked now; others when applied
+                if let Some(instances) = &self.hqplayer {
+                    if !instances.iter().any(|o| o.name == link.instance) {
+                        errors.push(format!(
+                            "zone_links[{}].instance: \"{}\" is not declared in hqplayer",
+                            i, link.instance
+                        ));
+                    }
+                }
+            }
+        }
+
+        if let Some(knobs) = &self.knobs {
+            if let Err(e) = knob_defaults(&knobs.defaults) {
+                errors.push(e);
+            }
+        }
+
+        if let Some(policy) = &self.volume_policy {
+            if let Err(e) = policy.default.to_policy().validate() {
+                errors.push(format!("volume_policy.default: {}", e));
+            }
+            for (i, zone) in policy.zones.iter().enumerate() {
+                if zone.zone_id.trim().is_empty() {
+                    errors.push(format!(
+                        "volume_policy.zones[{}].zone_id: must not be empty",
+                        i
+                    ));
+                } else if policy.zones[..i].iter().any(|o| o.zone_id == zone.zone_id) {
+                    errors.push(format!(
+                        "volume_policy.zones[{}].zone_id: \"{}\" appears twice",
+                        i, zone.zone_id
+                    ));
+                }
+            }
+            for (zone_id, zone) in policy.to_policies().zones {
+                if let Err(e) = zone.validate() {
+                    let i = policy
+                        .zones
+                        .iter()
+                        .position(|z| z.zone_id == zone_id)
+                        .unwrap_or_default();
+                    errors.push(format!("volume_policy.zones[{}]: {}", i, e));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// App settings with the declared adapter toggles applied
+    pub fn app_settings(&self, current: &AppSettings) -> AppSettings {
+        let mut settings = current.clone();
+        if let Some(adapters) = &self.adapters {
+            settings.adapters = adapters.apply_to(&current.adapters);
+        }
+        settings
+    }
+
+    /// Write the declared sections through to the stores.
+    /// With `previous` (a reload), sections that did not change are skipped and a
+    /// changed LMS server is reconnected. Adapter toggles are handled by the caller.
+    pub async fn apply(&self, state: &AppState, previous: Option<&DeclaredConfig>) {
+        let mut applied = Vec::new();
+
+        if let Some(lms) = &self.lms {
+            if previous.is_none_or(|p| p.lms.as_ref() != Some(lms)) {
+                let reload = previous.is_some();
+                if reload {
+                    state.lms.stop().await;
+                }
+                state
+                    .lms
+                    .configure(
+                        lms.host.clone(),
+                        lms.port,
+                        lms.username.clone(),
+                        lms.password.clone(),
+                    )
+                    .await;
+                if reload && state.coordinator.is_enabled("lms").await {
+                    if let Err(e) = state.lms.start().await {
+                        warn!("Config file: failed to start LMS: {}", e);
+                    }
+                }
+                applied.push("lms");
+            }
+        }
+
+        if let Some(servers) = &self.lms_servers {
+            if previous.is_none_or(|p| p.lms_servers.as_ref() != Some(servers)) {
+                apply_lms_servers(state, servers).await;
+                applied.push("lms_servers");
+            }
+        }
+
+        if let Some(instances) = &self.hqplayer {
+            if previous.is_none_or(|p| p.hqplayer.as_ref() != Some(instances)) {
+                apply_hqp_instances(state, instances).await;
+                applied.push("hqplayer");
+            }
+        }
+
+        if let Some(links) = &self.zone_links {
+            // Re-linked after instance changes, which drop links to removed instances
+            if previous.is_none_or(|p| p.zone_links.as_ref() != Some(links))
+                || applied.contains(&"hqplayer")
+            {
+                apply_zone_links(state, links).await;
+                applied.push("zone_links");
+            }
+        }
+
+        if let Some(knobs) = &self.knobs {
+            if previous.is_none_or(|p| p.knobs.as_ref() != Some(knobs)) {
+                match knob_defaults(&knobs.defaults) {
+                    Ok(config) => {
+                        state.knobs.set_defaults(config).await;
+                        applied.push("knobs");
+                    }
+                    Err(e) => warn!("Config file: {}", e),
+                }
+            }
+        }
+
+        if let Some(policy) = &self.volume_policy {
+            let policies = policy.to_policies();
+            let changed = previous.is_none_or(|p| p.volume_policy.as_ref() != Some(policy));
+            if changed && state.volume_policy.get().await != policies {
+                match state.volume_policy.set(policies).await {
+                    Ok(()) => applied.push("volume_policy"),
+                    Err(e) => warn!("Config file: volume_policy: {}", e),
+                }
+            }
+        }
+
+        if !applied.is_empty() {
+            info!("Config file applied: {}", applied.join(", "));
+        }
+    }
+}
+
+/// Knob config from the declared defaults, or an error naming the bad setting
+fn knob_defaults(defaults: &Map<String, Value>) -> std::result::Result<KnobConfig, String> {
+    let base = match serde_json::to_value(KnobConfig::default()) {
+        Ok(Value::Object(base)) => base,
+        _ => return Err("knobs.defaults: built-in defaults unavailable".to_string()),
+    };
+
+    let mut merged = base.clone();
+    for (key, value) in defaults {
+        if !base.contains_key(key) {
+            return Err(format!("knobs.defaults.{}: unknown setting", key));
+        }
+        // Check each setting on its own so the error names it
+        let mut single = base.clone();
+        single.insert(key.clone(), value.clone());
+        if let Err(e) = serde_json::from_value::<KnobConfig>(Value::Object(single)) {
+            return Err(format!("knobs.defaults.{}: {}", key, e));
+        }
+        merged.insert(key.clone(), value.clone());
+    }
+
+    serde_json::from_value(Value::Object(merged)).map_err(|e| format!("knobs.defaults: {}", e))
+}
+
+/// Make the additional LMS servers exactly the declared ones
+async fn apply_lms_servers(state: &AppState, servers: &[DeclaredLmsServer]) {
+    let current = state.lms.server_configs().await;
+
+    // Removed first, so a server moved to a new id isn't refused as a duplicate
+    for config in &current {
+        if !servers.iter().any(|s| s.server_id() == config.id) {
+            remove_lms_server(state, &config.id).await;
+        }
+    }
+
+    for server in servers {
+        if current.contains(&server.to_saved()) {
+            continue;
+        }
+        let request = LmsAddServerRequest {
+            id: Some(server.server_id()),
+            host: server.host.clone(),
+            port: server.port,
+            username: server.username.clone(),
+            password: server.password.clone(),
+        };
+        if let Err(e) = add_lms_server(state, request).await {
+            warn!("Config file: lms_servers: {}: {}", server.host, e);
+        }
+    }
+}
+
+/// Make the HQPlayer instances exactly the declared ones
+async fn apply_hqp_instances(state: &AppState, instances: &[DeclaredHqpInstance]) {
+    let current = load_hqp_configs();
+
+    for instance in instances {
+        let config = instance.to_instance_config();
+        if current.iter().any(|c| *c == config) {
+            continue;
+        }
+        state
+            .hqp_instances
+            .add_instance(
+                config.name,
+                config.host,
+                Some(config.port),
+                Some(config.web_port),
+                config.username,
+                config.password,
+            )
+            .await;
+    }
+
+    for config in current {
+        if !instances.iter().any(|i| i.name == config.name) {
+            state
+                .hqp_zone_links
+                .remove_links_for_instance(&config.name)
+                .await;
+            state.hqp_instances.remove_instance(&config.name).await;
+        }
+    }
+}
+
+/// Make the zone links exactly the declared ones
+async fn apply_zone_links(state: &AppState, links: &[DeclaredZoneLink]) {
+    for link in state.hqp_zone_links.get_links().await {
+        let declared = links
+            .iter()
+            .any(|l| l.zone_id == link.zone_id && l.instance == link.instance);
+        if !declared {
+            state.hqp_zone_links.unlink_zone(&link.zone_id).await;
+        }
+    }
+
+    let current = state.hqp_zone_links.get_links().await;
+    for link in links {
+        if current
+            .iter()
+            .any(|l| l.zone_id == link.zone_id && l.instance == link.instance)
+        {
+            continue;
+        }
+        if let Err(e) = state
+            .hqp_zone_links
+            .link_zone(link.zone_id.clone(), link.instance.clone())
+            .await
+        {
+            warn!("Config file: zone_links: {}: {}", link.zone_id, e);
+        }
+    }
+}
+
+/// Load the config file at startup, if there is one, and write its adapter
+/// toggles to the app settings before adapters are registered
+pub fn load_at_startup(settings: AppSettings) -> Result<(Option<DeclaredConfig>, AppSettings)> {
+    let Some(path) = config_file_path() else {
+        return Ok((None, settings));
+    };
+
+    let declared = DeclaredConfig::load(&path)?;
+    info!("Config file loaded: {}", path.display());
+
+    let updated = declared.app_settings(&settings);
+    if updated.adapters != settings.adapters {
+        save_app_settings(&updated);
+    }
+    Ok((Some(declared), updated))
+}
+
+/// Modification time and size, to notice edits
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Poll the config file and apply valid edits until shutdown
+pub async fn watch(state: AppState, mut current: DeclaredConfig) {
+    let Some(path) = config_file_path() else {
+        return;
+    };
+    let mut stamp = file_stamp(&path);
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = state.shutdown.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+
+        let new_stamp = file_stamp(&path);
+        if new_stamp == stamp {
+            continue;
+        }
+        stamp = new_stamp;
+
+        if stamp.is_none() {
+            warn!(
+                "Config file {} removed; keeping current settings",
+                path.display()
+            );
+            continue;
+        }
+
+        let declared = match DeclaredConfig::load(&path) {
+            Ok(declared) => declared,
+            Err(e) => {
+                warn!("Ignoring config file change: {:#}", e);
+                continue;
+            }
+        };
+        if declared == current {
+            continue;
+        }
+        info!("Config file changed, applying");
+
+        if declared.adapters != current.adapters {
+            let settings = crate::api::load_app_settings();
+            let updated = declared.app_settings(&settings);
+            if updated.adapters != settings.adapters && save_app_settings(&updated) {
+                apply_adapter_settings(&state, &settings.adapters, &updated.adapters).await;
+            }
+        }
+        declared.apply(&state, Some(&current)).await;
+        current = declared;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(dir: &tempfile::TempDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn load_err(name: &str, content: &str) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(&dir, name, content);
+        format!("{:#}", DeclaredConfig::load(&path).unwrap_err())
+    }
+
+    #[test]
+    fn test_load_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(
+            &dir,
+            "unified-hifi.toml",
+            r#"
+[adapters]
+lms = true
+upnp = false
+
+[lms]
+host = "192.168.1.10"
+
+[[lms_servers]]
+host = "upstairs.local"
+
+[[lms_servers]]
+id = "garage"
+host = "192.168.1.30"
+port = 9002
+
+[[hqplayer]]
+name = "den"
+host = "192.168.1.20"
+web_port = 8089
+
+[[zone_links]]
+zone_id = "roon:1601"
+instance = "den"
+
+[knobs.defaults]
+rotation_charging = 0
+volume_step_override = 2.0
+
+[volume_policy.default]
+max_volume = 80
+
+[[volume_policy.zones]]
+zone_id = "lms:00:04:20:aa:bb:cc"
+max_volume = 60
+max_step = 5
+"#,
+        );
+
+        let config = DeclaredConfig::load(&path).unwrap();
+
+        let adapters = config.adapters.clone().unwrap();
+        assert_eq!(adapters.lms, Some(true));
+        assert_eq!(adapters.roon, None);
+        assert_eq!(config.lms.as_ref().unwrap().port, None);
+
+        let servers = config.lms_servers.as_ref().unwrap();
+        assert_eq!(servers[0].to_saved().id, "upstairs-local");
+        assert_eq!(servers[0].to_saved().port, 9000);
+        assert_eq!(servers[1].to_saved().id, "garage");
+
+        let hqp = config.hqplayer.as_ref().unwrap()[0].to_instance_config();
+        assert_eq!(hqp.port, 4321);
+        assert_eq!(hqp.web_port, 8089);
+
+        let knob = knob_defaults(&config.knobs.as_ref().unwrap().defaults).unwrap();
+        assert_eq!(knob.rotation_charging, 0);
+        assert_eq!(knob.volume_step_override, Some(2.0));
+        assert_eq!(knob.sleep_poll_stopped_sec, 60);
+
+        let policies = config.volume_policy.as_ref().unwrap().to_policies();
+        assert_eq!(policies.default.max_volume, Some(80.0));
+        assert_eq!(policies.zones["lms:00:04:20:aa:bb:cc"].max_step, Some(5.0));
+    }
+
+    #[test]
+    fn test_load_yaml_and_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = write_config(
+            &dir,
+            "a.yaml",
+            "adapters:\n  roon: false\nhqplayer:\n  - name: den\n    host: hqp.local\n",
+        );
+        let json = write_config(
+            &dir,
+            "b.json",
+            r#"{"adapters": {"roon": false}, "hqplayer": [{"name": "den", "host": "hqp.local"}]}"#,
+        );
+
+        let from_yaml = DeclaredConfig::load(&yaml).unwrap();
+        assert_eq!(from_yaml, DeclaredConfig::load(&json).unwrap());
+        assert_eq!(from_yaml.adapters.unwrap().roon, Some(false));
+    }
+
+    #[test]
+    fn test_empty_file_declares_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(&dir, "unified-hifi.toml", "");
+        assert_eq!(
+            DeclaredConfig::load(&path).unwrap(),
+            DeclaredConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_unknown_and_mistyped_keys_rejected() {
+        let err = load_err("c.toml", "[adaptors]\nlms = true\n");
+        assert!(err.contains("adaptors"), "{}", err);
+
+        let err = load_err("c.toml", "[adapters]\nlms = \"maybe\"\n");
+        assert!(err.contains("adapters.lms"), "{}", err);
+
+        let err = load_err(
+            "c.toml",
+            "[[hqplayer]]\nname = \"den\"\nhost = \"h\"\nweb_prot = 1\n",
+        );
+        assert!(err.contains("web_prot"), "{}", err);
+    }
+
+    #[test]
+    fn test_validation_names_offending_value() {
+        let err = load_err(
+            "c.toml",
+            r#"
+[[lms_servers]]
+host = "a.local"
+
+[[lms_servers]]
+id = "a-local"
+host = "b.local"
+
+[[lms_servers]]
+id = "Upstairs"
+host = "c.local"
+
+[[hqplayer]]
+name = "den"
+host = "a"
+
+[[hqplayer]]
+name = "den"
+host = ""
+
+[[zone_links]]
+zone_id = "1601"
+instance = "den"
+
+[[zone_links]]
+zone_id = "roon:1601"
+instance = "study"
+
+[knobs.defaults]
+rotaton_charging = 90
+
+[[volume_policy.zones]]
+zone_id = "roon:1601"
+max_volume = 50
+start_cap = 70
+"#,
+        );
+
+        assert!(
+            err.contains("lms_servers[1].id: duplicate server \"a-local\""),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("lms_servers[2].id: Invalid server id"),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("hqplayer[1].name: duplicate instance \"den\""),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("hqplayer[1].host: must not be empty"),
+            "{}",
+            err
+        );
+        assert!(err.contains("zone_links[0].zone_id"), "{}", err);
+        assert!(
+            err.contains("zone_links[1].instance: \"study\" is not declared"),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("knobs.defaults.rotaton_charging: unknown setting"),
+            "{}",
+            err
+        );
+        assert!(err.contains("volume_policy.zones[0]: start_cap"), "{}", err);
+    }
+
+    #[test]
+    fn test_knob_default_type_error_names_setting() {
+        let mut defaults = Map::new();
+        defaults.insert("sleep_poll_stopped_sec".to_string(), Value::from("soon"));
+        let err = knob_defaults(&defaults).unwrap_err();
+        assert!(
+            err.starts_with("knobs.defaults.sleep_poll_stopped_sec:"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_app_settings_overlay_keeps_undeclared() {
+        let config = DeclaredConfig {
+            adapters: Some(DeclaredAdapters {
+                lms: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let current = AppSettings::default();
+
+        let updated = config.app_settings(&current);
+        assert!(updated.adapters.lms);
+        assert_eq!(updated.adapters.roon, current.adapters.roon);
+        assert_eq!(updated.hide_knobs_page, current.hide_knobs_page);
+    }
+}
@@ -0,0 +1,182 @@
+//! Shared pieces of the JSON stores in the config dir
+//!
+//! Fades, auth, rooms, scenes, rules, schedules and volume policy each keep one
+//! JSON file. [`ConfigFile`] reads and writes it (or nothing, for the in-memory
+//! stores used in tests), and [`normalize_id`] checks the ids users give to
+//! rooms, scenes, rules and schedules.
+
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::PathBuf;
+use tokio::sync::RwLock;
+use tracing::{debug, error, warn};
+
+use crate::config::{get_config_file_path, read_config_file};
+
+/// A store's JSON file; `None` for in-memory stores (tests)
+#[derive(Debug, Clone)]
+pub(crate) struct ConfigFile {
+    file: Option<(&'static str, PathBuf)>,
+    /// What the file holds, for log messages ("fade settings", "rooms", ...)
+    what: &'static str,
+}
+
+impl ConfigFile {
+    /// File in the config dir
+    pub(crate) fn new(file: &'static str, what: &'static str) -> Self {
+        Self {
+            file: Some((file, get_config_file_path(file))),
+            what,
+        }
+    }
+
+    /// Nothing is read or written
+    pub(crate) fn in_memory(what: &'static str) -> Self {
+        Self { file: None, what }
+    }
+
+    pub(crate) fn is_in_memory(&self) -> bool {
+        self.file.is_none()
+    }
+
+    /// Saved contents; `None` if there is no file yet
+    pub(crate) fn read<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        let Some((file, _)) = &self.file else {
+            return Ok(None);
+        };
+        let Some(content) = read_config_file(file) else {
+            return Ok(None);
+        };
+        let value = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", self.what))?;
+        Ok(Some(value))
+    }
+
+    /// Saved contents, or the default when there is no file or it can't be parsed
+    pub(crate) fn load_or_default<T: DeserializeOwned + Default>(&self) -> T {
+        match self.read() {
+            Ok(value) => value.unwrap_or_default(),
+            Err(e) => {
+                warn!("{:#}", e);
+                T::default()
+            }
+        }
+    }
+
+    /// Replace `current` with a fresh read of the file (after a restore);
+    /// in-memory stores keep what they have
+    pub(crate) async fn reload<T>(&self, current: &RwLock<T>, read: impl FnOnce(&Self) -> T) {
+        if self.is_in_memory() {
+            return;
+        }
+        let value = read(self);
+        *current.write().await = value;
+    }
+
+    pub(crate) fn save<T: Serialize + ?Sized>(&self, value: &T) {
+        let Some((_, path)) = &self.file else { return };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        match serde_json::to_string_pretty(value) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    error!("Failed to save {}: {}", self.what, e);
+                } else {
+                    debug!("Saved {} to disk", self.what);
+                }
+            }
+            Err(e) => error!("Failed to serialize {}: {}", self.what, e),
+        }
+    }
+}
+
+/// Fill in a missing id from the name, then check it can be used in URLs and
+/// zone ids
+pub(crate) fn normalize_id(id: &mut String, name: &str) -> Result<()> {
+    if id.trim().is_empty() {
+        *id = slugify(name);
+    }
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        bail!("id must be lowercase letters, digits, '-' or '_'");
+    }
+    Ok(())
+}
+
+/// Lowercase slug for ids ("Living Room" -> "living-room")
+pub(crate) fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// `id`, or `id-2`, `id-3`, ... if it's taken, so a new item never replaces an
+/// existing one that happens to share its name
+pub(crate) fn unique_id(id: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(id) {
+        return id.to_string();
+    }
+    let mut n = 2;
+    loop {
+        let candidate = format!("{}-{}", id, n);
+        if !taken(&candidate) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_id() {
+        let mut id = String::new();
+        normalize_id(&mut id, "Living Room").unwrap();
+        assert_eq!(id, "living-room");
+
+        let mut id = "  ".to_string();
+        normalize_id(&mut id, "Den #2").unwrap();
+        assert_eq!(id, "den-2");
+
+        let mut id = "kept_id".to_string();
+        normalize_id(&mut id, "Other").unwrap();
+        assert_eq!(id, "kept_id");
+
+        let mut id = "Bad Id".to_string();
+        assert!(normalize_id(&mut id, "x").is_err());
+        let mut id = String::new();
+        assert!(normalize_id(&mut id, "!!!").is_err());
+    }
+
+    #[test]
+    fn test_unique_id() {
+        let taken = ["den", "den-2"];
+        assert_eq!(unique_id("den", |id| taken.contains(&id)), "den-3");
+        assert_eq!(unique_id("kitchen", |id| taken.contains(&id)), "kitchen");
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_file_is_never_read_or_written() {
+        let file = ConfigFile::in_memory("things");
+        assert!(file.read::<Vec<String>>().unwrap().is_none());
+        file.save(&["a"]);
+
+        let current = RwLock::new(vec!["kept".to_string()]);
+        file.reload(&current, |_| Vec::new()).await;
+        assert_eq!(*current.read().await, vec!["kept".to_string()]);
+    }
+}
@@ -2,11 +2,17 @@
 //!
 //! The coordinator serves as a registry of all available adapters and manages their lifecycle.
 //! It tracks which adapters are enabled and handles starting/stopping them uniformly.
+//! It also follows adapter lifecycle events on the bus to keep per-adapter health
+//! (connection state, failures, last error, retry schedule) for `/status` and the
+//! diagnostics page.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::time::Duration;
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
@@ -17,6 +23,9 @@ use crate::api::AdapterSettings;
 use crate::bus::{BusEvent, SharedBus};
 use std::sync::Arc;
 
+/// State transitions kept per adapter
+const TRANSITION_HISTORY: usize = 20;
+
 /// All available adapters in the system.
 /// This is the single source of truth for what adapters exist.
 /// Note: "lms-cli" is a companion to "lms" and shares its enabled state.
@@ -33,6 +42,184 @@ struct RegisteredAdapter {
     handle: Option<JoinHandle<()>>,
     /// Cancellation token for this adapter
     cancel: CancellationToken,
+    /// Health as seen from lifecycle events on the bus
+    health: AdapterHealth,
+}
+
+/// Connection state of an adapter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// Not running (never started, disabled or stopped)
+    Stopped,
+    /// Run loop started; no connection reported yet (or the adapter has no
+    /// backend connection of its own, like discovery-based adapters)
+    Running,
+    Connected,
+    Disconnected,
+    /// Failed; waiting for the next attempt
+    Retrying,
+}
+
+impl ConnectionState {
+    pub const ALL: [ConnectionState; 5] = [
+        ConnectionState::Stopped,
+        ConnectionState::Running,
+        ConnectionState::Connected,
+        ConnectionState::Disconnected,
+        ConnectionState::Retrying,
+    ];
+
+    /// Same name as the serialized form
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionState::Stopped => "stopped",
+            ConnectionState::Running => "running",
+            ConnectionState::Connected => "connected",
+            ConnectionState::Disconnected => "disconnected",
+            ConnectionState::Retrying => "retrying",
+        }
+    }
+}
+
+/// One change of an adapter's connection state
+#[derive(Debug, Clone, Serialize)]
+pub struct StateTransition {
+    pub at: DateTime<Utc>,
+    pub state: ConnectionState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Supervision details for an adapter
+#[derive(Debug, Clone, Serialize)]
+pub struct AdapterHealth {
+    pub state: ConnectionState,
+    /// When the current state was entered
+    pub since: Option<DateTime<Utc>>,
+    /// Failures in a row (reset by a connection or a stable run)
+    pub consecutive_failures: u32,
+    /// Run attempts since startup (first start included)
+    pub attempts: u32,
+    /// Restarts requested through the API
+    pub manual_restarts: u32,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    /// Last zone, playback or connection event seen from the adapter
+    pub last_event_at: Option<DateTime<Utc>>,
+    /// Scheduled time of the next attempt while retrying
+    pub next_retry_at: Option<DateTime<Utc>>,
+    pub retry_delay_secs: Option<u64>,
+    /// Recent state changes, oldest first
+    pub transitions: VecDeque<StateTransition>,
+}
+
+impl Default for AdapterHealth {
+    fn default() -> Self {
+        Self {
+            state: ConnectionState::Stopped,
+            since: None,
+            consecutive_failures: 0,
+            attempts: 0,
+            manual_restarts: 0,
+            last_error: None,
+            last_error_at: None,
+            last_event_at: None,
+            next_retry_at: None,
+            retry_delay_secs: None,
+            transitions: VecDeque::new(),
+        }
+    }
+}
+
+impl AdapterHealth {
+    fn transition(&mut self, state: ConnectionState, detail: Option<String>, now: DateTime<Utc>) {
+        if state == self.state && detail.is_none() {
+            return;
+        }
+        self.state = state;
+        self.since = Some(now);
+        if self.transitions.len() == TRANSITION_HISTORY {
+            self.transitions.pop_front();
+        }
+        self.transitions.push_back(StateTransition {
+            at: now,
+            state,
+            detail,
+        });
+    }
+
+    /// Update from one bus event about this adapter
+    fn apply(&mut self, event: &BusEvent, now: DateTime<Utc>) {
+        match event {
+            BusEvent::AdapterStarting { attempt, .. } => {
+                self.attempts += 1;
+                self.next_retry_at = None;
+                self.retry_delay_secs = None;
+                let detail = (*attempt > 1).then(|| format!("attempt {}", attempt));
+                self.transition(ConnectionState::Running, detail, now);
+            }
+            BusEvent::AdapterRetrying {
+                error,
+                consecutive_failures,
+                retry_in_secs,
+                ..
+            } => {
+                self.consecutive_failures = *consecutive_failures;
+                self.last_error = Some(error.clone());
+                self.last_error_at = Some(now);
+                self.retry_delay_secs = Some(*retry_in_secs);
+                self.next_retry_at = Some(now + chrono::Duration::seconds(*retry_in_secs as i64));
+                self.transition(ConnectionState::Retrying, Some(error.clone()), now);
+            }
+            BusEvent::AdapterConnected { details, .. } => {
+                self.consecutive_failures = 0;
+                self.last_event_at = Some(now);
+                self.transition(ConnectionState::Connected, details.clone(), now);
+            }
+            BusEvent::RoonConnected { core_name, .. } => {
+                self.consecutive_failures = 0;
+                self.last_event_at = Some(now);
+                self.transition(ConnectionState::Connected, Some(core_name.clone()), now);
+            }
+            BusEvent::LmsConnected { host } => {
+                self.consecutive_failures = 0;
+                self.last_event_at = Some(now);
+                self.transition(ConnectionState::Connected, Some(host.clone()), now);
+            }
+            BusEvent::AdapterDisconnected { reason, .. } => {
+                self.transition(ConnectionState::Disconnected, reason.clone(), now);
+            }
+            BusEvent::RoonDisconnected | BusEvent::LmsDisconnected { .. } => {
+                self.transition(ConnectionState::Disconnected, None, now);
+            }
+            BusEvent::AdapterStopped { .. } => {
+                self.next_retry_at = None;
+                self.retry_delay_secs = None;
+                self.transition(ConnectionState::Stopped, None, now);
+            }
+            _ => self.last_event_at = Some(now),
+        }
+    }
+}
+
+/// Adapter an event is about, for the events that feed adapter health
+fn event_adapter(event: &BusEvent) -> Option<&str> {
+    match event {
+        BusEvent::AdapterStarting { adapter, .. }
+        | BusEvent::AdapterRetrying { adapter, .. }
+        | BusEvent::AdapterConnected { adapter, .. }
+        | BusEvent::AdapterDisconnected { adapter, .. }
+        | BusEvent::AdapterStopped { adapter } => Some(adapter),
+        BusEvent::RoonConnected { .. } | BusEvent::RoonDisconnected => Some("roon"),
+        BusEvent::LmsConnected { .. } | BusEvent::LmsDisconnected { .. } => Some("lms"),
+        BusEvent::ZoneDiscovered { zone } => zone.zone_id.split(':').next(),
+        BusEvent::ZoneUpdated { zone_id, .. }
+        | BusEvent::NowPlayingChanged { zone_id, .. }
+        | BusEvent::TrackMetadataChanged { zone_id, .. }
+        | BusEvent::SeekPositionChanged { zone_id, .. } => Some(zone_id.source()),
+        _ => None,
+    }
 }
 
 /// AdapterCoordinator manages adapter lifecycle:
@@ -128,11 +315,19 @@ impl AdapterCoordinator {
                 enabled,
                 handle: None,
                 cancel: self.shutdown.child_token(),
+                health: AdapterHealth::default(),
             },
         );
         debug!("Registered adapter: {} (enabled: {})", prefix, enabled);
     }
 
+    /// Forget an adapter that no longer exists (e.g. a removed LMS server)
+    pub async fn unregister(&self, prefix: &str) {
+        if self.adapters.write().await.remove(prefix).is_some() {
+            debug!("Unregistered adapter: {}", prefix);
+        }
+    }
+
     /// Start an adapter with the given spawn function
     /// The spawn function receives (bus, cancel_token) and should spawn the adapter task
     pub async fn start_adapter<F, Fut>(&self, prefix: &str, spawn_fn: F) -> Result<()>
@@ -323,7 +518,11 @@ impl AdapterCoordinator {
                 Ok(Ok(_)) => {
                     // Other event, continue waiting
                 }
-                Ok(Err(_)) => {
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    // An ACK may be among the skipped events; keep waiting for the rest
+                    warn!("Shutdown ACK wait lagged, skipped {} events", skipped);
+                }
+                Ok(Err(RecvError::Closed)) => {
                     // Channel closed
                     break;
                 }
@@ -353,20 +552,101 @@ impl AdapterCoordinator {
                     AdapterStatus {
                         prefix: prefix.clone(),
                         enabled: adapter.enabled,
-                        running: adapter.handle.is_some(),
+                        running: adapter.handle.is_some()
+                            || !matches!(adapter.health.state, ConnectionState::Stopped),
+                        health: adapter.health.clone(),
                     },
                 )
             })
             .collect()
     }
+
+    /// Update adapter health from a bus event (ignores unrelated events)
+    pub async fn observe(&self, event: &BusEvent) {
+        let Some(name) = event_adapter(event) else {
+            return;
+        };
+        let mut adapters = self.adapters.write().await;
+        if let Some(adapter) = adapters.get_mut(name) {
+            adapter.health.apply(event, Utc::now());
+        }
+    }
+
+    /// Follow adapter lifecycle events on the bus
+    /// Should be spawned as a task; stops on coordinator shutdown
+    pub async fn run(self: Arc<Self>) {
+        let mut rx = self.bus.subscribe();
+        debug!("Adapter health tracking started");
+
+        loop {
+            tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                event = rx.recv() => match event {
+                    Ok(event) => self.observe(&event).await,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Adapter health tracking lagged, skipped {} events", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+
+        debug!("Adapter health tracking stopped");
+    }
+
+    /// Stop and start one adapter, waiting for it to stop in between
+    pub async fn restart(&self, adapter: &Arc<dyn Startable>) -> Result<()> {
+        let name = adapter.name();
+        if !self.is_enabled(name).await {
+            anyhow::bail!("Adapter {} is disabled", name);
+        }
+        if !adapter.can_start().await {
+            anyhow::bail!("Adapter {} is not configured", name);
+        }
+
+        {
+            let mut adapters = self.adapters.write().await;
+            if let Some(registered) = adapters.get_mut(name) {
+                registered.health.manual_restarts += 1;
+                registered.health.transition(
+                    registered.health.state,
+                    Some("restart requested".to_string()),
+                    Utc::now(),
+                );
+            }
+        }
+
+        info!("Restarting adapter: {}", name);
+        let mut rx = self.bus.subscribe();
+        adapter.stop().await;
+
+        // Adapters refuse to start while their previous run is still winding down
+        let stopped = tokio::time::timeout(self.shutdown_timeout, async {
+            loop {
+                match rx.recv().await {
+                    Ok(BusEvent::AdapterStopped { adapter }) if adapter == name => break,
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+        .await;
+        if stopped.is_err() {
+            warn!("Adapter {} did not report stopping, starting anyway", name);
+        }
+
+        adapter.start().await
+    }
 }
 
 /// Status information for an adapter
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AdapterStatus {
     pub prefix: String,
     pub enabled: bool,
     pub running: bool,
+    #[serde(flatten)]
+    pub health: AdapterHealth,
 }
 
 #[cfg(test)]
@@ -386,6 +666,13 @@ mod tests {
 
         coord.register("disabled", false).await;
         assert!(!coord.is_enabled("disabled").await);
+
+        coord.unregister("test").await;
+        assert!(!coord.is_enabled("test").await);
+        assert_eq!(
+            coord.registered_adapters().await,
+            vec!["disabled".to_string()]
+        );
     }
 
     #[tokio::test]
@@ -501,4 +788,91 @@ mod tests {
         assert!(!status["a"].running);
         assert!(!status["b"].enabled);
     }
+
+    #[tokio::test]
+    async fn test_health_follows_lifecycle_events() {
+        let bus = create_bus();
+        let coord = AdapterCoordinator::new(bus);
+        coord.register("openhome", true).await;
+
+        coord
+            .observe(&BusEvent::AdapterStarting {
+                adapter: "openhome".to_string(),
+                attempt: 1,
+            })
+            .await;
+        coord
+            .observe(&BusEvent::AdapterRetrying {
+                adapter: "openhome".to_string(),
+                error: "SSDP socket closed".to_string(),
+                consecutive_failures: 3,
+                retry_in_secs: 20,
+            })
+            .await;
+
+        let health = coord.adapter_status().await["openhome"].health.clone();
+        assert_eq!(health.state, ConnectionState::Retrying);
+        assert_eq!(health.consecutive_failures, 3);
+        assert_eq!(health.last_error.as_deref(), Some("SSDP socket closed"));
+        assert_eq!(health.retry_delay_secs, Some(20));
+        assert!(health.next_retry_at.is_some());
+        assert_eq!(health.transitions.len(), 2);
+
+        // Zone events from the adapter count as signs of life
+        coord
+            .observe(&BusEvent::ZoneRemoved {
+                zone_id: crate::bus::PrefixedZoneId::openhome("uuid"),
+            })
+            .await;
+        assert!(coord.adapter_status().await["openhome"]
+            .health
+            .last_event_at
+            .is_none());
+        coord
+            .observe(&BusEvent::ZoneUpdated {
+                zone_id: crate::bus::PrefixedZoneId::openhome("uuid"),
+                display_name: "Kitchen".to_string(),
+                state: "playing".to_string(),
+            })
+            .await;
+
+        coord
+            .observe(&BusEvent::AdapterStarting {
+                adapter: "openhome".to_string(),
+                attempt: 2,
+            })
+            .await;
+        let status = coord.adapter_status().await["openhome"].clone();
+        assert!(status.running);
+        assert_eq!(status.health.state, ConnectionState::Running);
+        assert_eq!(status.health.attempts, 2);
+        assert!(status.health.last_event_at.is_some());
+        assert!(status.health.next_retry_at.is_none());
+        // The last error stays visible after recovery
+        assert!(status.health.last_error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_health_ignores_unregistered_adapters() {
+        let bus = create_bus();
+        let coord = AdapterCoordinator::new(bus);
+        coord.register("roon", true).await;
+
+        coord
+            .observe(&BusEvent::RoonConnected {
+                core_name: "Core".to_string(),
+                version: "2.0".to_string(),
+            })
+            .await;
+        coord
+            .observe(&BusEvent::AdapterStarting {
+                adapter: "hqplayer".to_string(),
+                attempt: 1,
+            })
+            .await;
+
+        let status = coord.adapter_status().await;
+        assert_eq!(status.len(), 1);
+        assert_eq!(status["roon"].health.state, ConnectionState::Connected);
+    }
 }
@@ -0,0 +1,318 @@
+//! Volume fades around transport commands
+//!
+//! When enabled for a zone and the control surface a command came from, pause
+//! and stop fade the volume out first and put the original level back once the
+//! zone is silent, track skips dip and come back, and resume fades in. Fades are
+//! stepped `VolumeAbsolute` levels sent by the bridge, so they work for any zone
+//! that reports a `VolumeControl`. Any other volume command for the zone cancels a
+//! running fade. Settings are stored in `fades.json` (config dir).
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+use crate::bus::ControlSurface;
+use crate::config_store::ConfigFile;
+
+/// Fade settings file (config dir)
+pub const FADES_FILE: &str = "fades.json";
+
+/// Time between fade steps
+pub const FADE_STEP_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest fade allowed (commands wait for their fade to finish)
+const MAX_FADE_MS: u64 = 5000;
+
+/// Per-zone override
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ZoneFade {
+    pub enabled: bool,
+    /// Overrides the default duration
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+/// Fade settings: defaults, the surfaces that fade, and per-zone overrides
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FadeSettings {
+    /// Whether zones without an override fade
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_duration_ms")]
+    pub duration_ms: u64,
+    /// Fade in when playback resumes
+    #[serde(default = "default_true")]
+    pub fade_in: bool,
+    /// Control surfaces whose commands fade
+    #[serde(default = "default_surfaces")]
+    pub surfaces: BTreeSet<ControlSurface>,
+    /// Keyed by zone id (adapter zones or `room:` zones)
+    #[serde(default)]
+    pub zones: BTreeMap<String, ZoneFade>,
+}
+
+fn default_duration_ms() -> u64 {
+    1500
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_surfaces() -> BTreeSet<ControlSurface> {
+    BTreeSet::from([ControlSurface::Web, ControlSurface::Knob])
+}
+
+impl Default for FadeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration_ms: default_duration_ms(),
+            fade_in: true,
+            surfaces: default_surfaces(),
+            zones: BTreeMap::new(),
+        }
+    }
+}
+
+impl FadeSettings {
+    fn validate(&self) -> Result<()> {
+        let durations = std::iter::once(self.duration_ms)
+            .chain(self.zones.values().filter_map(|z| z.duration_ms));
+        for ms in durations {
+            if ms == 0 || ms > MAX_FADE_MS {
+                bail!("fade duration must be between 1 and {} ms", MAX_FADE_MS);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fade duration for a command, or None when it shouldn't fade.
+    /// The first of `zone_ids` with an override decides; otherwise the default.
+    pub fn duration_for(&self, zone_ids: &[&str], surface: ControlSurface) -> Option<Duration> {
+        if !self.surfaces.contains(&surface) {
+            return None;
+        }
+        let (enabled, duration_ms) = match zone_ids.iter().find_map(|id| self.zones.get(*id)) {
+            Some(zone) => (zone.enabled, zone.duration_ms.unwrap_or(self.duration_ms)),
+            None => (self.enabled, self.duration_ms),
+        };
+        enabled.then(|| Duration::from_millis(duration_ms))
+    }
+}
+
+/// Levels to step through from `from` to `to` over `duration`, ending exactly at `to`
+pub fn fade_levels(from: f32, to: f32, duration: Duration) -> Vec<f32> {
+    let steps = (duration.as_millis() / FADE_STEP_INTERVAL.as_millis()).max(1) as usize;
+    (1..=steps)
+        .map(|i| from + (to - from) * i as f32 / steps as f32)
+        .collect()
+}
+
+/// A running fade; cancelled when another fade or volume command takes over the zone
+pub struct FadeHandle {
+    id: u64,
+    pub token: CancellationToken,
+    /// Level before fading, to put back once the fade is done or fails
+    pub level: f32,
+}
+
+/// Fade settings plus the fades currently running
+pub struct Fades {
+    settings: RwLock<FadeSettings>,
+    /// Running fade per (volume) zone id, with its pre-fade level
+    active: Mutex<HashMap<String, (u64, CancellationToken, f32)>>,
+    next_id: AtomicU64,
+    file: ConfigFile,
+}
+
+impl Default for Fades {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fades {
+    /// Load settings from the config dir
+    pub fn new() -> Self {
+        let file = ConfigFile::new(FADES_FILE, "fade settings");
+        Self::with_settings(file.load_or_default(), file)
+    }
+
+    /// Settings that are never written to disk
+    pub fn in_memory() -> Self {
+        Self::with_settings(
+            FadeSettings::default(),
+            ConfigFile::in_memory("fade settings"),
+        )
+    }
+
+    /// Re-read settings from the config dir (after a restore)
+    pub async fn reload(&self) {
+        self.file
+            .reload(&self.settings, ConfigFile::load_or_default)
+            .await;
+    }
+
+    fn with_settings(settings: FadeSettings, file: ConfigFile) -> Self {
+        Self {
+            settings: RwLock::new(settings),
+            active: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+            file,
+        }
+    }
+
+    /// Current settings
+    pub async fn settings(&self) -> FadeSettings {
+        self.settings.read().await.clone()
+    }
+
+    /// Replace the settings
+    pub async fn set_settings(&self, settings: FadeSettings) -> Result<()> {
+        settings.validate()?;
+        let mut current = self.settings.write().await;
+        *current = settings;
+        self.file.save(&*current);
+        info!("Fade settings updated");
+        Ok(())
+    }
+
+    /// Register a new fade for a zone, cancelling any fade already running there
+    pub fn begin(&self, zone_id: &str, level: f32) -> FadeHandle {
+        let mut handle = FadeHandle {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            token: CancellationToken::new(),
+            level,
+        };
+        if let Ok(mut active) = self.active.lock() {
+            // A fade cut short leaves the zone part-way down: the newer fade takes
+            // over the original level, so it is what gets put back
+            if let Some((_, previous, level)) = active.remove(zone_id) {
+                previous.cancel();
+                handle.level = level;
+            }
+            active.insert(
+                zone_id.to_string(),
+                (handle.id, handle.token.clone(), handle.level),
+            );
+        }
+        handle
+    }
+
+    /// Forget a finished fade (unless a newer one replaced it)
+    pub fn finish(&self, zone_id: &str, handle: &FadeHandle) {
+        if let Ok(mut active) = self.active.lock() {
+            if active
+                .get(zone_id)
+                .is_some_and(|(id, _, _)| *id == handle.id)
+            {
+                active.remove(zone_id);
+            }
+        }
+    }
+
+    /// Cancel a running fade, e.g. because the user changed the volume
+    pub fn cancel(&self, zone_id: &str) {
+        if let Ok(mut active) = self.active.lock() {
+            if let Some((_, token, _)) = active.remove(zone_id) {
+                debug!("Cancelled fade on {}", zone_id);
+                token.cancel();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_end_at_target() {
+        let levels = fade_levels(-10.0, -60.0, Duration::from_millis(1000));
+        assert_eq!(levels.len(), 10);
+        assert_eq!(levels[0], -15.0);
+        assert_eq!(*levels.last().unwrap(), -60.0);
+
+        // Shorter than one step still reaches the target
+        assert_eq!(fade_levels(50.0, 0.0, Duration::from_millis(10)), vec![0.0]);
+    }
+
+    #[test]
+    fn fades_are_selected_per_zone_and_surface() {
+        let mut settings = FadeSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        settings.zones.insert(
+            "lms:aa".to_string(),
+            ZoneFade {
+                enabled: false,
+                duration_ms: None,
+            },
+        );
+        settings.zones.insert(
+            "hqplayer:den".to_string(),
+            ZoneFade {
+                enabled: true,
+                duration_ms: Some(3000),
+            },
+        );
+
+        // Knobs fade, MCP does not (default surfaces)
+        assert_eq!(
+            settings.duration_for(&["roon:1"], ControlSurface::Knob),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            settings.duration_for(&["roon:1"], ControlSurface::Mcp),
+            None
+        );
+
+        assert_eq!(
+            settings.duration_for(&["lms:aa"], ControlSurface::Knob),
+            None
+        );
+        assert_eq!(
+            settings.duration_for(&["hqplayer:den"], ControlSurface::Web),
+            Some(Duration::from_millis(3000))
+        );
+
+        settings.enabled = false;
+        assert_eq!(
+            settings.duration_for(&["roon:1"], ControlSurface::Knob),
+            None
+        );
+    }
+
+    #[test]
+    fn durations_are_bounded() {
+        let mut settings = FadeSettings::default();
+        settings.duration_ms = 0;
+        assert!(settings.validate().is_err());
+        settings.duration_ms = 60_000;
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn new_fade_cancels_the_running_one() {
+        let fades = Fades::in_memory();
+        let first = fades.begin("lms:aa", -20.0);
+        let second = fades.begin("lms:aa", -45.0);
+        assert!(first.token.is_cancelled());
+        assert!(!second.token.is_cancelled());
+        // The zone was at -20 before the first fade lowered it
+        assert_eq!(second.level, -20.0);
+
+        // A stale fade finishing doesn't forget the newer one
+        fades.finish("lms:aa", &first);
+        fades.cancel("lms:aa");
+        assert!(second.token.is_cancelled());
+    }
+}
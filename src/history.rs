@@ -0,0 +1,719 @@
+//! Listening history - an embedded, file-based log of what played where
+//!
+//! `HistoryRecorder` follows playback events on the bus and turns each track
+//! start/stop per zone into a `HistoryEntry`. `HistoryStore` keeps recent
+//! entries in memory and appends them to a JSON Lines file in the data dir.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::bus::{BusEvent, PlaybackState, SharedBus, TrackMetadata};
+use crate::config::get_data_dir;
+
+/// History file (JSON Lines, under the data dir)
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+/// Entries kept in memory (and on disk after compaction)
+const MAX_ENTRIES: usize = 50_000;
+
+/// Default number of entries returned by a query
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Upper bound on entries returned by a query
+pub const MAX_LIMIT: usize = 1000;
+
+/// Plays shorter than this (skipped tracks, previews) are not recorded
+const MIN_LISTENED: Duration = Duration::from_secs(5);
+
+/// One track played on one zone
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryEntry {
+    pub zone_id: String,
+    pub zone_name: String,
+    /// Adapter that reported the play (e.g. "roon", "lms")
+    pub source: String,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Time actually spent playing, pauses excluded
+    pub listened_secs: u64,
+    /// Track length, if the adapter reported it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<TrackMetadata>,
+}
+
+/// Query filter for [`HistoryStore::query`]
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Zone ids or names (case-insensitive); empty matches every zone
+    pub zones: Vec<String>,
+    pub source: Option<String>,
+    /// Case-insensitive substring of the artist
+    pub artist: Option<String>,
+    /// Only plays still running at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only plays started at or before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Maximum entries (defaults to [`DEFAULT_LIMIT`], capped at [`MAX_LIMIT`])
+    pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        if !self.zones.is_empty()
+            && !self
+                .zones
+                .iter()
+                .any(|z| *z == entry.zone_id || z.eq_ignore_ascii_case(&entry.zone_name))
+        {
+            return false;
+        }
+        if let Some(ref source) = self.source {
+            if !source.eq_ignore_ascii_case(&entry.source) {
+                return false;
+            }
+        }
+        if let Some(ref artist) = self.artist {
+            if !entry.artist.to_lowercase().contains(&artist.to_lowercase()) {
+                return false;
+            }
+        }
+        if self.since.is_some_and(|since| entry.ended_at < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| entry.started_at > until) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Persistent listening history
+pub struct HistoryStore {
+    entries: RwLock<VecDeque<HistoryEntry>>,
+    /// None for history kept only in memory
+    file: Option<Arc<HistoryFile>>,
+}
+
+/// The JSON Lines file behind a [`HistoryStore`]. Writes are queued while the
+/// entries are locked and carried out afterwards on a blocking thread.
+struct HistoryFile {
+    path: PathBuf,
+    /// Lines in the file (queued ones included), so it can be compacted once it
+    /// outgrows memory
+    lines: AtomicUsize,
+    pending: Mutex<PendingWrites>,
+    /// Held while writing, so queued writes reach the file in order
+    writing: Mutex<()>,
+}
+
+/// Writes waiting for the history file
+#[derive(Default)]
+struct PendingWrites {
+    /// Replace the file with these entries before appending (compaction)
+    rewrite: Option<VecDeque<HistoryEntry>>,
+    lines: Vec<String>,
+}
+
+impl Default for HistoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HistoryStore {
+    /// Open the history file in the data dir
+    pub fn new() -> Self {
+        Self::open(get_data_dir().join(HISTORY_FILE))
+    }
+
+    /// Open a history file at a specific path (loads existing entries)
+    pub fn open(path: PathBuf) -> Self {
+        let (entries, mut file_lines) = load_history(&path);
+        if !entries.is_empty() {
+            info!("Loaded {} history entries", entries.len());
+        }
+        if file_lines > MAX_ENTRIES && rewrite_history(&path, &entries) {
+            file_lines = entries.len();
+        }
+        Self {
+            entries: RwLock::new(entries),
+            file: Some(Arc::new(HistoryFile {
+                path,
+                lines: AtomicUsize::new(file_lines),
+                pending: Mutex::new(PendingWrites::default()),
+                writing: Mutex::new(()),
+            })),
+        }
+    }
+
+    /// History kept only in memory (nothing is written to disk)
+    pub fn in_memory() -> Self {
+        Self {
+            entries: RwLock::new(VecDeque::new()),
+            file: None,
+        }
+    }
+
+    /// Record a finished play
+    pub async fn record(&self, entry: HistoryEntry) {
+        debug!(
+            "History: {} - {} on {} ({}s)",
+            entry.artist, entry.title, entry.zone_id, entry.listened_secs
+        );
+        let line = serde_json::to_string(&entry);
+        {
+            let mut entries = self.entries.write().await;
+            entries.push_back(entry);
+            while entries.len() > MAX_ENTRIES {
+                entries.pop_front();
+            }
+            if let Some(file) = &self.file {
+                file.queue(line, &entries);
+            }
+        }
+
+        // File IO happens after the entries are unlocked, off the async threads
+        if let Some(file) = &self.file {
+            let file = file.clone();
+            if let Err(e) = tokio::task::spawn_blocking(move || file.flush()).await {
+                warn!("Failed to write history: {}", e);
+            }
+        }
+    }
+
+    /// Entries matching the filter, newest first
+    pub async fn query(&self, filter: &HistoryFilter) -> Vec<HistoryEntry> {
+        let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        self.entries
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|e| filter.matches(e))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Number of stored entries
+    pub async fn len(&self) -> usize {
+        self.entries.read().await.len()
+    }
+
+    /// Whether no entries are stored
+    pub async fn is_empty(&self) -> bool {
+        self.entries.read().await.is_empty()
+    }
+}
+
+impl HistoryFile {
+    /// Queue the line of an entry just added to `entries`, or a compaction once
+    /// the file holds twice what is kept in memory
+    fn queue(&self, line: serde_json::Result<String>, entries: &VecDeque<HistoryEntry>) {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to append history entry: {}", e);
+                return;
+            }
+        };
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if self.lines.fetch_add(1, Ordering::Relaxed) + 1 > MAX_ENTRIES * 2 {
+            // The entries already include every queued line
+            pending.rewrite = Some(entries.clone());
+            pending.lines.clear();
+            self.lines.store(entries.len(), Ordering::Relaxed);
+        } else {
+            pending.lines.push(line);
+        }
+    }
+
+    /// Carry out the queued writes (blocking)
+    fn flush(&self) {
+        let _writing = self.writing.lock().unwrap_or_else(|e| e.into_inner());
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+
+        if let Some(entries) = pending.rewrite {
+            rewrite_history(&self.path, &entries);
+        }
+        if pending.lines.is_empty() {
+            return;
+        }
+        if let Some(parent) = self.path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| {
+                pending
+                    .lines
+                    .iter()
+                    .try_for_each(|line| writeln!(file, "{}", line))
+            });
+        if let Err(e) = result {
+            warn!("Failed to append history entry: {}", e);
+        }
+    }
+}
+
+/// Rewrite the file with just the given entries; false if it couldn't be written
+fn rewrite_history(path: &Path, entries: &VecDeque<HistoryEntry>) -> bool {
+    let mut content = String::new();
+    for entry in entries {
+        if let Ok(line) = serde_json::to_string(entry) {
+            content.push_str(&line);
+            content.push('\n');
+        }
+    }
+    match std::fs::write(path, content) {
+        Ok(()) => {
+            debug!("Compacted history file to {} entries", entries.len());
+            true
+        }
+        Err(e) => {
+            warn!("Failed to compact history file: {}", e);
+            false
+        }
+    }
+}
+
+/// Load the newest `MAX_ENTRIES` entries; also returns the file's line count
+fn load_history(path: &Path) -> (VecDeque<HistoryEntry>, usize) {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return (VecDeque::new(), 0);
+    };
+
+    let mut entries = VecDeque::new();
+    let mut lines = 0;
+    let mut invalid = 0;
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        lines += 1;
+        match serde_json::from_str::<HistoryEntry>(line) {
+            Ok(entry) => {
+                entries.push_back(entry);
+                if entries.len() > MAX_ENTRIES {
+                    entries.pop_front();
+                }
+            }
+            Err(_) => invalid += 1,
+        }
+    }
+    if invalid > 0 {
+        warn!("Skipped {} unreadable history lines", invalid);
+    }
+    (entries, lines)
+}
+
+// =============================================================================
+// Recorder
+// =============================================================================
+
+/// A track currently loaded on a zone
+struct Play {
+    title: String,
+    artist: String,
+    album: String,
+    duration: Option<f64>,
+    metadata: Option<TrackMetadata>,
+    started_at: DateTime<Utc>,
+    listened: Duration,
+    playing_since: Option<Instant>,
+}
+
+impl Play {
+    fn pause(&mut self, now: Instant) {
+        if let Some(since) = self.playing_since.take() {
+            self.listened += now.saturating_duration_since(since);
+        }
+    }
+}
+
+/// What the recorder knows about a zone
+struct ZoneSession {
+    zone_name: String,
+    source: String,
+    playing: bool,
+    current: Option<Play>,
+}
+
+/// Per-zone play tracking, fed with bus events.
+///
+/// Also drives the scrobbler, which applies its own rules to finished plays.
+#[derive(Default)]
+pub(crate) struct PlayTracker {
+    zones: HashMap<String, ZoneSession>,
+}
+
+impl PlayTracker {
+    fn session(&mut self, zone_id: &str) -> &mut ZoneSession {
+        self.zones
+            .entry(zone_id.to_string())
+            .or_insert_with(|| ZoneSession {
+                zone_name: zone_id.to_string(),
+                source: zone_id.split(':').next().unwrap_or_default().to_string(),
+                playing: false,
+                current: None,
+            })
+    }
+
+    /// Apply a bus event; returns plays that ended because of it
+    pub(crate) fn apply(&mut self, event: &BusEvent, now: Instant) -> Vec<HistoryEntry> {
+        match event {
+            BusEvent::ZoneDiscovered { zone } => {
+                let session = self.session(&zone.zone_id);
+                session.zone_name = zone.zone_name.clone();
+                session.source = zone.source.clone();
+                let mut finished = self.set_state(&zone.zone_id, zone.state, now);
+                if let Some(ref np) = zone.now_playing {
+                    finished.extend(self.set_track(
+                        &zone.zone_id,
+                        &np.title,
+                        &np.artist,
+                        &np.album,
+                        np.duration,
+                        now,
+                    ));
+                    if let Some(play) = self.session(&zone.zone_id).current.as_mut() {
+                        if np.metadata.is_some() {
+                            play.metadata = np.metadata.clone();
+                        }
+                    }
+                }
+                finished
+            }
+            BusEvent::ZoneUpdated {
+                zone_id,
+                display_name,
+                state,
+            } => {
+                self.session(zone_id.as_str()).zone_name = display_name.clone();
+                self.set_state(zone_id.as_str(), state.as_str().into(), now)
+            }
+            BusEvent::NowPlayingChanged {
+                zone_id,
+                title,
+                artist,
+                album,
+                duration,
+                ..
+            } => self.set_track(
+                zone_id.as_str(),
+                title.as_deref().unwrap_or_default(),
+                artist.as_deref().unwrap_or_default(),
+                album.as_deref().unwrap_or_default(),
+                *duration,
+                now,
+            ),
+            BusEvent::TrackMetadataChanged { zone_id, metadata } => {
+                if let Some(play) = self.session(zone_id.as_str()).current.as_mut() {
+                    play.metadata = metadata.clone();
+                }
+                Vec::new()
+            }
+            BusEvent::ZoneRemoved { zone_id } => self
+                .zones
+                .remove(zone_id.as_str())
+                .and_then(|mut session| finish(zone_id.as_str(), &mut session, now))
+                .into_iter()
+                .collect(),
+            BusEvent::AdapterStopping { adapter, .. } => {
+                let prefix = format!("{}:", adapter);
+                self.finish_where(|zone_id| zone_id.starts_with(&prefix), now)
+            }
+            BusEvent::ShuttingDown { .. } => self.finish_where(|_| true, now),
+            _ => Vec::new(),
+        }
+    }
+
+    fn set_state(
+        &mut self,
+        zone_id: &str,
+        state: PlaybackState,
+        now: Instant,
+    ) -> Vec<HistoryEntry> {
+        let session = self.session(zone_id);
+        let playing = state == PlaybackState::Playing;
+        if let Some(play) = session.current.as_mut() {
+            if playing && play.playing_since.is_none() {
+                play.playing_since = Some(now);
+            } else if !playing {
+                play.pause(now);
+            }
+        }
+        session.playing = playing;
+
+        // Stopping ends the play; pausing keeps it open
+        if state == PlaybackState::Stopped {
+            finish(zone_id, session, now).into_iter().collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn set_track(
+        &mut self,
+        zone_id: &str,
+        title: &str,
+        artist: &str,
+        album: &str,
+        duration: Option<f64>,
+        now: Instant,
+    ) -> Vec<HistoryEntry> {
+        let session = self.session(zone_id);
+        if let Some(play) = session.current.as_mut() {
+            if play.title == title && play.artist == artist && play.album == album {
+                play.duration = duration.or(play.duration);
+                return Vec::new();
+            }
+        }
+
+        let finished = finish(zone_id, session, now);
+        if !title.is_empty() || !artist.is_empty() {
+            session.current = Some(Play {
+                title: title.to_string(),
+                artist: artist.to_string(),
+                album: album.to_string(),
+                duration,
+                metadata: None,
+                started_at: Utc::now(),
+                listened: Duration::ZERO,
+                playing_since: session.playing.then_some(now),
+            });
+        }
+        finished.into_iter().collect()
+    }
+
+    fn finish_where(&mut self, pred: impl Fn(&str) -> bool, now: Instant) -> Vec<HistoryEntry> {
+        self.zones
+            .iter_mut()
+            .filter(|(zone_id, _)| pred(zone_id))
+            .filter_map(|(zone_id, session)| finish(zone_id, session, now))
+            .collect()
+    }
+}
+
+/// End the zone's current play, returning an entry if it was listened to long enough
+fn finish(zone_id: &str, session: &mut ZoneSession, now: Instant) -> Option<HistoryEntry> {
+    let mut play = session.current.take()?;
+    play.pause(now);
+    if play.listened < MIN_LISTENED {
+        return None;
+    }
+    Some(HistoryEntry {
+        zone_id: zone_id.to_string(),
+        zone_name: session.zone_name.clone(),
+        source: session.source.clone(),
+        title: play.title,
+        artist: play.artist,
+        album: play.album,
+        started_at: play.started_at,
+        ended_at: Utc::now(),
+        listened_secs: play.listened.as_secs(),
+        duration_secs: play.duration.map(|d| d.round() as u64),
+        metadata: play.metadata,
+    })
+}
+
+/// Follows playback events on the bus and records finished plays
+pub struct HistoryRecorder {
+    bus: SharedBus,
+    store: Arc<HistoryStore>,
+}
+
+impl HistoryRecorder {
+    pub fn new(bus: SharedBus, store: Arc<HistoryStore>) -> Self {
+        Self { bus, store }
+    }
+
+    /// Start the recorder's event loop
+    /// Should be spawned as a task
+    pub async fn run(&self) {
+        let mut rx = self.bus.subscribe();
+        let mut tracker = PlayTracker::default();
+
+        info!("HistoryRecorder started");
+
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                // A missed event can cost at most the play in progress
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("HistoryRecorder lagged, skipped {} events", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            for entry in tracker.apply(&event, Instant::now()) {
+                self.store.record(entry).await;
+            }
+            if matches!(event, BusEvent::ShuttingDown { .. }) {
+                break;
+            }
+        }
+
+        info!("HistoryRecorder stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::PrefixedZoneId;
+
+    fn track(title: &str) -> BusEvent {
+        BusEvent::NowPlayingChanged {
+            zone_id: PrefixedZoneId::lms("aa"),
+            title: Some(title.to_string()),
+            artist: Some("Artist".to_string()),
+            album: Some("Album".to_string()),
+            image_key: None,
+            duration: Some(300.0),
+        }
+    }
+
+    fn state(state: &str) -> BusEvent {
+        BusEvent::ZoneUpdated {
+            zone_id: PrefixedZoneId::lms("aa"),
+            display_name: "Kitchen".to_string(),
+            state: state.to_string(),
+        }
+    }
+
+    fn entry(zone_id: &str, zone_name: &str, artist: &str, hour: u32) -> HistoryEntry {
+        let started_at = DateTime::parse_from_rfc3339(&format!("2026-01-10T{:02}:00:00Z", hour))
+            .unwrap()
+            .with_timezone(&Utc);
+        HistoryEntry {
+            zone_id: zone_id.to_string(),
+            zone_name: zone_name.to_string(),
+            source: zone_id.split(':').next().unwrap().to_string(),
+            title: "Song".to_string(),
+            artist: artist.to_string(),
+            album: "Album".to_string(),
+            started_at,
+            ended_at: started_at + chrono::Duration::minutes(4),
+            listened_secs: 240,
+            duration_secs: Some(250),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn listened_time_excludes_pauses() {
+        let mut sessions = PlayTracker::default();
+        let t0 = Instant::now();
+
+        assert!(sessions.apply(&state("playing"), t0).is_empty());
+        assert!(sessions.apply(&track("One"), t0).is_empty());
+        sessions.apply(&state("paused"), t0 + Duration::from_secs(30));
+        sessions.apply(&state("playing"), t0 + Duration::from_secs(90));
+
+        let finished = sessions.apply(&track("Two"), t0 + Duration::from_secs(100));
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].title, "One");
+        assert_eq!(finished[0].zone_name, "Kitchen");
+        assert_eq!(finished[0].source, "lms");
+        assert_eq!(finished[0].listened_secs, 40);
+        assert_eq!(finished[0].duration_secs, Some(300));
+
+        // Same track repeated doesn't end the play
+        assert!(sessions
+            .apply(&track("Two"), t0 + Duration::from_secs(110))
+            .is_empty());
+
+        let finished = sessions.apply(&state("stopped"), t0 + Duration::from_secs(200));
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].title, "Two");
+        assert_eq!(finished[0].listened_secs, 100);
+    }
+
+    #[test]
+    fn skipped_tracks_are_not_recorded() {
+        let mut sessions = PlayTracker::default();
+        let t0 = Instant::now();
+
+        sessions.apply(&state("playing"), t0);
+        sessions.apply(&track("One"), t0);
+        assert!(sessions
+            .apply(&track("Two"), t0 + Duration::from_secs(2))
+            .is_empty());
+
+        let finished = sessions.apply(
+            &BusEvent::ShuttingDown { reason: None },
+            t0 + Duration::from_secs(60),
+        );
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].title, "Two");
+    }
+
+    #[tokio::test]
+    async fn query_filters_and_orders_newest_first() {
+        let store = HistoryStore::in_memory();
+        store
+            .record(entry("roon:1", "Living Room", "Miles Davis", 20))
+            .await;
+        store
+            .record(entry("lms:aa", "Kitchen", "Nina Simone", 21))
+            .await;
+        store
+            .record(entry("roon:1", "Living Room", "Nina Simone", 22))
+            .await;
+
+        let all = store.query(&HistoryFilter::default()).await;
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].started_at.format("%H").to_string(), "22");
+
+        let filter = HistoryFilter {
+            zones: vec!["living room".to_string()],
+            artist: Some("nina".to_string()),
+            ..Default::default()
+        };
+        let found = store.query(&filter).await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].zone_id, "roon:1");
+
+        let filter = HistoryFilter {
+            source: Some("roon".to_string()),
+            until: Some(all[1].started_at),
+            ..Default::default()
+        };
+        let found = store.query(&filter).await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].artist, "Miles Davis");
+    }
+
+    #[tokio::test]
+    async fn entries_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(HISTORY_FILE);
+
+        let store = HistoryStore::open(path.clone());
+        store
+            .record(entry("roon:1", "Living Room", "Miles Davis", 20))
+            .await;
+        store
+            .record(entry("lms:aa", "Kitchen", "Nina Simone", 21))
+            .await;
+
+        let reopened = HistoryStore::open(path);
+        assert_eq!(reopened.len().await, 2);
+        let newest = reopened.query(&HistoryFilter::default()).await;
+        assert_eq!(newest[0].artist, "Nina Simone");
+    }
+}
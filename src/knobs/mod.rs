@@ -10,10 +10,13 @@
 //! - Device store (registration, config, status tracking)
 //! - Hardware API endpoints (/now_playing, /control, /config)
 //! - RGB565 image conversion for LCD display
+//! - Coalescing of rapid volume steps from the encoder
 
 pub mod image;
 pub mod routes;
 pub mod store;
+pub mod volume;
 
 pub use routes::*;
 pub use store::KnobStore;
+pub use volume::VolumeCoalescer;
@@ -22,10 +22,16 @@ use serde::{Deserialize, Serialize};
 
 use sha2::{Digest, Sha256};
 
+use crate::adapters::AdapterCommand;
 use crate::api::AppState;
-use crate::bus::VolumeControl;
+use crate::audit::{request_origin, CommandAudit};
+use crate::bus::{
+    Command, CommandOrigin, ControlSurface, TrackMetadata, VolumeControl, ZoneCapabilities,
+};
 use crate::knobs::image::placeholder_svg;
 use crate::knobs::store::{KnobConfigUpdate, KnobStatusUpdate};
+use crate::rooms::RoomRole;
+use crate::sleep_timer::{SleepMode, SleepRequest, SleepTimerStatus};
 
 /// Extract knob ID from headers or query params
 fn extract_knob_id(headers: &HeaderMap, query_knob_id: Option<&str>) -> Option<String> {
@@ -53,7 +59,10 @@ fn format_ip(ip: std::net::IpAddr) -> String {
 }
 
 /// Extract client IP from headers (X-Forwarded-For, X-Real-IP) or socket address
-fn extract_client_ip(headers: &HeaderMap, socket_addr: Option<SocketAddr>) -> Option<String> {
+pub(crate) fn extract_client_ip(
+    headers: &HeaderMap,
+    socket_addr: Option<SocketAddr>,
+) -> Option<String> {
     // Check X-Forwarded-For first (when behind a proxy)
     if let Some(forwarded) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
         // X-Forwarded-For can be a comma-separated list; take the first one
@@ -99,6 +108,9 @@ pub struct ZoneInfo {
     pub volume_control: Option<VolumeControl>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dsp: Option<DspInfo>,
+    pub capabilities: ZoneCapabilities,
+    /// Last-known zone from before a bridge restart, not yet confirmed by its adapter
+    pub is_stale: bool,
 }
 
 /// GET /knob/zones response
@@ -112,6 +124,7 @@ pub async fn knob_zones_handler(
     State(state): State<AppState>,
     _headers: HeaderMap,
 ) -> Json<ZonesResponse> {
+    state.metrics.record_knob_request("zones");
     let zones = get_all_zones_internal(&state).await;
     Json(ZonesResponse { zones })
 }
@@ -149,8 +162,8 @@ pub async fn get_all_zones_internal(state: &AppState) -> Vec<ZoneInfo> {
     // Get all zones from aggregator (already prefixed with source:)
     let all_zones = state.aggregator.get_zones().await;
 
-    // Filter by enabled adapters and convert to ZoneInfo
-    all_zones
+    // Filter by enabled adapters, then merge room members into their room zone
+    let enabled_zones: Vec<_> = all_zones
         .into_iter()
         .filter(|z| {
             // Filter based on adapter settings
@@ -168,6 +181,13 @@ pub async fn get_all_zones_internal(state: &AppState) -> Vec<ZoneInfo> {
                 true // Unknown prefix, include by default
             }
         })
+        .collect();
+
+    state
+        .rooms
+        .merge(enabled_zones)
+        .await
+        .into_iter()
         .map(|z| ZoneInfo {
             dsp: get_dsp(&z.zone_id),
             zone_id: z.zone_id,
@@ -175,6 +195,8 @@ pub async fn get_all_zones_internal(state: &AppState) -> Vec<ZoneInfo> {
             source: z.source,
             state: z.state.to_string(),
             volume_control: z.volume_control,
+            capabilities: z.capabilities,
+            is_stale: z.is_stale,
         })
         .collect()
 }
@@ -206,6 +228,10 @@ pub struct NowPlayingResponse {
     pub image_key: Option<String>,
     pub seek_position: Option<i64>,
     pub length: Option<u32>,
+    /// Short stream format label, e.g. "FLAC 24/192" or "DSD128"
+    pub format: Option<String>,
+    /// Full stream format details when the adapter reports them
+    pub metadata: Option<TrackMetadata>,
     pub is_play_allowed: bool,
     pub is_pause_allowed: bool,
     pub is_next_allowed: bool,
@@ -213,6 +239,8 @@ pub struct NowPlayingResponse {
     pub zones: Vec<ZoneInfo>,
     pub config_sha: Option<String>,
     pub zones_sha: Option<String>,
+    /// Running sleep timer, for a countdown
+    pub sleep_timer: Option<SleepTimerStatus>,
 }
 
 /// Helper to build zone info list for error responses
@@ -247,6 +275,7 @@ pub async fn knob_now_playing_handler(
     headers: HeaderMap,
     Query(params): Query<NowPlayingQuery>,
 ) -> Result<Json<NowPlayingResponse>, (StatusCode, Json<serde_json::Value>)> {
+    state.metrics.record_knob_request("now_playing");
     // Check zone_id first
     let zone_id = match params.zone_id {
         Some(id) => id,
@@ -303,8 +332,8 @@ pub async fn knob_now_playing_handler(
         zone_id.clone()
     };
 
-    // Get zone from aggregator (single source of truth)
-    let zone = match state.aggregator.get_zone(&prefixed_zone_id).await {
+    // Get zone from aggregator (single source of truth, rooms merged)
+    let zone = match state.get_zone(&prefixed_zone_id).await {
         Some(z) => z,
         None => {
             let zones_sha = compute_zones_sha(&zone_infos);
@@ -380,6 +409,8 @@ pub async fn knob_now_playing_handler(
         None => "fixed".to_string(),
     };
 
+    let sleep_timer = state.sleep_timers.get(&zone.zone_id).await;
+
     Ok(Json(NowPlayingResponse {
         zone_id: zone.zone_id,
         line1,
@@ -398,6 +429,10 @@ pub async fn knob_now_playing_handler(
         image_key: np.and_then(|n| n.image_key.clone()),
         seek_position: np.and_then(|n| n.seek_position.map(|p| p as i64)),
         length: np.and_then(|n| n.duration.map(|d| d as u32)),
+        format: np
+            .and_then(|n| n.metadata.as_ref())
+            .and_then(|m| m.summary()),
+        metadata: np.and_then(|n| n.metadata.clone()),
         is_play_allowed: zone.is_play_allowed,
         is_pause_allowed: zone.is_pause_allowed,
         is_next_allowed: zone.is_next_allowed,
@@ -405,6 +440,7 @@ pub async fn knob_now_playing_handler(
         zones: zone_infos.clone(),
         config_sha,
         zones_sha: Some(compute_zones_sha(&zone_infos)),
+        sleep_timer,
     }))
 }
 
@@ -427,6 +463,7 @@ pub async fn knob_image_handler(
     State(state): State<AppState>,
     Query(params): Query<ImageQuery>,
 ) -> Response {
+    state.metrics.record_knob_request("image");
     let target_width = params.width.unwrap_or(240);
     let target_height = params.height.unwrap_or(240);
     let format = params.format.as_deref();
@@ -467,6 +504,12 @@ pub async fn knob_image_handler(
         params.zone_id.clone()
     };
 
+    // Rooms serve artwork from their metadata member
+    let zone_id = match state.resolve_zone_id(&zone_id, RoomRole::Metadata).await {
+        Ok(id) => id,
+        Err(_) => return placeholder_response(),
+    };
+
     // Get zone from aggregator to find image_key
     let zone = match state.aggregator.get_zone(&zone_id).await {
         Some(z) => z,
@@ -526,22 +569,89 @@ pub struct KnobControlRequest {
 /// POST /knob/control - Send control command (routes by zone_id prefix)
 pub async fn knob_control_handler(
     State(state): State<AppState>,
-    _headers: HeaderMap,
+    connect_info: Result<ConnectInfo<SocketAddr>, axum::extract::rejection::ExtensionRejection>,
+    headers: HeaderMap,
     Json(req): Json<KnobControlRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    state.metrics.record_knob_request("control");
+    // Knobs identify themselves; everything else here is the web UI
+    let surface = if headers.contains_key("x-knob-id") {
+        ControlSurface::Knob
+    } else {
+        ControlSurface::Web
+    };
+    let socket_addr = connect_info.ok().map(|c| c.0);
+    let origin = request_origin(&state.knobs, surface, &headers, socket_addr).await;
+    if req.action.starts_with("sleep") {
+        return control_sleep(&state, &req).await;
+    }
+    if req.action == "scene" {
+        return control_scene(&state, &req, &origin).await;
+    }
+    if let Some(command) = AdapterCommand::from_action(&req.action) {
+        let fades = state.fades.settings().await;
+        if fades.duration_for(&[&req.zone_id], surface).is_some() {
+            return control_with_fade(&state, &req.zone_id, command, origin).await;
+        }
+    }
+
+    // Transport sent straight to the adapter is published here; volume by set_volume
+    let audit = AdapterCommand::from_action(&req.action).map(|command| {
+        CommandAudit::begin(
+            &state.bus,
+            &req.zone_id,
+            Command::from(&command),
+            origin.clone(),
+        )
+    });
+    let result = control_zone(&state, req, &origin).await;
+    if let Some(audit) = audit {
+        let outcome = result
+            .as_ref()
+            .map(|_| ())
+            .map_err(|(_, body)| body.0["error"].as_str().unwrap_or_default().to_string());
+        audit.finish(outcome, None);
+    }
+    result
+}
+
+/// Send an action to the adapter owning the zone
+async fn control_zone(
+    state: &AppState,
+    mut req: KnobControlRequest,
+    origin: &CommandOrigin,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    if matches!(
+        req.action.as_str(),
+        "vol_up" | "volume_up" | "vol_down" | "volume_down" | "vol_abs" | "volume"
+    ) {
+        return control_volume(state, &req, origin).await;
+    }
+
+    // Rooms route transport to their transport member
+    req.zone_id = state
+        .resolve_zone_id(&req.zone_id, RoomRole::Transport)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({"error": e.to_string()})),
+            )
+        })?;
+
     // Route based on zone_id prefix
     if req.zone_id.starts_with("lms:") {
         // LMS player control
         let player_id = req.zone_id.trim_start_matches("lms:");
-        return control_lms(&state, player_id, &req.action, req.value.as_ref()).await;
+        return control_lms(state, player_id, &req.action).await;
     } else if req.zone_id.starts_with("openhome:") {
         // OpenHome zone control
         let udn = req.zone_id.trim_start_matches("openhome:");
-        return control_openhome(&state, udn, &req.action).await;
+        return control_openhome(state, udn, &req.action).await;
     } else if req.zone_id.starts_with("upnp:") {
         // UPnP zone control
         let udn = req.zone_id.trim_start_matches("upnp:");
-        return control_upnp(&state, udn, &req.action).await;
+        return control_upnp(state, udn, &req.action).await;
     }
 
     // Roon zone (or legacy zone_id without prefix)
@@ -551,7 +661,147 @@ pub async fn knob_control_handler(
         req.zone_id.clone()
     };
 
-    control_roon(&state, &roon_zone_id, &req.action, req.value.as_ref()).await
+    control_roon(state, &roon_zone_id, &req.action).await
+}
+
+/// Volume actions: `vol_up`/`vol_down` (value = step, default the zone's own step)
+/// and `vol_abs` (value = level), for every zone type
+async fn control_volume(
+    state: &AppState,
+    req: &KnobControlRequest,
+    origin: &CommandOrigin,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let value = req
+        .value
+        .as_ref()
+        .and_then(|v| v.as_f64())
+        .map(|v| v as f32);
+    let (value, relative) = match req.action.as_str() {
+        // Use as_f64() which handles both JSON integers and floats
+        // (as_i64() returns None for floats like 75.0, causing fallback to 50)
+        "vol_abs" | "volume" => (value.unwrap_or(50.0), false),
+        action => {
+            // Use provided value, or look up the zone's actual step
+            let step = match value {
+                Some(step) => step,
+                None => {
+                    let volume_zone = state
+                        .resolve_zone_id(&req.zone_id, RoomRole::Volume)
+                        .await
+                        .map_err(|e| {
+                            (
+                                StatusCode::NOT_FOUND,
+                                Json(serde_json::json!({"error": e.to_string()})),
+                            )
+                        })?;
+                    get_zone_step(state, &volume_zone).await
+                }
+            };
+            if matches!(action, "vol_down" | "volume_down") {
+                (-step, true)
+            } else {
+                (step, true)
+            }
+        }
+    };
+    knob_volume(state, &req.zone_id, value, relative, origin).await
+}
+
+/// Sleep timer actions: `sleep` (value = minutes, 0 cancels), `sleep_track`,
+/// `sleep_album` and `sleep_cancel`
+async fn control_sleep(
+    state: &AppState,
+    req: &KnobControlRequest,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let request = match req.action.as_str() {
+        "sleep" => match req.value.as_ref().and_then(|v| v.as_f64()) {
+            Some(minutes) if minutes >= 1.0 => {
+                SleepRequest::minutes(&req.zone_id, minutes.round() as u32)
+            }
+            Some(_) => {
+                let was_cancelled = state.sleep_timers.cancel(state, &req.zone_id).await;
+                return Ok(Json(
+                    serde_json::json!({"ok": true, "was_cancelled": was_cancelled}),
+                ));
+            }
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": "sleep requires a value (minutes)"})),
+                ));
+            }
+        },
+        "sleep_track" => SleepRequest::until(&req.zone_id, SleepMode::EndOfTrack),
+        "sleep_album" => SleepRequest::until(&req.zone_id, SleepMode::EndOfAlbum),
+        "sleep_cancel" => {
+            let was_cancelled = state.sleep_timers.cancel(state, &req.zone_id).await;
+            return Ok(Json(
+                serde_json::json!({"ok": true, "was_cancelled": was_cancelled}),
+            ));
+        }
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": format!("Unknown action: {}", req.action)})),
+            ));
+        }
+    };
+
+    match state.sleep_timers.set(state, request).await {
+        Ok(timer) => Ok(Json(serde_json::json!({"ok": true, "sleep_timer": timer}))),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": e.to_string()})),
+        )),
+    }
+}
+
+/// Scene recall: `scene` with the scene id (or name) as value. The zone the
+/// knob is on doesn't matter; the scene lists its own zones.
+async fn control_scene(
+    state: &AppState,
+    req: &KnobControlRequest,
+    origin: &CommandOrigin,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let Some(id) = req.value.as_ref().and_then(|v| v.as_str()) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "scene requires a value (scene id)"})),
+        ));
+    };
+
+    match state.scenes.recall(state, id, origin).await {
+        Ok(recall) => Ok(Json(serde_json::json!({"ok": recall.ok, "recall": recall}))),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": e.to_string()})),
+        )),
+    }
+}
+
+/// Send a transport command with the volume fade around it
+async fn control_with_fade(
+    state: &AppState,
+    zone_id: &str,
+    command: AdapterCommand,
+    origin: CommandOrigin,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let response = state
+        .dispatch_command_from(zone_id, command, origin)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({"error": e.to_string()})),
+            )
+        })?;
+    response.into_result().map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": e.to_string()})),
+        )
+    })?;
+    Ok(Json(serde_json::json!({"ok": true})))
 }
 
 /// Control Roon zone
@@ -559,7 +809,6 @@ async fn control_roon(
     state: &AppState,
     zone_id: &str,
     action: &str,
-    value: Option<&serde_json::Value>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
     let roon_action = match action {
         "play" => "play",
@@ -568,58 +817,6 @@ async fn control_roon(
         "next" => "next",
         "previous" | "prev" => "previous",
         "stop" => "stop",
-        "vol_up" | "volume_up" => {
-            // Use provided value, or look up zone's actual step from aggregator
-            let step = match value.and_then(|v| v.as_f64()) {
-                Some(v) => v as f32,
-                None => get_zone_step(state, &format!("roon:{}", zone_id)).await,
-            };
-            state
-                .roon
-                .change_volume(zone_id, step, true)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"error": e.to_string()})),
-                    )
-                })?;
-            return Ok(Json(serde_json::json!({"ok": true})));
-        }
-        "vol_down" | "volume_down" => {
-            // Use provided value, or look up zone's actual step from aggregator
-            let step = match value.and_then(|v| v.as_f64()) {
-                Some(v) => v as f32,
-                None => get_zone_step(state, &format!("roon:{}", zone_id)).await,
-            };
-            state
-                .roon
-                .change_volume(zone_id, -step, true)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"error": e.to_string()})),
-                    )
-                })?;
-            return Ok(Json(serde_json::json!({"ok": true})));
-        }
-        "vol_abs" | "volume" => {
-            // Use as_f64() which handles both JSON integers and floats
-            // (as_i64() returns None for floats like 75.0, causing fallback to 50)
-            let vol = value.and_then(|v| v.as_f64()).unwrap_or(50.0) as f32;
-            state
-                .roon
-                .change_volume(zone_id, vol, false)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"error": e.to_string()})),
-                    )
-                })?;
-            return Ok(Json(serde_json::json!({"ok": true})));
-        }
         _ => {
             return Err((
                 StatusCode::BAD_REQUEST,
@@ -642,7 +839,6 @@ async fn control_lms(
     state: &AppState,
     player_id: &str,
     action: &str,
-    value: Option<&serde_json::Value>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
     let lms_action = match action {
         "play" => "play",
@@ -651,57 +847,6 @@ async fn control_lms(
         "next" => "next",
         "previous" | "prev" => "prev",
         "stop" => "stop",
-        "vol_up" | "volume_up" => {
-            // Use provided value, or look up zone's actual step from aggregator
-            let step = match value.and_then(|v| v.as_f64()) {
-                Some(v) => v as f32,
-                None => get_zone_step(state, &format!("lms:{}", player_id)).await,
-            };
-            state
-                .lms
-                .change_volume(player_id, step, true)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"error": e.to_string()})),
-                    )
-                })?;
-            return Ok(Json(serde_json::json!({"ok": true})));
-        }
-        "vol_down" | "volume_down" => {
-            // Use provided value, or look up zone's actual step from aggregator
-            let step = match value.and_then(|v| v.as_f64()) {
-                Some(v) => v as f32,
-                None => get_zone_step(state, &format!("lms:{}", player_id)).await,
-            };
-            state
-                .lms
-                .change_volume(player_id, -step, true)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"error": e.to_string()})),
-                    )
-                })?;
-            return Ok(Json(serde_json::json!({"ok": true})));
-        }
-        "vol_abs" | "volume" => {
-            // Use as_f64() which handles both JSON integers and floats
-            let vol = value.and_then(|v| v.as_f64()).unwrap_or(50.0) as f32;
-            state
-                .lms
-                .change_volume(player_id, vol, false)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"error": e.to_string()})),
-                    )
-                })?;
-            return Ok(Json(serde_json::json!({"ok": true})));
-        }
         _ => {
             return Err((
                 StatusCode::BAD_REQUEST,
@@ -778,13 +923,38 @@ async fn control_upnp(
         )),
     }
 }
+
+/// Change volume through the volume safety policy. Rapid steps are coalesced per
+/// zone; the response carries the level the zone is heading for.
+async fn knob_volume(
+    state: &AppState,
+    zone_id: &str,
+    value: f32,
+    relative: bool,
+    origin: &CommandOrigin,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let outcome = state
+        .volume_coalescer
+        .step(state, zone_id, value, relative, origin.clone())
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": e.to_string()})),
+            )
+        })?;
+    Ok(Json(serde_json::json!({
+        "ok": true,
+        "value": outcome.value,
+        "reason": outcome.reason
+    })))
+}
+
 /// Helper to get zone's volume step from aggregator (returns 1.0 if not found)
 async fn get_zone_step(state: &AppState, zone_id: &str) -> f32 {
     state
-        .aggregator
-        .get_zone(zone_id)
+        .volume_control(zone_id)
         .await
-        .and_then(|z| z.volume_control)
         .map(|vc| vc.step)
         .unwrap_or(1.0)
 }
@@ -795,6 +965,7 @@ pub async fn knob_config_handler(
     headers: HeaderMap,
     Query(params): Query<KnobIdQuery>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    state.metrics.record_knob_request("config");
     let knob_id = extract_knob_id(&headers, params.knob_id.as_deref()).ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
@@ -871,6 +1042,7 @@ pub async fn knob_config_by_path_handler(
     headers: HeaderMap,
     axum::extract::Path(knob_id): axum::extract::Path<String>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    state.metrics.record_knob_request("config");
     let version = extract_knob_version(&headers);
 
     // Get or create knob (ensures it exists for newly connected devices)
@@ -1197,6 +1369,8 @@ mod tests {
             state: "stopped".to_string(),
             volume_control: None,
             dsp: None,
+            capabilities: ZoneCapabilities::default(),
+            is_stale: false,
         }
     }
 
@@ -140,6 +140,8 @@ fn compute_sha(config: &KnobConfig, name: &str) -> String {
 #[derive(Clone)]
 pub struct KnobStore {
     knobs: Arc<RwLock<HashMap<String, Knob>>>,
+    /// Config given to newly registered knobs
+    defaults: Arc<RwLock<KnobConfig>>,
 }
 
 impl Default for KnobStore {
@@ -155,9 +157,21 @@ impl KnobStore {
         let knobs = Self::load_from_disk();
         Self {
             knobs: Arc::new(RwLock::new(knobs)),
+            defaults: Arc::new(RwLock::new(KnobConfig::default())),
         }
     }
 
+    /// Re-read knobs from disk (after a restore)
+    pub async fn reload(&self) {
+        let knobs = Self::load_from_disk();
+        *self.knobs.write().await = knobs;
+    }
+
+    /// Set the config given to knobs registered from now on (existing knobs keep theirs)
+    pub async fn set_defaults(&self, config: KnobConfig) {
+        *self.defaults.write().await = config;
+    }
+
     /// Load knobs from disk with backwards-compatible fallback
     /// Issue #76: Uses read_config_file to check subdir first, fall back to root
     fn load_from_disk() -> HashMap<String, Knob> {
@@ -194,6 +208,7 @@ impl KnobStore {
 
     /// Get or create knob, updating last_seen and version
     pub async fn get_or_create(&self, knob_id: &str, version: Option<&str>) -> Knob {
+        let config = self.defaults.read().await.clone();
         let mut knobs = self.knobs.write().await;
 
         if let Some(knob) = knobs.get_mut(knob_id) {
@@ -208,7 +223,6 @@ impl KnobStore {
         }
 
         // Create new knob
-        let config = KnobConfig::default();
         let name = String::new();
         let config_sha = compute_sha(&config, &name);
 
@@ -0,0 +1,405 @@
+//! Coalescing of knob volume steps
+//!
+//! Spinning a knob sends a burst of `vol_up`/`vol_down` requests. Passing each one
+//! to the adapter queues them behind slow endpoints (UPnP/OpenHome SOAP calls can
+//! take seconds), so the volume keeps moving after the knob stops.
+//!
+//! Instead, steps for a zone are folded into one absolute target and the request is
+//! answered straight away with that level. One task per zone sends the target: at
+//! most once per [`COALESCE_WINDOW`]. A step that arrives while a send is in flight
+//! cancels that send, and the new target goes out as soon as the window allows.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+use crate::api::{AppState, VolumeOutcome};
+use crate::bus::{CommandOrigin, ControlSurface, VolumeControl};
+use crate::rooms::RoomRole;
+use crate::volume_policy::VolumePolicy;
+
+/// Minimum time between two sends to the same zone
+pub const COALESCE_WINDOW: Duration = Duration::from_millis(75);
+/// How long a target outlives its last step. Steps within this build on the target
+/// rather than on a zone level the adapter may not have reported yet.
+pub const SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// Target level of one (volume) zone
+struct PendingVolume {
+    /// Zone id as requested (a room keeps its own volume policy)
+    zone_id: String,
+    target: f32,
+    /// Origin of the latest step, published with the send
+    origin: CommandOrigin,
+    /// Target changed since it was last taken for sending
+    dirty: bool,
+    /// A task is sending the target
+    sending: bool,
+    /// The adapter hasn't answered the latest send yet
+    in_flight: bool,
+    /// Woken by a step that arrives while a send is in flight
+    superseded: Arc<Notify>,
+    last_sent: Option<Instant>,
+    updated: Instant,
+}
+
+/// Per-zone volume targets for knob steps
+#[derive(Default)]
+pub struct VolumeCoalescer {
+    /// Keyed by the resolved volume zone id, so a room and its member share a target
+    zones: Mutex<HashMap<String, PendingVolume>>,
+}
+
+impl VolumeCoalescer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Change a zone's volume from a knob. Returns the level the zone is heading for
+    /// without waiting for the adapter.
+    pub async fn step(
+        &self,
+        state: &AppState,
+        zone_id: &str,
+        value: f32,
+        relative: bool,
+        origin: CommandOrigin,
+    ) -> anyhow::Result<VolumeOutcome> {
+        let resolved = state.resolve_zone_id(zone_id, RoomRole::Volume).await?;
+        let Some(control) = state.volume_control(&resolved).await else {
+            // Level unknown: there is nothing to fold the step into
+            return state.set_volume(zone_id, value, relative, origin).await;
+        };
+        let policy = state
+            .volume_policy
+            .policy_for(&[zone_id, resolved.as_str()])
+            .await;
+
+        let (outcome, start) = self.submit(
+            &resolved,
+            zone_id,
+            value,
+            relative,
+            &control,
+            &policy,
+            origin,
+            Instant::now(),
+        );
+        if start {
+            let state = state.clone();
+            tokio::spawn(async move {
+                state.volume_coalescer.send_pending(&state, &resolved).await;
+            });
+        }
+        Ok(outcome)
+    }
+
+    /// Fold a step into the zone's target. Returns the new target and whether a
+    /// task needs to be started to send it.
+    #[allow(clippy::too_many_arguments)]
+    fn submit(
+        &self,
+        key: &str,
+        zone_id: &str,
+        value: f32,
+        relative: bool,
+        control: &VolumeControl,
+        policy: &VolumePolicy,
+        origin: CommandOrigin,
+        now: Instant,
+    ) -> (VolumeOutcome, bool) {
+        let Ok(mut zones) = self.zones.lock() else {
+            return (
+                VolumeOutcome {
+                    value: None,
+                    reason: None,
+                },
+                false,
+            );
+        };
+
+        let pending = zones
+            .get(key)
+            .filter(|p| p.sending || now.duration_since(p.updated) < SETTLE_TIME);
+        let base = pending.map_or(control.value, |p| p.target);
+        let requested = if relative { base + value } else { value };
+        // Checked against the zone's reported level, as set_volume will when it's sent
+        let decision = policy.apply(
+            requested,
+            Some(control.value),
+            Some((control.min, control.max)),
+        );
+
+        let entry = zones
+            .entry(key.to_string())
+            .or_insert_with(|| PendingVolume {
+                zone_id: zone_id.to_string(),
+                target: decision.value,
+                origin: origin.clone(),
+                dirty: false,
+                sending: false,
+                in_flight: false,
+                superseded: Arc::new(Notify::new()),
+                last_sent: None,
+                updated: now,
+            });
+        entry.zone_id = zone_id.to_string();
+        entry.target = decision.value;
+        entry.origin = origin;
+        entry.dirty = true;
+        entry.updated = now;
+        if entry.in_flight {
+            entry.superseded.notify_waiters();
+        }
+        let start = !entry.sending;
+        entry.sending = true;
+
+        (
+            VolumeOutcome {
+                value: Some(decision.value),
+                reason: decision.reason,
+            },
+            start,
+        )
+    }
+
+    /// Notified when a send to the zone is superseded by a newer step
+    fn superseded(&self, key: &str) -> Option<Arc<Notify>> {
+        let zones = self.zones.lock().ok()?;
+        zones.get(key).map(|p| p.superseded.clone())
+    }
+
+    /// When the zone may be sent to next
+    fn next_send(&self, key: &str) -> Option<Instant> {
+        let zones = self.zones.lock().ok()?;
+        zones
+            .get(key)
+            .and_then(|p| p.last_sent)
+            .map(|sent| sent + COALESCE_WINDOW)
+    }
+
+    /// Take the target to send, or None once nothing changed since the last send
+    /// (the zone is then idle and the next step starts a new task)
+    fn take(&self, key: &str, now: Instant) -> Option<(String, f32, CommandOrigin)> {
+        let mut zones = self.zones.lock().ok()?;
+        let pending = zones.get_mut(key)?;
+        if !pending.dirty {
+            pending.sending = false;
+            return None;
+        }
+        pending.dirty = false;
+        pending.in_flight = true;
+        pending.last_sent = Some(now);
+        Some((
+            pending.zone_id.clone(),
+            pending.target,
+            pending.origin.clone(),
+        ))
+    }
+
+    /// Record the level actually sent (the policy may have clamped it further)
+    fn sent(&self, key: &str, value: Option<f32>, now: Instant) {
+        if let Ok(mut zones) = self.zones.lock() {
+            if let Some(pending) = zones.get_mut(key) {
+                if let (Some(value), false) = (value, pending.dirty) {
+                    pending.target = value;
+                }
+                pending.in_flight = false;
+                pending.updated = now;
+            }
+        }
+    }
+
+    /// Forget a zone's target so the next step starts from the zone's own level
+    fn reset(&self, key: &str) {
+        if let Ok(mut zones) = self.zones.lock() {
+            zones.remove(key);
+        }
+    }
+
+    /// Send the zone's target until no newer step is waiting. A send still waiting
+    /// for a slow adapter is dropped as soon as a newer step arrives.
+    async fn send_pending(&self, state: &AppState, key: &str) {
+        let Some(superseded) = self.superseded(key) else {
+            return;
+        };
+        loop {
+            if let Some(at) = self.next_send(key) {
+                tokio::select! {
+                    _ = state.shutdown.cancelled() => return,
+                    _ = tokio::time::sleep_until(at) => {}
+                }
+            }
+            // Created before the take, so it already sees a step right after it
+            let notified = superseded.notified();
+            tokio::pin!(notified);
+
+            let Some((zone_id, target, origin)) = self.take(key, Instant::now()) else {
+                return;
+            };
+            debug!("Sending coalesced volume {} to {}", target, zone_id);
+            tokio::select! {
+                _ = state.shutdown.cancelled() => return,
+                _ = &mut notified => {
+                    debug!("Volume {} for {} superseded before it was applied", target, zone_id);
+                }
+                result = state.set_volume(&zone_id, target, false, origin) => match result {
+                    Ok(outcome) => self.sent(key, outcome.value, Instant::now()),
+                    Err(e) => {
+                        warn!("Failed to set volume of {} to {}: {}", zone_id, target, e);
+                        self.reset(key);
+                        return;
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::{ControlSurface, VolumeScale};
+
+    fn control(value: f32) -> VolumeControl {
+        VolumeControl {
+            value,
+            min: -64.0,
+            max: 0.0,
+            step: 1.0,
+            is_muted: false,
+            scale: VolumeScale::Decibel,
+            output_id: None,
+        }
+    }
+
+    fn knob() -> CommandOrigin {
+        CommandOrigin::new(ControlSurface::Knob)
+    }
+
+    #[test]
+    fn steps_fold_into_one_target() {
+        let coalescer = VolumeCoalescer::new();
+        let policy = VolumePolicy::default();
+        let now = Instant::now();
+
+        let (outcome, start) = coalescer.submit(
+            "roon:1",
+            "roon:1",
+            1.0,
+            true,
+            &control(-30.0),
+            &policy,
+            knob(),
+            now,
+        );
+        assert_eq!(outcome.value, Some(-29.0));
+        assert!(start);
+
+        // The zone hasn't reported the first step yet; later steps build on the target
+        for _ in 0..3 {
+            let (_, start) = coalescer.submit(
+                "roon:1",
+                "roon:1",
+                1.0,
+                true,
+                &control(-30.0),
+                &policy,
+                knob(),
+                now,
+            );
+            assert!(!start);
+        }
+
+        let (zone_id, target, _) = coalescer.take("roon:1", now).unwrap();
+        assert_eq!(zone_id, "roon:1");
+        assert_eq!(target, -26.0);
+        assert!(coalescer.take("roon:1", now).is_none());
+
+        // Idle again: the next step needs a new task
+        let (_, start) = coalescer.submit(
+            "roon:1",
+            "roon:1",
+            -1.0,
+            true,
+            &control(-26.0),
+            &policy,
+            knob(),
+            now,
+        );
+        assert!(start);
+    }
+
+    #[test]
+    fn target_respects_range_and_policy() {
+        let coalescer = VolumeCoalescer::new();
+        let policy = VolumePolicy {
+            max_volume: Some(-20.0),
+            ..Default::default()
+        };
+        let now = Instant::now();
+
+        let (outcome, _) = coalescer.submit(
+            "lms:a",
+            "lms:a",
+            5.0,
+            true,
+            &control(-22.0),
+            &policy,
+            knob(),
+            now,
+        );
+        assert_eq!(outcome.value, Some(-20.0));
+        assert!(outcome.reason.is_some());
+
+        let (outcome, _) = coalescer.submit(
+            "lms:a",
+            "lms:a",
+            -90.0,
+            false,
+            &control(-22.0),
+            &policy,
+            knob(),
+            now,
+        );
+        assert_eq!(outcome.value, Some(-64.0));
+    }
+
+    #[test]
+    fn settled_target_gives_way_to_zone_level() {
+        let coalescer = VolumeCoalescer::new();
+        let policy = VolumePolicy::default();
+        let now = Instant::now();
+
+        coalescer.submit(
+            "upnp:x",
+            "upnp:x",
+            2.0,
+            true,
+            &control(-30.0),
+            &policy,
+            knob(),
+            now,
+        );
+        coalescer.take("upnp:x", now).unwrap();
+        coalescer.sent("upnp:x", Some(-28.0), now);
+        assert!(coalescer.take("upnp:x", now).is_none());
+
+        // Someone else changed the volume since; after settling the zone's level wins
+        let later = now + SETTLE_TIME + Duration::from_millis(1);
+        let (outcome, _) = coalescer.submit(
+            "upnp:x",
+            "upnp:x",
+            1.0,
+            true,
+            &control(-40.0),
+            &policy,
+            knob(),
+            later,
+        );
+        assert_eq!(outcome.value, Some(-39.0));
+    }
+}
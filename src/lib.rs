@@ -40,18 +40,50 @@ pub mod aggregator;
 #[cfg(feature = "server")]
 pub mod api;
 #[cfg(feature = "server")]
+pub mod audit;
+#[cfg(feature = "server")]
+pub mod auth;
+#[cfg(feature = "server")]
+pub mod backup;
+#[cfg(feature = "server")]
 pub mod bus;
 #[cfg(feature = "server")]
 pub mod config;
 #[cfg(feature = "server")]
+pub mod config_file;
+#[cfg(feature = "server")]
+pub mod config_store;
+#[cfg(feature = "server")]
 pub mod coordinator;
 #[cfg(feature = "server")]
 pub mod embedded;
 #[cfg(feature = "server")]
+pub mod fades;
+#[cfg(feature = "server")]
 pub mod firmware;
 #[cfg(feature = "server")]
+pub mod history;
+#[cfg(feature = "server")]
 pub mod knobs;
 #[cfg(feature = "server")]
 pub mod mcp;
 #[cfg(feature = "server")]
 pub mod mdns;
+#[cfg(feature = "server")]
+pub mod metrics;
+#[cfg(feature = "server")]
+pub mod rooms;
+#[cfg(feature = "server")]
+pub mod rules;
+#[cfg(feature = "server")]
+pub mod scenes;
+#[cfg(feature = "server")]
+pub mod scheduler;
+#[cfg(feature = "server")]
+pub mod scrobbler;
+#[cfg(feature = "server")]
+pub mod sleep_timer;
+#[cfg(feature = "server")]
+pub mod tls;
+#[cfg(feature = "server")]
+pub mod volume_policy;
@@ -6,8 +6,9 @@
 #[cfg(feature = "server")]
 mod server {
     use unified_hifi_control::{
-        adapters, aggregator, api, app, bus, config, coordinator, embedded, firmware, knobs, mcp,
-        mdns,
+        adapters, aggregator, api, app, audit, auth, bus, config, config_file, coordinator,
+        embedded, fades, firmware, history, knobs, mcp, mdns, rooms, rules, scenes, scheduler,
+        scrobbler, sleep_timer, tls, volume_policy,
     };
 
     // Import Startable trait for adapter lifecycle methods
@@ -110,6 +111,8 @@ mod server {
 
         // Load app settings and create adapter coordinator (single source of truth for lifecycle)
         let app_settings = load_app_settings();
+        // Declarative config file (optional); its adapter toggles win over the settings page
+        let (declared_config, app_settings) = config_file::load_at_startup(app_settings)?;
         let coord = Arc::new(coordinator::AdapterCoordinator::new(bus.clone()));
         coord.register_from_settings(&app_settings.adapters).await;
         tracing::info!("Adapter coordinator initialized");
@@ -128,6 +131,15 @@ mod server {
         // Initialize Knob device store early (needed for Roon extension status)
         // Issue #76: Uses config subdirectory for knobs.json
         let knob_store = knobs::KnobStore::new();
+        let room_store = Arc::new(rooms::RoomStore::new());
+        let history_store = Arc::new(history::HistoryStore::new());
+        let scrobbler_service = Arc::new(scrobbler::Scrobbler::new(bus.clone()));
+        let scheduler_service = Arc::new(scheduler::Scheduler::new());
+        let rules_engine = Arc::new(rules::RulesEngine::new());
+        let volume_policy_store = Arc::new(volume_policy::VolumePolicyStore::load()?);
+        let fade_store = Arc::new(fades::Fades::new());
+        let sleep_timers = Arc::new(sleep_timer::SleepTimers::new());
+        let scene_store = Arc::new(scenes::SceneStore::new());
         tracing::info!("Knob store initialized");
 
         // Roon adapter - coordinator handles starting based on enabled state
@@ -207,37 +219,76 @@ mod server {
             .await;
         }
 
+        // Additional LMS servers (lms-servers.json) follow the LMS enabled setting
+        let lms_servers = lms.server_startables().await;
+        for adapter in &lms_servers {
+            coord
+                .register(adapter.name(), app_settings.adapters.lms)
+                .await;
+        }
+
         // OpenHome adapter
         let openhome = Arc::new(adapters::openhome::OpenHomeAdapter::new(bus.clone()));
 
         // UPnP adapter
         let upnp = Arc::new(adapters::upnp::UPnPAdapter::new(bus.clone()));
 
+        // External adapters (child processes from external_adapters.json)
+        let external = Arc::new(adapters::external::ExternalAdapters::load(bus.clone()));
+        for adapter in external.list() {
+            coord.register(adapter.name(), adapter.enabled()).await;
+        }
+
         // =========================================================================
         // Start enabled adapters (single codepath using coordinator)
         // =========================================================================
 
         // Build list of startable adapters
         // Note: lms_cli shares config with lms - both start when LMS is configured
-        let startable_adapters: Vec<Arc<dyn adapters::Startable>> = vec![
+        let mut startable_adapters: Vec<Arc<dyn adapters::Startable>> = vec![
             roon.clone(),
             lms.clone(),
             lms_cli.clone(),
             openhome.clone(),
             upnp.clone(),
         ];
+        startable_adapters.extend(
+            external
+                .list()
+                .iter()
+                .map(|adapter| adapter.clone() as Arc<dyn adapters::Startable>),
+        );
 
-        // Single loop to start all enabled adapters
-        coord.start_all_enabled(&startable_adapters).await;
-
-        // Initialize ZoneAggregator for unified zone state
-        let zone_aggregator = Arc::new(aggregator::ZoneAggregator::new(bus.clone()));
+        // Initialize ZoneAggregator for unified zone state, warm-started from the
+        // zone cache. Started before the adapters so it sees their first discoveries.
+        let zone_aggregator = Arc::new(aggregator::ZoneAggregator::with_cache(
+            bus.clone(),
+            config::get_config_file_path(aggregator::ZONE_CACHE_FILE),
+        ));
         let aggregator_for_spawn = zone_aggregator.clone();
         tokio::spawn(async move {
             aggregator_for_spawn.run().await;
         });
         tracing::info!("ZoneAggregator started");
 
+        // Record listening history from playback events
+        let history_recorder = history::HistoryRecorder::new(bus.clone(), history_store.clone());
+        tokio::spawn(async move {
+            history_recorder.run().await;
+        });
+
+        // Scrobble finished plays (no-op until enabled in settings)
+        let scrobbler_for_spawn = scrobbler_service.clone();
+        tokio::spawn(async move {
+            scrobbler_for_spawn.run().await;
+        });
+
+        // Track adapter health (connection state, failures, retries) from bus events
+        let coord_for_spawn = coord.clone();
+        tokio::spawn(async move {
+            coord_for_spawn.run().await;
+        });
+
         // Clone Roon adapter for shutdown access (cheap - just Arc clones)
         let roon_for_shutdown = roon.clone();
 
@@ -253,7 +304,17 @@ mod server {
             lms.clone(),
             openhome.clone(),
             upnp.clone(),
+            external.clone(),
             knob_store,
+            room_store,
+            history_store,
+            scrobbler_service,
+            scheduler_service.clone(),
+            rules_engine.clone(),
+            volume_policy_store.clone(),
+            fade_store,
+            sleep_timers.clone(),
+            scene_store,
             bus.clone(),
             zone_aggregator,
             coord.clone(),
@@ -262,16 +323,65 @@ mod server {
             shutdown_token.clone(),
         );
 
+        // Audit every zone command with the surface it came from
+        let audit_recorder = audit::AuditRecorder::new(bus.clone(), state.audit.clone());
+        tokio::spawn(async move {
+            audit_recorder.run().await;
+        });
+
+        // Apply the config file before adapters start, then watch it for edits
+        if let Some(declared) = declared_config {
+            declared.apply(&state, None).await;
+            let config_state = state.clone();
+            tokio::spawn(async move {
+                config_file::watch(config_state, declared).await;
+            });
+        }
+
+        // Single loop to start all enabled adapters
+        coord.start_all_enabled(&startable_adapters).await;
+        // Re-read: the config file may have added or removed servers
+        coord
+            .start_all_enabled(&lms.server_startables().await)
+            .await;
+
+        // Run scheduled actions (needs the full state to reach every adapter)
+        let scheduler_state = state.clone();
+        tokio::spawn(async move {
+            scheduler_service.run(scheduler_state).await;
+        });
+
+        // Evaluate automation rules against bus events
+        let rules_state = state.clone();
+        tokio::spawn(async move {
+            rules_engine.run(rules_state).await;
+        });
+
+        // Apply the volume start cap when playback starts outside this app
+        let volume_policy_state = state.clone();
+        tokio::spawn(async move {
+            volume_policy_store.run(volume_policy_state).await;
+        });
+
+        // Run down sleep timers
+        let sleep_timers_state = state.clone();
+        tokio::spawn(async move {
+            sleep_timers.run(sleep_timers_state).await;
+        });
+
         // Clone state for shutdown diagnostics
         let state_for_shutdown = state.clone();
 
         // Create MCP extension (state for MCP handlers)
         let mcp_extension = mcp::create_mcp_extension(state.clone());
+        let auth_state = state.clone();
 
         // Build API routes
         let router = Router::new()
             // Health check
             .route("/status", get(api::status_handler))
+            .route("/metrics", get(api::metrics_handler))
+            .route("/adapters/restart", post(api::adapter_restart_handler))
             // Roon routes
             .route("/roon/status", get(api::roon_status_handler))
             .route("/roon/zones", get(api::roon_zones_handler))
@@ -358,6 +468,9 @@ mod server {
             .route("/lms/control", post(api::lms_control_handler))
             .route("/lms/volume", post(api::lms_volume_handler))
             .route("/lms/discover", get(api::lms_discover_handler))
+            .route("/lms/servers", get(api::lms_servers_handler))
+            .route("/lms/servers", post(api::lms_add_server_handler))
+            .route("/lms/servers/{id}", delete(api::lms_remove_server_handler))
             // OpenHome routes
             .route("/openhome/status", get(api::openhome_status_handler))
             .route("/openhome/zones", get(api::openhome_zones_handler))
@@ -377,6 +490,17 @@ mod server {
             // App settings API
             .route("/api/settings", get(api::api_settings_get_handler))
             .route("/api/settings", post(api::api_settings_post_handler))
+            // API authentication (tokens, UI login)
+            .route("/auth/status", get(api::auth_status_handler))
+            .route("/auth/login", post(api::auth_login_handler))
+            .route("/auth/logout", post(api::auth_logout_handler))
+            .route("/auth/settings", post(api::auth_settings_handler))
+            .route("/auth/tokens", get(api::auth_tokens_handler))
+            .route("/auth/tokens", post(api::auth_create_token_handler))
+            .route("/auth/tokens/{id}", delete(api::auth_revoke_token_handler))
+            // Backup / restore
+            .route("/api/backup", get(api::backup_handler))
+            .route("/api/restore", post(api::restore_handler))
             // Event stream (SSE)
             .route("/events", get(api::events_handler))
             // Knob hardware API routes
@@ -406,6 +530,38 @@ mod server {
             )
             // Protocol route: /zones returns JSON (for knob, iOS, etc.)
             .route("/zones", get(knobs::knob_zones_handler))
+            // Source-agnostic zone commands (serialized bus Command)
+            .route("/zones/{zone_id}/command", post(api::zone_command_handler))
+            // Logical rooms (merge duplicate zones of one physical system)
+            .route("/rooms", get(api::rooms_handler))
+            .route("/rooms", post(api::room_save_handler))
+            .route("/rooms/delete", post(api::room_delete_handler))
+            .route("/history", get(api::history_handler))
+            .route("/audit", get(api::audit_handler))
+            .route("/scrobbler/status", get(api::scrobbler_status_handler))
+            .route("/scrobbler/config", post(api::scrobbler_config_handler))
+            .route("/rules", get(api::rules_handler))
+            .route("/rules", post(api::rule_save_handler))
+            .route("/rules/delete", post(api::rule_delete_handler))
+            .route("/schedules", get(api::schedules_handler))
+            .route("/schedules", post(api::schedule_save_handler))
+            .route("/schedules/delete", post(api::schedule_delete_handler))
+            .route("/schedules/run", post(api::schedule_run_handler))
+            // Volume safety policy and fades
+            .route("/volume/policy", get(api::volume_policy_handler))
+            .route("/volume/policy", post(api::volume_policy_save_handler))
+            .route("/fades", get(api::fades_handler))
+            .route("/fades", post(api::fades_save_handler))
+            // Sleep timers
+            .route("/sleep", get(api::sleep_timers_handler))
+            .route("/sleep", post(api::sleep_timer_set_handler))
+            .route("/sleep/cancel", post(api::sleep_timer_cancel_handler))
+            // Scenes
+            .route("/scenes", get(api::scenes_handler))
+            .route("/scenes", post(api::scene_save_handler))
+            .route("/scenes/capture", post(api::scene_capture_handler))
+            .route("/scenes/delete", post(api::scene_delete_handler))
+            .route("/scenes/recall", post(api::scene_recall_handler))
             // Legacy SSR routes (flash page not yet migrated)
             .route("/knobs/flash", get(flash_page))
             // Legacy redirects
@@ -476,21 +632,45 @@ mod server {
             router.serve_dioxus_application(dioxus::server::ServeConfig::new(), app::App)
         };
 
+        // Token auth covers the API and the UI pages (a no-op until enabled)
+        let router = router.layer(axum::middleware::from_fn_with_state(
+            auth_state,
+            auth::require_auth,
+        ));
+
         // Start server with graceful shutdown
         let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
         tracing::info!("Listening on http://{}", addr);
 
-        // Advertise via mDNS for knob discovery
-        let _mdns = match mdns::advertise(config.port, "Unified Hi-Fi Control", &base_url) {
-            Ok(daemon) => {
-                tracing::info!("mDNS advertising started");
-                Some(daemon)
-            }
-            Err(e) => {
-                tracing::warn!("Failed to start mDNS advertising: {}", e);
-                None
-            }
+        // Optional HTTPS listener (same routes); certificate problems stop startup
+        let https = if config.tls.enabled {
+            let source = tls::cert_source(&config.tls)?;
+            let rustls_config = tls::load(&source).await?;
+            tokio::spawn(tls::watch(
+                source,
+                rustls_config.clone(),
+                shutdown_token.clone(),
+            ));
+            let https_addr = SocketAddr::from(([0, 0, 0, 0], config.tls.port));
+            tracing::info!("Listening on https://{}", https_addr);
+            Some((https_addr, rustls_config))
+        } else {
+            None
         };
+        let https_port = https.as_ref().map(|(addr, _)| addr.port());
+
+        // Advertise via mDNS for knob discovery
+        let _mdns =
+            match mdns::advertise(config.port, https_port, "Unified Hi-Fi Control", &base_url) {
+                Ok(daemon) => {
+                    tracing::info!("mDNS advertising started");
+                    Some(daemon)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to start mDNS advertising: {}", e);
+                    None
+                }
+            };
 
         // Start firmware auto-update service
         let firmware_auto_update = std::env::var("FIRMWARE_AUTO_UPDATE")
@@ -515,6 +695,31 @@ mod server {
 
         let listener = tokio::net::TcpListener::bind(addr).await?;
 
+        let https_server = https.map(|(https_addr, rustls_config)| {
+            let handle = axum_server::Handle::new();
+            let shutdown_handle = handle.clone();
+            let token = shutdown_token.clone();
+            tokio::spawn(async move {
+                token.cancelled().await;
+                shutdown_handle.graceful_shutdown(Some(std::time::Duration::from_secs(5)));
+            });
+
+            // Marks requests as HTTPS, e.g. for `Secure` session cookies
+            let server = axum_server::bind_rustls(https_addr, rustls_config)
+                .handle(handle)
+                .serve(
+                    router
+                        .clone()
+                        .layer(axum::Extension(tls::OverTls))
+                        .into_make_service_with_connect_info::<SocketAddr>(),
+                );
+            tokio::spawn(async move {
+                if let Err(e) = server.await {
+                    tracing::error!("HTTPS server failed: {}", e);
+                }
+            })
+        });
+
         // Create shutdown future that cancels token before graceful shutdown (fixes #73)
         let graceful_shutdown = {
             let token = shutdown_token.clone();
@@ -542,6 +747,11 @@ mod server {
         )
         .with_graceful_shutdown(graceful_shutdown)
         .await?;
+        if let Some(server) = https_server {
+            if let Err(e) = server.await {
+                tracing::warn!("HTTPS server task failed: {}", e);
+            }
+        }
 
         // Cleanup: publish ShuttingDown event and stop adapters
         tracing::info!("Shutting down adapters...");
@@ -560,8 +770,12 @@ mod server {
             fw.stop();
         }
         lms.stop().await;
+        coord.stop_all(&lms.server_startables().await).await;
         openhome.stop().await;
         upnp.stop().await;
+        for adapter in external.list() {
+            adapter.stop().await;
+        }
         tracing::info!("Shutdown complete");
 
         Ok(())
@@ -608,6 +822,27 @@ async fn main() -> anyhow::Result<()> {
         );
         return Ok(());
     }
+    if let Some(pos) = args.iter().position(|a| a == "--check-config") {
+        // Validate without starting (e.g. Ansible's `validate:`)
+        let path = args
+            .get(pos + 1)
+            .map(std::path::PathBuf::from)
+            .or_else(unified_hifi_control::config_file::config_file_path);
+        let Some(path) = path else {
+            eprintln!("No config file found");
+            std::process::exit(1);
+        };
+        match unified_hifi_control::config_file::DeclaredConfig::load(&path) {
+            Ok(_) => {
+                println!("{}: OK", path.display());
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("{:#}", e);
+                std::process::exit(1);
+            }
+        }
+    }
     if args.iter().any(|a| a == "--help" || a == "-h") {
         println!(
             "unified-hifi-control {} ({})",
@@ -625,10 +860,13 @@ async fn main() -> anyhow::Result<()> {
         println!("OPTIONS:");
         println!("    -h, --help       Print help information");
         println!("    -V, --version    Print version information");
+        println!("    --check-config [FILE]");
+        println!("                     Validate the config file and exit");
         println!();
         println!("ENVIRONMENT VARIABLES:");
         println!("    PORT             HTTP server port (default: 8088)");
         println!("    CONFIG_DIR       Configuration directory");
+        println!("    UHC_CONFIG_FILE  Config file (default: unified-hifi.toml/.yaml/.json in CONFIG_DIR)");
         println!("    LOG_LEVEL        Log level (debug, info, warn, error)");
         println!("    LMS_HOST         LMS server host (auto-enables LMS backend)");
         println!("    LMS_PORT         LMS server port (default: 9000)");
@@ -3,7 +3,13 @@
 //! Provides HTTP endpoints for MCP clients with both Streamable HTTP and SSE transports.
 //! Routes are integrated into the main Axum app on port 8088 at /mcp endpoint.
 
-use crate::api::{load_app_settings, AppState};
+use crate::adapters::AdapterCommand;
+use crate::api::{history_filter, hqp_zone_id, load_app_settings, AppState, HistoryParams};
+use crate::audit::{audit_action, audit_command};
+use crate::bus::{Command, CommandOrigin, ControlSurface, ZoneCapabilities};
+use crate::rooms::RoomRole;
+use crate::scenes::CaptureRequest;
+use crate::sleep_timer::{SleepMode, SleepRequest};
 use async_trait::async_trait;
 use axum::http::{HeaderMap, Method, Uri};
 use axum::{body::Body, extract::Extension, response::IntoResponse};
@@ -32,7 +38,7 @@ const MCP_SESSION_ID_HEADER: &str = "mcp-session-id";
 /// List all available playback zones
 #[mcp_tool(
     name = "hifi_zones",
-    description = "List all available playback zones (Roon, LMS, OpenHome, UPnP)",
+    description = "List all available playback zones (Roon, LMS, OpenHome, UPnP, and rooms that merge them) with the controls each supports",
     read_only_hint = true
 )]
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -41,7 +47,7 @@ pub struct HifiZonesTool {}
 /// Get current playback state for a zone
 #[mcp_tool(
     name = "hifi_now_playing",
-    description = "Get current playback state for a zone (track, artist, album, play state, volume)",
+    description = "Get current playback state for a zone (track, artist, album, stream format, play state, volume)",
     read_only_hint = true
 )]
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -155,6 +161,74 @@ pub struct HifiHqplayerSetPipelineTool {
     pub value: String,
 }
 
+/// Query listening history
+#[mcp_tool(
+    name = "hifi_history",
+    description = "Listening history: tracks played per zone with start/end time, time actually listened, source and stream format. Filter by zone (id or name), source, artist and time range. Times are RFC 3339 (UTC); the response includes the current time for relative questions like 'last night'.",
+    read_only_hint = true
+)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct HifiHistoryTool {
+    /// Zone ID or zone name (e.g., "Living Room"); omit for all zones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone_id: Option<String>,
+    /// Adapter that played the track: roon, lms, openhome, upnp, hqplayer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Artist name or part of it (case-insensitive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    /// Start of the time range (RFC 3339, e.g. "2026-01-10T18:00:00Z")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// End of the time range (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// Maximum tracks to return (default 100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// Set or cancel a sleep timer
+#[mcp_tool(
+    name = "hifi_sleep",
+    description = "Sleep timer: stop a zone after a number of minutes, at the end of the current track, or at the end of the current album. Fades the volume down over the final minute and powers the zone off (LMS) or puts it in standby (Roon) where supported. Use cancel=true to clear the zone's timer."
+)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct HifiSleepTool {
+    /// The zone ID (get from hifi_zones)
+    pub zone_id: String,
+    /// Minutes until playback stops
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minutes: Option<u32>,
+    /// Instead of minutes: "end_of_track" or "end_of_album"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// Cancel the zone's sleep timer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancel: Option<bool>,
+}
+
+/// List, recall or capture scenes
+#[mcp_tool(
+    name = "hifi_scene",
+    description = "Scenes: named snapshots of several zones (volume, mute, linked HQPlayer mode/filter/shaper/rate, optionally what to play). action=list shows saved scenes, action=recall applies one to all of its zones at once and puts them back as they were if any step fails, action=capture saves the current state of zone_ids under a new name."
+)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct HifiSceneTool {
+    /// "list", "recall" or "capture"
+    pub action: String,
+    /// Scene id or name (recall), or the new scene's name (capture)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene: Option<String>,
+    /// Zones to capture (get from hifi_zones)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone_ids: Option<Vec<String>>,
+    /// Capture: also store the current track to play on recall
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_playing: Option<bool>,
+}
+
 // Generate toolbox enum with all tools
 tool_box!(
     HifiTools,
@@ -168,7 +242,10 @@ tool_box!(
         HifiHqplayerStatusTool,
         HifiHqplayerProfilesTool,
         HifiHqplayerLoadProfileTool,
-        HifiHqplayerSetPipelineTool
+        HifiHqplayerSetPipelineTool,
+        HifiHistoryTool,
+        HifiSleepTool,
+        HifiSceneTool
     ]
 );
 
@@ -183,6 +260,9 @@ struct McpZone {
     state: String,
     volume: Option<f64>,
     is_muted: Option<bool>,
+    capabilities: ZoneCapabilities,
+    /// Last-known zone from before a restart, not yet confirmed by its adapter
+    is_stale: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -193,6 +273,8 @@ struct McpNowPlaying {
     title: Option<String>,
     artist: Option<String>,
     album: Option<String>,
+    /// Stream format label, e.g. "FLAC 24/192" or "DSD128"
+    format: Option<String>,
     volume: Option<f64>,
     is_muted: Option<bool>,
 }
@@ -253,31 +335,33 @@ impl HifiMcpHandler {
         zone_id: &str,
         value: f64,
         relative: bool,
+        origin: CommandOrigin,
     ) -> Result<CallToolResult, CallToolError> {
-        let result = if zone_id.starts_with("lms:") {
-            self.state
-                .lms
-                .change_volume(zone_id, value as f32, relative)
-                .await
-        } else if zone_id.starts_with("roon:") || !zone_id.contains(':') {
-            self.state
-                .roon
-                .change_volume(zone_id, value as f32, relative)
-                .await
-        } else {
-            return Self::error_result("Volume control not supported for this zone type".into());
-        };
+        // Volume goes through the safety policy, which resolves rooms itself
+        let result = self
+            .state
+            .set_volume(zone_id, value as f32, relative, origin)
+            .await;
 
         match result {
-            Ok(()) => Ok(Self::text_result(format!(
-                "Volume {}",
-                if relative { "adjusted" } else { "set" }
-            ))),
+            Ok(outcome) => Ok(Self::text_result(match outcome.reason {
+                Some(reason) => format!("Volume limited by safety policy: {}", reason),
+                None => format!("Volume {}", if relative { "adjusted" } else { "set" }),
+            })),
             Err(e) => Self::error_result(format!("Volume error: {}", e)),
         }
     }
 }
 
+/// Origin of a tool call: the MCP session and the client's name
+fn mcp_origin(runtime: &dyn McpServer) -> CommandOrigin {
+    CommandOrigin {
+        id: runtime.session_id(),
+        name: runtime.client_info().map(|c| c.client_info.name),
+        ..CommandOrigin::new(ControlSurface::Mcp)
+    }
+}
+
 #[async_trait]
 impl ServerHandler for HifiMcpHandler {
     async fn handle_list_tools_request(
@@ -303,13 +387,14 @@ impl ServerHandler for HifiMcpHandler {
     async fn handle_call_tool_request(
         &self,
         params: CallToolRequestParams,
-        _runtime: Arc<dyn McpServer>,
+        runtime: Arc<dyn McpServer>,
     ) -> Result<CallToolResult, CallToolError> {
         let tool: HifiTools = HifiTools::try_from(params).map_err(CallToolError::new)?;
+        let origin = mcp_origin(runtime.as_ref());
 
         match tool {
             HifiTools::HifiZonesTool(_) => {
-                let zones = self.state.aggregator.get_zones().await;
+                let zones = self.state.get_zones().await;
                 let mcp_zones: Vec<McpZone> = zones
                     .into_iter()
                     .map(|z| McpZone {
@@ -318,29 +403,34 @@ impl ServerHandler for HifiMcpHandler {
                         state: z.state.to_string(),
                         volume: z.volume_control.as_ref().map(|v| v.value as f64),
                         is_muted: z.volume_control.as_ref().map(|v| v.is_muted),
+                        capabilities: z.capabilities,
+                        is_stale: z.is_stale,
                     })
                     .collect();
                 Ok(Self::json_result(&mcp_zones))
             }
 
-            HifiTools::HifiNowPlayingTool(args) => {
-                match self.state.aggregator.get_zone(&args.zone_id).await {
-                    Some(z) => {
-                        let np = McpNowPlaying {
-                            zone_id: z.zone_id,
-                            zone_name: z.zone_name,
-                            state: z.state.to_string(),
-                            title: z.now_playing.as_ref().map(|n| n.title.clone()),
-                            artist: z.now_playing.as_ref().map(|n| n.artist.clone()),
-                            album: z.now_playing.as_ref().map(|n| n.album.clone()),
-                            volume: z.volume_control.as_ref().map(|v| v.value as f64),
-                            is_muted: z.volume_control.as_ref().map(|v| v.is_muted),
-                        };
-                        Ok(Self::json_result(&np))
-                    }
-                    None => Self::error_result(format!("Zone not found: {}", args.zone_id)),
+            HifiTools::HifiNowPlayingTool(args) => match self.state.get_zone(&args.zone_id).await {
+                Some(z) => {
+                    let np = McpNowPlaying {
+                        zone_id: z.zone_id,
+                        zone_name: z.zone_name,
+                        state: z.state.to_string(),
+                        title: z.now_playing.as_ref().map(|n| n.title.clone()),
+                        artist: z.now_playing.as_ref().map(|n| n.artist.clone()),
+                        album: z.now_playing.as_ref().map(|n| n.album.clone()),
+                        format: z
+                            .now_playing
+                            .as_ref()
+                            .and_then(|n| n.metadata.as_ref())
+                            .and_then(|m| m.summary()),
+                        volume: z.volume_control.as_ref().map(|v| v.value as f64),
+                        is_muted: z.volume_control.as_ref().map(|v| v.is_muted),
+                    };
+                    Ok(Self::json_result(&np))
                 }
-            }
+                None => Self::error_result(format!("Zone not found: {}", args.zone_id)),
+            },
 
             HifiTools::HifiControlTool(args) => {
                 // Map MCP actions to backend actions
@@ -352,46 +442,80 @@ impl ServerHandler for HifiMcpHandler {
                     "previous" | "prev" => "previous",
                     "volume_set" => {
                         if let Some(v) = args.value {
-                            return self.set_volume(&args.zone_id, v, false).await;
+                            return self.set_volume(&args.zone_id, v, false, origin).await;
                         }
                         return Self::error_result("volume_set requires a value (0-100)".into());
                     }
                     "volume_up" => {
                         let delta = args.value.unwrap_or(5.0);
-                        return self.set_volume(&args.zone_id, delta, true).await;
+                        return self.set_volume(&args.zone_id, delta, true, origin).await;
                     }
                     "volume_down" => {
                         let delta = args.value.unwrap_or(5.0);
-                        return self.set_volume(&args.zone_id, -delta, true).await;
+                        return self.set_volume(&args.zone_id, -delta, true, origin).await;
                     }
                     other => other,
                 };
 
+                // Fade around transport when fades are enabled for MCP
+                let fade_command = match backend_action {
+                    "play" => Some(AdapterCommand::Play),
+                    "pause" => Some(AdapterCommand::Pause),
+                    "play_pause" => Some(AdapterCommand::PlayPause),
+                    "next" => Some(AdapterCommand::Next),
+                    "previous" => Some(AdapterCommand::Previous),
+                    "stop" => Some(AdapterCommand::Stop),
+                    _ => None,
+                };
+                let fades = self.state.fades.settings().await;
+                let fades_enabled = fades
+                    .duration_for(&[&args.zone_id], ControlSurface::Mcp)
+                    .is_some();
+
+                // Rooms delegate transport to their transport member
+                let target = match self
+                    .state
+                    .resolve_zone_id(&args.zone_id, RoomRole::Transport)
+                    .await
+                {
+                    Ok(id) => id,
+                    Err(e) => return Self::error_result(format!("Control error: {}", e)),
+                };
+
                 // Determine which adapter to use based on zone_id prefix
-                let result = if args.zone_id.starts_with("lms:") {
-                    self.state
-                        .lms
-                        .control(&args.zone_id, backend_action, None)
-                        .await
-                } else if args.zone_id.starts_with("openhome:") {
-                    self.state
-                        .openhome
-                        .control(&args.zone_id, backend_action, None)
-                        .await
-                } else if args.zone_id.starts_with("upnp:") {
-                    self.state
-                        .upnp
-                        .control(&args.zone_id, backend_action, None)
+                let result = if let Some(command) = fade_command.filter(|_| fades_enabled) {
+                    match self
+                        .state
+                        .dispatch_command_from(&args.zone_id, command, origin)
                         .await
+                    {
+                        Ok(response) => response.into_result().map_err(anyhow::Error::from),
+                        Err(e) => Err(e),
+                    }
                 } else {
-                    // Default to Roon
-                    self.state.roon.control(&args.zone_id, backend_action).await
+                    let control = async {
+                        if target.starts_with("lms:") {
+                            self.state.lms.control(&target, backend_action, None).await
+                        } else if target.starts_with("openhome:") {
+                            self.state
+                                .openhome
+                                .control(&target, backend_action, None)
+                                .await
+                        } else if target.starts_with("upnp:") {
+                            self.state.upnp.control(&target, backend_action, None).await
+                        } else {
+                            // Default to Roon
+                            self.state.roon.control(&target, backend_action).await
+                        }
+                    };
+                    let bus = &self.state.bus;
+                    audit_action(bus, &args.zone_id, backend_action, origin, control).await
                 };
 
                 match result {
                     Ok(()) => {
                         // Return updated state
-                        if let Some(zone) = self.state.aggregator.get_zone(&args.zone_id).await {
+                        if let Some(zone) = self.state.get_zone(&args.zone_id).await {
                             let np = McpNowPlaying {
                                 zone_id: zone.zone_id,
                                 zone_name: zone.zone_name,
@@ -399,6 +523,11 @@ impl ServerHandler for HifiMcpHandler {
                                 title: zone.now_playing.as_ref().map(|n| n.title.clone()),
                                 artist: zone.now_playing.as_ref().map(|n| n.artist.clone()),
                                 album: zone.now_playing.as_ref().map(|n| n.album.clone()),
+                                format: zone
+                                    .now_playing
+                                    .as_ref()
+                                    .and_then(|n| n.metadata.as_ref())
+                                    .and_then(|m| m.summary()),
                                 volume: zone.volume_control.as_ref().map(|v| v.value as f64),
                                 is_muted: zone.volume_control.as_ref().map(|v| v.is_muted),
                             };
@@ -579,7 +708,16 @@ impl ServerHandler for HifiMcpHandler {
             }
 
             HifiTools::HifiHqplayerLoadProfileTool(args) => {
-                match self.state.hqplayer.load_profile(&args.profile).await {
+                let zone_id = hqp_zone_id(&self.state)
+                    .await
+                    .unwrap_or_else(|| "hqplayer".to_string());
+                let command = Command::HqpDsp {
+                    setting: "profile".to_string(),
+                    value: args.profile.clone(),
+                };
+                let change = self.state.hqplayer.load_profile(&args.profile);
+                let bus = &self.state.bus;
+                match audit_command(bus, &zone_id, command, origin, change).await {
                     Ok(()) => Ok(Self::text_result(format!(
                         "Loaded profile: {}",
                         args.profile
@@ -591,22 +729,13 @@ impl ServerHandler for HifiMcpHandler {
             HifiTools::HifiHqplayerSetPipelineTool(args) => {
                 // All settings now use name-based lookups - adapter handles conversion
                 // Only samplerate needs numeric parsing (Hz value)
-                let result = match args.setting.as_str() {
-                    "mode" => {
-                        // Accepts name like "PCM", "DSD", "[source]"
-                        self.state.hqplayer.set_mode(&args.value).await
-                    }
-                    "filter1x" | "filter_1x" => {
-                        self.state.hqplayer.set_filter_1x(&args.value).await
-                    }
-                    "filterNx" | "filter_nx" | "filternx" => {
-                        self.state.hqplayer.set_filter_nx(&args.value).await
-                    }
-                    "shaper" | "dither" => self.state.hqplayer.set_shaper(&args.value).await,
+                let rate = match args.setting.as_str() {
+                    "mode" | "filter1x" | "filter_1x" | "filterNx" | "filter_nx" | "filternx"
+                    | "shaper" | "dither" => None,
                     "rate" | "samplerate" => {
                         // Samplerate uses Hz value (e.g., "48000", "96000")
                         if let Ok(v) = args.value.parse::<u32>() {
-                            self.state.hqplayer.set_rate(v).await
+                            Some(v)
                         } else {
                             return Self::error_result(
                                 "Invalid rate value (expected Hz like 48000, 96000)".into(),
@@ -620,8 +749,29 @@ impl ServerHandler for HifiMcpHandler {
                         ));
                     }
                 };
+                let hqplayer = &self.state.hqplayer;
+                let change = async {
+                    match (args.setting.as_str(), rate) {
+                        (_, Some(v)) => hqplayer.set_rate(v).await,
+                        // Accepts name like "PCM", "DSD", "[source]"
+                        ("mode", _) => hqplayer.set_mode(&args.value).await,
+                        ("filter1x" | "filter_1x", _) => hqplayer.set_filter_1x(&args.value).await,
+                        ("filterNx" | "filter_nx" | "filternx", _) => {
+                            hqplayer.set_filter_nx(&args.value).await
+                        }
+                        _ => hqplayer.set_shaper(&args.value).await,
+                    }
+                };
+                let zone_id = hqp_zone_id(&self.state)
+                    .await
+                    .unwrap_or_else(|| "hqplayer".to_string());
+                let command = Command::HqpDsp {
+                    setting: args.setting.clone(),
+                    value: args.value.clone(),
+                };
+                let bus = &self.state.bus;
 
-                match result {
+                match audit_command(bus, &zone_id, command, origin, change).await {
                     Ok(()) => Ok(Self::text_result(format!(
                         "Set {} to {}",
                         args.setting, args.value
@@ -629,6 +779,96 @@ impl ServerHandler for HifiMcpHandler {
                     Err(e) => Self::error_result(format!("Failed to set {}: {}", args.setting, e)),
                 }
             }
+
+            HifiTools::HifiHistoryTool(args) => {
+                let params = HistoryParams {
+                    zone_id: args.zone_id,
+                    source: args.source,
+                    artist: args.artist,
+                    since: args.since,
+                    until: args.until,
+                    limit: args.limit,
+                };
+                match history_filter(&self.state, params).await {
+                    Ok(filter) => {
+                        let entries = self.state.history.query(&filter).await;
+                        Ok(Self::json_result(&serde_json::json!({
+                            "now": chrono::Utc::now().to_rfc3339(),
+                            "count": entries.len(),
+                            "entries": entries
+                        })))
+                    }
+                    Err(e) => Self::error_result(e),
+                }
+            }
+
+            HifiTools::HifiSleepTool(args) => {
+                if args.cancel == Some(true) {
+                    let was_cancelled = self
+                        .state
+                        .sleep_timers
+                        .cancel(&self.state, &args.zone_id)
+                        .await;
+                    return Ok(Self::text_result(if was_cancelled {
+                        "Sleep timer cancelled".to_string()
+                    } else {
+                        "No sleep timer was set".to_string()
+                    }));
+                }
+                let request = match (args.until.as_deref(), args.minutes) {
+                    (Some("end_of_track"), _) => {
+                        SleepRequest::until(&args.zone_id, SleepMode::EndOfTrack)
+                    }
+                    (Some("end_of_album"), _) => {
+                        SleepRequest::until(&args.zone_id, SleepMode::EndOfAlbum)
+                    }
+                    (Some(other), _) => {
+                        return Self::error_result(format!(
+                            "Unknown until '{}': use end_of_track or end_of_album",
+                            other
+                        ))
+                    }
+                    (None, Some(minutes)) => SleepRequest::minutes(&args.zone_id, minutes),
+                    (None, None) => return Self::error_result("Give minutes or until".to_string()),
+                };
+                match self.state.sleep_timers.set(&self.state, request).await {
+                    Ok(timer) => Ok(Self::json_result(&timer)),
+                    Err(e) => Self::error_result(format!("Sleep timer error: {}", e)),
+                }
+            }
+
+            HifiTools::HifiSceneTool(args) => match args.action.as_str() {
+                "list" => Ok(Self::json_result(&self.state.scenes.list().await)),
+                "recall" => {
+                    let Some(scene) = args.scene else {
+                        return Self::error_result("recall needs a scene".to_string());
+                    };
+                    match self.state.scenes.recall(&self.state, &scene, &origin).await {
+                        Ok(recall) => Ok(Self::json_result(&recall)),
+                        Err(e) => Self::error_result(format!("Scene recall failed: {}", e)),
+                    }
+                }
+                "capture" => {
+                    let (Some(name), Some(zone_ids)) = (args.scene, args.zone_ids) else {
+                        return Self::error_result(
+                            "capture needs a scene name and zone_ids".to_string(),
+                        );
+                    };
+                    let request = CaptureRequest {
+                        name,
+                        zone_ids,
+                        include_playing: args.include_playing.unwrap_or(false),
+                    };
+                    match self.state.scenes.capture(&self.state, request).await {
+                        Ok(scene) => Ok(Self::json_result(&scene)),
+                        Err(e) => Self::error_result(format!("Scene capture failed: {}", e)),
+                    }
+                }
+                other => Self::error_result(format!(
+                    "Unknown action '{}': use list, recall or capture",
+                    other
+                )),
+            },
         }
     }
 }
@@ -1,18 +1,27 @@
 //! mDNS service advertising for knob discovery
 //!
 //! Publishes a _roonknob._tcp service so S3 Knob devices can discover the server.
+//! `base` is the plain HTTP URL; `https_port` is added when HTTPS is enabled.
 
 use mdns_sd::{ServiceDaemon, ServiceInfo};
 use std::collections::HashMap;
 
 /// Advertise the service via mDNS
-pub fn advertise(port: u16, name: &str, base_url: &str) -> anyhow::Result<ServiceDaemon> {
+pub fn advertise(
+    port: u16,
+    https_port: Option<u16>,
+    name: &str,
+    base_url: &str,
+) -> anyhow::Result<ServiceDaemon> {
     let mdns = ServiceDaemon::new()?;
 
     // Build TXT records
     let mut txt = HashMap::new();
     txt.insert("base".to_string(), base_url.to_string());
     txt.insert("api".to_string(), "1".to_string());
+    if let Some(https_port) = https_port {
+        txt.insert("https_port".to_string(), https_port.to_string());
+    }
 
     // Create service info
     // Type is "_roonknob._tcp.local."
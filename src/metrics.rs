@@ -0,0 +1,460 @@
+//! Prometheus metrics
+//!
+//! Commands, knob requests and image fetches are counted as they happen;
+//! everything else (zones, adapter health, bus, SSE clients, knob batteries,
+//! HQPlayer instances) is read from the live state when `/metrics` is scraped.
+//!
+//! The text exposition format (version 0.0.4) is written by hand, so there is
+//! no metrics registry to configure and nothing to push anywhere.
+
+use crate::api::AppState;
+use crate::coordinator::ConnectionState;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Content type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds (seconds) of the command latency histogram buckets
+pub const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0];
+
+/// Counts and latency histogram for one adapter/action pair
+#[derive(Debug, Default, Clone)]
+struct CommandStats {
+    ok: u64,
+    failed: u64,
+    /// Per-bucket (not cumulative) counts; the last slot is +Inf
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    sum_secs: f64,
+}
+
+impl CommandStats {
+    fn observe(&mut self, elapsed: Duration, ok: bool) {
+        let secs = elapsed.as_secs_f64();
+        let slot = LATENCY_BUCKETS
+            .iter()
+            .position(|&le| secs <= le)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[slot] += 1;
+        self.sum_secs += secs;
+        if ok {
+            self.ok += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+
+    fn count(&self) -> u64 {
+        self.ok + self.failed
+    }
+}
+
+/// Counters recorded while the server runs
+#[derive(Default)]
+pub struct Metrics {
+    /// Keyed by (adapter, action)
+    commands: Mutex<BTreeMap<(String, &'static str), CommandStats>>,
+    /// Keyed by knob endpoint
+    knob_requests: Mutex<BTreeMap<&'static str, u64>>,
+    /// Keyed by (source, result)
+    images: Mutex<BTreeMap<(String, &'static str), u64>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // Counters stay meaningful after a panic elsewhere; keep serving them
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Adapter owning a zone id ("roon" for legacy unprefixed ids)
+pub fn zone_adapter(zone_id: &str) -> &str {
+    zone_id
+        .split_once(':')
+        .map(|(prefix, _)| prefix)
+        .unwrap_or("roon")
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a command sent to an adapter
+    pub fn record_command(&self, zone_id: &str, action: &'static str, elapsed: Duration, ok: bool) {
+        lock(&self.commands)
+            .entry((zone_adapter(zone_id).to_string(), action))
+            .or_default()
+            .observe(elapsed, ok);
+    }
+
+    /// Record a request from a knob (or the knob API)
+    pub fn record_knob_request(&self, endpoint: &'static str) {
+        *lock(&self.knob_requests).entry(endpoint).or_default() += 1;
+    }
+
+    /// Record an artwork fetch for a zone
+    pub fn record_image(&self, zone_id: &str, ok: bool) {
+        let result = if ok { "ok" } else { "error" };
+        *lock(&self.images)
+            .entry((zone_adapter(zone_id).to_string(), result))
+            .or_default() += 1;
+    }
+
+    fn encode(&self, out: &mut Exposition) {
+        let commands = lock(&self.commands).clone();
+        out.family(
+            "hifi_commands_total",
+            "counter",
+            "Commands sent to adapters, by adapter, action and outcome",
+        );
+        for ((adapter, action), stats) in &commands {
+            for (outcome, value) in [("ok", stats.ok), ("error", stats.failed)] {
+                out.sample(
+                    "hifi_commands_total",
+                    &[
+                        ("adapter", adapter.as_str()),
+                        ("action", *action),
+                        ("outcome", outcome),
+                    ],
+                    value,
+                );
+            }
+        }
+
+        out.family(
+            "hifi_command_duration_seconds",
+            "histogram",
+            "Time for an adapter to handle a command",
+        );
+        for ((adapter, action), stats) in &commands {
+            let mut cumulative = 0;
+            for (i, count) in stats.buckets.iter().enumerate() {
+                cumulative += count;
+                let le = LATENCY_BUCKETS
+                    .get(i)
+                    .map(|b| b.to_string())
+                    .unwrap_or_else(|| "+Inf".to_string());
+                out.sample(
+                    "hifi_command_duration_seconds_bucket",
+                    &[
+                        ("adapter", adapter.as_str()),
+                        ("action", *action),
+                        ("le", le.as_str()),
+                    ],
+                    cumulative,
+                );
+            }
+            let labels = [("adapter", adapter.as_str()), ("action", *action)];
+            out.sample("hifi_command_duration_seconds_sum", &labels, stats.sum_secs);
+            out.sample(
+                "hifi_command_duration_seconds_count",
+                &labels,
+                stats.count(),
+            );
+        }
+
+        out.family(
+            "hifi_knob_requests_total",
+            "counter",
+            "Requests to the knob API, by endpoint",
+        );
+        for (endpoint, value) in lock(&self.knob_requests).iter() {
+            out.sample(
+                "hifi_knob_requests_total",
+                &[("endpoint", *endpoint)],
+                *value,
+            );
+        }
+
+        // There is no artwork cache: every request goes to the adapter, so
+        // this counts fetches and failures rather than hit rates.
+        out.family(
+            "hifi_image_requests_total",
+            "counter",
+            "Artwork fetched from adapters, by source and result",
+        );
+        for ((source, result), value) in lock(&self.images).iter() {
+            out.sample(
+                "hifi_image_requests_total",
+                &[("source", source.as_str()), ("result", *result)],
+                *value,
+            );
+        }
+    }
+}
+
+/// Writer for the Prometheus text exposition format
+#[derive(Default)]
+pub struct Exposition {
+    out: String,
+}
+
+/// Escape a label value (backslash, double quote and newline)
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl Exposition {
+    /// Start a metric family with its HELP and TYPE lines
+    pub fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+        let _ = writeln!(self.out, "# HELP {name} {help}");
+        let _ = writeln!(self.out, "# TYPE {name} {kind}");
+    }
+
+    /// Write one sample line
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+                .collect();
+            let _ = write!(self.out, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.out, " {value}");
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+/// Render every metric for a scrape
+pub async fn render(state: &AppState) -> String {
+    let mut out = Exposition::default();
+
+    out.family(
+        "hifi_uptime_seconds",
+        "gauge",
+        "Seconds since the server started",
+    );
+    out.sample(
+        "hifi_uptime_seconds",
+        &[],
+        state.start_time.elapsed().as_secs(),
+    );
+
+    // Adapters: connection state and zone counts
+    let mut adapters: Vec<_> = state
+        .coordinator
+        .adapter_status()
+        .await
+        .into_values()
+        .collect();
+    adapters.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+
+    let mut zones: BTreeMap<String, u64> = adapters
+        .iter()
+        .filter(|a| a.enabled)
+        .map(|a| (a.prefix.clone(), 0))
+        .collect();
+    for zone in state.aggregator.get_zones().await {
+        *zones
+            .entry(zone_adapter(&zone.zone_id).to_string())
+            .or_default() += 1;
+    }
+    out.family(
+        "hifi_adapter_zones",
+        "gauge",
+        "Zones currently reported by each adapter",
+    );
+    for (adapter, count) in &zones {
+        out.sample(
+            "hifi_adapter_zones",
+            &[("adapter", adapter.as_str())],
+            count,
+        );
+    }
+
+    out.family(
+        "hifi_adapter_enabled",
+        "gauge",
+        "Whether the adapter is enabled in settings",
+    );
+    for adapter in &adapters {
+        out.sample(
+            "hifi_adapter_enabled",
+            &[("adapter", adapter.prefix.as_str())],
+            u8::from(adapter.enabled),
+        );
+    }
+
+    out.family(
+        "hifi_adapter_state",
+        "gauge",
+        "Adapter connection state (1 for the current state)",
+    );
+    for adapter in &adapters {
+        for candidate in ConnectionState::ALL {
+            out.sample(
+                "hifi_adapter_state",
+                &[
+                    ("adapter", adapter.prefix.as_str()),
+                    ("state", candidate.as_str()),
+                ],
+                u8::from(adapter.health.state == candidate),
+            );
+        }
+    }
+
+    out.family(
+        "hifi_adapter_consecutive_failures",
+        "gauge",
+        "Adapter failures in a row",
+    );
+    for adapter in &adapters {
+        out.sample(
+            "hifi_adapter_consecutive_failures",
+            &[("adapter", adapter.prefix.as_str())],
+            adapter.health.consecutive_failures,
+        );
+    }
+
+    // Event bus
+    out.family(
+        "hifi_bus_events_published_total",
+        "counter",
+        "Events published on the internal bus",
+    );
+    out.sample(
+        "hifi_bus_events_published_total",
+        &[],
+        state.bus.published(),
+    );
+    out.family(
+        "hifi_bus_queue_depth",
+        "gauge",
+        "Events not yet received by the slowest bus subscriber",
+    );
+    out.sample("hifi_bus_queue_depth", &[], state.bus.queue_depth());
+    out.family("hifi_bus_subscribers", "gauge", "Active bus subscribers");
+    out.sample("hifi_bus_subscribers", &[], state.bus.subscriber_count());
+
+    out.family("hifi_sse_connections", "gauge", "Open SSE streams");
+    out.sample("hifi_sse_connections", &[], state.active_sse_connections());
+
+    // Knobs
+    let knobs = state.knobs.list().await;
+    out.family("hifi_knobs", "gauge", "Registered knobs");
+    out.sample("hifi_knobs", &[], knobs.len());
+    out.family(
+        "hifi_knob_battery_percent",
+        "gauge",
+        "Last battery level reported by each knob",
+    );
+    for knob in &knobs {
+        if let Some(level) = knob.status.battery_level {
+            out.sample(
+                "hifi_knob_battery_percent",
+                &[
+                    ("knob_id", knob.knob_id.as_str()),
+                    ("name", knob.name.as_str()),
+                ],
+                level,
+            );
+        }
+    }
+
+    // HQPlayer
+    out.family(
+        "hifi_hqplayer_connected",
+        "gauge",
+        "Whether each HQPlayer instance is connected",
+    );
+    let instances = state.hqp_instances.list_instances().await;
+    if instances.is_empty() {
+        let status = state.hqplayer.get_status().await;
+        if let Some(host) = status.host {
+            out.sample(
+                "hifi_hqplayer_connected",
+                &[("instance", "default"), ("host", host.as_str())],
+                u8::from(status.connected),
+            );
+        }
+    }
+    for instance in &instances {
+        out.sample(
+            "hifi_hqplayer_connected",
+            &[
+                ("instance", instance.name.as_str()),
+                ("host", instance.host.as_deref().unwrap_or("")),
+            ],
+            u8::from(instance.connected),
+        );
+    }
+
+    state.metrics.encode(&mut out);
+    out.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exposition_escapes_labels() {
+        let mut out = Exposition::default();
+        out.family("hifi_test", "gauge", "A test metric");
+        out.sample("hifi_test", &[("name", "Den \"main\"\\\nknob")], 1);
+        out.sample("hifi_test", &[], 2.5);
+        assert_eq!(
+            out.finish(),
+            "# HELP hifi_test A test metric\n\
+             # TYPE hifi_test gauge\n\
+             hifi_test{name=\"Den \\\"main\\\"\\\\\\nknob\"} 1\n\
+             hifi_test 2.5\n"
+        );
+    }
+
+    #[test]
+    fn test_command_histogram_is_cumulative() {
+        let metrics = Metrics::new();
+        metrics.record_command("lms:aa", "play", Duration::from_millis(3), true);
+        metrics.record_command("lms:aa", "play", Duration::from_millis(300), false);
+        metrics.record_command("lms:aa", "play", Duration::from_secs(30), true);
+
+        let mut out = Exposition::default();
+        metrics.encode(&mut out);
+        let text = out.finish();
+
+        assert!(text
+            .contains("hifi_commands_total{adapter=\"lms\",action=\"play\",outcome=\"ok\"} 2\n"));
+        assert!(text.contains(
+            "hifi_commands_total{adapter=\"lms\",action=\"play\",outcome=\"error\"} 1\n"
+        ));
+        assert!(text.contains(
+            "hifi_command_duration_seconds_bucket{adapter=\"lms\",action=\"play\",le=\"0.005\"} 1\n"
+        ));
+        assert!(text.contains(
+            "hifi_command_duration_seconds_bucket{adapter=\"lms\",action=\"play\",le=\"0.5\"} 2\n"
+        ));
+        assert!(text.contains(
+            "hifi_command_duration_seconds_bucket{adapter=\"lms\",action=\"play\",le=\"+Inf\"} 3\n"
+        ));
+        assert!(text
+            .contains("hifi_command_duration_seconds_count{adapter=\"lms\",action=\"play\"} 3\n"));
+    }
+
+    #[test]
+    fn test_legacy_zone_ids_count_as_roon() {
+        let metrics = Metrics::new();
+        metrics.record_image("1601bb42", true);
+        metrics.record_image("openhome:uuid", false);
+        metrics.record_knob_request("now_playing");
+
+        let mut out = Exposition::default();
+        metrics.encode(&mut out);
+        let text = out.finish();
+
+        assert!(text.contains("hifi_image_requests_total{source=\"roon\",result=\"ok\"} 1\n"));
+        assert!(
+            text.contains("hifi_image_requests_total{source=\"openhome\",result=\"error\"} 1\n")
+        );
+        assert!(text.contains("hifi_knob_requests_total{endpoint=\"now_playing\"} 1\n"));
+    }
+}
@@ -0,0 +1,476 @@
+//! Logical rooms - merge zones from different adapters that represent one physical system
+//!
+//! A single streamer often shows up as a Roon zone, an OpenHome device and a UPnP
+//! renderer at the same time. A room binds those zone ids together, chooses which
+//! member is authoritative for transport, volume and metadata, and is presented to
+//! clients as a single `room:<id>` zone in place of its members.
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::bus::{PrefixedZoneId, Zone, ZoneCapabilities};
+use crate::config_store::{normalize_id, unique_id, ConfigFile};
+
+const ROOMS_FILE: &str = "rooms.json";
+
+/// Zone id prefix for merged room zones
+pub const ROOM_PREFIX: &str = "room:";
+
+/// Which aspect of a room a member zone is authoritative for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoomRole {
+    /// Play/pause/skip/seek and playback state
+    Transport,
+    /// Volume level and mute
+    Volume,
+    /// Now playing (title, artist, artwork, format)
+    Metadata,
+}
+
+/// A persisted logical room
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Room {
+    /// Stable identifier (lowercase slug); derived from the name when empty
+    #[serde(default)]
+    pub id: String,
+    /// Display name shown instead of the member zone names
+    pub name: String,
+    /// Member zone ids in priority order (e.g. "roon:1601...", "openhome:uuid")
+    pub zone_ids: Vec<String>,
+    /// Member used for transport (defaults to the first available member)
+    #[serde(default)]
+    pub transport_zone: Option<String>,
+    /// Member used for volume (defaults to the first available member with volume)
+    #[serde(default)]
+    pub volume_zone: Option<String>,
+    /// Member used for now playing (defaults to the first available member playing)
+    #[serde(default)]
+    pub metadata_zone: Option<String>,
+}
+
+impl Room {
+    /// Zone id this room is exposed under
+    pub fn zone_id(&self) -> String {
+        format!("{}{}", ROOM_PREFIX, self.id)
+    }
+
+    fn preferred(&self, role: RoomRole) -> Option<&str> {
+        match role {
+            RoomRole::Transport => self.transport_zone.as_deref(),
+            RoomRole::Volume => self.volume_zone.as_deref(),
+            RoomRole::Metadata => self.metadata_zone.as_deref(),
+        }
+    }
+
+    /// Pick the member zone that handles `role` among the zones currently online.
+    ///
+    /// The configured member wins when it is online; otherwise the first online
+    /// member that can serve the role, then simply the first online member.
+    pub fn authority<'a>(
+        &self,
+        role: RoomRole,
+        zones: &'a HashMap<String, Zone>,
+    ) -> Option<&'a Zone> {
+        if let Some(zone) = self.preferred(role).and_then(|id| zones.get(id)) {
+            return Some(zone);
+        }
+
+        let online: Vec<&Zone> = self
+            .zone_ids
+            .iter()
+            .filter_map(|id| zones.get(id))
+            .collect();
+        let serves = |zone: &Zone| match role {
+            RoomRole::Transport => zone.capabilities.transport,
+            RoomRole::Volume => zone.volume_control.is_some(),
+            RoomRole::Metadata => zone.now_playing.is_some(),
+        };
+        online
+            .iter()
+            .copied()
+            .find(|zone| serves(zone))
+            .or_else(|| online.first().copied())
+    }
+
+    /// Check the room is well-formed, filling in the id from the name if missing
+    pub(crate) fn normalize(&mut self) -> Result<()> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            bail!("name is required");
+        }
+        normalize_id(&mut self.id, &self.name)?;
+
+        let mut seen = HashSet::new();
+        self.zone_ids.retain(|id| seen.insert(id.clone()));
+        if self.zone_ids.is_empty() {
+            bail!("at least one zone is required");
+        }
+        for zone_id in &self.zone_ids {
+            if PrefixedZoneId::parse(zone_id).is_none() {
+                bail!("{} is not an adapter zone id", zone_id);
+            }
+        }
+
+        for role_zone in [&self.transport_zone, &self.volume_zone, &self.metadata_zone]
+            .into_iter()
+            .flatten()
+        {
+            if !self.zone_ids.contains(role_zone) {
+                bail!("{} is not a member of the room", role_zone);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Build the merged zone for a room, or `None` when no member is online
+fn merge_room(room: &Room, zones: &HashMap<String, Zone>) -> Option<Zone> {
+    let transport = room.authority(RoomRole::Transport, zones)?;
+    let volume = room.authority(RoomRole::Volume, zones);
+    let metadata = room.authority(RoomRole::Metadata, zones);
+
+    let last_updated = room
+        .zone_ids
+        .iter()
+        .filter_map(|id| zones.get(id))
+        .map(|z| z.last_updated)
+        .max()
+        .unwrap_or(transport.last_updated);
+
+    let volume_caps = volume.map(|z| &z.capabilities);
+
+    Some(Zone {
+        zone_id: room.zone_id(),
+        zone_name: room.name.clone(),
+        state: transport.state,
+        volume_control: volume.and_then(|z| z.volume_control.clone()),
+        now_playing: metadata.and_then(|z| z.now_playing.clone()),
+        source: "room".to_string(),
+        is_controllable: transport.is_controllable,
+        is_seekable: transport.is_seekable,
+        last_updated,
+        is_play_allowed: transport.is_play_allowed,
+        is_pause_allowed: transport.is_pause_allowed,
+        is_next_allowed: transport.is_next_allowed,
+        is_previous_allowed: transport.is_previous_allowed,
+        capabilities: ZoneCapabilities {
+            volume: volume_caps.is_some_and(|c| c.volume),
+            volume_db: volume_caps.is_some_and(|c| c.volume_db),
+            mute: volume_caps.is_some_and(|c| c.mute),
+            ..transport.capabilities.clone()
+        },
+        is_stale: transport.is_stale,
+    })
+}
+
+/// Replace the members of each active room with a single merged zone.
+///
+/// Rooms with no online members are omitted and hide nothing.
+pub fn merge_rooms(rooms: &[Room], zones: Vec<Zone>) -> Vec<Zone> {
+    let by_id: HashMap<String, Zone> = zones
+        .iter()
+        .map(|z| (z.zone_id.clone(), z.clone()))
+        .collect();
+
+    let merged: Vec<(Zone, &Room)> = rooms
+        .iter()
+        .filter_map(|room| merge_room(room, &by_id).map(|zone| (zone, room)))
+        .collect();
+
+    let hidden: HashSet<&str> = merged
+        .iter()
+        .flat_map(|(_, room)| room.zone_ids.iter().map(String::as_str))
+        .collect();
+
+    let mut result: Vec<Zone> = zones
+        .into_iter()
+        .filter(|z| !hidden.contains(z.zone_id.as_str()))
+        .collect();
+    result.extend(merged.into_iter().map(|(zone, _)| zone));
+    result
+}
+
+/// Service for managing persisted rooms
+pub struct RoomStore {
+    rooms: Arc<RwLock<Vec<Room>>>,
+    file: ConfigFile,
+}
+
+impl Default for RoomStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RoomStore {
+    /// Create the store, loading saved rooms from disk
+    pub fn new() -> Self {
+        let file = ConfigFile::new(ROOMS_FILE, "rooms");
+        Self {
+            rooms: Arc::new(RwLock::new(Self::read(&file))),
+            file,
+        }
+    }
+
+    /// Store that is never written to disk
+    pub fn in_memory() -> Self {
+        Self {
+            rooms: Arc::new(RwLock::new(Vec::new())),
+            file: ConfigFile::in_memory("rooms"),
+        }
+    }
+
+    /// Re-read rooms from disk (after a restore)
+    pub async fn reload(&self) {
+        self.file.reload(&self.rooms, Self::read).await;
+    }
+
+    fn read(file: &ConfigFile) -> Vec<Room> {
+        let rooms: Vec<Room> = file.load_or_default();
+        if !rooms.is_empty() {
+            tracing::info!("Loaded {} rooms from disk", rooms.len());
+        }
+        rooms
+    }
+
+    /// Save rooms to disk
+    async fn save(&self) {
+        let rooms = self.rooms.read().await;
+        self.file.save(&*rooms);
+    }
+
+    /// Get all rooms
+    pub async fn list(&self) -> Vec<Room> {
+        self.rooms.read().await.clone()
+    }
+
+    /// Get a room by id (accepts "room:<id>" or the bare id)
+    pub async fn get(&self, id: &str) -> Option<Room> {
+        let id = id.strip_prefix(ROOM_PREFIX).unwrap_or(id);
+        self.rooms.read().await.iter().find(|r| r.id == id).cloned()
+    }
+
+    /// Create or replace a room. A zone may only belong to one room. One without
+    /// an id is always created, with a suffix on its id if another room already
+    /// has that name.
+    pub async fn save_room(&self, mut room: Room) -> Result<Room> {
+        let creating = room.id.trim().is_empty();
+        room.normalize()?;
+
+        {
+            let mut rooms = self.rooms.write().await;
+            if creating {
+                room.id = unique_id(&room.id, |id| rooms.iter().any(|r| r.id == id));
+            }
+            if let Some(other) = rooms
+                .iter()
+                .filter(|r| r.id != room.id)
+                .find(|r| r.zone_ids.iter().any(|z| room.zone_ids.contains(z)))
+            {
+                return Err(anyhow!("zone already belongs to room '{}'", other.name));
+            }
+
+            match rooms.iter_mut().find(|r| r.id == room.id) {
+                Some(existing) => *existing = room.clone(),
+                None => rooms.push(room.clone()),
+            }
+        }
+
+        self.save().await;
+        tracing::info!("Saved room {} ({} zones)", room.id, room.zone_ids.len());
+        Ok(room)
+    }
+
+    /// Delete a room, returning whether it existed
+    pub async fn remove(&self, id: &str) -> bool {
+        let id = id.strip_prefix(ROOM_PREFIX).unwrap_or(id);
+        let removed = {
+            let mut rooms = self.rooms.write().await;
+            let before = rooms.len();
+            rooms.retain(|r| r.id != id);
+            rooms.len() != before
+        };
+
+        if removed {
+            self.save().await;
+            tracing::info!("Removed room {}", id);
+        }
+        removed
+    }
+
+    /// Merge adapter zones into room zones (see [`merge_rooms`])
+    pub async fn merge(&self, zones: Vec<Zone>) -> Vec<Zone> {
+        let rooms = self.rooms.read().await;
+        if rooms.is_empty() {
+            return zones;
+        }
+        merge_rooms(&rooms, zones)
+    }
+
+    /// Resolve a room zone id to the member zone that handles `role`.
+    /// Returns `None` for unknown rooms or rooms with no online member.
+    pub async fn resolve(
+        &self,
+        room_zone_id: &str,
+        role: RoomRole,
+        zones: Vec<Zone>,
+    ) -> Option<String> {
+        let room = self.get(room_zone_id).await?;
+        let by_id: HashMap<String, Zone> =
+            zones.into_iter().map(|z| (z.zone_id.clone(), z)).collect();
+        room.authority(role, &by_id).map(|z| z.zone_id.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::{NowPlaying, PlaybackState, VolumeControl, VolumeScale};
+
+    fn zone(zone_id: &str, source: &str) -> Zone {
+        Zone {
+            zone_id: zone_id.to_string(),
+            zone_name: format!("{} zone", source),
+            state: PlaybackState::Stopped,
+            volume_control: None,
+            now_playing: None,
+            source: source.to_string(),
+            is_controllable: true,
+            is_seekable: false,
+            last_updated: 0,
+            is_play_allowed: true,
+            is_pause_allowed: false,
+            is_next_allowed: false,
+            is_previous_allowed: false,
+            capabilities: ZoneCapabilities {
+                transport: true,
+                ..Default::default()
+            },
+            is_stale: false,
+        }
+    }
+
+    fn volume(output_id: &str, value: f32) -> Option<VolumeControl> {
+        Some(VolumeControl {
+            value,
+            min: 0.0,
+            max: 100.0,
+            step: 1.0,
+            is_muted: false,
+            scale: VolumeScale::Percentage,
+            output_id: Some(output_id.to_string()),
+        })
+    }
+
+    fn living_room() -> Room {
+        Room {
+            id: "living".to_string(),
+            name: "Living Room".to_string(),
+            zone_ids: vec!["roon:abc".to_string(), "openhome:uuid".to_string()],
+            transport_zone: None,
+            volume_zone: Some("openhome:uuid".to_string()),
+            metadata_zone: None,
+        }
+    }
+
+    #[test]
+    fn merge_replaces_members_with_room() {
+        let mut roon = zone("roon:abc", "roon");
+        roon.state = PlaybackState::Playing;
+        roon.now_playing = Some(NowPlaying {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            album: "Album".to_string(),
+            image_key: None,
+            seek_position: None,
+            duration: None,
+            metadata: None,
+        });
+        roon.volume_control = volume("roon:out", 20.0);
+        let mut openhome = zone("openhome:uuid", "openhome");
+        openhome.volume_control = volume("openhome:uuid", 40.0);
+        openhome.capabilities.volume = true;
+        let other = zone("lms:kitchen", "lms");
+
+        let merged = merge_rooms(&[living_room()], vec![roon, openhome, other]);
+
+        assert_eq!(merged.len(), 2);
+        assert!(merged.iter().any(|z| z.zone_id == "lms:kitchen"));
+        let room = merged.iter().find(|z| z.zone_id == "room:living").unwrap();
+        assert_eq!(room.zone_name, "Living Room");
+        assert_eq!(room.state, PlaybackState::Playing);
+        assert_eq!(room.now_playing.as_ref().unwrap().title, "Song");
+        // Configured volume authority wins over the transport zone's volume
+        assert_eq!(room.volume_control.as_ref().unwrap().value, 40.0);
+        assert!(room.capabilities.volume);
+    }
+
+    #[test]
+    fn authority_falls_back_when_preferred_offline() {
+        let room = living_room();
+        let mut roon = zone("roon:abc", "roon");
+        roon.volume_control = volume("roon:out", 20.0);
+        let zones: HashMap<String, Zone> = [("roon:abc".to_string(), roon)].into_iter().collect();
+
+        let authority = room.authority(RoomRole::Volume, &zones).unwrap();
+        assert_eq!(authority.zone_id, "roon:abc");
+    }
+
+    #[test]
+    fn room_without_online_members_is_hidden() {
+        let merged = merge_rooms(&[living_room()], vec![zone("lms:kitchen", "lms")]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].zone_id, "lms:kitchen");
+    }
+
+    #[test]
+    fn normalize_validates_members() {
+        let mut room = Room {
+            id: String::new(),
+            name: " Living Room ".to_string(),
+            zone_ids: vec!["roon:abc".to_string()],
+            transport_zone: None,
+            volume_zone: None,
+            metadata_zone: None,
+        };
+        room.normalize().unwrap();
+        assert_eq!(room.id, "living-room");
+        assert_eq!(room.name, "Living Room");
+
+        room.volume_zone = Some("upnp:other".to_string());
+        assert!(room.normalize().is_err());
+
+        room.volume_zone = None;
+        room.zone_ids = vec!["room:nested".to_string()];
+        assert!(room.normalize().is_err());
+    }
+
+    #[tokio::test]
+    async fn new_rooms_never_replace_existing_ones() {
+        let store = RoomStore::in_memory();
+        let first = store
+            .save_room(Room {
+                id: String::new(),
+                ..living_room()
+            })
+            .await
+            .unwrap();
+        let second = store
+            .save_room(Room {
+                id: String::new(),
+                zone_ids: vec!["lms:kitchen".to_string()],
+                volume_zone: None,
+                ..living_room()
+            })
+            .await
+            .unwrap();
+        assert_eq!(first.id, "living-room");
+        assert_eq!(second.id, "living-room-2");
+
+        store.save_room(first).await.unwrap();
+        assert_eq!(store.list().await.len(), 2);
+    }
+}
@@ -0,0 +1,828 @@
+//! Automation rules - "when this bus event happens, do that"
+//!
+//! Rules are declared in `rules.json` (config dir) and edited via the API. A rule
+//! names a bus event type, optional conditions on the event payload and an action.
+//! With `for_secs` the conditions must keep holding for that long (no later event
+//! for the same zone contradicts them) before the rule fires, e.g. "zone stopped
+//! for 20 minutes". Every firing is logged and kept in a short in-memory log.
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::RwLock;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, warn};
+
+use crate::adapters::AdapterCommand;
+use crate::api::{hqp_zone_id, AppState};
+use crate::bus::{BusEvent, Command, CommandOrigin, ControlSurface};
+use crate::config_store::{normalize_id, unique_id, ConfigFile};
+
+/// Rules file (config dir)
+pub const RULES_FILE: &str = "rules.json";
+
+/// Firings kept for `GET /rules`
+const MAX_FIRINGS: usize = 100;
+
+/// How often sustained (`for_secs`) rules are checked
+const PENDING_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// HQPlayer pipelines are polled this often while a rule listens for changes
+const PIPELINE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Event types rules can trigger on (`BusEvent::event_type`)
+pub const RULE_EVENTS: &[&str] = &[
+    "zone_discovered",
+    "zone_updated",
+    "zone_removed",
+    "now_playing_changed",
+    "track_metadata_changed",
+    "volume_changed",
+    "command_result",
+    "adapter_stopping",
+    "adapter_stopped",
+    "adapter_connected",
+    "adapter_disconnected",
+    "roon_connected",
+    "roon_disconnected",
+    "hqp_connected",
+    "hqp_disconnected",
+    "hqp_state_changed",
+    "hqp_pipeline_changed",
+    "lms_connected",
+    "lms_disconnected",
+    "lms_player_state_changed",
+];
+
+/// Events whose payload names a zone (`zone_id`, or `zone.zone_id` on discovery)
+const ZONE_EVENTS: &[&str] = &[
+    "zone_discovered",
+    "zone_updated",
+    "zone_removed",
+    "now_playing_changed",
+    "track_metadata_changed",
+];
+
+/// Comparison applied to a payload field
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOp {
+    /// Equal (strings compare case-insensitively)
+    Eq,
+    Ne,
+    /// Substring, case-insensitive
+    Contains,
+    /// Prefix, case-insensitive
+    StartsWith,
+    Gt,
+    Lt,
+    /// Field is present and not null (`value` is ignored)
+    Exists,
+}
+
+/// Condition on the event payload, e.g. `{"field": "rate", "op": "starts_with", "value": "DSD"}`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuleCondition {
+    /// Dotted path into the payload ("state", "zone.zone_name", "metadata.bit_depth")
+    pub field: String,
+    pub op: ConditionOp,
+    #[serde(default)]
+    pub value: Value,
+}
+
+impl RuleCondition {
+    fn matches(&self, payload: &Value) -> bool {
+        let actual = self
+            .field
+            .split('.')
+            .try_fold(payload, |v, key| v.get(key))
+            .filter(|v| !v.is_null());
+        let Some(actual) = actual else {
+            return self.op == ConditionOp::Ne;
+        };
+
+        let text = |v: &Value| match v {
+            Value::String(s) => s.to_lowercase(),
+            other => other.to_string().to_lowercase(),
+        };
+        match self.op {
+            ConditionOp::Exists => true,
+            ConditionOp::Eq => text(actual) == text(&self.value),
+            ConditionOp::Ne => text(actual) != text(&self.value),
+            ConditionOp::Contains => text(actual).contains(&text(&self.value)),
+            ConditionOp::StartsWith => text(actual).starts_with(&text(&self.value)),
+            ConditionOp::Gt | ConditionOp::Lt => match (actual.as_f64(), self.value.as_f64()) {
+                (Some(a), Some(b)) if self.op == ConditionOp::Gt => a > b,
+                (Some(a), Some(b)) => a < b,
+                _ => false,
+            },
+        }
+    }
+}
+
+/// What a rule does when it fires
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Send a command to a zone (defaults to the zone of the triggering event)
+    ZoneCommand {
+        #[serde(default)]
+        zone_id: Option<String>,
+        command: Command,
+    },
+    /// Send a command to an HQPlayer instance (defaults to the instance linked
+    /// to the triggering zone, then the default instance)
+    HqpCommand {
+        #[serde(default)]
+        instance: Option<String>,
+        command: Command,
+    },
+    /// POST the rule and triggering event as JSON
+    Webhook { url: String },
+}
+
+/// A persisted automation rule
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Rule {
+    /// Stable identifier (lowercase slug); derived from the name when empty
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Bus event type that triggers the rule (see `RULE_EVENTS`)
+    pub event: String,
+    /// All must match the event payload
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
+    /// Fire only once the conditions have held this long
+    #[serde(default)]
+    pub for_secs: Option<u64>,
+    /// Minimum time between firings (per zone)
+    #[serde(default)]
+    pub cooldown_secs: u64,
+    pub action: RuleAction,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Rule {
+    /// Check the rule is well-formed, filling in the id from the name if missing
+    pub(crate) fn normalize(&mut self) -> Result<()> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            bail!("name is required");
+        }
+        normalize_id(&mut self.id, &self.name)?;
+
+        self.event = self.event.trim().to_lowercase();
+        if !RULE_EVENTS.contains(&self.event.as_str()) {
+            bail!(
+                "unknown event '{}' (expected one of: {})",
+                self.event,
+                RULE_EVENTS.join(", ")
+            );
+        }
+
+        for condition in &self.conditions {
+            if condition.field.trim().is_empty() {
+                bail!("condition field is required");
+            }
+            if matches!(condition.op, ConditionOp::Gt | ConditionOp::Lt)
+                && condition.value.as_f64().is_none()
+            {
+                bail!(
+                    "{:?} on '{}' needs a numeric value",
+                    condition.op,
+                    condition.field
+                );
+            }
+        }
+
+        if self.for_secs == Some(0) {
+            self.for_secs = None;
+        }
+
+        if let RuleAction::ZoneCommand { command, .. } | RuleAction::HqpCommand { command, .. } =
+            &self.action
+        {
+            AdapterCommand::try_from(command.clone())?;
+        }
+        match &self.action {
+            RuleAction::ZoneCommand { zone_id: None, .. }
+                if !ZONE_EVENTS.contains(&self.event.as_str()) =>
+            {
+                bail!("zone_id is required: '{}' events have no zone", self.event);
+            }
+            RuleAction::Webhook { url }
+                if !url.starts_with("http://") && !url.starts_with("https://") =>
+            {
+                bail!("webhook url must start with http:// or https://");
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn matches(&self, event_type: &str, payload: &Value) -> bool {
+        self.event == event_type && self.conditions.iter().all(|c| c.matches(payload))
+    }
+
+    /// Whether the event reports a command this rule sent. Such events never
+    /// trigger the rule, or a rule on `command_result` could fire itself forever.
+    fn caused(&self, event: &BusEvent) -> bool {
+        let origin = match event {
+            BusEvent::CommandReceived { origin, .. } | BusEvent::CommandResult { origin, .. } => {
+                origin.as_ref()
+            }
+            _ => None,
+        };
+        origin.is_some_and(|o| {
+            o.surface == ControlSurface::Rules && o.id.as_deref() == Some(self.id.as_str())
+        })
+    }
+}
+
+/// Zone named by an event payload, if any
+fn event_zone(payload: &Value) -> Option<String> {
+    payload
+        .get("zone_id")
+        .or_else(|| payload.get("zone").and_then(|z| z.get("zone_id")))
+        .and_then(|v| v.as_str())
+        .map(String::from)
+}
+
+/// Event type and payload as JSON (the bus serializes as `{type, payload}`)
+fn event_payload(event: &BusEvent) -> Value {
+    serde_json::to_value(event)
+        .ok()
+        .and_then(|mut v| v.get_mut("payload").map(Value::take))
+        .unwrap_or(Value::Null)
+}
+
+/// One rule firing, as logged
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleFiring {
+    pub rule_id: String,
+    pub event: String,
+    pub zone_id: Option<String>,
+    pub at: DateTime<Utc>,
+    pub ok: bool,
+    pub message: String,
+}
+
+/// Rule plus any validation error from loading it
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleStatus {
+    #[serde(flatten)]
+    pub rule: Rule,
+    /// Set when the rule on disk failed validation; such rules never fire
+    pub error: Option<String>,
+}
+
+/// A matched sustained rule waiting for its `for_secs` to pass
+struct Pending {
+    due: Instant,
+    payload: Value,
+}
+
+/// Persisted rules and the bus consumer that evaluates them
+pub struct RulesEngine {
+    rules: RwLock<Vec<RuleStatus>>,
+    firings: RwLock<VecDeque<RuleFiring>>,
+    client: Client,
+    file: ConfigFile,
+}
+
+impl Default for RulesEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RulesEngine {
+    /// Create the engine, loading and validating saved rules from the config dir
+    pub fn new() -> Self {
+        let file = ConfigFile::new(RULES_FILE, "rules");
+        Self::with_rules(Self::read(&file), file)
+    }
+
+    /// Engine that is never written to disk
+    pub fn in_memory() -> Self {
+        Self::with_rules(Vec::new(), ConfigFile::in_memory("rules"))
+    }
+
+    /// Re-read rules from the config dir (after a restore)
+    pub async fn reload(&self) {
+        self.file.reload(&self.rules, Self::read).await;
+    }
+
+    fn read(file: &ConfigFile) -> Vec<RuleStatus> {
+        let rules: Vec<Rule> = file.load_or_default();
+        if !rules.is_empty() {
+            info!("Loaded {} rules from disk", rules.len());
+        }
+        validate_loaded(rules)
+    }
+
+    fn with_rules(rules: Vec<RuleStatus>, file: ConfigFile) -> Self {
+        Self {
+            rules: RwLock::new(rules),
+            firings: RwLock::new(VecDeque::new()),
+            client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            file,
+        }
+    }
+
+    fn save(&self, rules: &[RuleStatus]) {
+        let rules: Vec<&Rule> = rules.iter().map(|r| &r.rule).collect();
+        self.file.save(&rules);
+    }
+
+    /// All rules, including ones that failed validation on load
+    pub async fn list(&self) -> Vec<RuleStatus> {
+        self.rules.read().await.clone()
+    }
+
+    /// Recent firings, newest first
+    pub async fn firings(&self) -> Vec<RuleFiring> {
+        self.firings.read().await.iter().rev().cloned().collect()
+    }
+
+    /// Create or replace a rule. One without an id is always created, with a
+    /// suffix on its id if another rule already has that name.
+    pub async fn save_rule(&self, mut rule: Rule) -> Result<Rule> {
+        let creating = rule.id.trim().is_empty();
+        rule.normalize()?;
+
+        let mut rules = self.rules.write().await;
+        if creating {
+            rule.id = unique_id(&rule.id, |id| rules.iter().any(|r| r.rule.id == id));
+        }
+        let status = RuleStatus {
+            rule: rule.clone(),
+            error: None,
+        };
+        match rules.iter_mut().find(|r| r.rule.id == rule.id) {
+            Some(existing) => *existing = status,
+            None => rules.push(status),
+        }
+        self.save(&rules);
+        info!("Saved rule {} (on {})", rule.id, rule.event);
+        Ok(rule)
+    }
+
+    /// Delete a rule, returning whether it existed
+    pub async fn remove(&self, id: &str) -> bool {
+        let mut rules = self.rules.write().await;
+        let before = rules.len();
+        rules.retain(|r| r.rule.id != id);
+        let removed = rules.len() != before;
+        if removed {
+            self.save(&rules);
+            info!("Removed rule {}", id);
+        }
+        removed
+    }
+
+    /// Valid, enabled rules
+    async fn active(&self) -> Vec<Rule> {
+        self.rules
+            .read()
+            .await
+            .iter()
+            .filter(|r| r.error.is_none() && r.rule.enabled)
+            .map(|r| r.rule.clone())
+            .collect()
+    }
+
+    async fn log_firing(&self, firing: RuleFiring) {
+        if firing.ok {
+            info!(
+                "Rule {} fired on {}: {}",
+                firing.rule_id, firing.event, firing.message
+            );
+        } else {
+            warn!(
+                "Rule {} fired on {} but failed: {}",
+                firing.rule_id, firing.event, firing.message
+            );
+        }
+        let mut firings = self.firings.write().await;
+        if firings.len() >= MAX_FIRINGS {
+            firings.pop_front();
+        }
+        firings.push_back(firing);
+    }
+
+    /// Start the engine's event loop
+    /// Should be spawned as a task; stops on shutdown
+    pub async fn run(self: Arc<Self>, state: AppState) {
+        let mut rx = state.bus.subscribe();
+        let mut pending: HashMap<(String, String), Pending> = HashMap::new();
+        let mut last_fired: HashMap<(String, String), Instant> = HashMap::new();
+        let mut pending_tick = tokio::time::interval(PENDING_CHECK_INTERVAL);
+        pending_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut pipeline_tick = tokio::time::interval(PIPELINE_POLL_INTERVAL);
+        pipeline_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        info!("RulesEngine started");
+
+        loop {
+            let mut due: Vec<(Rule, Value)> = Vec::new();
+            tokio::select! {
+                _ = state.shutdown.cancelled() => break,
+                event = rx.recv() => {
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("RulesEngine lagged, skipped {} events", skipped);
+                            continue;
+                        }
+                        Err(RecvError::Closed) => break,
+                    };
+                    if matches!(event, BusEvent::ShuttingDown { .. }) {
+                        break;
+                    }
+
+                    let event_type = event.event_type();
+                    let payload = event_payload(&event);
+                    let zone = event_zone(&payload).unwrap_or_default();
+                    for rule in self.active().await {
+                        let key = (rule.id.clone(), zone.clone());
+                        if rule.event != event_type || rule.caused(&event) {
+                            continue;
+                        }
+                        if !rule.matches(event_type, &payload) {
+                            // A contradicting event resets a sustained rule
+                            pending.remove(&key);
+                            continue;
+                        }
+                        match rule.for_secs {
+                            Some(secs) => {
+                                pending.entry(key).or_insert_with(|| Pending {
+                                    due: Instant::now() + Duration::from_secs(secs),
+                                    payload: payload.clone(),
+                                });
+                            }
+                            None => due.push((rule, payload.clone())),
+                        }
+                    }
+                }
+                _ = pending_tick.tick() => {
+                    let now = Instant::now();
+                    let ready: Vec<(String, String)> = pending
+                        .iter()
+                        .filter(|(_, p)| p.due <= now)
+                        .map(|(key, _)| key.clone())
+                        .collect();
+                    let active = self.active().await;
+                    for key in ready {
+                        let Some(p) = pending.remove(&key) else { continue };
+                        if let Some(rule) = active.iter().find(|r| r.id == key.0) {
+                            due.push((rule.clone(), p.payload));
+                        }
+                    }
+                }
+                _ = pipeline_tick.tick() => {
+                    let watching = self
+                        .active()
+                        .await
+                        .iter()
+                        .any(|r| r.event == "hqp_pipeline_changed");
+                    if watching {
+                        poll_hqp_pipelines(&state).await;
+                    }
+                }
+            }
+
+            for (rule, payload) in due {
+                let zone = event_zone(&payload);
+                let key = (rule.id.clone(), zone.clone().unwrap_or_default());
+                if rule.cooldown_secs > 0 {
+                    let cooldown = Duration::from_secs(rule.cooldown_secs);
+                    if last_fired.get(&key).is_some_and(|t| t.elapsed() < cooldown) {
+                        debug!("Rule {} skipped: cooling down", rule.id);
+                        continue;
+                    }
+                }
+                last_fired.insert(key, Instant::now());
+
+                let engine = self.clone();
+                let state = state.clone();
+                tokio::spawn(async move {
+                    let result = engine.execute(&state, &rule, &payload).await;
+                    let (ok, message) = match result {
+                        Ok(message) => (true, message),
+                        Err(e) => (false, e.to_string()),
+                    };
+                    engine
+                        .log_firing(RuleFiring {
+                            rule_id: rule.id.clone(),
+                            event: rule.event.clone(),
+                            zone_id: zone,
+                            at: Utc::now(),
+                            ok,
+                            message,
+                        })
+                        .await;
+                });
+            }
+        }
+
+        info!("RulesEngine stopped");
+    }
+
+    /// Perform a rule's action for the triggering event payload
+    async fn execute(&self, state: &AppState, rule: &Rule, payload: &Value) -> Result<String> {
+        let event_zone = event_zone(payload);
+        match &rule.action {
+            RuleAction::ZoneCommand { zone_id, command } => {
+                let zone_id = zone_id
+                    .clone()
+                    .or(event_zone)
+                    .ok_or_else(|| anyhow!("no zone to send {:?} to", command))?;
+                let origin =
+                    CommandOrigin::service(ControlSurface::Rules, &rule.id, Some(&rule.name));
+                state
+                    .dispatch_command_from(&zone_id, command.clone().try_into()?, origin)
+                    .await?
+                    .into_result()?;
+                Ok(format!("Sent {:?} to {}", command, zone_id))
+            }
+            RuleAction::HqpCommand { instance, command } => {
+                let instance = match instance {
+                    Some(name) => Some(name.clone()),
+                    None => match &event_zone {
+                        Some(zone_id) => state.hqp_zone_links.get_instance_for_zone(zone_id).await,
+                        None => None,
+                    },
+                };
+                // Sent as a command to the instance's zone, so the volume policy and
+                // audit log cover it like any other zone command
+                let zone_id = match &instance {
+                    Some(name) => {
+                        if state.hqp_instances.get(name).await.is_none() {
+                            bail!("HQPlayer instance not found: {}", name);
+                        }
+                        format!("hqplayer:{}", name)
+                    }
+                    None => hqp_zone_id(state)
+                        .await
+                        .ok_or_else(|| anyhow!("HQPlayer not configured"))?,
+                };
+                let origin =
+                    CommandOrigin::service(ControlSurface::Rules, &rule.id, Some(&rule.name));
+                state
+                    .dispatch_command_from(&zone_id, command.clone().try_into()?, origin)
+                    .await?
+                    .into_result()?;
+                Ok(format!(
+                    "Sent {:?} to HQPlayer {}",
+                    command,
+                    instance.as_deref().unwrap_or("default")
+                ))
+            }
+            RuleAction::Webhook { url } => {
+                let body = serde_json::json!({
+                    "rule": rule.id,
+                    "name": rule.name,
+                    "event": rule.event,
+                    "payload": payload,
+                    "fired_at": Utc::now(),
+                });
+                let response = self.client.post(url).json(&body).send().await?;
+                let status = response.status();
+                if !status.is_success() {
+                    bail!("webhook returned {}", status);
+                }
+                Ok(format!("Webhook {} returned {}", url, status))
+            }
+        }
+    }
+}
+
+/// Validate rules read from disk; invalid ones are kept (so saving doesn't lose
+/// them) but carry their error and never fire
+fn validate_loaded(rules: Vec<Rule>) -> Vec<RuleStatus> {
+    rules
+        .into_iter()
+        .map(|mut rule| {
+            let error = rule.normalize().err().map(|e| {
+                warn!("Rule '{}' is invalid and disabled: {}", rule.name, e);
+                e.to_string()
+            });
+            RuleStatus { rule, error }
+        })
+        .collect()
+}
+
+/// Read every connected HQPlayer's pipeline; the adapter publishes
+/// `HqpPipelineChanged` when it differs from the last one seen
+async fn poll_hqp_pipelines(state: &AppState) {
+    if state.hqplayer.get_status().await.connected {
+        let _ = state.hqplayer.get_pipeline_status().await;
+    }
+    for instance in state.hqp_instances.list_instances().await {
+        if !instance.connected {
+            continue;
+        }
+        if let Some(adapter) = state.hqp_instances.get(&instance.name).await {
+            let _ = adapter.get_pipeline_status().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::{CommandResponse, PrefixedZoneId};
+
+    fn rule(json: Value) -> Rule {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn conditions_match_payload_fields() {
+        let event = BusEvent::HqpPipelineChanged {
+            host: "10.0.0.5".to_string(),
+            filter: Some("poly-sinc-gauss-long".to_string()),
+            shaper: Some("ASDM7EC".to_string()),
+            rate: Some("DSD256".to_string()),
+        };
+        let payload = event_payload(&event);
+
+        let dsd = rule(serde_json::json!({
+            "name": "DSD volume",
+            "event": "hqp_pipeline_changed",
+            "conditions": [{"field": "rate", "op": "starts_with", "value": "dsd"}],
+            "action": {"type": "zone_command", "zone_id": "hqplayer:10.0.0.5",
+                       "command": {"action": "VolumeAbsolute", "params": {"value": -30.0}}}
+        }));
+        assert!(dsd.matches(event.event_type(), &payload));
+
+        let pcm = RuleCondition {
+            field: "rate".to_string(),
+            op: ConditionOp::Eq,
+            value: Value::from("192000"),
+        };
+        assert!(!pcm.matches(&payload));
+
+        let missing = RuleCondition {
+            field: "matrix".to_string(),
+            op: ConditionOp::Exists,
+            value: Value::Null,
+        };
+        assert!(!missing.matches(&payload));
+    }
+
+    #[test]
+    fn nested_fields_and_numbers() {
+        let payload = serde_json::json!({"zone": {"zone_id": "lms:aa"}, "value": -20.5});
+        assert_eq!(event_zone(&payload).as_deref(), Some("lms:aa"));
+
+        let loud = RuleCondition {
+            field: "value".to_string(),
+            op: ConditionOp::Gt,
+            value: Value::from(-25),
+        };
+        assert!(loud.matches(&payload));
+
+        let nested = RuleCondition {
+            field: "zone.zone_id".to_string(),
+            op: ConditionOp::Contains,
+            value: Value::from("LMS:"),
+        };
+        assert!(nested.matches(&payload));
+    }
+
+    #[test]
+    fn rules_are_validated() {
+        let mut ok = rule(serde_json::json!({
+            "name": "Stop HQP when idle",
+            "event": "zone_updated",
+            "conditions": [{"field": "state", "op": "eq", "value": "stopped"}],
+            "for_secs": 1200,
+            "action": {"type": "hqp_command", "command": {"action": "Stop"}}
+        }));
+        ok.normalize().unwrap();
+        assert_eq!(ok.id, "stop-hqp-when-idle");
+
+        let mut unknown_event = ok.clone();
+        unknown_event.event = "zone_exploded".to_string();
+        assert!(unknown_event.normalize().is_err());
+
+        // LMS disconnects have no zone, so a zone command needs an explicit zone
+        let mut no_zone = rule(serde_json::json!({
+            "name": "Pause on LMS loss",
+            "event": "lms_disconnected",
+            "action": {"type": "zone_command", "command": {"action": "Pause"}}
+        }));
+        assert!(no_zone.normalize().is_err());
+
+        let mut bad_url = rule(serde_json::json!({
+            "name": "Notify",
+            "event": "adapter_disconnected",
+            "action": {"type": "webhook", "url": "ftp://example.com"}
+        }));
+        assert!(bad_url.normalize().is_err());
+    }
+
+    #[test]
+    fn invalid_rules_on_disk_are_kept_but_flagged() {
+        let rules = validate_loaded(vec![
+            rule(serde_json::json!({
+                "name": "Good",
+                "event": "adapter_disconnected",
+                "conditions": [{"field": "adapter", "op": "eq", "value": "lms"}],
+                "action": {"type": "webhook", "url": "http://localhost:9/hook"}
+            })),
+            rule(serde_json::json!({
+                "name": "Bad",
+                "event": "nope",
+                "action": {"type": "webhook", "url": "http://localhost:9/hook"}
+            })),
+        ]);
+        assert!(rules[0].error.is_none());
+        assert!(rules[1].error.as_deref().unwrap().contains("unknown event"));
+    }
+
+    #[test]
+    fn rules_ignore_their_own_commands() {
+        let echo = rule(serde_json::json!({
+            "id": "echo",
+            "name": "Echo",
+            "event": "command_result",
+            "action": {"type": "zone_command", "command": {"action": "Pause"}}
+        }));
+        let result = |origin: CommandOrigin| BusEvent::CommandResult {
+            response: CommandResponse {
+                zone_id: "lms:aa".to_string(),
+                command: Command::Pause,
+                success: true,
+                error: None,
+                reason: None,
+                timestamp: 0,
+            },
+            request_id: None,
+            origin: Some(origin),
+        };
+
+        assert!(echo.caused(&result(CommandOrigin::service(
+            ControlSurface::Rules,
+            "echo",
+            None
+        ))));
+        assert!(!echo.caused(&result(CommandOrigin::service(
+            ControlSurface::Rules,
+            "other",
+            None
+        ))));
+        assert!(!echo.caused(&result(CommandOrigin::new(ControlSurface::Web))));
+    }
+
+    #[test]
+    fn zone_events_carry_their_zone() {
+        let event = BusEvent::ZoneUpdated {
+            zone_id: PrefixedZoneId::lms("aa"),
+            display_name: "Kitchen".to_string(),
+            state: "stopped".to_string(),
+        };
+        let payload = event_payload(&event);
+        assert_eq!(event_zone(&payload).as_deref(), Some("lms:aa"));
+
+        for event_type in ZONE_EVENTS {
+            assert!(RULE_EVENTS.contains(event_type));
+        }
+    }
+
+    #[tokio::test]
+    async fn new_rules_never_replace_existing_ones() {
+        let engine = RulesEngine::in_memory();
+        let dsd = rule(serde_json::json!({
+            "name": "DSD volume",
+            "event": "hqp_pipeline_changed",
+            "action": {"type": "zone_command", "zone_id": "hqplayer:10.0.0.5",
+                       "command": {"action": "VolumeAbsolute", "params": {"value": -30.0}}}
+        }));
+
+        let first = engine.save_rule(dsd.clone()).await.unwrap();
+        let second = engine.save_rule(dsd).await.unwrap();
+        assert_eq!(first.id, "dsd-volume");
+        assert_eq!(second.id, "dsd-volume-2");
+
+        engine.save_rule(first).await.unwrap();
+        assert_eq!(engine.list().await.len(), 2);
+    }
+}
@@ -0,0 +1,672 @@
+//! Scenes - named snapshots of several zones, recalled with one call
+//!
+//! A scene stores, per zone, the volume and mute state, the settings of the
+//! HQPlayer instance linked to the zone (mode, filters, shaper, rate and
+//! optionally a configuration profile) and optionally something to play. Scenes
+//! are captured from the live state or written by hand, and recalled from the
+//! API, the `hifi_scene` MCP tool or a knob. Recall checks every zone and
+//! HQPlayer instance and snapshots what the scene will change before changing
+//! anything, then applies all zones in parallel: HQPlayer first, then volume
+//! (through the volume safety policy), mute and playback. If any step fails, the
+//! snapshot is applied to put every zone back as it was.
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::adapters::hqplayer::HqpAdapter;
+use crate::adapters::AdapterCommand;
+use crate::api::AppState;
+use crate::bus::{CommandOrigin, PlaybackState};
+use crate::config_store::{normalize_id, ConfigFile};
+use crate::rooms::RoomRole;
+
+/// Scenes file (config dir, next to app-settings.json)
+pub const SCENES_FILE: &str = "scenes.json";
+
+/// HQPlayer settings restored with a zone. Values are the names shown by
+/// HQPlayer (as in the pipeline status); unset fields are left alone.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SceneHqp {
+    /// Instance name; the instance linked to the zone when unset
+    #[serde(default)]
+    pub instance: Option<String>,
+    /// Configuration profile loaded before the other settings. HQPlayer does not
+    /// report the active profile, so capturing never fills this in.
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub filter1x: Option<String>,
+    #[serde(default)]
+    pub filter_nx: Option<String>,
+    #[serde(default)]
+    pub shaper: Option<String>,
+    /// Output rate in Hz
+    #[serde(default)]
+    pub rate: Option<u32>,
+}
+
+/// Something to start playing on recall (Roon or LMS zones)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScenePlay {
+    pub query: String,
+    /// Roon only: "library" (default), "tidal" or "qobuz"
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// One zone's part of a scene
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SceneZone {
+    /// Any zone id, including `room:` zones
+    pub zone_id: String,
+    /// Volume in the zone's own scale
+    #[serde(default)]
+    pub volume: Option<f32>,
+    #[serde(default)]
+    pub muted: Option<bool>,
+    #[serde(default)]
+    pub hqplayer: Option<SceneHqp>,
+    #[serde(default)]
+    pub play: Option<ScenePlay>,
+}
+
+/// A persisted scene
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Scene {
+    /// Stable identifier (lowercase slug); derived from the name when empty
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub zones: Vec<SceneZone>,
+}
+
+impl Scene {
+    /// Check the scene is well-formed, filling in the id from the name if missing
+    pub(crate) fn normalize(&mut self) -> Result<()> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            bail!("name is required");
+        }
+        normalize_id(&mut self.id, &self.name)?;
+
+        if self.zones.is_empty() {
+            bail!("a scene needs at least one zone");
+        }
+        let mut seen = Vec::new();
+        for zone in &mut self.zones {
+            zone.zone_id = zone.zone_id.trim().to_string();
+            if zone.zone_id.is_empty() {
+                bail!("zone_id is required");
+            }
+            if seen.contains(&zone.zone_id) {
+                bail!("zone {} appears more than once", zone.zone_id);
+            }
+            seen.push(zone.zone_id.clone());
+
+            if zone.volume.is_some_and(|v| !v.is_finite()) {
+                bail!("volume for {} is not a number", zone.zone_id);
+            }
+            if zone
+                .play
+                .as_ref()
+                .is_some_and(|p| p.query.trim().is_empty())
+            {
+                bail!("play query for {} is empty", zone.zone_id);
+            }
+            if zone.volume.is_none()
+                && zone.muted.is_none()
+                && zone.hqplayer.is_none()
+                && zone.play.is_none()
+            {
+                bail!("zone {} has nothing to recall", zone.zone_id);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Capture the current state of some zones into a new scene
+#[derive(Debug, Clone, Deserialize)]
+pub struct CaptureRequest {
+    pub name: String,
+    pub zone_ids: Vec<String>,
+    /// Also store the current track as a search query to play on recall
+    #[serde(default)]
+    pub include_playing: bool,
+}
+
+/// Result of one recall step
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneStep {
+    pub zone_id: String,
+    pub step: &'static str,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SceneStep {
+    fn new(zone_id: &str, step: &'static str, result: Result<()>) -> Self {
+        if let Err(e) = &result {
+            warn!("Scene step {} on {} failed: {}", step, zone_id, e);
+        }
+        Self {
+            zone_id: zone_id.to_string(),
+            step,
+            ok: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// Outcome of recalling a scene
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneRecall {
+    pub scene: String,
+    /// Whether every step succeeded
+    pub ok: bool,
+    pub steps: Vec<SceneStep>,
+    /// After a failed step: putting the zones back as they were before the recall
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rollback: Vec<SceneStep>,
+}
+
+// =============================================================================
+// Store
+// =============================================================================
+
+/// Persisted scenes
+pub struct SceneStore {
+    scenes: RwLock<Vec<Scene>>,
+    file: ConfigFile,
+}
+
+impl Default for SceneStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SceneStore {
+    /// Create the store, loading saved scenes from the config dir
+    pub fn new() -> Self {
+        let file = ConfigFile::new(SCENES_FILE, "scenes");
+        Self {
+            scenes: RwLock::new(Self::read(&file)),
+            file,
+        }
+    }
+
+    /// Store that is never written to disk
+    pub fn in_memory() -> Self {
+        Self {
+            scenes: RwLock::new(Vec::new()),
+            file: ConfigFile::in_memory("scenes"),
+        }
+    }
+
+    /// Re-read scenes from the config dir (after a restore)
+    pub async fn reload(&self) {
+        self.file.reload(&self.scenes, Self::read).await;
+    }
+
+    fn read(file: &ConfigFile) -> Vec<Scene> {
+        let scenes: Vec<Scene> = file.load_or_default();
+        if !scenes.is_empty() {
+            info!("Loaded {} scenes from disk", scenes.len());
+        }
+        scenes
+    }
+
+    /// Get all scenes
+    pub async fn list(&self) -> Vec<Scene> {
+        self.scenes.read().await.clone()
+    }
+
+    /// Get a scene by id, or by name ignoring case
+    pub async fn get(&self, id: &str) -> Option<Scene> {
+        let scenes = self.scenes.read().await;
+        scenes
+            .iter()
+            .find(|s| s.id == id)
+            .or_else(|| scenes.iter().find(|s| s.name.eq_ignore_ascii_case(id)))
+            .cloned()
+    }
+
+    /// Create or replace a scene
+    pub async fn save_scene(&self, mut scene: Scene) -> Result<Scene> {
+        scene.normalize()?;
+
+        let mut scenes = self.scenes.write().await;
+        match scenes.iter_mut().find(|s| s.id == scene.id) {
+            Some(existing) => *existing = scene.clone(),
+            None => scenes.push(scene.clone()),
+        }
+        self.file.save(&*scenes);
+        info!("Saved scene {} ({} zones)", scene.id, scene.zones.len());
+        Ok(scene)
+    }
+
+    /// Delete a scene, returning whether it existed
+    pub async fn remove(&self, id: &str) -> bool {
+        let mut scenes = self.scenes.write().await;
+        let before = scenes.len();
+        scenes.retain(|s| s.id != id);
+        let removed = scenes.len() != before;
+        if removed {
+            self.file.save(&*scenes);
+            info!("Removed scene {}", id);
+        }
+        removed
+    }
+
+    /// Snapshot the given zones and save them as a scene
+    pub async fn capture(&self, state: &AppState, request: CaptureRequest) -> Result<Scene> {
+        let mut zones = Vec::with_capacity(request.zone_ids.len());
+        for zone_id in &request.zone_ids {
+            zones.push(capture_zone(state, zone_id.trim(), request.include_playing).await?);
+        }
+        self.save_scene(Scene {
+            id: String::new(),
+            name: request.name,
+            zones,
+        })
+        .await
+    }
+
+    /// Recall a scene. Nothing is changed unless every zone is online, every
+    /// HQPlayer instance is configured and the current state could be read. If a
+    /// step fails, the others still run, then every zone is restored from that
+    /// snapshot (HQPlayer profiles can't be read back, so only the settings are).
+    pub async fn recall(
+        &self,
+        state: &AppState,
+        id: &str,
+        origin: &CommandOrigin,
+    ) -> Result<SceneRecall> {
+        let scene = self
+            .get(id)
+            .await
+            .ok_or_else(|| anyhow!("Scene not found: {}", id))?;
+
+        let mut plan = Vec::with_capacity(scene.zones.len());
+        for zone in &scene.zones {
+            let Some(current) = state.get_zone(&zone.zone_id).await else {
+                bail!("Zone not found: {}", zone.zone_id);
+            };
+            let hqp = match &zone.hqplayer {
+                Some(settings) => Some(hqp_adapter(state, &zone.zone_id, settings).await?),
+                None => None,
+            };
+            let snapshot = ZoneSnapshot {
+                before: snapshot_zone(state, zone, hqp.as_deref()).await?,
+                was_playing: current.state == PlaybackState::Playing,
+            };
+            plan.push((zone, hqp, snapshot));
+        }
+
+        info!("Recalling scene {} ({} zones)", scene.id, plan.len());
+        let steps: Vec<SceneStep> = futures::future::join_all(
+            plan.iter()
+                .map(|(zone, hqp, _)| recall_zone(state, zone, hqp.clone(), origin)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let ok = steps.iter().all(|s| s.ok);
+        let rollback = if ok {
+            Vec::new()
+        } else {
+            warn!("Scene {} failed, restoring the previous state", scene.id);
+            futures::future::join_all(plan.iter().map(|(zone, hqp, snapshot)| {
+                restore_zone(state, zone, hqp.clone(), snapshot, origin)
+            }))
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+        };
+        Ok(SceneRecall {
+            scene: scene.id,
+            ok,
+            steps,
+            rollback,
+        })
+    }
+}
+
+// =============================================================================
+// Capture and recall
+// =============================================================================
+
+async fn capture_zone(state: &AppState, zone_id: &str, include_playing: bool) -> Result<SceneZone> {
+    let zone = state
+        .get_zone(zone_id)
+        .await
+        .ok_or_else(|| anyhow!("Zone not found: {}", zone_id))?;
+
+    let volume_zone = state.resolve_zone_id(zone_id, RoomRole::Volume).await?;
+    let control = state.volume_control(&volume_zone).await;
+
+    let hqplayer = match linked_hqp(state, zone_id).await {
+        Some((instance, adapter)) => match hqp_settings(&adapter).await {
+            Ok(settings) => Some(SceneHqp {
+                instance,
+                ..settings
+            }),
+            Err(e) => {
+                warn!("Could not read HQPlayer settings for {}: {}", zone_id, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let play = if include_playing {
+        zone.now_playing
+            .filter(|np| !np.title.is_empty())
+            .map(|np| ScenePlay {
+                query: format!("{} {}", np.title, np.artist).trim().to_string(),
+                source: None,
+            })
+    } else {
+        None
+    };
+
+    Ok(SceneZone {
+        zone_id: zone_id.to_string(),
+        volume: control.as_ref().map(|vc| vc.value),
+        muted: control.as_ref().map(|vc| vc.is_muted),
+        hqplayer,
+        play,
+    })
+}
+
+/// An instance's current mode, filters, shaper and rate
+async fn hqp_settings(adapter: &HqpAdapter) -> Result<SceneHqp> {
+    let settings = adapter.get_pipeline_status().await?.settings;
+    let selected = |value: String| (!value.is_empty()).then_some(value);
+    Ok(SceneHqp {
+        instance: None,
+        profile: None,
+        mode: selected(settings.mode.selected.value),
+        filter1x: selected(settings.filter1x.selected.value),
+        filter_nx: selected(settings.filter_nx.selected.value),
+        shaper: selected(settings.shaper.selected.value),
+        rate: settings.samplerate.selected.value.parse().ok(),
+    })
+}
+
+/// A zone as it was before a recall
+struct ZoneSnapshot {
+    /// The current values of what the scene sets, applied to undo it
+    before: SceneZone,
+    was_playing: bool,
+}
+
+/// The current state of whatever recalling `zone` will change
+async fn snapshot_zone(
+    state: &AppState,
+    zone: &SceneZone,
+    hqp: Option<&HqpAdapter>,
+) -> Result<SceneZone> {
+    let control = if zone.volume.is_some() || zone.muted.is_some() {
+        let volume_zone = state
+            .resolve_zone_id(&zone.zone_id, RoomRole::Volume)
+            .await?;
+        state.volume_control(&volume_zone).await
+    } else {
+        None
+    };
+    let hqplayer = match hqp {
+        Some(adapter) => Some(hqp_settings(adapter).await.map_err(|e| {
+            anyhow!(
+                "Could not read HQPlayer settings for {}: {}",
+                zone.zone_id,
+                e
+            )
+        })?),
+        None => None,
+    };
+    Ok(SceneZone {
+        zone_id: zone.zone_id.clone(),
+        volume: zone.volume.and(control.as_ref().map(|vc| vc.value)),
+        muted: zone.muted.and(control.as_ref().map(|vc| vc.is_muted)),
+        hqplayer,
+        play: None,
+    })
+}
+
+/// HQPlayer instance behind a zone: the zone itself for `hqplayer:` zones,
+/// otherwise the instance linked to it (or to its transport member)
+async fn linked_hqp(state: &AppState, zone_id: &str) -> Option<(Option<String>, Arc<HqpAdapter>)> {
+    if let Some(raw) = zone_id.strip_prefix("hqplayer:") {
+        let adapter = state.hqp_instances.get_for_zone(raw).await?;
+        return Some((adapter.get_instance_name().await, adapter));
+    }
+
+    let transport = state
+        .resolve_zone_id(zone_id, RoomRole::Transport)
+        .await
+        .ok();
+    for id in std::iter::once(zone_id).chain(transport.as_deref()) {
+        if let Some(name) = state.hqp_zone_links.get_instance_for_zone(id).await {
+            let adapter = state.hqp_instances.get(&name).await?;
+            return Some((Some(name), adapter));
+        }
+    }
+    None
+}
+
+async fn hqp_adapter(
+    state: &AppState,
+    zone_id: &str,
+    settings: &SceneHqp,
+) -> Result<Arc<HqpAdapter>> {
+    let adapter = match &settings.instance {
+        Some(name) => state
+            .hqp_instances
+            .get(name)
+            .await
+            .ok_or_else(|| anyhow!("HQPlayer instance not found: {}", name))?,
+        None => match linked_hqp(state, zone_id).await {
+            Some((_, adapter)) => adapter,
+            None => bail!("No HQPlayer instance linked to {}", zone_id),
+        },
+    };
+    if !adapter.is_configured().await {
+        bail!("HQPlayer instance for {} is not configured", zone_id);
+    }
+    Ok(adapter)
+}
+
+async fn recall_zone(
+    state: &AppState,
+    zone: &SceneZone,
+    hqp: Option<Arc<HqpAdapter>>,
+    origin: &CommandOrigin,
+) -> Vec<SceneStep> {
+    let zone_id = zone.zone_id.as_str();
+    let mut steps = Vec::new();
+    let mut record =
+        |step: &'static str, result: Result<()>| steps.push(SceneStep::new(zone_id, step, result));
+
+    // The profile and mode change which filters and shapers exist, so they go first
+    if let (Some(settings), Some(adapter)) = (&zone.hqplayer, hqp) {
+        if let Some(profile) = &settings.profile {
+            record("hqp_profile", adapter.load_profile(profile).await);
+        }
+        if let Some(mode) = &settings.mode {
+            record("hqp_mode", adapter.set_mode(mode).await);
+        }
+        if let Some(filter) = &settings.filter1x {
+            record("hqp_filter1x", adapter.set_filter_1x(filter).await);
+        }
+        if let Some(filter) = &settings.filter_nx {
+            record("hqp_filter_nx", adapter.set_filter_nx(filter).await);
+        }
+        if let Some(shaper) = &settings.shaper {
+            record("hqp_shaper", adapter.set_shaper(shaper).await);
+        }
+        if let Some(rate) = settings.rate {
+            record("hqp_rate", adapter.set_rate(rate).await);
+        }
+    }
+
+    if let Some(volume) = zone.volume {
+        record(
+            "volume",
+            state
+                .set_volume(zone_id, volume, false, origin.clone())
+                .await
+                .map(|_| ()),
+        );
+    }
+
+    if let Some(muted) = zone.muted {
+        let result = async {
+            state
+                .dispatch_command_from(zone_id, AdapterCommand::Mute(muted), origin.clone())
+                .await?
+                .into_result()?;
+            Ok(())
+        }
+        .await;
+        record("mute", result);
+    }
+
+    if let Some(play) = &zone.play {
+        let result =
+            crate::scheduler::play(state, zone_id, &play.query, play.source.as_deref(), None).await;
+        record("play", result.map(|_| ()));
+    }
+
+    steps
+}
+
+/// Undo a recall of `zone`: re-apply the snapshot, and pause a zone the scene
+/// started playing
+async fn restore_zone(
+    state: &AppState,
+    zone: &SceneZone,
+    hqp: Option<Arc<HqpAdapter>>,
+    snapshot: &ZoneSnapshot,
+    origin: &CommandOrigin,
+) -> Vec<SceneStep> {
+    let mut steps = recall_zone(state, &snapshot.before, hqp, origin).await;
+    if zone.play.is_some() && !snapshot.was_playing {
+        let result = async {
+            state
+                .dispatch_command_from(&zone.zone_id, AdapterCommand::Pause, origin.clone())
+                .await?
+                .into_result()?;
+            Ok(())
+        }
+        .await;
+        steps.push(SceneStep::new(&zone.zone_id, "pause", result));
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(zone_id: &str) -> SceneZone {
+        SceneZone {
+            zone_id: zone_id.to_string(),
+            volume: Some(-30.0),
+            muted: Some(false),
+            hqplayer: None,
+            play: None,
+        }
+    }
+
+    #[test]
+    fn scenes_are_validated() {
+        let mut scene = Scene {
+            id: String::new(),
+            name: "  Late Night ".to_string(),
+            zones: vec![zone("roon:1601"), zone("lms:aa:bb")],
+        };
+        scene.normalize().unwrap();
+        assert_eq!(scene.id, "late-night");
+        assert_eq!(scene.name, "Late Night");
+
+        let mut empty = scene.clone();
+        empty.zones.clear();
+        assert!(empty.normalize().is_err());
+
+        let mut duplicate = scene.clone();
+        duplicate.zones.push(zone("roon:1601"));
+        assert!(duplicate.normalize().is_err());
+
+        let mut nothing = scene.clone();
+        nothing.zones[0].volume = None;
+        nothing.zones[0].muted = None;
+        assert!(nothing.normalize().is_err());
+
+        let mut blank_query = scene.clone();
+        blank_query.zones[0].play = Some(ScenePlay {
+            query: " ".to_string(),
+            source: None,
+        });
+        assert!(blank_query.normalize().is_err());
+
+        let mut bad_id = scene;
+        bad_id.id = "Late Night".to_string();
+        assert!(bad_id.normalize().is_err());
+    }
+
+    #[test]
+    fn scene_json_round_trips() {
+        let json = r#"{
+            "name": "Vinyl evening",
+            "zones": [
+                {
+                    "zone_id": "roon:1601",
+                    "volume": -28,
+                    "hqplayer": {"mode": "SDM (DSD)", "filter_nx": "poly-sinc-gauss-long", "shaper": "ASDM7EC", "rate": 11289600}
+                },
+                {"zone_id": "lms:00:04:20:aa:bb:cc", "muted": true, "play": {"query": "Kind of Blue"}}
+            ]
+        }"#;
+        let mut scene: Scene = serde_json::from_str(json).unwrap();
+        scene.normalize().unwrap();
+        assert_eq!(scene.id, "vinyl-evening");
+        let hqp = scene.zones[0].hqplayer.as_ref().unwrap();
+        assert_eq!(hqp.instance, None);
+        assert_eq!(hqp.rate, Some(11_289_600));
+
+        let back: Scene = serde_json::from_str(&serde_json::to_string(&scene).unwrap()).unwrap();
+        assert_eq!(back, scene);
+    }
+
+    #[tokio::test]
+    async fn store_finds_scenes_by_id_or_name() {
+        let store = SceneStore::in_memory();
+        store
+            .save_scene(Scene {
+                id: String::new(),
+                name: "Movie Night".to_string(),
+                zones: vec![zone("roon:1601")],
+            })
+            .await
+            .unwrap();
+
+        assert!(store.get("movie-night").await.is_some());
+        assert!(store.get("movie night").await.is_some());
+        assert!(store.get("party").await.is_none());
+
+        assert!(store.remove("movie-night").await);
+        assert!(!store.remove("movie-night").await);
+        assert!(store.list().await.is_empty());
+    }
+}
@@ -0,0 +1,1032 @@
+//! Scheduled actions - run zone actions at set times (alarm clock, bedtime pause, ...)
+//!
+//! A schedule fires either at a local time of day (optionally only on some weekdays)
+//! or on a five-field cron expression, evaluated in the server's local time zone.
+//! Actions go through the same paths as the API and MCP tools: `search_and_play` for
+//! Roon and LMS, the unified command dispatch for volume and transport, and the
+//! HQPlayer adapter for profiles. An alarm starts playback at a low volume and ramps
+//! up to the target level over a few minutes.
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, Timelike, Utc,
+    Weekday,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, warn};
+
+use crate::adapters::AdapterCommand;
+use crate::api::AppState;
+use crate::bus::{CommandOrigin, ControlSurface, VolumeControl};
+use crate::config_store::{normalize_id, unique_id, ConfigFile};
+use crate::rooms::RoomRole;
+
+/// Schedules file (config dir, next to app-settings.json)
+pub const SCHEDULES_FILE: &str = "schedules.json";
+
+/// How often the clock is checked for due schedules
+const TICK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Missed minutes are caught up to this far back (longer gaps mean the host slept)
+const MAX_CATCH_UP_MINUTES: i64 = 5;
+
+/// Time between alarm volume steps
+const RAMP_STEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Longest allowed alarm ramp
+const MAX_RAMP_MINUTES: u32 = 120;
+
+/// How far ahead to look for the next run (covers "29 February" expressions)
+const NEXT_RUN_SEARCH_DAYS: u32 = 366 * 4 + 1;
+
+// =============================================================================
+// Cron expressions
+// =============================================================================
+
+/// Parsed five-field cron expression: minute hour day-of-month month day-of-week.
+///
+/// Fields accept `*`, numbers, ranges (`1-5`), lists (`1,3,5`) and steps (`*/15`).
+/// Day of week is 0-7 with both 0 and 7 meaning Sunday. As in Vixie cron, when both
+/// day fields are restricted a date matches if either of them does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronExpr {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            bail!("cron expression needs 5 fields (minute hour day month weekday)");
+        };
+
+        // Fold 7 (Sunday) onto 0
+        let weekdays = parse_cron_field(weekday, 0, 7, "weekday")?;
+        Ok(Self {
+            minutes: parse_cron_field(minute, 0, 59, "minute")?,
+            hours: parse_cron_field(hour, 0, 23, "hour")?,
+            days: parse_cron_field(day, 1, 31, "day")?,
+            months: parse_cron_field(month, 1, 12, "month")?,
+            weekdays: (weekdays | weekdays >> 7) & 0x7f,
+            any_day: *day == "*",
+            any_weekday: *weekday == "*",
+        })
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & 1 << date.month() == 0 {
+            return false;
+        }
+        let day = self.days & 1 << date.day() != 0;
+        let weekday = self.weekdays & 1 << date.weekday().num_days_from_sunday() != 0;
+        if self.any_day || self.any_weekday {
+            day && weekday
+        } else {
+            day || weekday
+        }
+    }
+
+    /// Whether the expression fires in the minute containing `time`
+    pub fn matches(&self, time: NaiveDateTime) -> bool {
+        self.matches_date(time.date())
+            && self.hours & 1 << time.hour() != 0
+            && self.minutes & 1 << time.minute() != 0
+    }
+
+    /// First minute strictly after `after` at which the expression fires
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = truncate_to_minute(after) + ChronoDuration::minutes(1);
+        let mut date = start.date();
+        for _ in 0..NEXT_RUN_SEARCH_DAYS {
+            if self.matches_date(date) {
+                let (first_hour, first_minute) = if date == start.date() {
+                    (start.hour(), start.minute())
+                } else {
+                    (0, 0)
+                };
+                for hour in first_hour..24 {
+                    if self.hours & 1 << hour == 0 {
+                        continue;
+                    }
+                    let from = if hour == first_hour { first_minute } else { 0 };
+                    if let Some(minute) = (from..60).find(|m| self.minutes & 1 << m != 0) {
+                        return date.and_hms_opt(hour, minute, 0);
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+/// Parse one cron field into a bitmask of allowed values
+fn parse_cron_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64> {
+    let number = |s: &str| {
+        s.parse::<u32>()
+            .map_err(|_| anyhow!("invalid {} value: {}", name, s))
+    };
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, number(step)?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            bail!("{} step must be at least 1", name);
+        }
+
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (number(lo)?, number(hi)?)
+        } else {
+            // "5/15" means every 15 starting at 5
+            let value = number(range)?;
+            (value, if part.contains('/') { max } else { value })
+        };
+        if lo < min || hi > max || lo > hi {
+            bail!("{} must be within {}-{}: {}", name, min, max, part);
+        }
+
+        for value in (lo..=hi).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn truncate_to_minute(time: NaiveDateTime) -> NaiveDateTime {
+    time.with_second(0)
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(time)
+}
+
+// =============================================================================
+// Schedules
+// =============================================================================
+
+/// When a schedule fires
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleWhen {
+    /// Every day at a local "HH:MM", or only on the listed weekdays
+    Daily {
+        time: String,
+        #[serde(default)]
+        days: Vec<Weekday>,
+    },
+    /// Five-field cron expression in local time
+    Cron { expr: String },
+}
+
+impl ScheduleWhen {
+    /// Compile to a cron expression (daily schedules are a special case)
+    pub fn to_cron(&self) -> Result<CronExpr> {
+        match self {
+            Self::Daily { time, days } => {
+                let (hour, minute) = time
+                    .trim()
+                    .split_once(':')
+                    .and_then(|(h, m)| Some((h.parse::<u32>().ok()?, m.parse::<u32>().ok()?)))
+                    .filter(|(h, m)| *h < 24 && *m < 60)
+                    .ok_or_else(|| anyhow!("time must be HH:MM, got '{}'", time))?;
+                let weekdays = if days.is_empty() {
+                    "*".to_string()
+                } else {
+                    days.iter()
+                        .map(|d| d.num_days_from_sunday().to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                };
+                CronExpr::parse(&format!("{} {} * * {}", minute, hour, weekdays))
+            }
+            Self::Cron { expr } => CronExpr::parse(expr),
+        }
+    }
+}
+
+/// What a schedule does
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleAction {
+    /// Search and play the first result (Roon or LMS zones)
+    Play {
+        query: String,
+        /// Roon only: "library" (default), "tidal" or "qobuz"
+        #[serde(default)]
+        source: Option<String>,
+        /// "play" (default), "queue" or "radio" (Roon only)
+        #[serde(default)]
+        action: Option<String>,
+    },
+    /// Set the volume, in the zone's own scale
+    Volume {
+        value: f32,
+    },
+    /// Load an HQPlayer configuration profile
+    HqpProfile {
+        profile: String,
+    },
+    Pause,
+    Stop,
+}
+
+impl ScheduleAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Play { .. } => "play",
+            Self::Volume { .. } => "volume",
+            Self::HqpProfile { .. } => "hqp_profile",
+            Self::Pause => "pause",
+            Self::Stop => "stop",
+        }
+    }
+}
+
+/// Alarm volume ramp. Levels are percentages of the zone's volume range, so the
+/// same alarm works on dB and 0-100 zones.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlarmRamp {
+    /// Volume when playback starts
+    pub start_percent: f32,
+    /// Volume reached at the end of the ramp
+    pub target_percent: f32,
+    pub ramp_minutes: u32,
+}
+
+/// A persisted schedule
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Schedule {
+    /// Stable identifier (lowercase slug); derived from the name when empty
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Target zone (any zone id, including `room:` zones). For HQPlayer profiles
+    /// this optionally selects the instance ("hqplayer:<name>").
+    #[serde(default)]
+    pub zone_id: String,
+    pub when: ScheduleWhen,
+    pub action: ScheduleAction,
+    /// Start quietly and ramp up (play actions only)
+    #[serde(default)]
+    pub alarm: Option<AlarmRamp>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Schedule {
+    /// Check the schedule is well-formed, filling in the id from the name if missing
+    pub(crate) fn normalize(&mut self) -> Result<()> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            bail!("name is required");
+        }
+        normalize_id(&mut self.id, &self.name)?;
+
+        self.when.to_cron()?;
+
+        self.zone_id = self.zone_id.trim().to_string();
+        match &self.action {
+            ScheduleAction::HqpProfile { profile } => {
+                if profile.trim().is_empty() {
+                    bail!("profile is required");
+                }
+            }
+            ScheduleAction::Play { query, .. } if query.trim().is_empty() => {
+                bail!("query is required");
+            }
+            _ if self.zone_id.is_empty() => bail!("zone_id is required"),
+            _ => {}
+        }
+
+        if let Some(alarm) = &self.alarm {
+            if !matches!(self.action, ScheduleAction::Play { .. }) {
+                bail!("alarm ramp is only available for play actions");
+            }
+            for percent in [alarm.start_percent, alarm.target_percent] {
+                if !(0.0..=100.0).contains(&percent) {
+                    bail!("alarm volume must be between 0 and 100%");
+                }
+            }
+            if alarm.ramp_minutes == 0 || alarm.ramp_minutes > MAX_RAMP_MINUTES {
+                bail!("ramp_minutes must be between 1 and {}", MAX_RAMP_MINUTES);
+            }
+        }
+        Ok(())
+    }
+
+    /// Next local time this schedule fires (None when disabled or never)
+    pub fn next_run(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        if !self.enabled {
+            return None;
+        }
+        self.when.to_cron().ok()?.next_after(after)
+    }
+}
+
+/// Outcome of the last run of a schedule
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleRun {
+    pub at: DateTime<Utc>,
+    pub ok: bool,
+    pub message: String,
+}
+
+/// Schedule plus its run times, as returned by the API
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleStatus {
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    pub next_run: Option<DateTime<Local>>,
+    pub last_run: Option<ScheduleRun>,
+}
+
+// =============================================================================
+// Scheduler
+// =============================================================================
+
+/// Persisted schedules and the task that runs them
+pub struct Scheduler {
+    schedules: RwLock<Vec<Schedule>>,
+    runs: RwLock<HashMap<String, ScheduleRun>>,
+    file: ConfigFile,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler {
+    /// Create the scheduler, loading saved schedules from the config dir
+    pub fn new() -> Self {
+        let file = ConfigFile::new(SCHEDULES_FILE, "schedules");
+        Self {
+            schedules: RwLock::new(Self::read(&file)),
+            runs: RwLock::new(HashMap::new()),
+            file,
+        }
+    }
+
+    /// Scheduler that is never written to disk
+    pub fn in_memory() -> Self {
+        Self {
+            schedules: RwLock::new(Vec::new()),
+            runs: RwLock::new(HashMap::new()),
+            file: ConfigFile::in_memory("schedules"),
+        }
+    }
+
+    /// Re-read schedules from the config dir (after a restore)
+    pub async fn reload(&self) {
+        self.file.reload(&self.schedules, Self::read).await;
+    }
+
+    fn read(file: &ConfigFile) -> Vec<Schedule> {
+        let schedules: Vec<Schedule> = file.load_or_default();
+        if !schedules.is_empty() {
+            info!("Loaded {} schedules from disk", schedules.len());
+        }
+        schedules
+    }
+
+    /// Get all schedules
+    pub async fn list(&self) -> Vec<Schedule> {
+        self.schedules.read().await.clone()
+    }
+
+    /// Get a schedule by id
+    pub async fn get(&self, id: &str) -> Option<Schedule> {
+        self.schedules
+            .read()
+            .await
+            .iter()
+            .find(|s| s.id == id)
+            .cloned()
+    }
+
+    /// Schedules with their next and last run
+    pub async fn statuses(&self) -> Vec<ScheduleStatus> {
+        let now = Local::now().naive_local();
+        let schedules = self.list().await;
+        let runs = self.runs.read().await;
+        schedules
+            .into_iter()
+            .map(|schedule| ScheduleStatus {
+                next_run: schedule
+                    .next_run(now)
+                    .and_then(|t| t.and_local_timezone(Local).earliest()),
+                last_run: runs.get(&schedule.id).cloned(),
+                schedule,
+            })
+            .collect()
+    }
+
+    /// Create or replace a schedule. One without an id is always created, with
+    /// a suffix on its id if another schedule already has that name.
+    pub async fn save_schedule(&self, mut schedule: Schedule) -> Result<Schedule> {
+        let creating = schedule.id.trim().is_empty();
+        schedule.normalize()?;
+
+        let mut schedules = self.schedules.write().await;
+        if creating {
+            schedule.id = unique_id(&schedule.id, |id| schedules.iter().any(|s| s.id == id));
+        }
+        match schedules.iter_mut().find(|s| s.id == schedule.id) {
+            Some(existing) => *existing = schedule.clone(),
+            None => schedules.push(schedule.clone()),
+        }
+        self.file.save(&*schedules);
+        info!(
+            "Saved schedule {} ({})",
+            schedule.id,
+            schedule.action.name()
+        );
+        Ok(schedule)
+    }
+
+    /// Delete a schedule, returning whether it existed
+    pub async fn remove(&self, id: &str) -> bool {
+        let mut schedules = self.schedules.write().await;
+        let before = schedules.len();
+        schedules.retain(|s| s.id != id);
+        let removed = schedules.len() != before;
+        if removed {
+            self.file.save(&*schedules);
+            info!("Removed schedule {}", id);
+        }
+        drop(schedules);
+
+        self.runs.write().await.remove(id);
+        removed
+    }
+
+    /// Enabled schedules that fire in any minute from `from` to `to` inclusive
+    pub async fn due(&self, from: NaiveDateTime, to: NaiveDateTime) -> Vec<Schedule> {
+        let schedules = self.schedules.read().await;
+        schedules
+            .iter()
+            .filter(|s| s.enabled)
+            .filter(|s| {
+                let Ok(cron) = s.when.to_cron() else {
+                    return false;
+                };
+                let mut minute = truncate_to_minute(from);
+                while minute <= to {
+                    if cron.matches(minute) {
+                        return true;
+                    }
+                    minute += ChronoDuration::minutes(1);
+                }
+                false
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Run a schedule now and remember the outcome
+    pub async fn run_schedule(&self, state: &AppState, schedule: &Schedule) -> ScheduleRun {
+        info!(
+            "Running schedule {} ({})",
+            schedule.id,
+            schedule.action.name()
+        );
+        let run = match execute(state, schedule).await {
+            Ok(message) => ScheduleRun {
+                at: Utc::now(),
+                ok: true,
+                message,
+            },
+            Err(e) => {
+                warn!("Schedule {} failed: {}", schedule.id, e);
+                ScheduleRun {
+                    at: Utc::now(),
+                    ok: false,
+                    message: e.to_string(),
+                }
+            }
+        };
+        self.runs
+            .write()
+            .await
+            .insert(schedule.id.clone(), run.clone());
+        run
+    }
+
+    /// Start the scheduler's clock loop
+    /// Should be spawned as a task; stops on shutdown
+    pub async fn run(self: Arc<Self>, state: AppState) {
+        let mut tick = tokio::time::interval(TICK_INTERVAL);
+        tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut last_minute = truncate_to_minute(Local::now().naive_local());
+
+        info!("Scheduler started");
+
+        loop {
+            tokio::select! {
+                _ = state.shutdown.cancelled() => break,
+                _ = tick.tick() => {
+                    let minute = truncate_to_minute(Local::now().naive_local());
+                    // Clock went backwards (DST, NTP): wait for it to catch up
+                    if minute <= last_minute {
+                        continue;
+                    }
+                    let from = if minute - last_minute > ChronoDuration::minutes(MAX_CATCH_UP_MINUTES) {
+                        minute
+                    } else {
+                        last_minute + ChronoDuration::minutes(1)
+                    };
+                    last_minute = minute;
+
+                    for schedule in self.due(from, minute).await {
+                        let scheduler = self.clone();
+                        let state = state.clone();
+                        tokio::spawn(async move {
+                            scheduler.run_schedule(&state, &schedule).await;
+                        });
+                    }
+                }
+            }
+        }
+
+        info!("Scheduler stopped");
+    }
+}
+
+// =============================================================================
+// Actions
+// =============================================================================
+
+/// Perform a schedule's action, returning a short description of what happened
+pub async fn execute(state: &AppState, schedule: &Schedule) -> Result<String> {
+    let zone_id = schedule.zone_id.as_str();
+    let origin = CommandOrigin::service(
+        ControlSurface::Scheduler,
+        &schedule.id,
+        Some(&schedule.name),
+    );
+    match &schedule.action {
+        ScheduleAction::Play {
+            query,
+            source,
+            action,
+        } => {
+            let ramp = match &schedule.alarm {
+                Some(alarm) => start_alarm(state, zone_id, alarm, &origin).await?,
+                None => None,
+            };
+            let message = play(state, zone_id, query, source.as_deref(), action.as_deref()).await?;
+            if let Some(ramp) = ramp {
+                let state = state.clone();
+                let zone_id = zone_id.to_string();
+                tokio::spawn(async move {
+                    ramp.run(&state, &zone_id, &origin).await;
+                });
+            }
+            Ok(message)
+        }
+        ScheduleAction::Volume { value } => {
+            command(
+                state,
+                zone_id,
+                AdapterCommand::VolumeAbsolute(value.round() as i32),
+                &origin,
+            )
+            .await
+        }
+        ScheduleAction::HqpProfile { profile } => {
+            let adapter = match zone_id.strip_prefix("hqplayer:") {
+                Some(raw) => state
+                    .hqp_instances
+                    .get_for_zone(raw)
+                    .await
+                    .ok_or_else(|| anyhow!("HQPlayer instance not found: {}", raw))?,
+                None => state.hqplayer.clone(),
+            };
+            adapter.load_profile(profile).await?;
+            Ok(format!("Loaded HQPlayer profile {}", profile))
+        }
+        ScheduleAction::Pause => command(state, zone_id, AdapterCommand::Pause, &origin).await,
+        ScheduleAction::Stop => command(state, zone_id, AdapterCommand::Stop, &origin).await,
+    }
+}
+
+async fn command(
+    state: &AppState,
+    zone_id: &str,
+    command: AdapterCommand,
+    origin: &CommandOrigin,
+) -> Result<String> {
+    let name = command.name();
+    state
+        .dispatch_command_from(zone_id, command, origin.clone())
+        .await?
+        .into_result()?;
+    Ok(format!("Sent {} to {}", name, zone_id))
+}
+
+pub(crate) async fn play(
+    state: &AppState,
+    zone_id: &str,
+    query: &str,
+    source: Option<&str>,
+    action: Option<&str>,
+) -> Result<String> {
+    let zone_id = state.resolve_zone_id(zone_id, RoomRole::Transport).await?;
+    if zone_id.starts_with("lms:") {
+        use crate::adapters::lms::LmsPlayAction;
+
+        if action == Some("radio") {
+            bail!("Radio mode not supported for LMS");
+        }
+        state
+            .lms
+            .search_and_play(query, &zone_id, LmsPlayAction::parse(action))
+            .await
+    } else if zone_id.starts_with("roon:") || !zone_id.contains(':') {
+        use crate::adapters::roon::{PlayAction, SearchSource};
+
+        let source = match source {
+            Some("tidal") => SearchSource::Tidal,
+            Some("qobuz") => SearchSource::Qobuz,
+            _ => SearchSource::Library,
+        };
+        state
+            .roon
+            .search_and_play(
+                query,
+                &zone_id,
+                source,
+                PlayAction::parse(action.unwrap_or("play")),
+            )
+            .await
+    } else {
+        bail!("Play is only supported on Roon and LMS zones: {}", zone_id)
+    }
+}
+
+/// Set the alarm's starting volume before playback begins.
+///
+/// Returns the ramp to run once playing, or None for zones without volume control.
+async fn start_alarm(
+    state: &AppState,
+    zone_id: &str,
+    alarm: &AlarmRamp,
+    origin: &CommandOrigin,
+) -> Result<Option<VolumeRamp>> {
+    // Without the zone there is no level to start the ramp from, and playing
+    // would start at whatever volume the zone was left at
+    let Some(zone) = state.get_zone(zone_id).await else {
+        bail!(
+            "Alarm on {}: zone not found, not starting playback",
+            zone_id
+        );
+    };
+    let Some(volume) = zone.volume_control else {
+        warn!(
+            "Alarm on {}: zone has no volume control, skipping ramp",
+            zone_id
+        );
+        return Ok(None);
+    };
+
+    let ramp = VolumeRamp::new(&volume, alarm);
+    command(
+        state,
+        zone_id,
+        AdapterCommand::VolumeAbsolute(ramp.start),
+        origin,
+    )
+    .await?;
+    if volume.is_muted {
+        command(state, zone_id, AdapterCommand::Mute(false), origin).await?;
+    }
+    Ok(Some(ramp))
+}
+
+/// Volume steps for an alarm, in the zone's own scale
+#[derive(Debug, Clone, PartialEq)]
+struct VolumeRamp {
+    start: i32,
+    /// One level per RAMP_STEP_INTERVAL
+    levels: Vec<i32>,
+    /// Drift from the expected level that counts as someone else changing the volume
+    tolerance: i32,
+}
+
+impl VolumeRamp {
+    fn new(volume: &VolumeControl, alarm: &AlarmRamp) -> Self {
+        let steps = (u64::from(alarm.ramp_minutes) * 60 / RAMP_STEP_INTERVAL.as_secs()).max(1);
+        let levels = (1..=steps)
+            .map(|i| {
+                let percent = alarm.start_percent
+                    + (alarm.target_percent - alarm.start_percent) * i as f32 / steps as f32;
+                volume_at_percent(volume, percent)
+            })
+            .collect();
+        Self {
+            start: volume_at_percent(volume, alarm.start_percent),
+            levels,
+            tolerance: volume.step.abs().ceil().max(1.0) as i32,
+        }
+    }
+
+    /// Step the volume up, stopping early if the volume is changed by hand
+    async fn run(self, state: &AppState, zone_id: &str, origin: &CommandOrigin) {
+        let mut previous = self.start;
+        let mut current = self.start;
+        for level in self.levels {
+            tokio::select! {
+                _ = state.shutdown.cancelled() => return,
+                _ = tokio::time::sleep(RAMP_STEP_INTERVAL) => {}
+            }
+
+            // The zone may still report the level before our last step
+            let reported = state
+                .get_zone(zone_id)
+                .await
+                .and_then(|z| z.volume_control)
+                .map(|v| v.value.round() as i32);
+            if let Some(reported) = reported {
+                let low = previous.min(current) - self.tolerance;
+                let high = previous.max(current) + self.tolerance;
+                if reported < low || reported > high {
+                    info!(
+                        "Alarm ramp on {} stopped: volume changed to {}",
+                        zone_id, reported
+                    );
+                    return;
+                }
+            }
+
+            if level == current {
+                continue;
+            }
+            let step = AdapterCommand::VolumeAbsolute(level);
+            if let Err(e) = command(state, zone_id, step, origin).await {
+                warn!("Alarm ramp on {} stopped: {}", zone_id, e);
+                return;
+            }
+            previous = current;
+            current = level;
+        }
+        debug!("Alarm ramp on {} reached {}", zone_id, current);
+    }
+}
+
+/// Map a percentage of the zone's range onto its scale, snapped to the zone's step
+fn volume_at_percent(volume: &VolumeControl, percent: f32) -> i32 {
+    let percent = percent.clamp(0.0, 100.0);
+    let mut value = volume.min + (volume.max - volume.min) * percent / 100.0;
+    if volume.step > 0.0 {
+        value = volume.min + ((value - volume.min) / volume.step).round() * volume.step;
+    }
+    value.clamp(volume.min, volume.max).round() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::VolumeScale;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn schedule(when: ScheduleWhen, action: ScheduleAction) -> Schedule {
+        Schedule {
+            id: String::new(),
+            name: "Wake up".to_string(),
+            enabled: true,
+            zone_id: "lms:aa".to_string(),
+            when,
+            action,
+            alarm: None,
+        }
+    }
+
+    fn volume(min: f32, max: f32, step: f32) -> VolumeControl {
+        VolumeControl {
+            value: min,
+            min,
+            max,
+            step,
+            is_muted: false,
+            scale: VolumeScale::Decibel,
+            output_id: None,
+        }
+    }
+
+    #[test]
+    fn cron_fields_support_lists_ranges_and_steps() {
+        let cron = CronExpr::parse("*/15 7-9 * * 1-5").unwrap();
+        // 2026-10-19 is a Monday
+        assert!(cron.matches(at("2026-10-19", "07:00")));
+        assert!(cron.matches(at("2026-10-19", "09:45")));
+        assert!(!cron.matches(at("2026-10-19", "07:10")));
+        assert!(!cron.matches(at("2026-10-19", "10:00")));
+        assert!(!cron.matches(at("2026-10-18", "07:00")));
+
+        let sunday = CronExpr::parse("0 8 * * 7").unwrap();
+        assert!(sunday.matches(at("2026-10-18", "08:00")));
+
+        assert!(CronExpr::parse("0 8 * *").is_err());
+        assert!(CronExpr::parse("60 8 * * *").is_err());
+        assert!(CronExpr::parse("0 8 * * */0").is_err());
+    }
+
+    #[test]
+    fn cron_day_fields_match_either_when_both_restricted() {
+        // The 1st of the month or any Friday
+        let cron = CronExpr::parse("0 12 1 * 5").unwrap();
+        assert!(cron.matches(at("2026-10-01", "12:00")));
+        assert!(cron.matches(at("2026-10-23", "12:00")));
+        assert!(!cron.matches(at("2026-10-22", "12:00")));
+    }
+
+    #[test]
+    fn daily_schedule_finds_next_weekday() {
+        let when = ScheduleWhen::Daily {
+            time: "06:30".to_string(),
+            days: vec![Weekday::Mon, Weekday::Wed],
+        };
+        let mut s = schedule(when, ScheduleAction::Pause);
+        // Saturday evening -> Monday morning
+        assert_eq!(
+            s.next_run(at("2026-10-17", "21:00")),
+            Some(at("2026-10-19", "06:30"))
+        );
+        // Exactly at the firing minute -> the next occurrence
+        assert_eq!(
+            s.next_run(at("2026-10-19", "06:30")),
+            Some(at("2026-10-21", "06:30"))
+        );
+
+        s.enabled = false;
+        assert_eq!(s.next_run(at("2026-10-17", "21:00")), None);
+    }
+
+    #[test]
+    fn leap_day_cron_is_found_years_ahead() {
+        let cron = CronExpr::parse("0 0 29 2 *").unwrap();
+        assert_eq!(
+            cron.next_after(at("2026-10-17", "00:00")),
+            Some(at("2028-02-29", "00:00"))
+        );
+    }
+
+    #[test]
+    fn schedules_are_validated() {
+        let mut s = schedule(
+            ScheduleWhen::Daily {
+                time: "7:05".to_string(),
+                days: vec![],
+            },
+            ScheduleAction::Play {
+                query: "Kind of Blue".to_string(),
+                source: None,
+                action: None,
+            },
+        );
+        s.normalize().unwrap();
+        assert_eq!(s.id, "wake-up");
+
+        let mut bad_time = s.clone();
+        bad_time.when = ScheduleWhen::Daily {
+            time: "25:00".to_string(),
+            days: vec![],
+        };
+        assert!(bad_time.normalize().is_err());
+
+        let mut no_zone = s.clone();
+        no_zone.zone_id = String::new();
+        assert!(no_zone.normalize().is_err());
+
+        // HQPlayer profiles don't need a zone
+        let mut profile = s.clone();
+        profile.zone_id = String::new();
+        profile.action = ScheduleAction::HqpProfile {
+            profile: "night".to_string(),
+        };
+        assert!(profile.normalize().is_ok());
+
+        let mut alarm_on_pause = s.clone();
+        alarm_on_pause.action = ScheduleAction::Pause;
+        alarm_on_pause.alarm = Some(AlarmRamp {
+            start_percent: 5.0,
+            target_percent: 30.0,
+            ramp_minutes: 10,
+        });
+        assert!(alarm_on_pause.normalize().is_err());
+    }
+
+    #[test]
+    fn schedule_json_round_trips() {
+        let json = r#"{
+            "name": "Weekday alarm",
+            "zone_id": "roon:1601",
+            "when": {"type": "daily", "time": "06:45", "days": ["mon", "tue", "wed", "thu", "fri"]},
+            "action": {"type": "play", "query": "Morning jazz", "source": "qobuz"},
+            "alarm": {"start_percent": 5, "target_percent": 35, "ramp_minutes": 15}
+        }"#;
+        let mut s: Schedule = serde_json::from_str(json).unwrap();
+        assert!(s.enabled);
+        s.normalize().unwrap();
+
+        let back: Schedule = serde_json::from_str(&serde_json::to_string(&s).unwrap()).unwrap();
+        assert_eq!(back, s);
+    }
+
+    #[test]
+    fn alarm_levels_follow_zone_scale() {
+        let alarm = AlarmRamp {
+            start_percent: 10.0,
+            target_percent: 50.0,
+            ramp_minutes: 1,
+        };
+
+        // dB zone: -80..0 in 0.5 dB steps
+        let ramp = VolumeRamp::new(&volume(-80.0, 0.0, 0.5), &alarm);
+        assert_eq!(ramp.start, -72);
+        assert_eq!(ramp.levels.len(), 12);
+        assert_eq!(ramp.levels.last(), Some(&-40));
+        assert!(ramp.levels.windows(2).all(|w| w[0] <= w[1]));
+
+        // 0-100 zone stepping by 2
+        let ramp = VolumeRamp::new(&volume(0.0, 100.0, 2.0), &alarm);
+        assert_eq!(ramp.start, 10);
+        assert_eq!(ramp.levels.last(), Some(&50));
+
+        // Never outside the zone's range
+        assert_eq!(volume_at_percent(&volume(-64.0, 0.0, 1.0), 150.0), 0);
+        assert_eq!(volume_at_percent(&volume(-64.0, 0.0, 1.0), -5.0), -64);
+    }
+
+    #[tokio::test]
+    async fn due_catches_up_missed_minutes() {
+        let scheduler = Scheduler::in_memory();
+        let mut s = schedule(
+            ScheduleWhen::Cron {
+                expr: "30 6 * * *".to_string(),
+            },
+            ScheduleAction::Stop,
+        );
+        s.name = "Morning stop".to_string();
+        scheduler.save_schedule(s).await.unwrap();
+
+        assert_eq!(
+            scheduler
+                .due(at("2026-10-17", "06:28"), at("2026-10-17", "06:31"))
+                .await
+                .len(),
+            1
+        );
+        assert!(scheduler
+            .due(at("2026-10-17", "06:31"), at("2026-10-17", "06:33"))
+            .await
+            .is_empty());
+
+        assert!(scheduler.remove("morning-stop").await);
+        assert!(scheduler.list().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn new_schedules_never_replace_existing_ones() {
+        let scheduler = Scheduler::in_memory();
+        let s = schedule(
+            ScheduleWhen::Cron {
+                expr: "0 7 * * *".to_string(),
+            },
+            ScheduleAction::Stop,
+        );
+        let first = scheduler.save_schedule(s.clone()).await.unwrap();
+        let second = scheduler.save_schedule(s).await.unwrap();
+        assert_eq!(first.id, "wake-up");
+        assert_eq!(second.id, "wake-up-2");
+
+        let mut edited = first;
+        edited.enabled = false;
+        scheduler.save_schedule(edited).await.unwrap();
+        let schedules = scheduler.list().await;
+        assert_eq!(schedules.len(), 2);
+        assert!(!schedules[0].enabled);
+    }
+}
@@ -0,0 +1,470 @@
+//! Scrobbler - submits plays from every adapter to a ListenBrainz-compatible server
+//!
+//! Plays are tracked with the same [`PlayTracker`] as listening history. A play
+//! qualifies once it was listened to for half its length or four minutes,
+//! whichever comes first (tracks under 30 seconds never qualify). Qualifying
+//! listens are queued on disk and submitted in batches, so nothing is lost
+//! while the server or network is down.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, warn};
+
+use crate::bus::{BusEvent, SharedBus};
+use crate::config::{get_config_file_path, get_data_dir};
+use crate::history::{HistoryEntry, PlayTracker};
+
+/// Scrobbler settings file (under the config dir)
+pub const SCROBBLER_CONFIG_FILE: &str = "scrobbler.json";
+
+/// Pending submissions (under the data dir)
+pub const SCROBBLE_QUEUE_FILE: &str = "scrobble_queue.json";
+
+/// Public ListenBrainz API
+pub const DEFAULT_LISTENBRAINZ_URL: &str = "https://api.listenbrainz.org";
+
+/// Tracks shorter than this are never scrobbled
+const MIN_TRACK_SECS: u64 = 30;
+
+/// Listening this long always qualifies, however long the track
+const MAX_REQUIRED_SECS: u64 = 240;
+
+/// Listens per submission request
+const BATCH_SIZE: usize = 100;
+
+/// Oldest listens are dropped beyond this many queued
+const MAX_QUEUE: usize = 10_000;
+
+/// How often a non-empty queue is retried
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+fn default_url() -> String {
+    DEFAULT_LISTENBRAINZ_URL.to_string()
+}
+
+/// Scrobbler settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScrobblerConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// API root of a ListenBrainz-compatible server
+    #[serde(default = "default_url")]
+    pub url: String,
+    /// User token, sent as `Authorization: Token <token>`
+    #[serde(default)]
+    pub token: String,
+}
+
+impl Default for ScrobblerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: default_url(),
+            token: String::new(),
+        }
+    }
+}
+
+impl ScrobblerConfig {
+    fn is_active(&self) -> bool {
+        self.enabled && !self.token.is_empty() && !self.url.is_empty()
+    }
+}
+
+/// Apply the standard scrobble rule to a finished play
+pub fn should_scrobble(entry: &HistoryEntry) -> bool {
+    match entry.duration_secs {
+        Some(duration) if duration < MIN_TRACK_SECS => false,
+        Some(duration) => entry.listened_secs >= (duration / 2).min(MAX_REQUIRED_SECS),
+        None => entry.listened_secs >= MAX_REQUIRED_SECS,
+    }
+}
+
+/// One listen in ListenBrainz submission format
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Listen {
+    /// Unix seconds when the track started
+    pub listened_at: i64,
+    pub track_metadata: ListenMetadata,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListenMetadata {
+    pub artist_name: String,
+    pub track_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_name: Option<String>,
+    pub additional_info: ListenInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListenInfo {
+    pub media_player: String,
+    pub submission_client: String,
+    pub submission_client_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+impl Listen {
+    fn from_entry(entry: &HistoryEntry) -> Self {
+        Self {
+            listened_at: entry.started_at.timestamp(),
+            track_metadata: ListenMetadata {
+                artist_name: entry.artist.clone(),
+                track_name: entry.title.clone(),
+                release_name: Some(entry.album.clone()).filter(|a| !a.is_empty()),
+                additional_info: ListenInfo {
+                    media_player: entry.source.clone(),
+                    submission_client: "unified-hifi-control".to_string(),
+                    submission_client_version: env!("UHC_VERSION").to_string(),
+                    duration_ms: entry.duration_secs.map(|d| d * 1000),
+                },
+            },
+        }
+    }
+}
+
+/// Submission status for the API
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScrobblerStatus {
+    pub enabled: bool,
+    pub url: String,
+    pub has_token: bool,
+    pub queued: usize,
+    pub submitted: u64,
+    pub last_submitted_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct Counters {
+    submitted: u64,
+    last_submitted_at: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+/// Why a submission failed
+enum SubmitError {
+    /// Worth retrying later (network, 5xx, rate limit, bad token)
+    Retry(String),
+    /// The server will never accept this batch
+    Rejected(String),
+}
+
+/// ListenBrainz scrobbler
+pub struct Scrobbler {
+    bus: SharedBus,
+    client: Client,
+    config: RwLock<ScrobblerConfig>,
+    queue: Mutex<VecDeque<Listen>>,
+    counters: RwLock<Counters>,
+    config_path: PathBuf,
+    queue_path: PathBuf,
+}
+
+impl Scrobbler {
+    /// Scrobbler using the standard config and data dirs
+    pub fn new(bus: SharedBus) -> Self {
+        Self::open(
+            bus,
+            get_config_file_path(SCROBBLER_CONFIG_FILE),
+            get_data_dir().join(SCROBBLE_QUEUE_FILE),
+        )
+    }
+
+    /// Scrobbler with explicit settings and queue files (loads both)
+    pub fn open(bus: SharedBus, config_path: PathBuf, queue_path: PathBuf) -> Self {
+        let config: ScrobblerConfig = std::fs::read_to_string(&config_path)
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    warn!("Failed to parse scrobbler config: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let queue: VecDeque<Listen> = std::fs::read_to_string(&queue_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        if !queue.is_empty() {
+            info!("Scrobbler: {} listens waiting from last run", queue.len());
+        }
+
+        Self {
+            bus,
+            client: Client::builder()
+                .timeout(Duration::from_secs(15))
+                .build()
+                .unwrap_or_default(),
+            config: RwLock::new(config),
+            queue: Mutex::new(queue),
+            counters: RwLock::new(Counters::default()),
+            config_path,
+            queue_path,
+        }
+    }
+
+    /// Current settings
+    pub async fn config(&self) -> ScrobblerConfig {
+        self.config.read().await.clone()
+    }
+
+    /// Replace the settings and persist them
+    pub async fn set_config(&self, config: ScrobblerConfig) -> Result<()> {
+        if config.enabled && config.token.is_empty() {
+            return Err(anyhow!("A user token is required to enable scrobbling"));
+        }
+        if !config.url.starts_with("http://") && !config.url.starts_with("https://") {
+            return Err(anyhow!("Server URL must start with http:// or https://"));
+        }
+
+        let json = serde_json::to_string_pretty(&config)?;
+        if let Some(parent) = self.config_path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        std::fs::write(&self.config_path, json)?;
+        *self.config.write().await = config;
+
+        // Drain anything queued under the previous settings
+        self.flush().await;
+        Ok(())
+    }
+
+    /// Submission status
+    pub async fn status(&self) -> ScrobblerStatus {
+        let config = self.config().await;
+        let queued = self.queue.lock().await.len();
+        let counters = self.counters.read().await;
+        ScrobblerStatus {
+            enabled: config.enabled,
+            url: config.url,
+            has_token: !config.token.is_empty(),
+            queued,
+            submitted: counters.submitted,
+            last_submitted_at: counters.last_submitted_at,
+            last_error: counters.last_error.clone(),
+        }
+    }
+
+    /// Queue a finished play if it qualifies and scrobbling is enabled.
+    /// Returns true if it was queued.
+    pub async fn submit(&self, entry: &HistoryEntry) -> bool {
+        if !self.config.read().await.is_active() || !should_scrobble(entry) {
+            return false;
+        }
+        debug!("Scrobbling {} - {}", entry.artist, entry.title);
+
+        let mut queue = self.queue.lock().await;
+        queue.push_back(Listen::from_entry(entry));
+        while queue.len() > MAX_QUEUE {
+            queue.pop_front();
+        }
+        self.persist_queue(&queue);
+        true
+    }
+
+    /// Submit queued listens until the queue is empty or the server fails
+    pub async fn flush(&self) {
+        let config = self.config().await;
+        if !config.is_active() {
+            return;
+        }
+
+        loop {
+            let batch: Vec<Listen> = {
+                let queue = self.queue.lock().await;
+                queue.iter().take(BATCH_SIZE).cloned().collect()
+            };
+            if batch.is_empty() {
+                return;
+            }
+
+            let result = self.post_listens(&config, &batch).await;
+
+            let mut counters = self.counters.write().await;
+            match result {
+                Ok(()) => {
+                    counters.submitted += batch.len() as u64;
+                    counters.last_submitted_at = Some(Utc::now());
+                    counters.last_error = None;
+                    info!("Scrobbled {} listens", batch.len());
+                }
+                Err(SubmitError::Rejected(e)) => {
+                    warn!("Scrobble server rejected {} listens: {}", batch.len(), e);
+                    counters.last_error = Some(e);
+                }
+                Err(SubmitError::Retry(e)) => {
+                    debug!("Scrobble submission failed, will retry: {}", e);
+                    counters.last_error = Some(e);
+                    return;
+                }
+            }
+            drop(counters);
+
+            // Submitted or permanently rejected: either way it leaves the queue
+            let mut queue = self.queue.lock().await;
+            for _ in 0..batch.len().min(queue.len()) {
+                queue.pop_front();
+            }
+            self.persist_queue(&queue);
+        }
+    }
+
+    async fn post_listens(
+        &self,
+        config: &ScrobblerConfig,
+        listens: &[Listen],
+    ) -> Result<(), SubmitError> {
+        let url = format!("{}/1/submit-listens", config.url.trim_end_matches('/'));
+        let listen_type = if listens.len() == 1 {
+            "single"
+        } else {
+            "import"
+        };
+        let body = serde_json::json!({
+            "listen_type": listen_type,
+            "payload": listens,
+        });
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Token {}", config.token))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| SubmitError::Retry(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let detail = response.text().await.unwrap_or_default();
+        let message = format!("HTTP {} {}", status.as_u16(), detail.trim());
+        if status.is_client_error()
+            && status != reqwest::StatusCode::UNAUTHORIZED
+            && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+        {
+            Err(SubmitError::Rejected(message))
+        } else {
+            Err(SubmitError::Retry(message))
+        }
+    }
+
+    fn persist_queue(&self, queue: &VecDeque<Listen>) {
+        if let Some(parent) = self.queue_path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let result = if queue.is_empty() {
+            match std::fs::remove_file(&self.queue_path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        } else {
+            serde_json::to_string(queue)
+                .map_err(std::io::Error::other)
+                .and_then(|json| std::fs::write(&self.queue_path, json))
+        };
+        if let Err(e) = result {
+            warn!("Failed to save scrobble queue: {}", e);
+        }
+    }
+
+    /// Start the scrobbler's event loop
+    /// Should be spawned as a task
+    pub async fn run(&self) {
+        let mut rx = self.bus.subscribe();
+        let mut tracker = PlayTracker::default();
+        let mut retry = tokio::time::interval(RETRY_INTERVAL);
+        retry.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        info!("Scrobbler started");
+
+        loop {
+            tokio::select! {
+                event = rx.recv() => {
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Scrobbler lagged, skipped {} events", skipped);
+                            continue;
+                        }
+                        Err(RecvError::Closed) => break,
+                    };
+                    let mut queued = false;
+                    for entry in tracker.apply(&event, Instant::now()) {
+                        queued |= self.submit(&entry).await;
+                    }
+                    if matches!(event, BusEvent::ShuttingDown { .. }) {
+                        break;
+                    }
+                    if queued {
+                        self.flush().await;
+                    }
+                }
+                _ = retry.tick() => self.flush().await,
+            }
+        }
+
+        info!("Scrobbler stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(listened_secs: u64, duration_secs: Option<u64>) -> HistoryEntry {
+        HistoryEntry {
+            zone_id: "lms:aa".to_string(),
+            zone_name: "Kitchen".to_string(),
+            source: "lms".to_string(),
+            title: "So What".to_string(),
+            artist: "Miles Davis".to_string(),
+            album: "Kind of Blue".to_string(),
+            started_at: Utc::now(),
+            ended_at: Utc::now(),
+            listened_secs,
+            duration_secs,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn scrobble_rule_half_or_four_minutes() {
+        // Half of a 5 minute track
+        assert!(should_scrobble(&entry(150, Some(300))));
+        assert!(!should_scrobble(&entry(149, Some(300))));
+        // Four minutes of a long track
+        assert!(should_scrobble(&entry(240, Some(1200))));
+        assert!(!should_scrobble(&entry(239, Some(1200))));
+        // Very short tracks never count
+        assert!(!should_scrobble(&entry(25, Some(25))));
+        // Unknown length needs the full four minutes
+        assert!(should_scrobble(&entry(240, None)));
+        assert!(!should_scrobble(&entry(200, None)));
+    }
+
+    #[test]
+    fn listen_uses_listenbrainz_shape() {
+        let json = serde_json::to_value(Listen::from_entry(&entry(300, Some(545)))).unwrap();
+        let meta = &json["track_metadata"];
+        assert_eq!(meta["artist_name"], "Miles Davis");
+        assert_eq!(meta["track_name"], "So What");
+        assert_eq!(meta["release_name"], "Kind of Blue");
+        assert_eq!(meta["additional_info"]["duration_ms"], 545_000);
+        assert_eq!(meta["additional_info"]["media_player"], "lms");
+        assert!(json["listened_at"].is_i64());
+    }
+}
@@ -0,0 +1,592 @@
+//! Per-zone sleep timers
+//!
+//! "Stop in 30 minutes", "stop at the end of this track" or "stop at the end of
+//! this album". Timers can fade the volume down over their final minute, then stop
+//! the zone and, where the zone supports it, power it off (LMS `power 0`, Roon
+//! standby). LMS zones with a minute timer use LMS's own `sleep` command; every
+//! other zone is emulated here by polling the aggregator once a second.
+//! Timers are kept in memory only.
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::adapters::AdapterCommand;
+use crate::api::AppState;
+use crate::bus::{BusEvent, CommandOrigin, ControlSurface, NowPlaying, PlaybackState};
+use crate::fades::FadeHandle;
+use crate::rooms::RoomRole;
+
+/// Volume fades out over this long before the timer runs out
+pub const SLEEP_FADE: Duration = Duration::from_secs(60);
+
+/// How often running timers are checked
+const TICK: Duration = Duration::from_secs(1);
+
+/// Longest minute timer accepted
+const MAX_SLEEP_MINUTES: u32 = 12 * 60;
+
+/// When a timer runs out
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SleepMode {
+    /// After a number of minutes
+    #[default]
+    Timer,
+    /// When the current track ends
+    EndOfTrack,
+    /// When the current album ends (the album changes or playback stops)
+    EndOfAlbum,
+}
+
+impl SleepMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Timer => "timer",
+            Self::EndOfTrack => "end_of_track",
+            Self::EndOfAlbum => "end_of_album",
+        }
+    }
+}
+
+/// Request to set a sleep timer
+#[derive(Debug, Clone, Deserialize)]
+pub struct SleepRequest {
+    pub zone_id: String,
+    #[serde(default)]
+    pub mode: SleepMode,
+    /// Required for `timer`
+    #[serde(default)]
+    pub minutes: Option<u32>,
+    /// Fade the volume down over the final minute
+    #[serde(default = "default_true")]
+    pub fade: bool,
+    /// Power off / standby after stopping, where the zone supports it
+    #[serde(default = "default_true")]
+    pub power_off: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl SleepRequest {
+    /// Timer that stops a zone after `minutes`, fading and powering off
+    pub fn minutes(zone_id: &str, minutes: u32) -> Self {
+        Self {
+            zone_id: zone_id.to_string(),
+            mode: SleepMode::Timer,
+            minutes: Some(minutes),
+            fade: true,
+            power_off: true,
+        }
+    }
+
+    /// Timer that stops a zone at the end of its track or album
+    pub fn until(zone_id: &str, mode: SleepMode) -> Self {
+        Self {
+            zone_id: zone_id.to_string(),
+            mode,
+            minutes: None,
+            fade: true,
+            power_off: true,
+        }
+    }
+
+    fn duration(&self) -> Result<Option<Duration>> {
+        if self.mode != SleepMode::Timer {
+            return Ok(None);
+        }
+        match self.minutes {
+            Some(minutes) if (1..=MAX_SLEEP_MINUTES).contains(&minutes) => {
+                Ok(Some(Duration::from_secs(u64::from(minutes) * 60)))
+            }
+            _ => bail!("minutes must be between 1 and {}", MAX_SLEEP_MINUTES),
+        }
+    }
+}
+
+/// A running timer as reported to clients
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SleepTimerStatus {
+    pub zone_id: String,
+    pub mode: SleepMode,
+    /// None while the end is unknown (end of album)
+    pub remaining_secs: Option<u64>,
+    pub fade: bool,
+    pub power_off: bool,
+    /// Handled by the player itself (LMS `sleep`)
+    pub native: bool,
+}
+
+/// Volume fade in progress on a timer's zone
+#[derive(Clone)]
+struct SleepFade {
+    handle: Arc<FadeHandle>,
+    volume_zone: String,
+    /// Level to put back once the zone has stopped
+    level: f32,
+    floor: f32,
+}
+
+#[derive(Clone)]
+struct ActiveTimer {
+    mode: SleepMode,
+    /// Adapter zone that receives stop / power off
+    transport: String,
+    deadline: Option<Instant>,
+    fade: bool,
+    power_off: bool,
+    native: bool,
+    /// Track and album when the timer was set (end of track / album)
+    title: String,
+    album: String,
+    fading: Option<SleepFade>,
+}
+
+impl ActiveTimer {
+    fn status(&self, zone_id: &str) -> SleepTimerStatus {
+        SleepTimerStatus {
+            zone_id: zone_id.to_string(),
+            mode: self.mode,
+            remaining_secs: self.remaining().map(|r| r.as_secs()),
+            fade: self.fade,
+            power_off: self.power_off,
+            native: self.native,
+        }
+    }
+
+    fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+}
+
+/// Time left in the current track, when the adapter reports position and length
+pub fn track_remaining(now_playing: &NowPlaying) -> Option<Duration> {
+    let duration = now_playing.duration.filter(|d| *d > 0.0)?;
+    let position = now_playing.seek_position.unwrap_or(0.0);
+    Some(Duration::from_secs_f64((duration - position).max(0.0)))
+}
+
+/// Volume for a sleep fade with `remaining` left: `level` at the start of the
+/// fade window, `floor` when the timer runs out
+pub fn sleep_fade_level(level: f32, floor: f32, remaining: Duration) -> f32 {
+    let left = remaining.min(SLEEP_FADE).as_secs_f32() / SLEEP_FADE.as_secs_f32();
+    floor + (level - floor) * left
+}
+
+/// Running sleep timers, keyed by the zone id they were set on
+#[derive(Default)]
+pub struct SleepTimers {
+    timers: RwLock<HashMap<String, ActiveTimer>>,
+}
+
+impl SleepTimers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All running timers
+    pub async fn list(&self) -> Vec<SleepTimerStatus> {
+        let timers = self.timers.read().await;
+        let mut list: Vec<_> = timers.iter().map(|(id, t)| t.status(id)).collect();
+        list.sort_by(|a, b| a.zone_id.cmp(&b.zone_id));
+        list
+    }
+
+    /// Timer running on a zone
+    pub async fn get(&self, zone_id: &str) -> Option<SleepTimerStatus> {
+        let zone_id = normalize_zone_id(zone_id);
+        self.timers
+            .read()
+            .await
+            .get(&zone_id)
+            .map(|t| t.status(&zone_id))
+    }
+
+    /// Set (or replace) a zone's timer
+    pub async fn set(&self, state: &AppState, request: SleepRequest) -> Result<SleepTimerStatus> {
+        let duration = request.duration()?;
+        let zone_id = normalize_zone_id(&request.zone_id);
+        let transport = state.resolve_zone_id(&zone_id, RoomRole::Transport).await?;
+        let zone = state
+            .get_zone(&transport)
+            .await
+            .ok_or_else(|| anyhow!("Zone not found: {}", zone_id))?;
+
+        let now_playing = zone.now_playing.as_ref();
+        let title = now_playing.map(|n| n.title.clone()).unwrap_or_default();
+        let album = now_playing.map(|n| n.album.clone()).unwrap_or_default();
+        let deadline = match request.mode {
+            SleepMode::Timer => duration.map(|d| Instant::now() + d),
+            SleepMode::EndOfTrack | SleepMode::EndOfAlbum
+                if zone.state != PlaybackState::Playing =>
+            {
+                bail!("Nothing is playing on {}", zone_id)
+            }
+            SleepMode::EndOfTrack => now_playing
+                .and_then(track_remaining)
+                .map(|r| Instant::now() + r),
+            SleepMode::EndOfAlbum if album.is_empty() => {
+                bail!("{} is not playing an album", zone_id)
+            }
+            SleepMode::EndOfAlbum => None,
+        };
+
+        // LMS fades out and powers off by itself
+        let native =
+            request.mode == SleepMode::Timer && request.power_off && transport.starts_with("lms:");
+        if let Some(duration) = duration.filter(|_| native) {
+            state
+                .lms
+                .control(&transport, "sleep", Some(duration.as_secs() as i32))
+                .await?;
+        }
+
+        let timer = ActiveTimer {
+            mode: request.mode,
+            transport,
+            deadline,
+            fade: request.fade,
+            power_off: request.power_off,
+            native,
+            title,
+            album,
+            fading: None,
+        };
+        let status = timer.status(&zone_id);
+        let previous = self.timers.write().await.insert(zone_id.clone(), timer);
+        if let Some(previous) = previous {
+            release(state, &previous, native).await;
+        }
+
+        info!(
+            "Sleep timer set on {} ({}, {:?}s left)",
+            zone_id,
+            status.mode.as_str(),
+            status.remaining_secs
+        );
+        publish(state, &status);
+        Ok(status)
+    }
+
+    /// Cancel a zone's timer. Returns false if it had none.
+    pub async fn cancel(&self, state: &AppState, zone_id: &str) -> bool {
+        let zone_id = normalize_zone_id(zone_id);
+        let Some(timer) = self.timers.write().await.remove(&zone_id) else {
+            return false;
+        };
+        release(state, &timer, false).await;
+        info!("Sleep timer on {} cancelled", zone_id);
+        publish_cleared(state, &zone_id);
+        true
+    }
+
+    /// Check running timers once a second. Should be spawned as a task; stops on shutdown.
+    pub async fn run(self: Arc<Self>, state: AppState) {
+        let mut ticker = tokio::time::interval(TICK);
+
+        loop {
+            tokio::select! {
+                _ = state.shutdown.cancelled() => break,
+                _ = ticker.tick() => {
+                    let zone_ids: Vec<String> = self.timers.read().await.keys().cloned().collect();
+                    for zone_id in zone_ids {
+                        self.check(&state, &zone_id).await;
+                    }
+                }
+            }
+        }
+
+        debug!("Sleep timers stopped");
+    }
+
+    async fn check(&self, state: &AppState, zone_id: &str) {
+        let Some(mut timer) = self.timers.read().await.get(zone_id).cloned() else {
+            return;
+        };
+        let zone = state.get_zone(&timer.transport).await;
+
+        // LMS runs its own timer; just drop ours when it is due
+        if timer.native {
+            if timer.remaining().is_some_and(|r| r.is_zero()) {
+                self.finish(state, zone_id).await;
+            }
+            return;
+        }
+
+        let (playing, stopped) = match &zone {
+            Some(zone) => (
+                zone.state == PlaybackState::Playing,
+                zone.state == PlaybackState::Stopped,
+            ),
+            // Gone counts as stopped
+            None => (false, true),
+        };
+        let now_playing = zone.as_ref().and_then(|z| z.now_playing.as_ref());
+        let title = now_playing.map(|n| n.title.as_str()).unwrap_or_default();
+        let album = now_playing.map(|n| n.album.as_str()).unwrap_or_default();
+
+        let due = match timer.mode {
+            SleepMode::Timer => timer.remaining().is_some_and(|r| r.is_zero()),
+            SleepMode::EndOfTrack => {
+                // Follow the track position, so pauses and seeks move the deadline
+                if let Some(remaining) = now_playing.and_then(track_remaining).filter(|_| playing) {
+                    timer.deadline = Some(Instant::now() + remaining);
+                }
+                stopped || title != timer.title || timer.remaining().is_some_and(|r| r <= TICK)
+            }
+            SleepMode::EndOfAlbum => stopped || album != timer.album,
+        };
+
+        if due {
+            if let Err(e) = self.fire(state, zone_id, &timer).await {
+                warn!("Sleep timer on {} failed to stop the zone: {}", zone_id, e);
+            }
+            return;
+        }
+
+        if timer.fade && playing {
+            if let Some(remaining) = timer.remaining().filter(|r| *r <= SLEEP_FADE) {
+                self.fade_step(state, zone_id, &mut timer, remaining).await;
+            }
+        }
+
+        let still_running = match self.timers.write().await.get_mut(zone_id) {
+            Some(current) => {
+                current.deadline = timer.deadline;
+                current.fading = timer.fading.clone();
+                true
+            }
+            None => false,
+        };
+        // Cancelled while this check was fading
+        if !still_running {
+            restore_volume(state, &timer).await;
+        }
+    }
+
+    /// Step the volume towards the zone's minimum, unless someone took over the volume
+    async fn fade_step(
+        &self,
+        state: &AppState,
+        zone_id: &str,
+        timer: &mut ActiveTimer,
+        remaining: Duration,
+    ) {
+        if timer.fading.is_none() {
+            let Ok(volume_zone) = state.resolve_zone_id(zone_id, RoomRole::Volume).await else {
+                return;
+            };
+            let Some(control) = state
+                .volume_control(&volume_zone)
+                .await
+                .filter(|vc| !vc.is_muted)
+            else {
+                return;
+            };
+            debug!("Sleep timer on {} fading out", zone_id);
+            let handle = state.fades.begin(&volume_zone, control.value);
+            timer.fading = Some(SleepFade {
+                level: handle.level,
+                handle: Arc::new(handle),
+                volume_zone,
+                floor: control.min,
+            });
+        }
+
+        let Some(fade) = timer
+            .fading
+            .as_ref()
+            .filter(|f| !f.handle.token.is_cancelled())
+        else {
+            return;
+        };
+        let level = sleep_fade_level(fade.level, fade.floor, remaining);
+        if let Err(e) = state.send_volume(&fade.volume_zone, level, false).await {
+            warn!("Sleep fade on {} stopped: {}", zone_id, e);
+            fade.handle.token.cancel();
+        }
+    }
+
+    /// Stop the zone, put its volume back and power it off
+    async fn fire(&self, state: &AppState, zone_id: &str, timer: &ActiveTimer) -> Result<()> {
+        if self.timers.write().await.remove(zone_id).is_none() {
+            return Ok(());
+        }
+        info!("Sleep timer on {} ran out", zone_id);
+        publish_cleared(state, zone_id);
+
+        let playing = state
+            .get_zone(&timer.transport)
+            .await
+            .is_some_and(|z| !matches!(z.state, PlaybackState::Stopped | PlaybackState::Paused));
+        if playing {
+            let origin = CommandOrigin::new(ControlSurface::SleepTimer);
+            state
+                .dispatch_command_from(zone_id, AdapterCommand::Stop, origin)
+                .await?
+                .into_result()?;
+        }
+        restore_volume(state, timer).await;
+
+        if timer.power_off {
+            power_off(state, &timer.transport).await?;
+        }
+        Ok(())
+    }
+
+    /// Drop a timer the player handled itself
+    async fn finish(&self, state: &AppState, zone_id: &str) {
+        if self.timers.write().await.remove(zone_id).is_some() {
+            debug!("Sleep timer on {} handled by the player", zone_id);
+            publish_cleared(state, zone_id);
+        }
+    }
+}
+
+/// Unprefixed ids are Roon zones (legacy knob convention)
+fn normalize_zone_id(zone_id: &str) -> String {
+    if zone_id.contains(':') {
+        zone_id.to_string()
+    } else {
+        format!("roon:{}", zone_id)
+    }
+}
+
+/// Undo what a replaced or cancelled timer started.
+/// `keep_native` is set when the replacement also uses LMS's own timer.
+async fn release(state: &AppState, timer: &ActiveTimer, keep_native: bool) {
+    if timer.native && !keep_native {
+        if let Err(e) = state.lms.control(&timer.transport, "sleep", Some(0)).await {
+            warn!("Failed to cancel LMS sleep on {}: {}", timer.transport, e);
+        }
+    }
+    restore_volume(state, timer).await;
+}
+
+/// Put back the level a sleep fade started from, unless the user changed it since
+async fn restore_volume(state: &AppState, timer: &ActiveTimer) {
+    let Some(fade) = &timer.fading else { return };
+    if !fade.handle.token.is_cancelled() {
+        if let Err(e) = state
+            .send_volume(&fade.volume_zone, fade.level, false)
+            .await
+        {
+            warn!(
+                "Failed to restore volume on {} after sleep fade: {}",
+                fade.volume_zone, e
+            );
+        }
+    }
+    state.fades.finish(&fade.volume_zone, &fade.handle);
+}
+
+/// LMS `power 0` or Roon standby; other zones stay on
+async fn power_off(state: &AppState, zone_id: &str) -> Result<()> {
+    if zone_id.starts_with("lms:") {
+        state.lms.control(zone_id, "power", Some(0)).await
+    } else if zone_id.starts_with("roon:")
+        && state
+            .get_zone(zone_id)
+            .await
+            .is_some_and(|z| z.capabilities.standby)
+    {
+        state.roon.standby(zone_id).await
+    } else {
+        Ok(())
+    }
+}
+
+fn publish(state: &AppState, status: &SleepTimerStatus) {
+    state.bus.publish(BusEvent::SleepTimerChanged {
+        zone_id: status.zone_id.clone(),
+        mode: Some(status.mode.as_str().to_string()),
+        remaining_secs: status.remaining_secs,
+    });
+}
+
+fn publish_cleared(state: &AppState, zone_id: &str) {
+    state.bus.publish(BusEvent::SleepTimerChanged {
+        zone_id: zone_id.to_string(),
+        mode: None,
+        remaining_secs: None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing(position: Option<f64>, duration: Option<f64>) -> NowPlaying {
+        NowPlaying {
+            title: "Track".to_string(),
+            artist: "Artist".to_string(),
+            album: "Album".to_string(),
+            image_key: None,
+            seek_position: position,
+            duration,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn track_remaining_needs_a_length() {
+        assert_eq!(
+            track_remaining(&playing(Some(100.0), Some(240.0))),
+            Some(Duration::from_secs(140))
+        );
+        assert_eq!(
+            track_remaining(&playing(None, Some(30.0))),
+            Some(Duration::from_secs(30))
+        );
+        // Streams and radio have no length
+        assert_eq!(track_remaining(&playing(Some(10.0), None)), None);
+        // Position past the reported end
+        assert_eq!(
+            track_remaining(&playing(Some(250.0), Some(240.0))),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn fade_runs_over_the_final_minute() {
+        assert_eq!(sleep_fade_level(-20.0, -60.0, SLEEP_FADE), -20.0);
+        assert_eq!(
+            sleep_fade_level(-20.0, -60.0, Duration::from_secs(30)),
+            -40.0
+        );
+        assert_eq!(sleep_fade_level(50.0, 0.0, Duration::ZERO), 0.0);
+        // Outside the fade window the level is untouched
+        assert_eq!(sleep_fade_level(50.0, 0.0, Duration::from_secs(600)), 50.0);
+    }
+
+    #[test]
+    fn minute_timers_are_bounded() {
+        assert!(SleepRequest::minutes("lms:aa", 0).duration().is_err());
+        assert!(SleepRequest::minutes("lms:aa", 24 * 60).duration().is_err());
+        assert_eq!(
+            SleepRequest::minutes("lms:aa", 30).duration().unwrap(),
+            Some(Duration::from_secs(1800))
+        );
+        assert_eq!(
+            SleepRequest::until("lms:aa", SleepMode::EndOfAlbum)
+                .duration()
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn request_defaults_fade_and_power_off() {
+        let request: SleepRequest = serde_json::from_str(r#"{"zone_id":"roon:1"}"#).unwrap();
+        assert_eq!(request.mode, SleepMode::Timer);
+        assert!(request.fade);
+        assert!(request.power_off);
+    }
+}
@@ -0,0 +1,254 @@
+//! HTTPS listener
+//!
+//! When `tls.enabled` is set the bridge also listens on `tls.port` (default 8443)
+//! with the same routes; plain HTTP stays on `port` so knobs and existing clients
+//! keep working.
+//!
+//! The certificate is either a configured cert/key pair (reloaded when the files
+//! change, so renewals need no restart) or a self-signed one generated on first run
+//! and kept in the config dir as `tls-cert.pem` / `tls-key.pem`. The self-signed
+//! certificate is valid for 825 days (the longest Apple platforms accept) and is
+//! replaced when it gets close to expiry.
+
+use anyhow::{anyhow, bail, Context, Result};
+use axum_server::tls_rustls::RustlsConfig;
+use chrono::{Datelike, Duration as ChronoDuration, Utc};
+use rcgen::{date_time_ymd, CertificateParams, DistinguishedName, DnType, KeyPair};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::config::{get_config_file_path, TlsConfig};
+
+/// Self-signed certificate (config dir)
+pub const SELF_SIGNED_CERT_FILE: &str = "tls-cert.pem";
+/// Key for the self-signed certificate (config dir)
+pub const SELF_SIGNED_KEY_FILE: &str = "tls-key.pem";
+
+/// Validity of generated certificates
+const SELF_SIGNED_VALID_DAYS: i64 = 825;
+/// Generated certificates are replaced at startup once this old
+const SELF_SIGNED_RENEW_DAYS: u64 = 800;
+/// How often configured cert/key files are checked for changes
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Request extension added by the HTTPS listener, so responses can tell the
+/// request arrived over TLS (e.g. to mark cookies `Secure`)
+#[derive(Debug, Clone, Copy)]
+pub struct OverTls;
+
+/// Where the certificate comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum CertSource {
+    /// Configured cert/key pair
+    Files { cert: PathBuf, key: PathBuf },
+    /// Generated and kept in the config dir
+    SelfSigned { cert: PathBuf, key: PathBuf },
+}
+
+impl CertSource {
+    fn paths(&self) -> (&Path, &Path) {
+        match self {
+            Self::Files { cert, key } | Self::SelfSigned { cert, key } => (cert, key),
+        }
+    }
+}
+
+/// Pick the certificate source from config; cert and key must be set together
+pub fn cert_source(config: &TlsConfig) -> Result<CertSource> {
+    match (&config.cert, &config.key) {
+        (Some(cert), Some(key)) => Ok(CertSource::Files {
+            cert: cert.clone(),
+            key: key.clone(),
+        }),
+        (None, None) => Ok(CertSource::SelfSigned {
+            cert: get_config_file_path(SELF_SIGNED_CERT_FILE),
+            key: get_config_file_path(SELF_SIGNED_KEY_FILE),
+        }),
+        _ => bail!("tls.cert and tls.key must be set together"),
+    }
+}
+
+/// Names the self-signed certificate is issued for
+fn subject_alt_names() -> Vec<String> {
+    let hostname = gethostname::gethostname().to_string_lossy().to_string();
+    let mut names = vec![hostname.clone()];
+    if !hostname.ends_with(".local") {
+        names.push(format!("{}.local", hostname));
+    }
+    names.extend(["localhost", "127.0.0.1", "::1"].map(String::from));
+    names
+}
+
+/// Generate a self-signed certificate; returns (cert PEM, key PEM)
+fn generate_self_signed(names: Vec<String>) -> Result<(String, String)> {
+    let mut params = CertificateParams::new(names)?;
+    let mut dn = DistinguishedName::new();
+    dn.push(DnType::CommonName, "Unified Hi-Fi Control");
+    params.distinguished_name = dn;
+
+    let now = Utc::now();
+    let expires = now + ChronoDuration::days(SELF_SIGNED_VALID_DAYS);
+    params.not_before = date_time_ymd(now.year(), now.month() as u8, now.day() as u8);
+    params.not_after = date_time_ymd(expires.year(), expires.month() as u8, expires.day() as u8);
+
+    let key_pair = KeyPair::generate()?;
+    let cert = params.self_signed(&key_pair)?;
+    Ok((cert.pem(), key_pair.serialize_pem()))
+}
+
+fn is_due_for_renewal(cert: &Path) -> bool {
+    let age = std::fs::metadata(cert)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+    age.is_some_and(|age| age > Duration::from_secs(SELF_SIGNED_RENEW_DAYS * 24 * 60 * 60))
+}
+
+fn write_key(path: &Path, pem: &str) -> Result<()> {
+    std::fs::write(path, pem)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+/// Make sure a self-signed certificate exists and isn't about to expire
+fn ensure_self_signed(cert: &Path, key: &Path) -> Result<()> {
+    if cert.exists() && key.exists() && !is_due_for_renewal(cert) {
+        return Ok(());
+    }
+
+    let names = subject_alt_names();
+    info!(
+        "Generating self-signed TLS certificate for {}",
+        names.join(", ")
+    );
+    let (cert_pem, key_pem) = generate_self_signed(names)?;
+    if let Some(parent) = cert.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_key(key, &key_pem)?;
+    std::fs::write(cert, cert_pem)?;
+    Ok(())
+}
+
+/// Load (or first generate) the certificate for the HTTPS listener
+pub async fn load(source: &CertSource) -> Result<RustlsConfig> {
+    // Use ring, which the HTTP client already links, whatever else is compiled in
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    if let CertSource::SelfSigned { cert, key } = source {
+        ensure_self_signed(cert, key).context("Failed to create self-signed certificate")?;
+    }
+
+    let (cert, key) = source.paths();
+    RustlsConfig::from_pem_file(cert, key).await.map_err(|e| {
+        anyhow!(
+            "Failed to load TLS certificate {} / key {}: {}",
+            cert.display(),
+            key.display(),
+            e
+        )
+    })
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reload a configured cert/key pair when either file changes (e.g. after renewal).
+/// Should be spawned as a task; stops on shutdown.
+pub async fn watch(source: CertSource, config: RustlsConfig, shutdown: CancellationToken) {
+    let CertSource::Files { cert, key } = source else {
+        return;
+    };
+    let mut last = (modified(&cert), modified(&key));
+    let mut interval = tokio::time::interval(RELOAD_CHECK_INTERVAL);
+    interval.tick().await;
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {
+                let current = (modified(&cert), modified(&key));
+                if current == last {
+                    continue;
+                }
+                match config.reload_from_pem_file(&cert, &key).await {
+                    Ok(()) => {
+                        info!("Reloaded TLS certificate {}", cert.display());
+                        last = current;
+                    }
+                    // Keep serving the old certificate; retried at the next check
+                    Err(e) => warn!("Failed to reload TLS certificate: {}", e),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cert_source() {
+        let mut config = TlsConfig::default();
+        assert!(matches!(
+            cert_source(&config).unwrap(),
+            CertSource::SelfSigned { .. }
+        ));
+
+        config.cert = Some(PathBuf::from("/etc/uhc/cert.pem"));
+        assert!(cert_source(&config).is_err());
+
+        config.key = Some(PathBuf::from("/etc/uhc/key.pem"));
+        assert_eq!(
+            cert_source(&config).unwrap(),
+            CertSource::Files {
+                cert: PathBuf::from("/etc/uhc/cert.pem"),
+                key: PathBuf::from("/etc/uhc/key.pem"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_subject_alt_names_cover_local_access() {
+        let names = subject_alt_names();
+        assert!(names.iter().any(|n| n.ends_with(".local")));
+        assert!(names.contains(&"localhost".to_string()));
+        assert!(names.contains(&"127.0.0.1".to_string()));
+    }
+
+    #[test]
+    fn test_generate_self_signed() {
+        let (cert, key) = generate_self_signed(vec!["uhc.local".to_string()]).unwrap();
+        assert!(cert.starts_with("-----BEGIN CERTIFICATE-----"));
+        assert!(key.contains("PRIVATE KEY"));
+    }
+
+    #[test]
+    fn test_ensure_self_signed_keeps_existing_certificate() {
+        let dir = std::env::temp_dir().join(format!("uhc-tls-test-{}", std::process::id()));
+        let cert = dir.join(SELF_SIGNED_CERT_FILE);
+        let key = dir.join(SELF_SIGNED_KEY_FILE);
+
+        ensure_self_signed(&cert, &key).unwrap();
+        let first = std::fs::read_to_string(&cert).unwrap();
+        ensure_self_signed(&cert, &key).unwrap();
+        assert_eq!(std::fs::read_to_string(&cert).unwrap(), first);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&key).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
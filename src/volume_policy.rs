@@ -0,0 +1,383 @@
+//! Volume safety policy
+//!
+//! Every volume change (HTTP, knobs, MCP, scheduler, rules) goes through
+//! `AppState::set_volume`, which applies the zone's policy before anything reaches
+//! an adapter: a hard ceiling, a largest single increase, and a cap applied when
+//! playback starts after being stopped. Policies are stored in
+//! `volume_policy.json` (config dir); zones without their own policy use the default.
+//!
+//! A policy file that can't be read fails closed: the bridge refuses to start, and
+//! a reload (after a restore) keeps the last good policy, so a typo never silently
+//! removes the limits.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::RwLock;
+use tracing::{debug, error, info, warn};
+
+use crate::api::AppState;
+use crate::bus::{BusEvent, CommandOrigin, ControlSurface, PlaybackState};
+use crate::config::{get_config_file_path, read_config_file};
+use crate::config_store::ConfigFile;
+
+/// Volume policy file (config dir)
+pub const VOLUME_POLICY_FILE: &str = "volume_policy.json";
+
+/// Limits for one zone, in the zone's own scale (dB or percent)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct VolumePolicy {
+    /// Hard ceiling; no command may set the volume above this
+    #[serde(default)]
+    pub max_volume: Option<f32>,
+    /// Largest increase allowed in a single command (decreases are never limited)
+    #[serde(default)]
+    pub max_step: Option<f32>,
+    /// Volume is lowered to this when playback starts after being stopped
+    #[serde(default)]
+    pub start_cap: Option<f32>,
+}
+
+/// Level a request was clamped to, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeDecision {
+    pub value: f32,
+    /// Set when the requested level was changed
+    pub reason: Option<String>,
+}
+
+impl VolumePolicy {
+    /// Whether the policy sets no limits at all
+    pub fn is_empty(&self) -> bool {
+        self.max_volume.is_none() && self.max_step.is_none() && self.start_cap.is_none()
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        for (name, value) in [
+            ("max_volume", self.max_volume),
+            ("max_step", self.max_step),
+            ("start_cap", self.start_cap),
+        ] {
+            if value.is_some_and(|v| !v.is_finite()) {
+                bail!("{} must be a number", name);
+            }
+        }
+        if self.max_step.is_some_and(|s| s <= 0.0) {
+            bail!("max_step must be greater than zero");
+        }
+        if let (Some(cap), Some(max)) = (self.start_cap, self.max_volume) {
+            if cap > max {
+                bail!("start_cap ({}) is above max_volume ({})", cap, max);
+            }
+        }
+        Ok(())
+    }
+
+    /// Clamp a requested absolute level.
+    /// `current` and `range` come from the zone and are skipped when unknown.
+    pub fn apply(
+        &self,
+        requested: f32,
+        current: Option<f32>,
+        range: Option<(f32, f32)>,
+    ) -> VolumeDecision {
+        let mut value = requested;
+        let mut reasons = Vec::new();
+
+        if let Some((min, max)) = range {
+            if value < min || value > max {
+                value = value.clamp(min, max);
+                reasons.push(format!(
+                    "{} is outside the zone's range {}..{}",
+                    requested, min, max
+                ));
+            }
+        }
+        if let (Some(step), Some(current)) = (self.max_step, current) {
+            if value - current > step {
+                value = current + step;
+                reasons.push(format!("increase limited to {} per step", step));
+            }
+        }
+        if let Some(max) = self.max_volume {
+            if value > max {
+                value = max;
+                reasons.push(format!("ceiling is {}", max));
+            }
+        }
+
+        VolumeDecision {
+            value,
+            reason: (!reasons.is_empty()).then(|| reasons.join("; ")),
+        }
+    }
+
+    /// Level to drop to when playback starts, if `current` is above the start cap
+    pub fn start_level(&self, current: f32) -> Option<f32> {
+        self.start_cap.filter(|cap| current > *cap)
+    }
+}
+
+/// Default policy plus per-zone overrides
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct VolumePolicies {
+    #[serde(default)]
+    pub default: VolumePolicy,
+    /// Keyed by zone id (adapter zones or `room:` zones)
+    #[serde(default)]
+    pub zones: BTreeMap<String, VolumePolicy>,
+}
+
+impl VolumePolicies {
+    /// Check the default and every zone override
+    pub(crate) fn validate(&self) -> Result<()> {
+        self.default.validate()?;
+        for (zone_id, policy) in &self.zones {
+            policy
+                .validate()
+                .map_err(|e| anyhow::anyhow!("{}: {}", zone_id, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Persisted volume policies
+pub struct VolumePolicyStore {
+    policies: RwLock<VolumePolicies>,
+    file: ConfigFile,
+}
+
+/// Parse and validate a policy file
+fn parse_policies(content: &str) -> Result<VolumePolicies> {
+    let policies = serde_json::from_str::<VolumePolicies>(content)?;
+    policies.validate()?;
+    Ok(policies)
+}
+
+/// Saved policies from the config dir (none saved means no limits)
+fn read_policies() -> Result<VolumePolicies> {
+    let Some(content) = read_config_file(VOLUME_POLICY_FILE) else {
+        return Ok(VolumePolicies::default());
+    };
+    let policies = parse_policies(&content).with_context(|| {
+        format!(
+            "Invalid volume policy in {}; fix or remove it",
+            get_config_file_path(VOLUME_POLICY_FILE).display()
+        )
+    })?;
+    info!(
+        "Loaded volume policy ({} zone overrides)",
+        policies.zones.len()
+    );
+    Ok(policies)
+}
+
+impl VolumePolicyStore {
+    /// Create the store, loading saved policies from the config dir.
+    /// Fails if the file exists but can't be used.
+    pub fn load() -> Result<Self> {
+        Ok(Self {
+            policies: RwLock::new(read_policies()?),
+            file: ConfigFile::new(VOLUME_POLICY_FILE, "volume policy"),
+        })
+    }
+
+    /// Store that is never written to disk
+    pub fn in_memory() -> Self {
+        Self {
+            policies: RwLock::new(VolumePolicies::default()),
+            file: ConfigFile::in_memory("volume policy"),
+        }
+    }
+
+    /// Re-read policies from the config dir (after a restore); keeps the current
+    /// policies if the file can't be used
+    pub async fn reload(&self) {
+        if self.file.is_in_memory() {
+            return;
+        }
+        match read_policies() {
+            Ok(policies) => *self.policies.write().await = policies,
+            Err(e) => error!("{:#}; keeping the previous volume policy", e),
+        }
+    }
+
+    /// Current policies
+    pub async fn get(&self) -> VolumePolicies {
+        self.policies.read().await.clone()
+    }
+
+    /// Replace all policies; empty zone overrides are dropped
+    pub async fn set(&self, mut policies: VolumePolicies) -> Result<()> {
+        policies.default.validate()?;
+        for (zone_id, policy) in &policies.zones {
+            policy
+                .validate()
+                .map_err(|e| anyhow::anyhow!("{}: {}", zone_id, e))?;
+        }
+        policies.zones.retain(|_, policy| !policy.is_empty());
+
+        let mut current = self.policies.write().await;
+        *current = policies;
+        self.file.save(&*current);
+        info!(
+            "Volume policy updated ({} zone overrides)",
+            current.zones.len()
+        );
+        Ok(())
+    }
+
+    /// Policy for the first of `zone_ids` that has one, else the default
+    pub async fn policy_for(&self, zone_ids: &[&str]) -> VolumePolicy {
+        let policies = self.policies.read().await;
+        zone_ids
+            .iter()
+            .find_map(|id| policies.zones.get(*id))
+            .unwrap_or(&policies.default)
+            .clone()
+    }
+
+    /// Watch zone state and apply the start cap when playback starts from stopped,
+    /// including playback started outside this app (Roon remote, LMS web UI).
+    /// Should be spawned as a task; stops on shutdown.
+    pub async fn run(self: Arc<Self>, state: AppState) {
+        let mut rx = state.bus.subscribe();
+        let mut last_state: HashMap<String, PlaybackState> = HashMap::new();
+
+        loop {
+            tokio::select! {
+                _ = state.shutdown.cancelled() => break,
+                event = rx.recv() => {
+                    let (zone_id, playback) = match event {
+                        Ok(BusEvent::ZoneUpdated { zone_id, state: playback, .. }) => {
+                            (zone_id.as_str().to_string(), PlaybackState::from(playback.as_str()))
+                        }
+                        Ok(BusEvent::ZoneRemoved { zone_id }) => {
+                            last_state.remove(zone_id.as_str());
+                            continue;
+                        }
+                        Ok(BusEvent::ShuttingDown { .. }) | Err(RecvError::Closed) => break,
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Volume policy watcher lagged, skipped {} events", skipped);
+                            continue;
+                        }
+                    };
+
+                    // Loading/buffering sit between stopped and playing
+                    if matches!(playback, PlaybackState::Loading | PlaybackState::Buffering) {
+                        continue;
+                    }
+                    let previous = last_state.insert(zone_id.clone(), playback);
+                    if playback == PlaybackState::Playing && previous == Some(PlaybackState::Stopped) {
+                        let origin = CommandOrigin::new(ControlSurface::VolumePolicy);
+                        if let Err(e) = state.enforce_start_cap(&zone_id, &origin).await {
+                            warn!("Failed to apply start volume cap to {}: {}", zone_id, e);
+                        }
+                    }
+                }
+            }
+        }
+
+        debug!("Volume policy watcher stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> VolumePolicy {
+        VolumePolicy {
+            max_volume: Some(-10.0),
+            max_step: Some(6.0),
+            start_cap: Some(-30.0),
+        }
+    }
+
+    #[test]
+    fn ceiling_is_hard() {
+        let decision = policy().apply(-2.0, Some(-12.0), Some((-64.0, 0.0)));
+        assert_eq!(decision.value, -10.0);
+        assert!(decision.reason.unwrap().contains("ceiling"));
+    }
+
+    #[test]
+    fn increases_are_limited_but_decreases_are_not() {
+        let up = policy().apply(-20.0, Some(-40.0), Some((-64.0, 0.0)));
+        assert_eq!(up.value, -34.0);
+        assert!(up.reason.is_some());
+
+        let down = policy().apply(-60.0, Some(-20.0), Some((-64.0, 0.0)));
+        assert_eq!(down.value, -60.0);
+        assert!(down.reason.is_none());
+    }
+
+    #[test]
+    fn zone_range_is_applied_first() {
+        // THE -12 dB BUG: a dB value must never be read as a 0-100 level
+        let decision = VolumePolicy::default().apply(-12.0, None, Some((-64.0, 0.0)));
+        assert_eq!(decision.value, -12.0);
+        assert!(decision.reason.is_none());
+
+        let decision = VolumePolicy::default().apply(150.0, None, Some((0.0, 100.0)));
+        assert_eq!(decision.value, 100.0);
+        assert!(decision.reason.is_some());
+    }
+
+    #[test]
+    fn start_cap_only_lowers() {
+        assert_eq!(policy().start_level(-20.0), Some(-30.0));
+        assert_eq!(policy().start_level(-45.0), None);
+        assert_eq!(VolumePolicy::default().start_level(0.0), None);
+    }
+
+    #[test]
+    fn invalid_policies_are_rejected() {
+        let mut p = policy();
+        p.max_step = Some(0.0);
+        assert!(p.validate().is_err());
+
+        let mut p = policy();
+        p.start_cap = Some(-5.0);
+        assert!(p.validate().is_err());
+    }
+
+    #[test]
+    fn unusable_policy_files_are_errors() {
+        let policies = parse_policies(r#"{"default": {"max_volume": -10.0}}"#).unwrap();
+        assert_eq!(policies.default.max_volume, Some(-10.0));
+
+        // A typo must not turn into "no limits"
+        assert!(parse_policies(r#"{"default": {"max_volume": "-10"}}"#).is_err());
+        assert!(parse_policies(r#"{"default": {"max_volume": -10.0,}}"#).is_err());
+        assert!(parse_policies(r#"{"zones": {"lms:aa": {"max_step": 0.0}}}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn zone_override_wins_over_default() {
+        let store = VolumePolicyStore::in_memory();
+        let mut policies = VolumePolicies {
+            default: VolumePolicy {
+                max_volume: Some(80.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        policies.zones.insert("lms:aa".to_string(), policy());
+        policies
+            .zones
+            .insert("lms:bb".to_string(), VolumePolicy::default());
+        store.set(policies).await.unwrap();
+
+        assert_eq!(store.policy_for(&["lms:aa"]).await, policy());
+        assert_eq!(
+            store.policy_for(&["room:den", "lms:cc"]).await.max_volume,
+            Some(80.0)
+        );
+        // Empty overrides are dropped rather than disabling the default
+        assert!(!store.get().await.zones.contains_key("lms:bb"));
+    }
+}
@@ -354,6 +354,7 @@ mod bus_integration {
                 artist: Some("Artist".to_string()),
                 album: Some("Album".to_string()),
                 image_key: None,
+                duration: None,
             },
             BusEvent::SeekPositionChanged {
                 zone_id: PrefixedZoneId::roon("zone-1"),
@@ -765,3 +766,116 @@ mod mock_server_tests {
         mock.stop().await;
     }
 }
+
+// =============================================================================
+// Scrobbler tests (against a local ListenBrainz stand-in)
+// =============================================================================
+
+mod scrobbler_integration {
+    use super::*;
+    use crate::mock_servers::MockListenBrainz;
+    use unified_hifi_control::history::HistoryEntry;
+    use unified_hifi_control::scrobbler::{Scrobbler, ScrobblerConfig};
+
+    fn played(listened_secs: u64, duration_secs: u64) -> HistoryEntry {
+        let now = chrono::Utc::now();
+        HistoryEntry {
+            zone_id: "lms:aa:bb:cc:dd:ee:ff".to_string(),
+            zone_name: "Kitchen".to_string(),
+            source: "lms".to_string(),
+            title: "Blue in Green".to_string(),
+            artist: "Miles Davis".to_string(),
+            album: "Kind of Blue".to_string(),
+            started_at: now - chrono::Duration::seconds(listened_secs as i64),
+            ended_at: now,
+            listened_secs,
+            duration_secs: Some(duration_secs),
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn queues_while_offline_and_submits_after_restart() {
+        let mock = MockListenBrainz::start().await;
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("scrobbler.json");
+        let queue_path = dir.path().join("scrobble_queue.json");
+
+        let (bus, _rx) = test_bus();
+        let scrobbler = Scrobbler::open(bus.clone(), config_path.clone(), queue_path.clone());
+        scrobbler
+            .set_config(ScrobblerConfig {
+                enabled: true,
+                url: mock.url(),
+                token: "secret-token".to_string(),
+            })
+            .await
+            .unwrap();
+
+        // Server down: the listen stays queued
+        mock.set_failing(true).await;
+        assert!(scrobbler.submit(&played(200, 330)).await);
+        scrobbler.flush().await;
+        let status = scrobbler.status().await;
+        assert_eq!(status.queued, 1);
+        assert!(status.last_error.is_some());
+
+        // Skipped after a few seconds: never queued
+        assert!(!scrobbler.submit(&played(20, 330)).await);
+
+        // Queue and settings survive a restart
+        drop(scrobbler);
+        let scrobbler = Scrobbler::open(bus, config_path, queue_path.clone());
+        assert_eq!(scrobbler.status().await.queued, 1);
+
+        mock.set_failing(false).await;
+        scrobbler.flush().await;
+
+        let status = scrobbler.status().await;
+        assert_eq!(status.queued, 0);
+        assert_eq!(status.submitted, 1);
+        assert!(!queue_path.exists());
+
+        let submissions = mock.submissions().await;
+        assert_eq!(submissions.len(), 1);
+        assert_eq!(
+            submissions[0].authorization.as_deref(),
+            Some("Token secret-token")
+        );
+        let body = &submissions[0].body;
+        assert_eq!(body["listen_type"], "single");
+        assert_eq!(
+            body["payload"][0]["track_metadata"]["track_name"],
+            "Blue in Green"
+        );
+        assert_eq!(
+            body["payload"][0]["track_metadata"]["artist_name"],
+            "Miles Davis"
+        );
+
+        mock.stop().await;
+    }
+
+    #[tokio::test]
+    async fn disabled_scrobbler_queues_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let (bus, _rx) = test_bus();
+        let scrobbler = Scrobbler::open(
+            bus,
+            dir.path().join("scrobbler.json"),
+            dir.path().join("scrobble_queue.json"),
+        );
+
+        assert!(!scrobbler.submit(&played(300, 330)).await);
+        assert_eq!(scrobbler.status().await.queued, 0);
+
+        // Enabling requires a token
+        let result = scrobbler
+            .set_config(ScrobblerConfig {
+                enabled: true,
+                ..Default::default()
+            })
+            .await;
+        assert!(result.is_err());
+    }
+}
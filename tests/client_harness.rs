@@ -38,6 +38,7 @@ use std::{sync::Arc, time::Instant};
 use tokio_util::sync::CancellationToken;
 use tower::ServiceExt;
 
+use unified_hifi_control::adapters::external::ExternalAdapters;
 use unified_hifi_control::adapters::hqplayer::{HqpInstanceManager, HqpZoneLinkService};
 use unified_hifi_control::adapters::lms::LmsAdapter;
 use unified_hifi_control::adapters::openhome::OpenHomeAdapter;
@@ -47,9 +48,19 @@ use unified_hifi_control::adapters::Startable;
 use unified_hifi_control::aggregator::ZoneAggregator;
 use unified_hifi_control::api;
 use unified_hifi_control::api::AppState;
+use unified_hifi_control::auth::{self, AuthStore, Scope, KNOB_TOKEN_HEADER};
 use unified_hifi_control::bus::create_bus;
 use unified_hifi_control::coordinator::AdapterCoordinator;
+use unified_hifi_control::fades::Fades;
+use unified_hifi_control::history::HistoryStore;
 use unified_hifi_control::knobs::{self, KnobStore};
+use unified_hifi_control::rooms::RoomStore;
+use unified_hifi_control::rules::RulesEngine;
+use unified_hifi_control::scenes::SceneStore;
+use unified_hifi_control::scheduler::Scheduler;
+use unified_hifi_control::scrobbler::Scrobbler;
+use unified_hifi_control::sleep_timer::SleepTimers;
+use unified_hifi_control::volume_policy::VolumePolicyStore;
 
 // Stub HTML handlers for UI route tests (replacing deleted ui module)
 mod ui_stubs {
@@ -176,6 +187,11 @@ struct HqpLoadProfileRequest {
 
 /// Create a test app with disconnected/mock adapters
 async fn create_test_app() -> Router {
+    test_router(create_test_state().await)
+}
+
+/// Application state with disconnected/mock adapters
+async fn create_test_state() -> AppState {
     let bus = create_bus();
 
     // Create coordinator (tests don't need real lifecycle management)
@@ -190,13 +206,22 @@ async fn create_test_app() -> Router {
     let openhome = Arc::new(OpenHomeAdapter::new(bus.clone()));
     let upnp = Arc::new(UPnPAdapter::new(bus.clone()));
     let knob_store = KnobStore::new();
+    let room_store = Arc::new(RoomStore::in_memory());
+    let history_store = Arc::new(HistoryStore::in_memory());
+    let scrobbler = Arc::new(Scrobbler::new(bus.clone()));
+    let scheduler = Arc::new(Scheduler::in_memory());
+    let rules = Arc::new(RulesEngine::in_memory());
+    let volume_policy = Arc::new(VolumePolicyStore::in_memory());
+    let fades = Arc::new(Fades::in_memory());
+    let sleep_timers = Arc::new(SleepTimers::new());
+    let scenes = Arc::new(SceneStore::in_memory());
 
     // Build startable adapters list
     let startable_adapters: Vec<Arc<dyn Startable>> =
         vec![roon.clone(), lms.clone(), openhome.clone(), upnp.clone()];
 
     let aggregator = Arc::new(ZoneAggregator::new(bus.clone()));
-    let state = AppState::new(
+    let mut state = AppState::new(
         roon,
         hqplayer,
         hqp_instances,
@@ -204,7 +229,17 @@ async fn create_test_app() -> Router {
         lms,
         openhome,
         upnp,
+        Arc::new(ExternalAdapters::empty()),
         knob_store,
+        room_store,
+        history_store,
+        scrobbler,
+        scheduler,
+        rules,
+        volume_policy,
+        fades,
+        sleep_timers,
+        scenes,
         bus,
         aggregator,
         coordinator,
@@ -212,8 +247,12 @@ async fn create_test_app() -> Router {
         Instant::now(),
         CancellationToken::new(),
     );
+    state.auth = Arc::new(AuthStore::in_memory());
+    state
+}
 
-    // Build router with all routes (same as main.rs)
+/// Router with all routes (same as main.rs)
+fn test_router(state: AppState) -> Router {
     Router::new()
         // Health check
         .route("/status", get(api::status_handler))
@@ -289,6 +328,8 @@ async fn create_test_app() -> Router {
             get(api::upnp_now_playing_handler),
         )
         .route("/upnp/control", post(api::upnp_control_handler))
+        // Source-agnostic zone commands
+        .route("/zones/{zone_id}/command", post(api::zone_command_handler))
         // App settings API
         .route("/api/settings", get(api::api_settings_get_handler))
         .route("/api/settings", post(api::api_settings_post_handler))
@@ -1243,6 +1284,68 @@ mod error_handling {
             "Expected error for unknown action"
         );
     }
+
+    /// Test: Zone commands tell an unknown zone from one that can't do the command
+    #[tokio::test]
+    async fn zone_command_statuses() {
+        let state = create_test_state().await;
+        let app = test_router(state.clone());
+        let aggregator = state.aggregator.clone();
+        let handle = tokio::spawn(async move { aggregator.run().await });
+        // Let the aggregator subscribe before publishing
+        tokio::task::yield_now().await;
+
+        let zone: unified_hifi_control::bus::Zone = serde_json::from_value(json!({
+            "zone_id": "upnp:renderer-1",
+            "zone_name": "Renderer",
+            "state": "stopped",
+            "volume_control": null,
+            "now_playing": null,
+            "source": "upnp",
+            "is_controllable": true,
+            "is_seekable": true,
+            "last_updated": 0,
+            "is_play_allowed": true,
+            "is_pause_allowed": false,
+            "is_next_allowed": false,
+            "is_previous_allowed": false
+        }))
+        .unwrap();
+        state
+            .bus
+            .publish(unified_hifi_control::bus::BusEvent::ZoneDiscovered { zone });
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while state.get_zone("upnp:renderer-1").await.is_none() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("zone never reached the aggregator");
+
+        let next = json!({"action": "Next"});
+        let (status, _) = post_json(&app, "/zones/upnp:unknown/command", &next).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // Pure renderers have no queue
+        let (status, body) = post_json(&app, "/zones/upnp:renderer-1/command", &next).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let json = assert_json("Unsupported command", &body);
+        assert_eq!(json["success"], false);
+
+        // Known to the bridge, but the adapter lost the device
+        let (status, _) = post_json(
+            &app,
+            "/zones/upnp:renderer-1/command",
+            &json!({"action": "Stop"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+
+        state
+            .bus
+            .publish(unified_hifi_control::bus::BusEvent::ShuttingDown { reason: None });
+        handle.await.unwrap();
+    }
 }
 
 // =============================================================================
@@ -1315,3 +1418,307 @@ mod integration {
         println!("iOS: Got HQPlayer profiles");
     }
 }
+
+// =============================================================================
+// Authentication - knobs without tokens
+// =============================================================================
+
+mod knob_auth {
+    use super::*;
+
+    /// App with auth turned on, plus its state
+    async fn create_auth_app() -> (Router, AppState) {
+        let state = create_test_state().await;
+        state
+            .auth
+            .create_token("Admin", Scope::Admin)
+            .await
+            .unwrap();
+        state.auth.configure(true).await.unwrap();
+        let app = test_router(state.clone()).layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_auth,
+        ));
+        (app, state)
+    }
+
+    async fn knob_get(
+        app: &Router,
+        knob_id: &str,
+        token: Option<&str>,
+    ) -> axum::response::Response {
+        let mut request = Request::builder()
+            .uri("/knob/zones")
+            .header("X-Knob-Id", knob_id)
+            .header("Accept", "application/json");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        app.clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn unknown_knob_without_token_is_refused() {
+        let (app, state) = create_auth_app().await;
+
+        let response = knob_get(&app, "11:22:33:44:55:66", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().get(KNOB_TOKEN_HEADER).is_none());
+
+        // Pairing open, but this id is neither known nor approved
+        state.auth.set_knob_pairing(true, None).await;
+        let response = knob_get(&app, "11:22:33:44:55:66", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().get(KNOB_TOKEN_HEADER).is_none());
+        assert!(state
+            .auth
+            .tokens()
+            .await
+            .iter()
+            .all(|t| t.knob_id.is_none()));
+    }
+
+    #[tokio::test]
+    async fn approved_knob_enrolls_without_being_served() {
+        let (app, state) = create_auth_app().await;
+        state
+            .auth
+            .set_knob_pairing(true, Some("AA:BB:CC:00:00:01"))
+            .await;
+
+        // The enrolling request itself is refused, with the token attached
+        let response = knob_get(&app, "AA:BB:CC:00:00:01", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let token = response
+            .headers()
+            .get(KNOB_TOKEN_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+            .unwrap();
+
+        // Only one token per knob
+        let response = knob_get(&app, "AA:BB:CC:00:00:01", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().get(KNOB_TOKEN_HEADER).is_none());
+
+        let response = knob_get(&app, "AA:BB:CC:00:00:01", Some(&token)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
+
+// =============================================================================
+// Rules Engine
+// =============================================================================
+
+mod rules_engine {
+    use super::*;
+    use std::time::Duration;
+    use unified_hifi_control::bus::{
+        BusEvent, Command, CommandOrigin, CommandResponse, ControlSurface,
+    };
+    use unified_hifi_control::rules::Rule;
+
+    fn command_result(zone_id: &str, origin: CommandOrigin) -> BusEvent {
+        BusEvent::CommandResult {
+            response: CommandResponse {
+                zone_id: zone_id.to_string(),
+                command: Command::Pause,
+                success: true,
+                error: None,
+                reason: None,
+                timestamp: 0,
+            },
+            request_id: None,
+            origin: Some(origin),
+        }
+    }
+
+    /// A rule whose own command matches its trigger must not fire itself again
+    #[tokio::test]
+    async fn self_matching_rule_fires_once() {
+        let state = create_test_state().await;
+        let rule: Rule = serde_json::from_value(json!({
+            "name": "Echo",
+            "event": "command_result",
+            "action": {"type": "zone_command", "command": {"action": "Pause"}}
+        }))
+        .unwrap();
+        state.rules.save_rule(rule).await.unwrap();
+
+        let engine = state.rules.clone();
+        let run_state = state.clone();
+        let task = tokio::spawn(async move { engine.run(run_state).await });
+        // Let the engine subscribe before publishing
+        tokio::task::yield_now().await;
+
+        state.bus.publish(command_result(
+            "lms:echo",
+            CommandOrigin::new(ControlSurface::Web),
+        ));
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while state.rules.firings().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("rule never fired");
+        // Give a runaway rule the chance to fire on its own result
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(state.rules.firings().await.len(), 1);
+
+        state.shutdown.cancel();
+        task.await.unwrap();
+    }
+}
+
+// =============================================================================
+// Scheduler
+// =============================================================================
+
+mod scheduler_actions {
+    use super::*;
+    use unified_hifi_control::scheduler::{self, Schedule};
+
+    /// An alarm never starts playback at a level it couldn't set first
+    #[tokio::test]
+    async fn alarm_on_unknown_zone_does_not_play() {
+        let state = create_test_state().await;
+        let schedule: Schedule = serde_json::from_value(json!({
+            "id": "wake-up",
+            "name": "Wake up",
+            "zone_id": "lms:missing",
+            "when": {"type": "daily", "time": "06:45"},
+            "action": {"type": "play", "query": "Morning jazz"},
+            "alarm": {"start_percent": 5, "target_percent": 35, "ramp_minutes": 15}
+        }))
+        .unwrap();
+
+        let err = scheduler::execute(&state, &schedule).await.unwrap_err();
+        assert!(err.to_string().contains("zone not found"), "{}", err);
+    }
+}
+
+// =============================================================================
+// Knob Volume Coalescing
+// =============================================================================
+
+#[cfg(unix)]
+mod knob_volume {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use std::time::Duration;
+    use unified_hifi_control::adapters::external::ExternalAdapterConfig;
+    use unified_hifi_control::bus::{CommandOrigin, ControlSurface};
+
+    /// External adapter with one zone at volume 20. It logs every command to
+    /// `$COMMAND_LOG` and takes two seconds to answer it, like a slow SOAP device.
+    const SLOW_CHILD: &str = r#"
+read -r init
+echo '{"jsonrpc":"2.0","id":1,"result":{"name":"mock"}}'
+echo '{"jsonrpc":"2.0","method":"zone.discovered","params":{"zone":{"id":"den","name":"Den","volume":{"value":20},"capabilities":["volume"]}}}'
+while read -r line; do
+  case "$line" in
+    *'"shutdown"'*) exit 0 ;;
+    *'"command"'*)
+      echo "$line" >> "$COMMAND_LOG"
+      id=$(echo "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+      (sleep 2; echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":null}") & ;;
+  esac
+done
+"#;
+
+    /// Command params the adapter received, oldest first
+    fn commands(log: &Path) -> Vec<Value> {
+        std::fs::read_to_string(log)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["params"].clone())
+            .collect()
+    }
+
+    async fn wait_for_commands(log: &Path, count: usize, within: Duration) -> Vec<Value> {
+        tokio::time::timeout(within, async {
+            loop {
+                let commands = commands(log);
+                if commands.len() >= count {
+                    return commands;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("adapter never got {} commands", count))
+    }
+
+    /// A burst of knob steps reaches the adapter as one call with the final level,
+    /// and a burst during a slow call doesn't wait for it to be answered
+    #[tokio::test]
+    async fn knob_burst_sends_only_the_final_level() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("commands.log");
+        let mut state = create_test_state().await;
+        state.external = Arc::new(ExternalAdapters::from_configs(
+            vec![ExternalAdapterConfig {
+                name: "mock".to_string(),
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), SLOW_CHILD.to_string()],
+                env: BTreeMap::from([("COMMAND_LOG".to_string(), log.display().to_string())]),
+                enabled: true,
+            }],
+            state.bus.clone(),
+        ));
+
+        let aggregator = state.aggregator.clone();
+        let handle = tokio::spawn(async move { aggregator.run().await });
+        // Let the aggregator subscribe before the zone is announced
+        tokio::task::yield_now().await;
+        let adapter = state.external.list()[0].clone();
+        adapter.start().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while state.get_zone("ext-mock:den").await.is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("zone never reached the aggregator");
+
+        let knob = CommandOrigin::new(ControlSurface::Knob);
+        let mut levels = Vec::new();
+        for _ in 0..5 {
+            let outcome = state
+                .volume_coalescer
+                .step(&state, "ext-mock:den", 1.0, true, knob.clone())
+                .await
+                .unwrap();
+            levels.push(outcome.value);
+        }
+        assert_eq!(levels, [21.0, 22.0, 23.0, 24.0, 25.0].map(Some));
+
+        let sent = wait_for_commands(&log, 1, Duration::from_secs(1)).await;
+        // Longer than the coalescing window: nothing else is on its way
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(commands(&log).len(), 1);
+        assert_eq!(sent[0]["action"], "volume_absolute");
+        assert_eq!(sent[0]["value"], 25);
+
+        // The adapter is still working on 25: the next burst replaces that call
+        for _ in 0..2 {
+            state
+                .volume_coalescer
+                .step(&state, "ext-mock:den", 1.0, true, knob.clone())
+                .await
+                .unwrap();
+        }
+        let sent = wait_for_commands(&log, 2, Duration::from_secs(1)).await;
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1]["value"], 27);
+
+        adapter.stop().await;
+        handle.abort();
+    }
+}
@@ -0,0 +1,104 @@
+//! Mock ListenBrainz server for testing
+//!
+//! Accepts POST /1/submit-listens and records what was submitted.
+//! Can be switched into a failing mode to simulate an outage.
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+/// A submission as received by the mock
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub authorization: Option<String>,
+    pub body: Value,
+}
+
+#[derive(Default)]
+struct MockListenBrainzState {
+    submissions: Vec<Submission>,
+    failing: bool,
+}
+
+/// Mock ListenBrainz server
+pub struct MockListenBrainz {
+    addr: SocketAddr,
+    state: Arc<RwLock<MockListenBrainzState>>,
+    handle: JoinHandle<()>,
+}
+
+impl MockListenBrainz {
+    /// Start a mock ListenBrainz server on a random port
+    pub async fn start() -> Self {
+        let state = Arc::new(RwLock::new(MockListenBrainzState::default()));
+
+        let app = Router::new()
+            .route("/1/submit-listens", post(handle_submit))
+            .with_state(state.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        Self {
+            addr,
+            state,
+            handle,
+        }
+    }
+
+    /// Base URL to configure the scrobbler with
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Make every submission fail with 503 (or recover)
+    pub async fn set_failing(&self, failing: bool) {
+        self.state.write().await.failing = failing;
+    }
+
+    /// Accepted submissions
+    pub async fn submissions(&self) -> Vec<Submission> {
+        self.state.read().await.submissions.clone()
+    }
+
+    /// Stop the mock server
+    pub async fn stop(self) {
+        self.handle.abort();
+    }
+}
+
+async fn handle_submit(
+    State(state): State<Arc<RwLock<MockListenBrainzState>>>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> (StatusCode, Json<Value>) {
+    let mut state = state.write().await;
+    if state.failing {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "code": 503, "error": "maintenance" })),
+        );
+    }
+
+    state.submissions.push(Submission {
+        authorization: headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .map(String::from),
+        body,
+    });
+    (StatusCode::OK, Json(json!({ "status": "ok" })))
+}
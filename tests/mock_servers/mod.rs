@@ -1,15 +1,18 @@
 //! Mock servers for adapter integration testing
 //!
 //! These mock servers simulate real backend services (Roon, LMS, HQPlayer, UPnP, OpenHome)
-//! allowing full integration testing without real hardware.
+//! and external services (ListenBrainz), allowing full integration testing without
+//! real hardware or network access.
 
 pub mod hqplayer;
+pub mod listenbrainz;
 pub mod lms;
 pub mod openhome;
 pub mod roon;
 pub mod upnp;
 
 pub use hqplayer::MockHqpServer;
+pub use listenbrainz::MockListenBrainz;
 pub use lms::MockLmsServer;
 pub use openhome::MockOpenHomeDevice;
 pub use roon::MockRoonCore;
@@ -17,6 +17,7 @@ use std::{sync::Arc, time::Instant};
 use tokio_util::sync::CancellationToken;
 use tower::ServiceExt;
 
+use unified_hifi_control::adapters::external::ExternalAdapters;
 use unified_hifi_control::adapters::hqplayer::{HqpInstanceManager, HqpZoneLinkService};
 use unified_hifi_control::adapters::lms::LmsAdapter;
 use unified_hifi_control::adapters::openhome::OpenHomeAdapter;
@@ -28,7 +29,16 @@ use unified_hifi_control::api;
 use unified_hifi_control::api::AppState;
 use unified_hifi_control::bus::create_bus;
 use unified_hifi_control::coordinator::AdapterCoordinator;
+use unified_hifi_control::fades::Fades;
+use unified_hifi_control::history::HistoryStore;
 use unified_hifi_control::knobs::{self, KnobStore};
+use unified_hifi_control::rooms::RoomStore;
+use unified_hifi_control::rules::RulesEngine;
+use unified_hifi_control::scenes::SceneStore;
+use unified_hifi_control::scheduler::Scheduler;
+use unified_hifi_control::scrobbler::Scrobbler;
+use unified_hifi_control::sleep_timer::SleepTimers;
+use unified_hifi_control::volume_policy::VolumePolicyStore;
 
 // Stub HTML handlers for UI route tests (replacing deleted ui module)
 mod ui_stubs {
@@ -57,6 +67,15 @@ async fn create_test_app() -> Router {
     let openhome = Arc::new(OpenHomeAdapter::new(bus.clone()));
     let upnp = Arc::new(UPnPAdapter::new(bus.clone()));
     let knob_store = KnobStore::new();
+    let room_store = Arc::new(RoomStore::in_memory());
+    let history_store = Arc::new(HistoryStore::in_memory());
+    let scrobbler = Arc::new(Scrobbler::new(bus.clone()));
+    let scheduler = Arc::new(Scheduler::in_memory());
+    let rules = Arc::new(RulesEngine::in_memory());
+    let volume_policy = Arc::new(VolumePolicyStore::in_memory());
+    let fades = Arc::new(Fades::in_memory());
+    let sleep_timers = Arc::new(SleepTimers::new());
+    let scenes = Arc::new(SceneStore::in_memory());
 
     // Build startable adapters list
     let startable_adapters: Vec<Arc<dyn Startable>> =
@@ -71,7 +90,17 @@ async fn create_test_app() -> Router {
         lms,
         openhome,
         upnp,
+        Arc::new(ExternalAdapters::empty()),
         knob_store,
+        room_store,
+        history_store,
+        scrobbler,
+        scheduler,
+        rules,
+        volume_policy,
+        fades,
+        sleep_timers,
+        scenes,
         bus,
         aggregator,
         coordinator,
@@ -538,11 +538,13 @@ mod bus_event_schema {
             artist: Some("Test Artist".to_string()),
             album: Some("Test Album".to_string()),
             image_key: Some("img-123".to_string()),
+            duration: Some(245.0),
         };
 
         let json = serde_json::to_value(&event).unwrap();
         assert_eq!(json["type"], "NowPlayingChanged");
         assert_eq!(json["payload"]["title"], "Test Song");
+        assert_eq!(json["payload"]["duration"], 245.0);
     }
 
     #[test]
@@ -553,6 +555,7 @@ mod bus_event_schema {
             artist: None,
             album: None,
             image_key: None,
+            duration: None,
         };
 
         let json = serde_json::to_value(&event).unwrap();
@@ -6,6 +6,7 @@
 //! Fix: Use zone's actual volume range (e.g., -64 to 0 dB).
 
 use unified_hifi_control::adapters::roon::{clamp, get_volume_range, Output, VolumeInfo};
+use unified_hifi_control::volume_policy::VolumePolicy;
 
 // =============================================================================
 // dB scale zones (HQPlayer-like)
@@ -159,3 +160,44 @@ fn relative_step_clamped_prevents_wild_jumps() {
     assert_eq!(clamp(50.0, -max_step, max_step), max_step);
     assert_eq!(clamp(-50.0, -max_step, max_step), -max_step);
 }
+
+// =============================================================================
+// Central volume policy
+// =============================================================================
+
+#[test]
+fn policy_ceiling_applies_to_db_zones() {
+    let policy = VolumePolicy {
+        max_volume: Some(-20.0),
+        ..Default::default()
+    };
+    // -12 dB is within the zone's range but above the configured ceiling
+    let decision = policy.apply(-12.0, Some(-30.0), Some((-64.0, 0.0)));
+    assert_eq!(decision.value, -20.0);
+    assert!(decision.reason.is_some());
+}
+
+#[test]
+fn lint_volume_paths_use_policy() {
+    // Knob, MCP and automation volume must go through AppState::set_volume, not the adapters
+    for path in [
+        "src/knobs/routes.rs",
+        "src/knobs/volume.rs",
+        "src/mcp/mod.rs",
+        "src/rules.rs",
+        "src/scheduler.rs",
+        "src/sleep_timer.rs",
+    ] {
+        let src = std::fs::read_to_string(path).expect("Failed to read source file");
+        assert!(
+            !src.contains(".change_volume("),
+            "{} calls an adapter's change_volume directly, bypassing the volume policy",
+            path
+        );
+        assert!(
+            !src.contains(".handle_command("),
+            "{} sends commands to an adapter directly, bypassing the volume policy",
+            path
+        );
+    }
+}